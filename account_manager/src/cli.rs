@@ -1,4 +1,4 @@
-use crate::deposits;
+use crate::{deposits, exit, interchange};
 use clap::{App, Arg, SubCommand};
 
 pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
@@ -9,9 +9,19 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
             SubCommand::with_name("validator")
                 .about("Generate or manage Ethereum 2.0 validators.")
                 .subcommand(deposits::cli_app())
+                .subcommand(interchange::cli_app())
+                .subcommand(exit::cli_app())
                 .subcommand(
                     SubCommand::with_name("new")
                         .about("Create a new Ethereum 2.0 validator.")
+                        .arg(
+                            Arg::with_name("secrets-dir")
+                                .long("secrets-dir")
+                                .value_name("SECRETS_DIR")
+                                .takes_value(true)
+                                .help("The path where the validator keystore passwords will be \
+                                       stored. Defaults to ~/.lighthouse/secrets"),
+                        )
                         .arg(
                             Arg::with_name("deposit-value")
                                 .short("v")
@@ -74,6 +84,38 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                                         .required(true),
                                 ),
                         )
+                        .subcommand(
+                            SubCommand::with_name("mnemonic")
+                                .about("Derives keys from a BIP-39 mnemonic, along the EIP-2334 paths \
+                                        m/12381/3600/i/0 (withdrawal) and m/12381/3600/i/0/0 (voting). \
+                                        Creates and prints a new mnemonic unless --mnemonic-path is \
+                                        provided, in which case the validators at the given indices are \
+                                        recovered from the existing mnemonic.")
+                                .arg(
+                                    Arg::with_name("validator_count")
+                                        .index(1)
+                                        .value_name("INTEGER")
+                                        .help("The number of validators to derive.")
+                                        .takes_value(true)
+                                        .default_value("1"),
+                                )
+                                .arg(
+                                    Arg::with_name("first-index")
+                                        .long("first-index")
+                                        .value_name("INDEX")
+                                        .help("The index (i) of the first validator to derive.")
+                                        .takes_value(true)
+                                        .default_value("0"),
+                                )
+                                .arg(
+                                    Arg::with_name("mnemonic-path")
+                                        .long("mnemonic-path")
+                                        .value_name("FILE")
+                                        .help("A file containing an existing mnemonic phrase from \
+                                               which the validator keys will be recovered.")
+                                        .takes_value(true),
+                                ),
+                        )
                         .subcommand(
                             SubCommand::with_name("random")
                                 .about("Produces public keys using entropy from the Rust 'rand' library.")
@@ -36,6 +36,13 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .help("The path where the validator directories will be created. Defaults to ~/.lighthouse/validators")
                 .takes_value(true),
         )
+        .arg(
+            Arg::with_name("secrets-dir")
+                .long("secrets-dir")
+                .value_name("SECRETS_DIR")
+                .help("The path where the validator keystore passwords will be stored. Defaults to ~/.lighthouse/secrets")
+                .takes_value(true),
+        )
         .arg(
             Arg::with_name("eth1-ipc")
                 .long("eth1-ipc")
@@ -89,6 +96,11 @@ pub fn cli_run<T: EthSpec>(matches: &ArgMatches, mut env: Environment<T>) -> Res
         "validator_dir",
         PathBuf::new().join(".lighthouse").join("validators"),
     )?;
+    let secrets_dir = clap_utils::parse_path_with_default_in_home_dir(
+        matches,
+        "secrets-dir",
+        PathBuf::new().join(".lighthouse").join("secrets"),
+    )?;
     let eth1_ipc_path: PathBuf = clap_utils::parse_required(matches, "eth1-ipc")?;
     let from_address: Address = clap_utils::parse_required(matches, "from-address")?;
     let deposit_gwei = clap_utils::parse_optional(matches, "deposit-gwei")?
@@ -143,6 +155,7 @@ pub fn cli_run<T: EthSpec>(matches: &ArgMatches, mut env: Environment<T>) -> Res
                     .spec(spec.clone())
                     .custom_deposit_amount(deposit_gwei)
                     .thread_random_keypairs()
+                    .random_passwords()
                     .submit_eth1_deposit(web3.clone(), from_address, deposit_contract)
                     .map(move |(builder, tx_hash)| {
                         info!(
@@ -156,6 +169,7 @@ pub fn cli_run<T: EthSpec>(matches: &ArgMatches, mut env: Environment<T>) -> Res
             )?
             .create_directory(validator_dir.clone())?
             .write_keypair_files()?
+            .write_password_files(&secrets_dir)?
             .write_eth1_data_file()?
             .build()?;
     }
@@ -174,11 +188,16 @@ pub fn cli_run<T: EthSpec>(matches: &ArgMatches, mut env: Environment<T>) -> Res
 
 /// Returns the number of validators that exist in the given `validator_dir`.
 ///
-/// This function just assumes any file is a validator directory, making it likely to return a
-/// higher number than accurate but never a lower one.
+/// This function just assumes any directory is a validator directory, making it likely to return
+/// a higher number than accurate but never a lower one. Plain files (e.g., the slashing protection
+/// database) are ignored.
 fn existing_validator_count(validator_dir: &PathBuf) -> Result<usize, String> {
     fs::read_dir(&validator_dir)
-        .map(|iter| iter.count())
+        .map(|iter| {
+            iter.filter_map(Result::ok)
+                .filter(|entry| entry.path().is_dir())
+                .count()
+        })
         .map_err(|e| format!("Unable to read {:?}: {}", validator_dir, e))
 }
 
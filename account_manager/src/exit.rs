@@ -0,0 +1,165 @@
+use clap::{App, Arg, ArgMatches};
+use environment::Environment;
+use futures::Future;
+use remote_beacon_node::RemoteBeaconNode;
+use slog::info;
+use std::io::{stderr, stdin, Write};
+use std::path::PathBuf;
+use std::time::Duration;
+use types::{Epoch, EthSpec, VoluntaryExit};
+use validator_client::validator_directory::ValidatorDirectory;
+
+pub const CMD: &str = "exit";
+pub const DEFAULT_SERVER: &str = "http://localhost:5052/";
+pub const CONFIRMATION_PHRASE: &str = "Exit my validator";
+
+/// The timeout for requests to the beacon node.
+const HTTP_TIMEOUT: Duration = Duration::from_secs(12);
+
+pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
+    App::new(CMD)
+        .about(
+            "Sign a voluntary exit for a validator and publish it to the network via a beacon
+        node. Once the exit has been included in the chain it is irreversible, the validator will
+        never be able to propose or attest again.",
+        )
+        .arg(
+            Arg::with_name("validator")
+                .index(1)
+                .value_name("VALIDATOR_DIRECTORY")
+                .takes_value(true)
+                .required(true)
+                .help("The path to the directory of the validator that will exit."),
+        )
+        .arg(
+            Arg::with_name("secrets-dir")
+                .long("secrets-dir")
+                .value_name("SECRETS_DIR")
+                .takes_value(true)
+                .help(
+                    "The path where the validator keystore passwords are stored. \
+                     Defaults to ~/.lighthouse/secrets",
+                ),
+        )
+        .arg(
+            Arg::with_name("server")
+                .long("server")
+                .value_name("NETWORK_ADDRESS")
+                .takes_value(true)
+                .default_value(DEFAULT_SERVER)
+                .help("Address of the beacon node HTTP API which will publish the exit."),
+        )
+        .arg(
+            Arg::with_name("epoch")
+                .long("epoch")
+                .value_name("EPOCH")
+                .takes_value(true)
+                .help(
+                    "The earliest epoch at which the exit may be processed. Defaults to the \
+                     current epoch of the beacon node's head.",
+                ),
+        )
+        .arg(
+            Arg::with_name("no-confirmation")
+                .long("no-confirmation")
+                .help("Publish the exit without asking for confirmation."),
+        )
+}
+
+pub fn cli_run<T: EthSpec>(matches: &ArgMatches, mut env: Environment<T>) -> Result<(), String> {
+    let log = env.core_context().log;
+    let spec = env.core_context().eth2_config.spec;
+
+    let validator_dir: PathBuf = clap_utils::parse_required(matches, "validator")?;
+    let secrets_dir = clap_utils::parse_path_with_default_in_home_dir(
+        matches,
+        "secrets-dir",
+        PathBuf::new().join(".lighthouse").join("secrets"),
+    )?;
+    let server: String = clap_utils::parse_required(matches, "server")?;
+    let epoch: Option<Epoch> = clap_utils::parse_optional::<u64>(matches, "epoch")?.map(Epoch::new);
+    let no_confirmation = matches.is_present("no-confirmation");
+
+    let validator = ValidatorDirectory::load_for_signing(validator_dir.clone(), &secrets_dir)?;
+    let keypair = validator
+        .voting_keypair
+        .ok_or_else(|| format!("Unable to load voting keypair from {:?}", validator_dir))?;
+
+    let remote_node = RemoteBeaconNode::<T>::new_with_timeout(server, HTTP_TIMEOUT)
+        .map_err(|e| format!("Unable to init beacon node http client: {}", e))?;
+    let beacon = remote_node.http.beacon();
+
+    let (fork, genesis_validators_root, head) = env
+        .runtime()
+        .block_on(
+            beacon
+                .get_fork()
+                .join3(beacon.get_genesis_validators_root(), beacon.get_head()),
+        )
+        .map_err(|e| format!("Unable to query beacon node: {:?}", e))?;
+
+    let validator_index = env
+        .runtime()
+        .block_on(beacon.get_validators(vec![keypair.pk.clone()], None))
+        .map_err(|e| format!("Unable to query validator index: {:?}", e))?
+        .into_iter()
+        .next()
+        .and_then(|response| response.validator_index)
+        .ok_or_else(|| {
+            format!(
+                "Validator {} is not known to the beacon node",
+                keypair.pk.as_hex_string()
+            )
+        })?;
+
+    let epoch = epoch.unwrap_or_else(|| head.slot.epoch(T::slots_per_epoch()));
+
+    if !no_confirmation {
+        confirm_exit(validator_index, epoch)?;
+    }
+
+    let exit = VoluntaryExit {
+        epoch,
+        validator_index: validator_index as u64,
+    }
+    .sign(&keypair.sk, &fork, genesis_validators_root, &spec);
+
+    env.runtime()
+        .block_on(beacon.voluntary_exit(exit))
+        .map_err(|e| format!("Beacon node rejected the voluntary exit: {:?}", e))?;
+
+    info!(
+        log,
+        "Published voluntary exit";
+        "validator_index" => validator_index,
+        "epoch" => epoch.as_u64(),
+        "voting_pubkey" => keypair.pk.as_hex_string(),
+    );
+
+    Ok(())
+}
+
+/// Warns the user that exiting is irreversible and requires them to type `CONFIRMATION_PHRASE`
+/// before continuing.
+fn confirm_exit(validator_index: usize, epoch: Epoch) -> Result<(), String> {
+    eprintln!(
+        "WARNING: validator {} will exit at epoch {}. This is irreversible, the validator will \
+         not be able to perform duties again or rejoin the validator set.",
+        validator_index, epoch
+    );
+    eprint!("Type \"{}\" to continue: ", CONFIRMATION_PHRASE);
+    stderr()
+        .flush()
+        .map_err(|e| format!("Unable to flush stderr: {:?}", e))?;
+
+    let mut input = String::new();
+    stdin()
+        .read_line(&mut input)
+        .map_err(|e| format!("Unable to read confirmation: {:?}", e))?;
+
+    if input.trim() == CONFIRMATION_PHRASE {
+        Ok(())
+    } else {
+        Err("Confirmation phrase did not match, the exit was not published".to_string())
+    }
+}
@@ -0,0 +1,160 @@
+use clap::{App, Arg, ArgMatches, SubCommand};
+use environment::Environment;
+use slashing_protection::{
+    interchange::Interchange, SlashingDatabase, SLASHING_PROTECTION_FILENAME,
+};
+use slog::info;
+use std::fs::File;
+use std::path::PathBuf;
+use types::{EthSpec, Hash256};
+
+pub const CMD: &str = "slashing-protection";
+pub const IMPORT_CMD: &str = "import";
+pub const EXPORT_CMD: &str = "export";
+
+pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
+    App::new(CMD)
+        .about(
+            "Import or export the slashing protection history of the validators in --datadir
+        using the interchange JSON format. This allows validators to be moved between machines (or
+        clients) without losing the history which prevents them from signing slashable messages.",
+        )
+        .subcommand(
+            SubCommand::with_name(IMPORT_CMD)
+                .about(
+                    "Import an interchange file, merging it with the existing history. For each
+                validator only the highest block slot and attestation source and target epochs
+                are kept, the validator client will refuse to sign anything at or below them. It is
+                safe to import whilst a validator client is running.",
+                )
+                .arg(
+                    Arg::with_name("file")
+                        .index(1)
+                        .value_name("FILE")
+                        .takes_value(true)
+                        .required(true)
+                        .help("The interchange JSON file to import."),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name(EXPORT_CMD)
+                .about("Export the complete slashing protection history to an interchange file.")
+                .arg(
+                    Arg::with_name("file")
+                        .index(1)
+                        .value_name("FILE")
+                        .takes_value(true)
+                        .required(true)
+                        .help("The path where the interchange JSON file will be written."),
+                ),
+        )
+}
+
+pub fn cli_run<T: EthSpec>(
+    matches: &ArgMatches,
+    datadir: PathBuf,
+    mut env: Environment<T>,
+) -> Result<(), String> {
+    let log = env.core_context().log;
+    let slashing_protection_db_path = datadir.join(SLASHING_PROTECTION_FILENAME);
+    let genesis_validators_root = genesis_validators_root(&env)?;
+
+    match matches.subcommand() {
+        (IMPORT_CMD, Some(matches)) => {
+            let import_filename: PathBuf = clap_utils::parse_required(matches, "file")?;
+            let import_file = File::open(&import_filename).map_err(|e| {
+                format!(
+                    "Unable to open import file at {:?}: {:?}",
+                    import_filename, e
+                )
+            })?;
+            let interchange = Interchange::from_json_reader(&import_file)
+                .map_err(|e| format!("Error parsing file for import: {:?}", e))?;
+
+            let slashing_protection_database =
+                SlashingDatabase::open_or_create(&slashing_protection_db_path).map_err(|e| {
+                    format!(
+                        "Unable to open database at {:?}: {:?}",
+                        slashing_protection_db_path, e
+                    )
+                })?;
+
+            slashing_protection_database
+                .import_interchange_info(&interchange, genesis_validators_root)
+                .map_err(|e| {
+                    format!(
+                        "Error during import, the database was not modified: {:?}",
+                        e
+                    )
+                })?;
+
+            info!(
+                log,
+                "Imported slashing protection history";
+                "validators" => interchange.data.len(),
+                "file" => format!("{:?}", import_filename),
+            );
+        }
+        (EXPORT_CMD, Some(matches)) => {
+            let export_filename: PathBuf = clap_utils::parse_required(matches, "file")?;
+
+            if !slashing_protection_db_path.exists() {
+                return Err(format!(
+                    "No slashing protection database exists at {:?}",
+                    slashing_protection_db_path
+                ));
+            }
+
+            let slashing_protection_database = SlashingDatabase::open(&slashing_protection_db_path)
+                .map_err(|e| {
+                    format!(
+                        "Unable to open database at {:?}: {:?}",
+                        slashing_protection_db_path, e
+                    )
+                })?;
+
+            let interchange = slashing_protection_database
+                .export_interchange_info(genesis_validators_root)
+                .map_err(|e| format!("Error during export: {:?}", e))?;
+
+            let output_file = File::create(&export_filename).map_err(|e| {
+                format!("Error creating output file {:?}: {:?}", export_filename, e)
+            })?;
+
+            interchange
+                .write_to(&output_file)
+                .map_err(|e| format!("Error writing output file: {:?}", e))?;
+
+            info!(
+                log,
+                "Exported slashing protection history";
+                "validators" => interchange.data.len(),
+                "file" => format!("{:?}", export_filename),
+            );
+        }
+        _ => {
+            return Err(format!(
+                "No subcommand provided, see: lighthouse account validator {} --help",
+                CMD
+            ))
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns the `genesis_validators_root` of the testnet in use.
+///
+/// The interchange format is bound to a single chain, so it is an error to import or export
+/// history without a known genesis state.
+fn genesis_validators_root<T: EthSpec>(env: &Environment<T>) -> Result<Hash256, String> {
+    env.testnet
+        .as_ref()
+        .and_then(|testnet| testnet.genesis_state.as_ref())
+        .map(|genesis_state| genesis_state.genesis_validators_root)
+        .ok_or_else(|| {
+            "Unable to determine the genesis validators root, the testnet directory must \
+             contain a genesis state (see --testnet-dir)"
+                .to_string()
+        })
+}
@@ -1,9 +1,13 @@
 mod cli;
 mod deposits;
+mod exit;
+mod interchange;
 
+use bip39::{Language, Mnemonic, MnemonicType, Seed};
 use clap::ArgMatches;
 use deposit_contract::DEPOSIT_GAS;
 use environment::{Environment, RuntimeContext};
+use eth2_key_derivation::PlainText;
 use eth2_testnet_config::Eth2TestnetConfig;
 use futures::{future, Future, IntoFuture, Stream};
 use rayon::prelude::*;
@@ -51,6 +55,8 @@ pub fn run<T: EthSpec>(matches: &ArgMatches, mut env: Environment<T>) -> Result<
         ("validator", Some(matches)) => match matches.subcommand() {
             ("deposited", Some(matches)) => deposits::cli_run(matches, env)?,
             ("new", Some(matches)) => run_new_validator_subcommand(matches, datadir, env)?,
+            (interchange::CMD, Some(matches)) => interchange::cli_run(matches, datadir, env)?,
+            (exit::CMD, Some(matches)) => exit::cli_run(matches, env)?,
             _ => {
                 return Err("Invalid 'validator new' command. See --help.".to_string());
             }
@@ -69,6 +75,8 @@ enum KeygenMethod {
     Insecure(usize),
     /// Generate a new key from the `rand` thread random RNG.
     ThreadRandom,
+    /// Derive the keys at the given EIP-2334 validator index from a mnemonic seed.
+    Derived(u32),
 }
 
 /// Process the subcommand for creating new validators.
@@ -124,6 +132,9 @@ fn run_new_validator_subcommand<T: EthSpec>(
             )
         })?;
 
+    // Only set when keys are derived from a mnemonic.
+    let mut seed = None;
+
     let methods: Vec<KeygenMethod> = match matches.subcommand() {
         ("insecure", Some(matches)) => {
             let first = matches
@@ -148,6 +159,19 @@ fn run_new_validator_subcommand<T: EthSpec>(
 
             (0..count).map(|_| KeygenMethod::ThreadRandom).collect()
         }
+        ("mnemonic", Some(matches)) => {
+            let count: u32 = clap_utils::parse_required(matches, "validator_count")?;
+            let first_index: u32 = clap_utils::parse_required(matches, "first-index")?;
+            let last_index = first_index
+                .checked_add(count)
+                .ok_or_else(|| "Validator index overflow".to_string())?;
+
+            seed = Some(mnemonic_seed(matches)?);
+
+            (first_index..last_index)
+                .map(KeygenMethod::Derived)
+                .collect()
+        }
         _ => {
             return Err("Invalid 'validator' command. See --help.".to_string());
         }
@@ -159,9 +183,17 @@ fn run_new_validator_subcommand<T: EthSpec>(
         .parse::<u64>()
         .map_err(|e| format!("Unable to parse deposit-value: {}", e))?;
 
+    let secrets_dir = clap_utils::parse_path_with_default_in_home_dir(
+        matches,
+        "secrets-dir",
+        PathBuf::new().join(".lighthouse").join("secrets"),
+    )?;
+
     let validators = make_validators(
         datadir.clone(),
+        secrets_dir,
         &methods,
+        seed.as_ref(),
         deposit_value,
         &context.eth2_config.spec,
         &log,
@@ -245,10 +277,51 @@ fn run_new_validator_subcommand<T: EthSpec>(
     Ok(())
 }
 
+/// Returns the BIP-39 seed of the mnemonic in the file at `--mnemonic-path`.
+///
+/// If no path was supplied, a new 24-word mnemonic is generated and printed to stdout so the user
+/// can back it up.
+fn mnemonic_seed(matches: &ArgMatches) -> Result<PlainText, String> {
+    let mnemonic = if let Some(path) =
+        clap_utils::parse_optional::<PathBuf>(matches, "mnemonic-path")?
+    {
+        let mut phrase = String::new();
+        File::open(&path)
+            .map_err(|e| format!("Unable to open mnemonic file {:?}: {:?}", path, e))?
+            .read_to_string(&mut phrase)
+            .map_err(|e| format!("Unable to read mnemonic file {:?}: {:?}", path, e))?;
+
+        Mnemonic::from_phrase(phrase.trim(), Language::English)
+            .map_err(|e| format!("Unable to parse mnemonic: {:?}", e))?
+    } else {
+        let mnemonic = Mnemonic::new(MnemonicType::Words24, Language::English);
+
+        println!();
+        println!("Your validator keys will be derived from the following mnemonic:");
+        println!();
+        println!("{}", mnemonic.phrase());
+        println!();
+        println!("WARNING: DO NOT SHARE THE MNEMONIC WITH ANYONE, IT CONTROLS YOUR VALIDATORS.");
+        println!("Write it down and store it safely, it is the only way to recover the keys.");
+        println!();
+
+        mnemonic
+    };
+
+    // The seed is generated without a BIP-39 passphrase, as is the convention for eth2 wallets.
+    Ok(PlainText::from(
+        Seed::new(&mnemonic, "").as_bytes().to_vec(),
+    ))
+}
+
 /// Produces a validator directory for each of the key generation methods provided in `methods`.
+///
+/// The keystore passwords are randomly generated and stored in `secrets_dir`.
 fn make_validators(
     datadir: PathBuf,
+    secrets_dir: PathBuf,
     methods: &[KeygenMethod],
+    seed: Option<&PlainText>,
     deposit_value: u64,
     spec: &ChainSpec,
     log: &Logger,
@@ -263,11 +336,17 @@ fn make_validators(
             builder = match method {
                 KeygenMethod::Insecure(index) => builder.insecure_keypairs(*index),
                 KeygenMethod::ThreadRandom => builder.thread_random_keypairs(),
+                KeygenMethod::Derived(index) => {
+                    let seed = seed.ok_or_else(|| "Derived keypairs require a seed".to_string())?;
+                    builder.derived_keypairs(seed.as_bytes(), *index)?
+                }
             };
 
             let validator = builder
+                .random_passwords()
                 .create_directory(datadir.clone())?
                 .write_keypair_files()?
+                .write_password_files(&secrets_dir)?
                 .write_eth1_data_file()?
                 .build()?;
 
@@ -136,6 +136,33 @@ pub enum AttestationProcessingOutcome {
         index: CommitteeIndex,
     },
     Invalid(AttestationValidationError),
+    /// The attestation is the aggregate of a `SignedAggregateAndProof` whose aggregator is
+    /// invalid.
+    InvalidAggregator(InvalidAggregator),
+}
+
+/// The result of submitting an operation (e.g., a voluntary exit) for inclusion in the
+/// operation pool.
+#[derive(Debug, PartialEq)]
+pub enum OperationProcessingOutcome {
+    /// The operation was verified and added to the operation pool.
+    Processed,
+    /// The operation was neither verified nor added to the operation pool, either because this
+    /// node has no eth1 connection or because the wall-clock state was unavailable.
+    NotVerified,
+}
+
+/// The reason the aggregator of a `SignedAggregateAndProof` is invalid.
+#[derive(Debug, PartialEq)]
+pub enum InvalidAggregator {
+    /// The aggregator is not a member of the committee of the aggregate.
+    NotInCommittee(u64),
+    /// The selection proof does not select the aggregator to aggregate for the committee.
+    NotSelected(u64),
+    /// The aggregator index is not a known validator.
+    UnknownValidator(u64),
+    /// The selection proof or the signature over the `AggregateAndProof` is invalid.
+    InvalidSignature,
 }
 
 /// Defines how a `BeaconState` should be "skipped" through skip-slots.
@@ -213,6 +240,8 @@ pub struct BeaconChain<T: BeaconChainTypes> {
     pub(crate) validator_pubkey_cache: TimeoutRwLock<ValidatorPubkeyCache>,
     /// A list of any hard-coded forks that have been disabled.
     pub disabled_forks: Vec<String>,
+    /// A checkpoint that must be included in the finalized chain, if any.
+    pub weak_subjectivity_checkpoint: Option<Checkpoint>,
     /// Logging to CLI, etc.
     pub(crate) log: Logger,
 }
@@ -868,11 +897,41 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
         &self,
         attestation: Attestation<T::EthSpec>,
         attestation_type: AttestationType,
+    ) -> Result<AttestationProcessingOutcome, Error> {
+        self.process_attestation_with_aggregator(attestation, attestation_type, None)
+    }
+
+    /// Accept the aggregate of `signed_aggregate`, as `process_attestation` does for an
+    /// `AttestationType::Aggregated` attestation.
+    ///
+    /// The aggregate is only imported if its aggregator was selected to aggregate for the
+    /// committee of the aggregate and signed both the selection proof and the aggregate.
+    pub fn process_signed_aggregate(
+        &self,
+        signed_aggregate: &SignedAggregateAndProof<T::EthSpec>,
+    ) -> Result<AttestationProcessingOutcome, Error> {
+        self.process_attestation_with_aggregator(
+            signed_aggregate.message.aggregate.clone(),
+            AttestationType::Aggregated,
+            Some(signed_aggregate),
+        )
+    }
+
+    fn process_attestation_with_aggregator(
+        &self,
+        attestation: Attestation<T::EthSpec>,
+        attestation_type: AttestationType,
+        signed_aggregate: Option<&SignedAggregateAndProof<T::EthSpec>>,
     ) -> Result<AttestationProcessingOutcome, Error> {
         metrics::inc_counter(&metrics::ATTESTATION_PROCESSING_REQUESTS);
         let timer = metrics::start_timer(&metrics::ATTESTATION_PROCESSING_TIMES);
 
-        let outcome = self.process_attestation_internal(attestation.clone(), attestation_type);
+        let is_aggregate = attestation_type == AttestationType::Aggregated;
+        let outcome = self.process_attestation_internal(
+            attestation.clone(),
+            attestation_type,
+            signed_aggregate,
+        );
 
         match &outcome {
             Ok(outcome) => match outcome {
@@ -884,6 +943,13 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
                         "target_epoch" => attestation.data.target.epoch,
                         "index" => attestation.data.index,
                     );
+                    if is_aggregate {
+                        let _ = self.event_handler.register(
+                            EventKind::BeaconAggregateAttestationImported {
+                                attestation: Box::new(attestation.clone()),
+                            },
+                        );
+                    }
                     let _ = self
                         .event_handler
                         .register(EventKind::BeaconAttestationImported {
@@ -923,10 +989,15 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
         outcome
     }
 
+    /// Verifies and imports `attestation`.
+    ///
+    /// If `signed_aggregate` is supplied, its aggregator is verified before the attestation is
+    /// imported.
     pub fn process_attestation_internal(
         &self,
         attestation: Attestation<T::EthSpec>,
         attestation_type: AttestationType,
+        signed_aggregate: Option<&SignedAggregateAndProof<T::EthSpec>>,
     ) -> Result<AttestationProcessingOutcome, Error> {
         let initial_validation_timer =
             metrics::start_timer(&metrics::ATTESTATION_PROCESSING_INITIAL_VALIDATION_TIMES);
@@ -1030,6 +1101,12 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
                     let indexed_attestation =
                         get_indexed_attestation(committee.committee, &attestation)?;
 
+                    if let Some(invalid) = signed_aggregate.and_then(|signed_aggregate| {
+                        self.aggregator_selection_error(signed_aggregate, committee.committee)
+                    }) {
+                        return Ok(AttestationProcessingOutcome::InvalidAggregator(invalid));
+                    }
+
                     // Drop the shuffling cache to avoid holding the lock for any longer than
                     // required.
                     drop(shuffling_cache);
@@ -1095,6 +1172,12 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
                 if let Some(committee) = committee_cache
                     .get_beacon_committee(attestation.data.slot, attestation.data.index)
                 {
+                    if let Some(invalid) = signed_aggregate.and_then(|signed_aggregate| {
+                        self.aggregator_selection_error(signed_aggregate, committee.committee)
+                    }) {
+                        return Ok(AttestationProcessingOutcome::InvalidAggregator(invalid));
+                    }
+
                     get_indexed_attestation(committee.committee, &attestation)?
                 } else {
                     return Ok(AttestationProcessingOutcome::NoCommitteeForSlotAndIndex {
@@ -1123,6 +1206,27 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
                 )
             })?;
 
+        // Verify the aggregator before the aggregate, so that an aggregate is never imported on
+        // behalf of an invalid aggregator.
+        if let Some(signed_aggregate) = signed_aggregate {
+            let aggregator_index = signed_aggregate.message.aggregator_index;
+            let invalid_aggregator = match pubkey_cache.get(aggregator_index as usize) {
+                Some(pubkey) => {
+                    if signed_aggregate.is_valid(pubkey, &fork, genesis_validators_root, &self.spec)
+                    {
+                        None
+                    } else {
+                        Some(InvalidAggregator::InvalidSignature)
+                    }
+                }
+                None => Some(InvalidAggregator::UnknownValidator(aggregator_index)),
+            };
+
+            if let Some(invalid) = invalid_aggregator {
+                return Ok(AttestationProcessingOutcome::InvalidAggregator(invalid));
+            }
+        }
+
         let signature_set = indexed_attestation_signature_set_from_pubkeys(
             |validator_index| pubkey_cache.get(validator_index).map(Cow::Borrowed),
             &attestation.signature,
@@ -1305,81 +1409,114 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
         }
     }
 
-    /// Accept some exit and queue it for inclusion in an appropriate block.
-    pub fn process_voluntary_exit(
-        &self,
-        exit: SignedVoluntaryExit,
-    ) -> Result<(), ExitValidationError> {
+    /// Returns the state that operations are verified against before they are added to the
+    /// operation pool, or `None` if they cannot be verified.
+    ///
+    /// Operations are only verified if the node has an eth1 connection, as only such a node can
+    /// produce blocks that include them.
+    fn operation_verification_state(&self, operation: &str) -> Option<BeaconState<T::EthSpec>> {
         match self.wall_clock_state() {
-            Ok(state) => {
-                if self.eth1_chain.is_some() {
-                    self.op_pool.insert_voluntary_exit(exit, &state, &self.spec)
-                } else {
-                    Ok(())
-                }
-            }
+            Ok(state) => Some(state).filter(|_| self.eth1_chain.is_some()),
             Err(e) => {
                 error!(
                     &self.log,
-                    "Unable to process voluntary exit";
+                    "Unable to process operation";
+                    "operation" => operation,
                     "error" => format!("{:?}", e),
                     "reason" => "no state"
                 );
-                Ok(())
+                None
             }
         }
     }
 
+    /// Accept some exit and queue it for inclusion in an appropriate block.
+    pub fn process_voluntary_exit(
+        &self,
+        exit: SignedVoluntaryExit,
+    ) -> Result<OperationProcessingOutcome, ExitValidationError> {
+        let state = match self.operation_verification_state("voluntary exit") {
+            Some(state) => state,
+            None => return Ok(OperationProcessingOutcome::NotVerified),
+        };
+
+        self.op_pool
+            .insert_voluntary_exit(exit.clone(), &state, &self.spec)?;
+        let _ = self
+            .event_handler
+            .register(EventKind::VoluntaryExitImported {
+                voluntary_exit: Box::new(exit),
+            });
+        Ok(OperationProcessingOutcome::Processed)
+    }
+
     /// Accept some proposer slashing and queue it for inclusion in an appropriate block.
     pub fn process_proposer_slashing(
         &self,
         proposer_slashing: ProposerSlashing,
-    ) -> Result<(), ProposerSlashingValidationError> {
-        match self.wall_clock_state() {
-            Ok(state) => {
-                if self.eth1_chain.is_some() {
-                    self.op_pool
-                        .insert_proposer_slashing(proposer_slashing, &state, &self.spec)
-                } else {
-                    Ok(())
-                }
-            }
-            Err(e) => {
-                error!(
-                    &self.log,
-                    "Unable to process proposer slashing";
-                    "error" => format!("{:?}", e),
-                    "reason" => "no state"
-                );
-                Ok(())
-            }
-        }
+    ) -> Result<OperationProcessingOutcome, ProposerSlashingValidationError> {
+        let state = match self.operation_verification_state("proposer slashing") {
+            Some(state) => state,
+            None => return Ok(OperationProcessingOutcome::NotVerified),
+        };
+
+        self.op_pool
+            .insert_proposer_slashing(proposer_slashing.clone(), &state, &self.spec)?;
+        let _ = self
+            .event_handler
+            .register(EventKind::ProposerSlashingImported {
+                proposer_slashing: Box::new(proposer_slashing),
+            });
+        Ok(OperationProcessingOutcome::Processed)
     }
 
     /// Accept some attester slashing and queue it for inclusion in an appropriate block.
     pub fn process_attester_slashing(
         &self,
         attester_slashing: AttesterSlashing<T::EthSpec>,
-    ) -> Result<(), AttesterSlashingValidationError> {
-        match self.wall_clock_state() {
-            Ok(state) => {
-                if self.eth1_chain.is_some() {
-                    self.op_pool
-                        .insert_attester_slashing(attester_slashing, &state, &self.spec)
-                } else {
-                    Ok(())
-                }
-            }
-            Err(e) => {
-                error!(
-                    &self.log,
-                    "Unable to process attester slashing";
-                    "error" => format!("{:?}", e),
-                    "reason" => "no state"
-                );
-                Ok(())
-            }
+    ) -> Result<OperationProcessingOutcome, AttesterSlashingValidationError> {
+        let state = match self.operation_verification_state("attester slashing") {
+            Some(state) => state,
+            None => return Ok(OperationProcessingOutcome::NotVerified),
+        };
+
+        self.op_pool
+            .insert_attester_slashing(attester_slashing.clone(), &state, &self.spec)?;
+        let _ = self
+            .event_handler
+            .register(EventKind::AttesterSlashingImported {
+                attester_slashing: Box::new(attester_slashing),
+            });
+        Ok(OperationProcessingOutcome::Processed)
+    }
+
+    /// Returns the reason the aggregator of `signed_aggregate` may not aggregate for
+    /// `committee`, if any.
+    ///
+    /// The signatures of the aggregator are not verified.
+    fn aggregator_selection_error(
+        &self,
+        signed_aggregate: &SignedAggregateAndProof<T::EthSpec>,
+        committee: &[usize],
+    ) -> Option<InvalidAggregator> {
+        let aggregate_and_proof = &signed_aggregate.message;
+        let aggregator_index = aggregate_and_proof.aggregator_index;
+
+        if !committee.contains(&(aggregator_index as usize)) {
+            return Some(InvalidAggregator::NotInCommittee(aggregator_index));
+        }
+
+        let modulo = std::cmp::max(
+            1,
+            committee.len() as u64 / self.spec.target_aggregators_per_committee,
+        );
+        let selection_proof = SelectionProof::from(aggregate_and_proof.selection_proof.clone());
+        // The modulo is never zero, so the selection cannot fail.
+        if !selection_proof.is_aggregator(modulo).unwrap_or(false) {
+            return Some(InvalidAggregator::NotSelected(aggregator_index));
         }
+
+        None
     }
 
     /// Attempt to verify and import a chain of blocks to `self`.
@@ -1981,11 +2118,29 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
                 .map(|root| *root)
                 .unwrap_or_else(|_| Hash256::random());
 
+        // The latest block shared by the previous and the new head, if the new head is a re-org.
+        let reorg_common_ancestor = if is_reorg {
+            self.find_common_ancestor(current_head.block_root, &new_head.beacon_state)
+                .unwrap_or_else(|e| {
+                    warn!(
+                        self.log,
+                        "Unable to find re-org common ancestor";
+                        "error" => format!("{:?}", e)
+                    );
+                    None
+                })
+        } else {
+            None
+        };
+
         if is_reorg {
             metrics::inc_counter(&metrics::FORK_CHOICE_REORG_COUNT);
             warn!(
                 self.log,
                 "Beacon chain re-org";
+                "depth" => reorg_common_ancestor
+                    .map(|(_, slot)| format!("{}", current_head.slot.saturating_sub(slot)))
+                    .unwrap_or_else(|| "unknown".to_string()),
                 "previous_head" => format!("{}", current_head.block_root),
                 "previous_slot" => current_head.slot,
                 "new_head_parent" => format!("{}", new_head.beacon_block.parent_root()),
@@ -2005,6 +2160,9 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
             );
         };
 
+        let old_justified_epoch = current_head.current_justified_checkpoint.epoch;
+        let new_justified_checkpoint = new_head.beacon_state.current_justified_checkpoint.clone();
+
         let old_finalized_epoch = current_head.finalized_checkpoint.epoch;
         let new_finalized_epoch = new_head.beacon_state.finalized_checkpoint.epoch;
         let finalized_root = new_head.beacon_state.finalized_checkpoint.root;
@@ -2017,6 +2175,11 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
             });
         }
 
+        // Never finalize a chain that conflicts with the weak subjectivity checkpoint.
+        if new_finalized_epoch != old_finalized_epoch {
+            self.verify_weak_subjectivity_checkpoint(&new_head.beacon_state)?;
+        }
+
         if current_head.slot.epoch(T::EthSpec::slots_per_epoch())
             < new_head
                 .beacon_state
@@ -2066,9 +2229,118 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
             current_head_beacon_block_root: beacon_block_root,
         });
 
+        if let Some((common_ancestor_root, common_ancestor_slot)) = reorg_common_ancestor {
+            let _ = self.event_handler.register(EventKind::BeaconChainReorg {
+                depth: current_head
+                    .slot
+                    .saturating_sub(common_ancestor_slot)
+                    .as_u64(),
+                previous_head_beacon_block_root: current_head.block_root,
+                current_head_beacon_block_root: beacon_block_root,
+                common_ancestor_beacon_block_root: common_ancestor_root,
+                common_ancestor_slot,
+            });
+        }
+
+        if new_justified_checkpoint.epoch > old_justified_epoch {
+            let _ = self.event_handler.register(EventKind::BeaconJustification {
+                epoch: new_justified_checkpoint.epoch,
+                root: new_justified_checkpoint.root,
+            });
+        }
+
         Ok(())
     }
 
+    /// Returns the root and slot of the latest block that is an ancestor of both `old_head_root`
+    /// and the head block of `new_head_state`.
+    ///
+    /// Returns `None` if the common ancestor is older than the block roots kept in
+    /// `new_head_state`.
+    fn find_common_ancestor(
+        &self,
+        old_head_root: Hash256,
+        new_head_state: &BeaconState<T::EthSpec>,
+    ) -> Result<Option<(Hash256, Slot)>, Error> {
+        for (root, slot) in self.rev_iter_block_roots_from(old_head_root)? {
+            if slot >= new_head_state.slot {
+                continue;
+            }
+            match new_head_state.get_block_root(slot) {
+                Ok(new_chain_root) if *new_chain_root == root => {
+                    // skipped slots repeat the root of the prior block, so read the block's slot
+                    let block_slot = self
+                        .get_block(&root)?
+                        .map(|block| block.slot())
+                        .unwrap_or(slot);
+                    return Ok(Some((root, block_slot)));
+                }
+                Ok(_) => continue,
+                Err(_) => return Ok(None),
+            }
+        }
+        Ok(None)
+    }
+
+    /// Returns an error if the finalized chain of `state` does not include the weak subjectivity
+    /// checkpoint.
+    ///
+    /// States which have not yet finalized the epoch of the checkpoint always pass.
+    pub fn verify_weak_subjectivity_checkpoint(
+        &self,
+        state: &BeaconState<T::EthSpec>,
+    ) -> Result<(), Error> {
+        let checkpoint = match &self.weak_subjectivity_checkpoint {
+            Some(checkpoint) => checkpoint,
+            None => return Ok(()),
+        };
+
+        if state.finalized_checkpoint.epoch < checkpoint.epoch {
+            return Ok(());
+        }
+
+        let ancestor_root = if state.finalized_checkpoint.epoch == checkpoint.epoch {
+            Some(state.finalized_checkpoint.root)
+        } else {
+            let checkpoint_slot = checkpoint.epoch.start_slot(T::EthSpec::slots_per_epoch());
+            match state.get_block_root(checkpoint_slot) {
+                Ok(root) => Some(*root),
+                // The state no longer holds the root, so it must be read from the freezer.
+                Err(BeaconStateError::SlotOutOfBounds) => {
+                    self.store.get_frozen_block_root(checkpoint_slot)?
+                }
+                Err(e) => return Err(e.into()),
+            }
+        };
+
+        match ancestor_root {
+            Some(ancestor_root) if ancestor_root != checkpoint.root => {
+                crit!(
+                    self.log,
+                    "Finalized chain conflicts with weak subjectivity checkpoint";
+                    "msg" => "the node may be following an attacker's chain",
+                    "checkpoint_epoch" => checkpoint.epoch,
+                    "checkpoint_root" => format!("{:?}", checkpoint.root),
+                    "finalized_root" => format!("{:?}", ancestor_root),
+                );
+                Err(Error::WeakSubjectivityCheckpointConflict {
+                    checkpoint: checkpoint.clone(),
+                    ancestor_root,
+                })
+            }
+            Some(_) => Ok(()),
+            None => {
+                warn!(
+                    self.log,
+                    "Unable to verify weak subjectivity checkpoint";
+                    "msg" => "no block is known at the checkpoint slot",
+                    "checkpoint_epoch" => checkpoint.epoch,
+                );
+                Ok(())
+            }
+        }
+    }
+
     /// Called by the timer on every slot.
     ///
     /// Performs slot-based pruning.
@@ -60,11 +60,11 @@ use state_processing::{
 use std::borrow::Cow;
 use std::fs;
 use std::io::Write;
-use store::{Error as DBError, StateBatch};
+use store::{Error as DBError, StateBatch, Store};
 use tree_hash::TreeHash;
 use types::{
-    BeaconBlock, BeaconState, BeaconStateError, ChainSpec, CloneConfig, EthSpec, Hash256,
-    PublicKey, RelativeEpoch, SignedBeaconBlock, Slot,
+    BeaconBlock, BeaconState, BeaconStateError, ChainSpec, Checkpoint, CloneConfig, EthSpec,
+    Hash256, PublicKey, RelativeEpoch, SignedBeaconBlock, Slot,
 };
 
 mod block_processing_outcome;
@@ -120,6 +120,12 @@ pub enum BlockError {
     NonLinearSlots,
     /// The block failed the specification's `per_block_processing` function, it is invalid.
     PerBlockProcessingError(BlockProcessingError),
+    /// The block does not descend from the weak subjectivity checkpoint. Its ancestor at the slot
+    /// of the checkpoint (possibly the block itself) is `ancestor_root`.
+    WeakSubjectivityConflict {
+        checkpoint: Checkpoint,
+        ancestor_root: Hash256,
+    },
     /// There was an error whilst processing the block. It is not necessarily invalid.
     BeaconChainError(BeaconChainError),
 }
@@ -514,6 +520,12 @@ impl<T: BeaconChainTypes> FullyVerifiedBlock<T> {
 
         metrics::stop_timer(catchup_timer);
 
+        /*
+         * Reject any block that conflicts with the weak subjectivity checkpoint.
+         */
+
+        check_block_against_weak_subjectivity_checkpoint(&block, block_root, &state, chain)?;
+
         /*
          * Build the committee caches on the state.
          */
@@ -608,6 +620,10 @@ fn check_block_against_finalized_slot<T: BeaconChainTypes>(
         .epoch
         .start_slot(T::EthSpec::slots_per_epoch());
 
+    // A chain started from a checkpoint knows no blocks prior to its anchor, which may be later
+    // than the finalized checkpoint of the head state. Those blocks are treated as finalized.
+    let finalized_slot = std::cmp::max(finalized_slot, chain.store.get_split_slot());
+
     if block.slot <= finalized_slot {
         Err(BlockError::WouldRevertFinalizedSlot {
             block_slot: block.slot,
@@ -618,6 +634,46 @@ fn check_block_against_finalized_slot<T: BeaconChainTypes>(
     }
 }
 
+/// Returns `Ok(())` if the block descends from the weak subjectivity checkpoint of `chain`, or if
+/// it is prior to the checkpoint.
+///
+/// The `state` must be the parent state of the block, advanced to the slot of the block.
+fn check_block_against_weak_subjectivity_checkpoint<T: BeaconChainTypes>(
+    block: &SignedBeaconBlock<T::EthSpec>,
+    block_root: Hash256,
+    state: &BeaconState<T::EthSpec>,
+    chain: &BeaconChain<T>,
+) -> Result<(), BlockError> {
+    let checkpoint = match &chain.weak_subjectivity_checkpoint {
+        Some(checkpoint) => checkpoint,
+        None => return Ok(()),
+    };
+    let checkpoint_slot = checkpoint.epoch.start_slot(T::EthSpec::slots_per_epoch());
+
+    let ancestor_root = if block.slot() < checkpoint_slot {
+        return Ok(());
+    } else if block.slot() == checkpoint_slot {
+        block_root
+    } else {
+        match state.get_block_root(checkpoint_slot) {
+            Ok(root) => *root,
+            // The checkpoint is too far behind the block to be checked using the state. Fork
+            // choice will refuse to finalize a conflicting chain.
+            Err(BeaconStateError::SlotOutOfBounds) => return Ok(()),
+            Err(e) => return Err(e.into()),
+        }
+    };
+
+    if ancestor_root == checkpoint.root {
+        Ok(())
+    } else {
+        Err(BlockError::WeakSubjectivityConflict {
+            checkpoint: checkpoint.clone(),
+            ancestor_root,
+        })
+    }
+}
+
 /// Performs simple, cheap checks to ensure that the block is relevant to be imported.
 ///
 /// `Ok(block_root)` is returned if the block passes these checks and should progress with
@@ -1,6 +1,6 @@
 use crate::{BeaconChainError, BlockError};
 use state_processing::BlockProcessingError;
-use types::{Hash256, Slot};
+use types::{Checkpoint, Hash256, Slot};
 
 /// This is a legacy object that is being kept around to reduce merge conflicts.
 ///
@@ -50,6 +50,11 @@ pub enum BlockProcessingOutcome {
     NonLinearSlots,
     /// The block could not be applied to the state, it is invalid.
     PerBlockProcessingError(BlockProcessingError),
+    /// The block does not descend from the weak subjectivity checkpoint.
+    WeakSubjectivityConflict {
+        checkpoint: Checkpoint,
+        ancestor_root: Hash256,
+    },
 }
 
 impl BlockProcessingOutcome {
@@ -99,6 +104,13 @@ impl BlockProcessingOutcome {
             Err(BlockError::PerBlockProcessingError(e)) => {
                 Ok(BlockProcessingOutcome::PerBlockProcessingError(e))
             }
+            Err(BlockError::WeakSubjectivityConflict {
+                checkpoint,
+                ancestor_root,
+            }) => Ok(BlockProcessingOutcome::WeakSubjectivityConflict {
+                checkpoint,
+                ancestor_root,
+            }),
             Err(BlockError::BeaconChainError(e)) => Err(e),
         }
     }
@@ -26,7 +26,8 @@ use std::sync::Arc;
 use std::time::Duration;
 use store::Store;
 use types::{
-    BeaconBlock, BeaconState, ChainSpec, EthSpec, Hash256, Signature, SignedBeaconBlock, Slot,
+    BeaconBlock, BeaconState, ChainSpec, Checkpoint, EthSpec, Hash256, Signature,
+    SignedBeaconBlock, Slot,
 };
 
 pub const PUBKEY_CACHE_FILENAME: &str = "pubkey_cache.ssz";
@@ -89,6 +90,7 @@ pub struct BeaconChainBuilder<T: BeaconChainTypes> {
     validator_pubkey_cache: Option<ValidatorPubkeyCache>,
     spec: ChainSpec,
     disabled_forks: Vec<String>,
+    weak_subjectivity_checkpoint: Option<Checkpoint>,
     log: Option<Logger>,
 }
 
@@ -124,6 +126,7 @@ where
             pubkey_cache_path: None,
             data_dir: None,
             disabled_forks: Vec::new(),
+            weak_subjectivity_checkpoint: None,
             validator_pubkey_cache: None,
             spec: TEthSpec::default_spec(),
             log: None,
@@ -176,6 +179,12 @@ where
         self
     }
 
+    /// Sets a checkpoint which the finalized chain must include.
+    pub fn weak_subjectivity_checkpoint(mut self, checkpoint: Option<Checkpoint>) -> Self {
+        self.weak_subjectivity_checkpoint = checkpoint;
+        self
+    }
+
     /// Attempt to load an existing eth1 cache from the builder's `Store`.
     pub fn get_persisted_eth1_backend(&self) -> Result<Option<SszEth1>, String> {
         let store = self
@@ -338,6 +347,73 @@ where
         Ok(self.empty_op_pool())
     }
 
+    /// Starts a new chain from a trusted, finalized `beacon_state` and the `beacon_block` that
+    /// produced it, rather than from genesis.
+    ///
+    /// The anchor acts as genesis for the purposes of fork choice. No blocks or states prior to
+    /// the anchor are known to the chain.
+    pub fn weak_subjectivity_state(
+        mut self,
+        mut beacon_state: BeaconState<TEthSpec>,
+        beacon_block: SignedBeaconBlock<TEthSpec>,
+    ) -> Result<Self, String> {
+        let store = self
+            .store
+            .clone()
+            .ok_or_else(|| "weak_subjectivity_state requires a store")?;
+
+        if beacon_state.slot % TEthSpec::slots_per_epoch() != 0 {
+            return Err(format!(
+                "Checkpoint state at slot {} does not lie on an epoch boundary",
+                beacon_state.slot
+            ));
+        }
+
+        if beacon_block.slot() != beacon_state.slot {
+            return Err(format!(
+                "Checkpoint block slot {} does not match checkpoint state slot {}",
+                beacon_block.slot(),
+                beacon_state.slot
+            ));
+        }
+
+        beacon_state
+            .build_all_caches(&self.spec)
+            .map_err(|e| format!("Failed to build checkpoint state caches: {:?}", e))?;
+
+        let beacon_state_root = beacon_state
+            .update_tree_hash_cache()
+            .map_err(|e| format!("Error hashing checkpoint state: {:?}", e))?;
+
+        if beacon_block.state_root() != beacon_state_root {
+            return Err(format!(
+                "Checkpoint block state root {:?} does not match checkpoint state root {:?}",
+                beacon_block.state_root(),
+                beacon_state_root
+            ));
+        }
+
+        let beacon_block_root = beacon_block.canonical_root();
+
+        self.genesis_block_root = Some(beacon_block_root);
+
+        store
+            .init_anchor_state(&beacon_state_root, &beacon_state)
+            .map_err(|e| format!("Failed to store checkpoint state: {:?}", e))?;
+        store
+            .put(&beacon_block_root, &beacon_block)
+            .map_err(|e| format!("Failed to store checkpoint block: {:?}", e))?;
+
+        self.finalized_snapshot = Some(BeaconSnapshot {
+            beacon_block_root,
+            beacon_block,
+            beacon_state_root,
+            beacon_state,
+        });
+
+        Ok(self.empty_op_pool())
+    }
+
     /// Sets the `BeaconChain` eth1 backend.
     pub fn eth1_backend(mut self, backend: Option<TEth1Backend>) -> Self {
         self.eth1_chain = backend.map(Eth1Chain::new);
@@ -451,6 +527,7 @@ where
             shuffling_cache: TimeoutRwLock::new(ShufflingCache::new()),
             validator_pubkey_cache: TimeoutRwLock::new(validator_pubkey_cache),
             disabled_forks: self.disabled_forks,
+            weak_subjectivity_checkpoint: self.weak_subjectivity_checkpoint,
             log: log.clone(),
         };
 
@@ -458,6 +535,16 @@ where
             .head()
             .map_err(|e| format!("Failed to get head: {:?}", e))?;
 
+        beacon_chain
+            .verify_weak_subjectivity_checkpoint(&head.beacon_state)
+            .map_err(|e| {
+                format!(
+                    "Unable to verify the weak subjectivity checkpoint against the stored \
+                     chain, the database may need to be purged: {:?}",
+                    e
+                )
+            })?;
+
         info!(
             log,
             "Beacon chain initialized";
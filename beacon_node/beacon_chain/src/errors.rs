@@ -30,6 +30,11 @@ pub enum BeaconChainError {
         previous_epoch: Epoch,
         new_epoch: Epoch,
     },
+    /// The finalized chain does not include the weak subjectivity checkpoint.
+    WeakSubjectivityCheckpointConflict {
+        checkpoint: Checkpoint,
+        ancestor_root: Hash256,
+    },
     SlotClockDidNotStart,
     NoStateForSlot(Slot),
     UnableToFindTargetRoot(Slot),
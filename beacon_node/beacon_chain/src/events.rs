@@ -1,8 +1,19 @@
+use futures::sync::mpsc;
+use parking_lot::Mutex;
 use serde_derive::{Deserialize, Serialize};
+use std::collections::HashSet;
 use std::marker::PhantomData;
-use types::{Attestation, Epoch, EthSpec, Hash256, SignedBeaconBlock};
+use std::sync::Arc;
+use types::{
+    Attestation, AttesterSlashing, Epoch, EthSpec, Hash256, ProposerSlashing, SignedBeaconBlock,
+    SignedVoluntaryExit, Slot,
+};
 pub use websocket_server::WebSocketSender;
 
+/// The number of events buffered for each server-sent event subscriber. Subscribers that fall
+/// this far behind are disconnected.
+pub const SERVER_SENT_EVENT_BUFFER_SIZE: usize = 256;
+
 pub trait EventHandler<T: EthSpec>: Sized + Send + Sync {
     fn register(&self, kind: EventKind<T>) -> Result<(), String>;
 }
@@ -18,6 +29,112 @@ impl<T: EthSpec> EventHandler<T> for WebSocketSender<T> {
     }
 }
 
+/// A subscriber to the server-sent event stream.
+struct Subscriber {
+    topics: HashSet<String>,
+    sender: mpsc::Sender<String>,
+}
+
+/// Publishes events to the subscribers of the HTTP API event stream, each receiving only the
+/// topics it subscribed to.
+pub struct ServerSentEventHandler<T: EthSpec> {
+    subscribers: Mutex<Vec<Subscriber>>,
+    _phantom: PhantomData<T>,
+}
+
+impl<T: EthSpec> Default for ServerSentEventHandler<T> {
+    fn default() -> Self {
+        Self {
+            subscribers: Mutex::new(vec![]),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<T: EthSpec> ServerSentEventHandler<T> {
+    /// Returns a stream of the events of the given topics, each formatted as a server-sent event.
+    ///
+    /// Returns an error if any of the topics is unknown.
+    pub fn subscribe(&self, topics: HashSet<String>) -> Result<mpsc::Receiver<String>, String> {
+        if let Some(topic) = topics
+            .iter()
+            .find(|topic| !EventKind::<T>::TOPICS.contains(&topic.as_str()))
+        {
+            return Err(format!(
+                "Unknown event topic: {}. Expected one of: {}",
+                topic,
+                EventKind::<T>::TOPICS.join(", ")
+            ));
+        }
+
+        let (sender, receiver) = mpsc::channel(SERVER_SENT_EVENT_BUFFER_SIZE);
+        self.subscribers.lock().push(Subscriber { topics, sender });
+        Ok(receiver)
+    }
+
+    /// Returns the number of connected subscribers.
+    pub fn subscriber_count(&self) -> usize {
+        self.subscribers.lock().len()
+    }
+}
+
+impl<T: EthSpec> EventHandler<T> for ServerSentEventHandler<T> {
+    fn register(&self, kind: EventKind<T>) -> Result<(), String> {
+        let mut subscribers = self.subscribers.lock();
+        let topic = kind.topic();
+
+        if !subscribers
+            .iter()
+            .any(|subscriber| subscriber.topics.contains(topic))
+        {
+            return Ok(());
+        }
+
+        let event = kind.to_server_sent_event()?;
+        // drops subscribers that have disconnected or are not keeping up with the stream
+        *subscribers = subscribers
+            .drain(..)
+            .filter_map(|mut subscriber| {
+                if !subscriber.topics.contains(topic)
+                    || subscriber.sender.try_send(event.clone()).is_ok()
+                {
+                    Some(subscriber)
+                } else {
+                    None
+                }
+            })
+            .collect();
+
+        Ok(())
+    }
+}
+
+/// Publishes events to both the WebSocket server and the HTTP API event stream.
+pub struct TeeEventHandler<T: EthSpec> {
+    websocket_handler: WebSocketSender<T>,
+    server_sent_event_handler: Arc<ServerSentEventHandler<T>>,
+}
+
+impl<T: EthSpec> TeeEventHandler<T> {
+    pub fn new(
+        websocket_handler: WebSocketSender<T>,
+        server_sent_event_handler: Arc<ServerSentEventHandler<T>>,
+    ) -> Self {
+        Self {
+            websocket_handler,
+            server_sent_event_handler,
+        }
+    }
+}
+
+impl<T: EthSpec> EventHandler<T> for TeeEventHandler<T> {
+    fn register(&self, kind: EventKind<T>) -> Result<(), String> {
+        let websocket_result = self.websocket_handler.register(kind.clone());
+        self.server_sent_event_handler.register(kind)?;
+        websocket_result
+    }
+}
+
 impl<T: EthSpec> EventHandler<T> for NullEventHandler<T> {
     fn register(&self, _kind: EventKind<T>) -> Result<(), String> {
         Ok(())
@@ -30,7 +147,7 @@ impl<T: EthSpec> Default for NullEventHandler<T> {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(
     bound = "T: EthSpec",
     rename_all = "snake_case",
@@ -43,6 +160,18 @@ pub enum EventKind<T: EthSpec> {
         current_head_beacon_block_root: Hash256,
         previous_head_beacon_block_root: Hash256,
     },
+    BeaconChainReorg {
+        /// The number of slots from the common ancestor to the previous head.
+        depth: u64,
+        current_head_beacon_block_root: Hash256,
+        previous_head_beacon_block_root: Hash256,
+        common_ancestor_beacon_block_root: Hash256,
+        common_ancestor_slot: Slot,
+    },
+    BeaconJustification {
+        epoch: Epoch,
+        root: Hash256,
+    },
     BeaconFinalization {
         epoch: Epoch,
         root: Hash256,
@@ -62,4 +191,144 @@ pub enum EventKind<T: EthSpec> {
         reason: String,
         attestation: Box<Attestation<T>>,
     },
+    BeaconAggregateAttestationImported {
+        attestation: Box<Attestation<T>>,
+    },
+    VoluntaryExitImported {
+        voluntary_exit: Box<SignedVoluntaryExit>,
+    },
+    ProposerSlashingImported {
+        proposer_slashing: Box<ProposerSlashing>,
+    },
+    AttesterSlashingImported {
+        attester_slashing: Box<AttesterSlashing<T>>,
+    },
+    SyncStateChanged {
+        /// Whether the node has started a long-range sync, rather than finished one.
+        syncing: bool,
+    },
+}
+
+impl<T: EthSpec> EventKind<T> {
+    /// The topics that can be subscribed to on the HTTP API event stream.
+    pub const TOPICS: &'static [&'static str] = &[
+        "head",
+        "chain_reorg",
+        "block",
+        "block_rejected",
+        "justified_checkpoint",
+        "finalized_checkpoint",
+        "attestation",
+        "attestation_rejected",
+        "aggregate_attestation",
+        "voluntary_exit",
+        "proposer_slashing",
+        "attester_slashing",
+        "sync_state",
+    ];
+
+    /// Returns the topic of the event on the HTTP API event stream.
+    pub fn topic(&self) -> &'static str {
+        match self {
+            EventKind::BeaconHeadChanged { .. } => "head",
+            EventKind::BeaconChainReorg { .. } => "chain_reorg",
+            EventKind::BeaconJustification { .. } => "justified_checkpoint",
+            EventKind::BeaconFinalization { .. } => "finalized_checkpoint",
+            EventKind::BeaconBlockImported { .. } => "block",
+            EventKind::BeaconBlockRejected { .. } => "block_rejected",
+            EventKind::BeaconAttestationImported { .. } => "attestation",
+            EventKind::BeaconAttestationRejected { .. } => "attestation_rejected",
+            EventKind::BeaconAggregateAttestationImported { .. } => "aggregate_attestation",
+            EventKind::VoluntaryExitImported { .. } => "voluntary_exit",
+            EventKind::ProposerSlashingImported { .. } => "proposer_slashing",
+            EventKind::AttesterSlashingImported { .. } => "attester_slashing",
+            EventKind::SyncStateChanged { .. } => "sync_state",
+        }
+    }
+
+    /// Formats the event as a server-sent event, with the topic as the event type and the JSON
+    /// encoded event data.
+    pub fn to_server_sent_event(&self) -> Result<String, String> {
+        let mut value = serde_json::to_value(self)
+            .map_err(|e| format!("Unable to serialize event: {:?}", e))?;
+        let data = value
+            .get_mut("data")
+            .map(serde_json::Value::take)
+            .unwrap_or(serde_json::Value::Null);
+
+        Ok(format!("event: {}\ndata: {}\n\n", self.topic(), data))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::{Future, Stream};
+    use types::MinimalEthSpec;
+
+    type E = MinimalEthSpec;
+
+    fn head_event() -> EventKind<E> {
+        EventKind::BeaconHeadChanged {
+            reorg: false,
+            current_head_beacon_block_root: Hash256::repeat_byte(1),
+            previous_head_beacon_block_root: Hash256::repeat_byte(0),
+        }
+    }
+
+    fn finalization_event() -> EventKind<E> {
+        EventKind::BeaconFinalization {
+            epoch: Epoch::new(2),
+            root: Hash256::repeat_byte(2),
+        }
+    }
+
+    fn topics(topics: &[&str]) -> HashSet<String> {
+        topics.iter().map(|topic| topic.to_string()).collect()
+    }
+
+    #[test]
+    fn server_sent_events_are_filtered_by_topic() {
+        let handler = ServerSentEventHandler::<E>::default();
+        let head_receiver = handler.subscribe(topics(&["head"])).unwrap();
+        let all_receiver = handler
+            .subscribe(topics(&["head", "finalized_checkpoint"]))
+            .unwrap();
+
+        handler.register(head_event()).unwrap();
+        handler.register(finalization_event()).unwrap();
+        drop(handler);
+
+        let head_events = head_receiver.collect().wait().unwrap();
+        assert_eq!(
+            head_events,
+            vec![head_event().to_server_sent_event().unwrap()]
+        );
+        assert!(head_events[0].starts_with("event: head\ndata: {"));
+
+        let all_events = all_receiver.collect().wait().unwrap();
+        assert_eq!(all_events.len(), 2);
+        assert!(all_events[1].starts_with("event: finalized_checkpoint\n"));
+    }
+
+    #[test]
+    fn slow_server_sent_event_subscribers_are_dropped() {
+        let handler = ServerSentEventHandler::<E>::default();
+        let _receiver = handler.subscribe(topics(&["head"])).unwrap();
+
+        for _ in 0..=SERVER_SENT_EVENT_BUFFER_SIZE {
+            handler.register(head_event()).unwrap();
+        }
+        assert_eq!(handler.subscriber_count(), 1);
+
+        handler.register(head_event()).unwrap();
+        assert_eq!(handler.subscriber_count(), 0);
+    }
+
+    #[test]
+    fn unknown_server_sent_event_topics_are_rejected() {
+        let handler = ServerSentEventHandler::<E>::default();
+        assert!(handler.subscribe(topics(&["head", "blocks"])).is_err());
+        assert_eq!(handler.subscriber_count(), 0);
+    }
 }
@@ -23,7 +23,7 @@ mod validator_pubkey_cache;
 
 pub use self::beacon_chain::{
     AttestationProcessingOutcome, AttestationType, BeaconChain, BeaconChainTypes,
-    ChainSegmentResult, StateSkipConfig,
+    ChainSegmentResult, InvalidAggregator, OperationProcessingOutcome, StateSkipConfig,
 };
 pub use self::beacon_snapshot::BeaconSnapshot;
 pub use self::errors::{BeaconChainError, BlockProductionError};
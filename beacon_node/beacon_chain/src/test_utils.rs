@@ -6,10 +6,11 @@ pub use crate::persisted_beacon_chain::PersistedBeaconChain;
 use crate::{
     builder::{BeaconChainBuilder, Witness},
     eth1_chain::CachingEth1Backend,
-    events::NullEventHandler,
+    events::{EventHandler, EventKind, NullEventHandler},
     AttestationProcessingOutcome, AttestationType, BeaconChain, BeaconChainTypes, StateSkipConfig,
 };
 use genesis::interop_genesis_state;
+use parking_lot::Mutex;
 use rayon::prelude::*;
 use sloggers::{null::NullLoggerBuilder, Build};
 use slot_clock::TestingSlotClock;
@@ -22,9 +23,9 @@ use store::{DiskStore, MemoryStore, Store};
 use tempfile::{tempdir, TempDir};
 use tree_hash::TreeHash;
 use types::{
-    AggregateSignature, Attestation, BeaconState, BeaconStateHash, ChainSpec, Domain, EthSpec,
-    Hash256, Keypair, SecretKey, Signature, SignedBeaconBlock, SignedBeaconBlockHash, SignedRoot,
-    Slot,
+    AggregateSignature, Attestation, BeaconState, BeaconStateHash, ChainSpec, Checkpoint, Domain,
+    EthSpec, Hash256, Keypair, SecretKey, Signature, SignedBeaconBlock, SignedBeaconBlockHash,
+    SignedRoot, Slot,
 };
 
 pub use types::test_utils::generate_deterministic_keypairs;
@@ -34,17 +35,51 @@ pub const HARNESS_GENESIS_TIME: u64 = 1_567_552_690;
 // This parameter is required by a builder but not used because we use the `TestingSlotClock`.
 pub const HARNESS_SLOT_TIME: Duration = Duration::from_secs(1);
 
-pub type BaseHarnessType<TStore, TStoreMigrator, TEthSpec> = Witness<
+pub type BaseHarnessType<
+    TStore,
+    TStoreMigrator,
+    TEthSpec,
+    TEventHandler = NullEventHandler<TEthSpec>,
+> = Witness<
     TStore,
     TStoreMigrator,
     TestingSlotClock,
     CachingEth1Backend<TEthSpec, TStore>,
     TEthSpec,
-    NullEventHandler<TEthSpec>,
+    TEventHandler,
 >;
 
 pub type HarnessType<E> = BaseHarnessType<MemoryStore<E>, NullMigrator, E>;
 pub type DiskHarnessType<E> = BaseHarnessType<DiskStore<E>, BlockingMigrator<DiskStore<E>>, E>;
+pub type EventRecordingHarnessType<E> =
+    BaseHarnessType<MemoryStore<E>, NullMigrator, E, EventRecorder<E>>;
+
+/// An event handler which keeps every event registered with it, so tests can inspect them.
+pub struct EventRecorder<E: EthSpec> {
+    events: Mutex<Vec<EventKind<E>>>,
+}
+
+impl<E: EthSpec> EventRecorder<E> {
+    /// Returns all events registered since the last call, oldest first.
+    pub fn take(&self) -> Vec<EventKind<E>> {
+        std::mem::replace(&mut *self.events.lock(), vec![])
+    }
+}
+
+impl<E: EthSpec> Default for EventRecorder<E> {
+    fn default() -> Self {
+        Self {
+            events: Mutex::new(vec![]),
+        }
+    }
+}
+
+impl<E: EthSpec> EventHandler<E> for EventRecorder<E> {
+    fn register(&self, kind: EventKind<E>) -> Result<(), String> {
+        self.events.lock().push(kind);
+        Ok(())
+    }
+}
 
 /// Indicates how the `BeaconChainHarness` should produce blocks.
 #[derive(Clone, Copy, Debug)]
@@ -85,6 +120,15 @@ pub struct BeaconChainHarness<T: BeaconChainTypes> {
 impl<E: EthSpec> BeaconChainHarness<HarnessType<E>> {
     /// Instantiate a new harness with `validator_count` initial validators.
     pub fn new(eth_spec_instance: E, keypairs: Vec<Keypair>) -> Self {
+        Self::new_with_weak_subjectivity_checkpoint(eth_spec_instance, keypairs, None)
+    }
+
+    /// Instantiate a new harness which enforces the given weak subjectivity `checkpoint`.
+    pub fn new_with_weak_subjectivity_checkpoint(
+        eth_spec_instance: E,
+        keypairs: Vec<Keypair>,
+        checkpoint: Option<Checkpoint>,
+    ) -> Self {
         let data_dir = tempdir().expect("should create temporary data_dir");
         let spec = E::default_spec();
 
@@ -96,6 +140,7 @@ impl<E: EthSpec> BeaconChainHarness<HarnessType<E>> {
             .store(Arc::new(MemoryStore::open()))
             .store_migrator(NullMigrator)
             .data_dir(data_dir.path().to_path_buf())
+            .weak_subjectivity_checkpoint(checkpoint)
             .genesis_state(
                 interop_genesis_state::<E>(&keypairs, HARNESS_GENESIS_TIME, &spec)
                     .expect("should generate interop state"),
@@ -120,6 +165,45 @@ impl<E: EthSpec> BeaconChainHarness<HarnessType<E>> {
     }
 }
 
+impl<E: EthSpec> BeaconChainHarness<EventRecordingHarnessType<E>> {
+    /// Instantiate a new harness which records every event emitted by the `BeaconChain` in its
+    /// `event_handler`.
+    pub fn new_with_event_recorder(eth_spec_instance: E, keypairs: Vec<Keypair>) -> Self {
+        let data_dir = tempdir().expect("should create temporary data_dir");
+        let spec = E::default_spec();
+
+        let log = NullLoggerBuilder.build().expect("logger should build");
+
+        let chain = BeaconChainBuilder::new(eth_spec_instance)
+            .logger(log)
+            .custom_spec(spec.clone())
+            .store(Arc::new(MemoryStore::open()))
+            .store_migrator(NullMigrator)
+            .data_dir(data_dir.path().to_path_buf())
+            .genesis_state(
+                interop_genesis_state::<E>(&keypairs, HARNESS_GENESIS_TIME, &spec)
+                    .expect("should generate interop state"),
+            )
+            .expect("should build state using recent genesis")
+            .dummy_eth1_backend()
+            .expect("should build dummy backend")
+            .event_handler(EventRecorder::default())
+            .testing_slot_clock(HARNESS_SLOT_TIME)
+            .expect("should configure testing slot clock")
+            .reduced_tree_fork_choice()
+            .expect("should add fork choice to builder")
+            .build()
+            .expect("should build");
+
+        Self {
+            spec: chain.spec.clone(),
+            chain,
+            keypairs,
+            data_dir,
+        }
+    }
+}
+
 impl<E: EthSpec> BeaconChainHarness<DiskHarnessType<E>> {
     /// Instantiate a new harness with `validator_count` initial validators.
     pub fn new_with_disk_store(
@@ -164,6 +248,49 @@ impl<E: EthSpec> BeaconChainHarness<DiskHarnessType<E>> {
         }
     }
 
+    /// Instantiate a new harness which starts from the given finalized `beacon_state` and
+    /// `beacon_block`, rather than from genesis.
+    pub fn from_weak_subjectivity_state(
+        eth_spec_instance: E,
+        store: Arc<DiskStore<E>>,
+        keypairs: Vec<Keypair>,
+        beacon_state: BeaconState<E>,
+        beacon_block: SignedBeaconBlock<E>,
+    ) -> Self {
+        let data_dir = tempdir().expect("should create temporary data_dir");
+        let spec = E::default_spec();
+
+        let log = NullLoggerBuilder.build().expect("logger should build");
+
+        let chain = BeaconChainBuilder::new(eth_spec_instance)
+            .logger(log.clone())
+            .custom_spec(spec.clone())
+            .store(store.clone())
+            .store_migrator(<BlockingMigrator<_> as Migrate<_, E>>::new(
+                store,
+                log.clone(),
+            ))
+            .data_dir(data_dir.path().to_path_buf())
+            .weak_subjectivity_state(beacon_state, beacon_block)
+            .expect("should build state using weak subjectivity checkpoint")
+            .dummy_eth1_backend()
+            .expect("should build dummy backend")
+            .null_event_handler()
+            .testing_slot_clock(HARNESS_SLOT_TIME)
+            .expect("should configure testing slot clock")
+            .reduced_tree_fork_choice()
+            .expect("should add fork choice to builder")
+            .build()
+            .expect("should build");
+
+        Self {
+            spec: chain.spec.clone(),
+            chain,
+            keypairs,
+            data_dir,
+        }
+    }
+
     /// Instantiate a new harness with `validator_count` initial validators.
     pub fn resume_from_disk_store(
         eth_spec_instance: E,
@@ -205,11 +332,12 @@ impl<E: EthSpec> BeaconChainHarness<DiskHarnessType<E>> {
     }
 }
 
-impl<S, M, E> BeaconChainHarness<BaseHarnessType<S, M, E>>
+impl<S, M, E, H> BeaconChainHarness<BaseHarnessType<S, M, E, H>>
 where
     S: Store<E>,
     M: Migrate<S, E>,
     E: EthSpec,
+    H: EventHandler<E> + 'static,
 {
     /// Advance the slot of the `BeaconChain`.
     ///
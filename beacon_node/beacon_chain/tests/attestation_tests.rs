@@ -6,11 +6,11 @@ extern crate lazy_static;
 use beacon_chain::test_utils::{
     AttestationStrategy, BeaconChainHarness, BlockStrategy, HarnessType,
 };
-use beacon_chain::{AttestationProcessingOutcome, AttestationType};
+use beacon_chain::{AttestationProcessingOutcome, AttestationType, InvalidAggregator};
 use state_processing::per_slot_processing;
 use types::{
     test_utils::generate_deterministic_keypair, AggregateSignature, BitList, EthSpec, Hash256,
-    Keypair, MainnetEthSpec, Signature,
+    Keypair, MainnetEthSpec, RelativeEpoch, Signature, SignedAggregateAndProof,
 };
 
 pub const VALIDATOR_COUNT: usize = 128;
@@ -266,3 +266,94 @@ fn attestation_that_skips_epochs() {
         "should process attestation that skips slots"
     );
 }
+
+#[test]
+fn aggregate_is_only_imported_from_a_valid_aggregator() {
+    let harness = get_harness(VALIDATOR_COUNT);
+    let chain = &harness.chain;
+
+    // Produce blocks without attestations, so the op pool starts out empty.
+    harness.extend_chain(
+        2,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::SomeValidators(vec![]),
+    );
+
+    let head = chain.head().expect("should get head");
+    let mut state = head.beacon_state.clone();
+    state
+        .build_committee_cache(RelativeEpoch::Current, &harness.spec)
+        .expect("should build committee cache");
+
+    let aggregate = harness
+        .get_free_attestations(
+            &AttestationStrategy::AllValidators,
+            &state,
+            head.beacon_block_root,
+            head.beacon_block.slot(),
+        )
+        .first()
+        .cloned()
+        .expect("should get at least one attestation");
+    let committee = state
+        .get_beacon_committee(aggregate.data.slot, aggregate.data.index)
+        .expect("should get committee")
+        .committee
+        .to_vec();
+
+    let signed_aggregate = |aggregator_index: usize, signer_index: usize| {
+        SignedAggregateAndProof::from_aggregate(
+            aggregator_index as u64,
+            aggregate.clone(),
+            &generate_deterministic_keypair(signer_index).sk,
+            &state.fork,
+            state.genesis_validators_root,
+            &harness.spec,
+        )
+    };
+
+    /*
+     * Should reject aggregates from validators outside the committee.
+     */
+
+    let outsider = (0..VALIDATOR_COUNT)
+        .find(|index| !committee.contains(index))
+        .expect("should find a validator outside the committee");
+
+    assert_eq!(
+        chain.process_signed_aggregate(&signed_aggregate(outsider, outsider)),
+        Ok(AttestationProcessingOutcome::InvalidAggregator(
+            InvalidAggregator::NotInCommittee(outsider as u64)
+        )),
+        "should not accept an aggregate from outside the committee"
+    );
+
+    /*
+     * Should reject aggregates not signed by the aggregator.
+     */
+
+    assert_eq!(
+        chain.process_signed_aggregate(&signed_aggregate(committee[0], outsider)),
+        Ok(AttestationProcessingOutcome::InvalidAggregator(
+            InvalidAggregator::InvalidSignature
+        )),
+        "should not accept an aggregate signed by another validator"
+    );
+
+    assert_eq!(
+        chain.op_pool.num_attestations(),
+        0,
+        "should not import the aggregates of invalid aggregators"
+    );
+
+    /*
+     * Should accept aggregates from a selected aggregator.
+     */
+
+    assert_eq!(
+        chain.process_signed_aggregate(&signed_aggregate(committee[0], committee[0])),
+        Ok(AttestationProcessingOutcome::Processed),
+        "should accept an aggregate from a committee member"
+    );
+    assert_eq!(chain.op_pool.num_attestations(), 1);
+}
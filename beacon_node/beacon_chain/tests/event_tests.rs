@@ -0,0 +1,206 @@
+#![cfg(not(debug_assertions))]
+
+#[macro_use]
+extern crate lazy_static;
+
+use beacon_chain::events::EventKind;
+use beacon_chain::test_utils::{
+    AttestationStrategy, BeaconChainHarness, BlockStrategy, EventRecordingHarnessType,
+};
+use beacon_chain::OperationProcessingOutcome;
+use types::test_utils::{
+    build_double_vote_attester_slashing, build_proposer_slashing, AttesterSlashingTestTask,
+    ProposerSlashingTestTask,
+};
+use types::{EthSpec, Keypair, MinimalEthSpec, Slot};
+
+type E = MinimalEthSpec;
+
+// Should ideally be divisible by 3.
+pub const VALIDATOR_COUNT: usize = 24;
+
+lazy_static! {
+    /// A cached set of keys.
+    static ref KEYPAIRS: Vec<Keypair> = types::test_utils::generate_deterministic_keypairs(VALIDATOR_COUNT);
+}
+
+fn get_harness() -> BeaconChainHarness<EventRecordingHarnessType<E>> {
+    let harness = BeaconChainHarness::new_with_event_recorder(MinimalEthSpec, KEYPAIRS.to_vec());
+
+    harness.advance_slot();
+
+    harness
+}
+
+#[test]
+fn reorg_event_reports_depth_and_common_ancestor() {
+    let harness = get_harness();
+
+    // Blocks at slots 1 and 2, shared by both forks.
+    let common_ancestor = harness.extend_chain(
+        2,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+
+    // An unattested fork at slots 3 to 5, which becomes the head as it is the only one.
+    harness.advance_slot();
+    let abandoned_head = harness.extend_chain(
+        3,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::SomeValidators(vec![]),
+    );
+    assert_eq!(
+        harness.chain.head_info().unwrap().block_root,
+        abandoned_head
+    );
+    harness.chain.event_handler.take();
+
+    // An attested fork at slots 6 and 7, built on the block at slot 2.
+    let new_head = harness.extend_chain(
+        2,
+        BlockStrategy::ForkCanonicalChainAt {
+            previous_slot: Slot::new(2),
+            first_slot: Slot::new(6),
+        },
+        AttestationStrategy::AllValidators,
+    );
+    assert_eq!(harness.chain.head_info().unwrap().block_root, new_head);
+
+    let reorgs = harness
+        .chain
+        .event_handler
+        .take()
+        .into_iter()
+        .filter_map(|event| match event {
+            EventKind::BeaconChainReorg {
+                depth,
+                current_head_beacon_block_root,
+                previous_head_beacon_block_root,
+                common_ancestor_beacon_block_root,
+                common_ancestor_slot,
+            } => Some((
+                depth,
+                current_head_beacon_block_root,
+                previous_head_beacon_block_root,
+                common_ancestor_beacon_block_root,
+                common_ancestor_slot,
+            )),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(reorgs.len(), 1, "should reorg exactly once");
+    let (depth, current_head, previous_head, ancestor_root, ancestor_slot) = reorgs[0];
+
+    assert_eq!(
+        depth, 3,
+        "should count the slots from the ancestor to the old head"
+    );
+    assert_eq!(previous_head, abandoned_head);
+    assert_eq!(ancestor_root, common_ancestor);
+    assert_eq!(ancestor_slot, Slot::new(2));
+
+    let current_head_slot = harness
+        .chain
+        .get_block(&current_head)
+        .unwrap()
+        .expect("should have the new head block")
+        .slot();
+    assert!(
+        current_head_slot >= Slot::new(6),
+        "new head should be on the attested fork"
+    );
+}
+
+#[test]
+fn justification_event_fires_once_per_advance() {
+    let harness = get_harness();
+
+    harness.extend_chain(
+        E::slots_per_epoch() as usize * 5,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+
+    let justified = harness
+        .chain
+        .event_handler
+        .take()
+        .into_iter()
+        .filter_map(|event| match event {
+            EventKind::BeaconJustification { epoch, root } => Some((epoch, root)),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    assert!(!justified.is_empty(), "chain should justify");
+    assert!(
+        justified.windows(2).all(|pair| pair[0].0 < pair[1].0),
+        "each justified epoch should be reported once, in order: {:?}",
+        justified
+    );
+
+    let head_checkpoint = harness.get_head_state().current_justified_checkpoint;
+    assert_eq!(
+        *justified.last().unwrap(),
+        (head_checkpoint.epoch, head_checkpoint.root),
+        "last event should match the head's justified checkpoint"
+    );
+}
+
+#[test]
+fn slashing_events_fire_on_import() {
+    let harness = get_harness();
+    let chain = &harness.chain;
+
+    let state = harness.get_head_state();
+    let spec = &harness.spec;
+
+    let proposer_slashing = build_proposer_slashing::<E>(
+        ProposerSlashingTestTask::Valid,
+        0,
+        &KEYPAIRS[0].sk,
+        &state.fork,
+        state.genesis_validators_root,
+        spec,
+    );
+    let attester_slashing = build_double_vote_attester_slashing::<E>(
+        AttesterSlashingTestTask::Valid,
+        &[1, 2],
+        &[&KEYPAIRS[1].sk, &KEYPAIRS[2].sk],
+        &state.fork,
+        state.genesis_validators_root,
+        spec,
+    );
+
+    assert_eq!(
+        chain.process_proposer_slashing(proposer_slashing.clone()),
+        Ok(OperationProcessingOutcome::Processed)
+    );
+    assert_eq!(
+        chain.process_attester_slashing(attester_slashing.clone()),
+        Ok(OperationProcessingOutcome::Processed)
+    );
+
+    let events = chain.event_handler.take();
+    assert_eq!(events.len(), 2);
+    match &events[0] {
+        EventKind::ProposerSlashingImported {
+            proposer_slashing: imported,
+        } => assert_eq!(**imported, proposer_slashing),
+        other => panic!("expected a proposer slashing event, got {:?}", other),
+    }
+    match &events[1] {
+        EventKind::AttesterSlashingImported {
+            attester_slashing: imported,
+        } => assert_eq!(**imported, attester_slashing),
+        other => panic!("expected an attester slashing event, got {:?}", other),
+    }
+
+    // An invalid slashing is neither imported nor announced.
+    let mut invalid_slashing = proposer_slashing;
+    invalid_slashing.signed_header_2 = invalid_slashing.signed_header_1.clone();
+    assert!(chain.process_proposer_slashing(invalid_slashing).is_err());
+    assert!(chain.event_handler.take().is_empty());
+}
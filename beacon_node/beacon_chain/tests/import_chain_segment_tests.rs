@@ -259,6 +259,65 @@ fn chain_segment_non_linear_slots() {
     );
 }
 
+#[test]
+fn chain_segment_weak_subjectivity_checkpoint() {
+    let blocks = chain_segment_blocks();
+    let checkpoint_epoch = Epoch::new(2);
+    let checkpoint_root = blocks
+        .iter()
+        .find(|block| block.slot() == checkpoint_epoch.start_slot(E::slots_per_epoch()))
+        .expect("should have block at checkpoint slot")
+        .canonical_root();
+
+    let get_harness = |root| {
+        let harness = BeaconChainHarness::new_with_weak_subjectivity_checkpoint(
+            MainnetEthSpec,
+            KEYPAIRS[..].to_vec(),
+            Some(Checkpoint {
+                epoch: checkpoint_epoch,
+                root,
+            }),
+        );
+        harness
+            .chain
+            .slot_clock
+            .set_slot(blocks.last().unwrap().slot().as_u64());
+        harness
+    };
+
+    /*
+     * Test with a checkpoint in the chain.
+     */
+
+    let harness = get_harness(checkpoint_root);
+    harness
+        .chain
+        .process_chain_segment(blocks.clone())
+        .to_block_error()
+        .expect("should import chain including checkpoint");
+
+    /*
+     * Test with a checkpoint that conflicts with the chain.
+     */
+
+    let conflicting_root = Hash256::repeat_byte(42);
+    let harness = get_harness(conflicting_root);
+    assert_eq!(
+        harness
+            .chain
+            .process_chain_segment(blocks.clone())
+            .to_block_error(),
+        Err(BlockError::WeakSubjectivityConflict {
+            checkpoint: Checkpoint {
+                epoch: checkpoint_epoch,
+                root: conflicting_root,
+            },
+            ancestor_root: checkpoint_root,
+        }),
+        "should not import chain conflicting with checkpoint"
+    );
+}
+
 #[test]
 fn invalid_signatures() {
     let mut checked_attestation = false;
@@ -7,7 +7,9 @@ use beacon_chain::test_utils::{
     AttestationStrategy, BeaconChainHarness, BlockStrategy, DiskHarnessType,
 };
 use beacon_chain::BeaconSnapshot;
-use beacon_chain::{AttestationProcessingOutcome, AttestationType, StateSkipConfig};
+use beacon_chain::{
+    AttestationProcessingOutcome, AttestationType, ChainSegmentResult, StateSkipConfig,
+};
 use rand::Rng;
 use sloggers::{null::NullLoggerBuilder, Build};
 use std::collections::HashMap;
@@ -249,6 +251,121 @@ fn split_slot_restore() {
     assert_eq!(store.get_split_slot(), split_slot);
 }
 
+#[test]
+fn weak_subjectivity_sync() {
+    let num_blocks_produced = E::slots_per_epoch() * 5;
+    let db_path = tempdir().unwrap();
+    let store = get_store(&db_path);
+    let harness = get_harness(store.clone(), LOW_VALIDATOR_COUNT);
+
+    harness.extend_chain(
+        num_blocks_produced as usize,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+
+    let head = harness.chain.head().expect("should get head");
+    let finalized_root = head.beacon_state.finalized_checkpoint.root;
+    let wss_block = harness
+        .chain
+        .get_block(&finalized_root)
+        .unwrap()
+        .expect("should get finalized block");
+    let wss_state = store
+        .get_state(&wss_block.state_root(), Some(wss_block.slot()))
+        .unwrap()
+        .expect("should get finalized state");
+    let anchor_slot = wss_block.slot();
+
+    // Start a new chain from the finalized checkpoint.
+    let wss_db_path = tempdir().unwrap();
+    let wss_store = get_store(&wss_db_path);
+    let wss_harness = BeaconChainHarness::from_weak_subjectivity_state(
+        MinimalEthSpec,
+        wss_store.clone(),
+        KEYPAIRS[0..LOW_VALIDATOR_COUNT].to_vec(),
+        wss_state,
+        wss_block,
+    );
+    wss_harness
+        .chain
+        .slot_clock
+        .set_slot(harness.chain.slot().unwrap().as_u64());
+
+    assert_eq!(wss_store.get_split_slot(), anchor_slot);
+    assert_eq!(
+        wss_harness.chain.head().unwrap().beacon_block_root,
+        finalized_root
+    );
+
+    // Import the entire chain. Blocks up to and including the anchor are ignored.
+    let blocks = harness
+        .chain
+        .chain_dump()
+        .unwrap()
+        .into_iter()
+        .map(|snapshot| snapshot.beacon_block)
+        .collect::<Vec<_>>();
+
+    match wss_harness.chain.process_chain_segment(blocks.clone()) {
+        ChainSegmentResult::Successful { imported_blocks } => {
+            assert_eq!(
+                imported_blocks as u64,
+                (head.beacon_block.slot() - anchor_slot).as_u64()
+            )
+        }
+        ChainSegmentResult::Failed { error, .. } => {
+            panic!("should import chain segment: {:?}", error)
+        }
+    }
+
+    wss_harness
+        .chain
+        .fork_choice()
+        .expect("should run fork choice");
+
+    assert_eq!(
+        wss_harness.chain.head().unwrap().beacon_block_root,
+        head.beacon_block_root
+    );
+
+    // Backfill the blocks prior to the anchor, in descending batches.
+    let anchor = wss_store
+        .get_anchor_info()
+        .expect("should have anchor info");
+    assert_eq!(anchor.anchor_slot, anchor_slot);
+    assert_eq!(anchor.oldest_block_slot, anchor_slot);
+
+    let mut historical_blocks = blocks
+        .into_iter()
+        .filter(|block| block.slot() < anchor_slot)
+        .collect::<Vec<_>>();
+    historical_blocks.sort_by_key(|block| block.slot());
+
+    for batch in historical_blocks.rchunks(E::slots_per_epoch() as usize) {
+        wss_store
+            .put_backfilled_blocks(batch.to_vec())
+            .expect("should store backfilled blocks");
+        assert_eq!(
+            wss_store.get_anchor_info().unwrap().oldest_block_slot,
+            batch[0].slot()
+        );
+    }
+    assert!(wss_store
+        .get_anchor_info()
+        .unwrap()
+        .block_backfill_complete());
+
+    for block in &historical_blocks {
+        let block_root = block.canonical_root();
+        assert_eq!(
+            wss_store.get_frozen_block_root(block.slot()).unwrap(),
+            Some(block_root)
+        );
+        assert!(wss_store.get_block(&block_root).unwrap().is_some());
+    }
+}
+
 // Check attestation processing and `load_epoch_boundary_state` in the presence of a split DB.
 // This is a bit of a monster test in that it tests lots of different things, but until they're
 // tested elsewhere, this is as good a place as any.
@@ -4,6 +4,7 @@ use crate::Client;
 use beacon_chain::{
     builder::{BeaconChainBuilder, Witness},
     eth1_chain::{CachingEth1Backend, Eth1Chain},
+    events::{ServerSentEventHandler, TeeEventHandler},
     migrate::{BackgroundMigrator, Migrate, NullMigrator},
     slot_clock::{SlotClock, SystemTimeSlotClock},
     store::{DiskStore, MemoryStore, SimpleDiskStore, Store, StoreConfig},
@@ -13,22 +14,37 @@ use environment::RuntimeContext;
 use eth1::{Config as Eth1Config, Service as Eth1Service};
 use eth2_config::Eth2Config;
 use eth2_libp2p::NetworkGlobals;
-use futures::{future, Future, IntoFuture};
+use futures::{
+    future::{self, loop_fn, Loop},
+    Future, IntoFuture,
+};
 use genesis::{interop_genesis_state, Eth1GenesisService};
 use network::{NetworkConfig, NetworkMessage, NetworkService};
-use slog::info;
+use remote_beacon_node::RemoteBeaconNode;
+use slog::{info, warn, Logger};
 use ssz::Decode;
 use std::net::SocketAddr;
 use std::path::Path;
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::mpsc::UnboundedSender;
-use types::{test_utils::generate_deterministic_keypairs, BeaconState, ChainSpec, EthSpec};
+use types::{
+    test_utils::generate_deterministic_keypairs, BeaconState, ChainSpec, Epoch, EthSpec,
+    SignedBeaconBlock,
+};
 use websocket_server::{Config as WebSocketConfig, WebSocketSender};
 
 /// Interval between polling the eth1 node for genesis information.
 pub const ETH1_GENESIS_UPDATE_INTERVAL_MILLIS: u64 = 7_000;
 
+/// Timeout for requests to the remote beacon node during checkpoint sync. Downloading a state may
+/// take some time.
+pub const CHECKPOINT_SYNC_HTTP_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// The number of epochs prior to the finalized epoch which will be searched for a block at the
+/// start of an epoch during checkpoint sync.
+pub const CHECKPOINT_SYNC_MAX_EPOCHS_SEARCHED: u64 = 8;
+
 /// Builds a `Client` instance.
 ///
 /// ## Notes
@@ -53,6 +69,7 @@ pub struct ClientBuilder<T: BeaconChainTypes> {
     eth1_service: Option<Eth1Service>,
     exit_channels: Vec<tokio::sync::oneshot::Sender<()>>,
     event_handler: Option<T::EventHandler>,
+    server_sent_event_handler: Option<Arc<ServerSentEventHandler<T::EthSpec>>>,
     network_globals: Option<Arc<NetworkGlobals<T::EthSpec>>>,
     network_send: Option<UnboundedSender<NetworkMessage<T::EthSpec>>>,
     http_listen_addr: Option<SocketAddr>,
@@ -87,6 +104,7 @@ where
             eth1_service: None,
             exit_channels: vec![],
             event_handler: None,
+            server_sent_event_handler: None,
             network_globals: None,
             network_send: None,
             http_listen_addr: None,
@@ -121,6 +139,7 @@ where
         let eth_spec_instance = self.eth_spec_instance.clone();
         let data_dir = config.data_dir.clone();
         let disabled_forks = config.disabled_forks.clone();
+        let weak_subjectivity_checkpoint = config.weak_subjectivity_checkpoint.clone();
 
         future::ok(())
             .and_then(move |()| {
@@ -143,7 +162,8 @@ where
                     .store_migrator(store_migrator)
                     .data_dir(data_dir)
                     .custom_spec(spec.clone())
-                    .disabled_forks(disabled_forks);
+                    .disabled_forks(disabled_forks)
+                    .weak_subjectivity_checkpoint(weak_subjectivity_checkpoint);
 
                 Ok((builder, spec, context))
             })
@@ -203,6 +223,57 @@ where
 
                             Box::new(future)
                         }
+                        ClientGenesis::WeakSubjSszBytes {
+                            anchor_state_bytes,
+                            anchor_block_bytes,
+                        } => {
+                            info!(
+                                context.log,
+                                "Starting from known checkpoint state";
+                            );
+
+                            let result = BeaconState::from_ssz_bytes(&anchor_state_bytes)
+                                .map_err(|e| {
+                                    format!("Unable to parse checkpoint state SSZ: {:?}", e)
+                                })
+                                .and_then(|anchor_state| {
+                                    SignedBeaconBlock::from_ssz_bytes(&anchor_block_bytes)
+                                        .map(|anchor_block| (anchor_state, anchor_block))
+                                        .map_err(|e| {
+                                            format!("Unable to parse checkpoint block SSZ: {:?}", e)
+                                        })
+                                });
+
+                            let future = result
+                                .and_then(move |(anchor_state, anchor_block)| {
+                                    builder.weak_subjectivity_state(anchor_state, anchor_block)
+                                })
+                                .into_future()
+                                .map(|v| (v, None));
+
+                            Box::new(future)
+                        }
+                        ClientGenesis::CheckpointSyncUrl { url } => {
+                            info!(
+                                context.log,
+                                "Downloading finalized checkpoint";
+                                "url" => &url,
+                            );
+
+                            let log = context.log.clone();
+                            let future = RemoteBeaconNode::new_with_timeout(
+                                url,
+                                CHECKPOINT_SYNC_HTTP_TIMEOUT,
+                            )
+                            .into_future()
+                            .and_then(move |remote| fetch_finalized_checkpoint(remote, log))
+                            .and_then(move |(anchor_state, anchor_block)| {
+                                builder.weak_subjectivity_state(anchor_state, anchor_block)
+                            })
+                            .map(|v| (v, None));
+
+                            Box::new(future)
+                        }
                         ClientGenesis::DepositContract => {
                             info!(
                                 context.log,
@@ -328,6 +399,7 @@ where
             &context.executor,
             beacon_chain,
             network_info,
+            self.server_sent_event_handler.clone(),
             client_config
                 .create_db_path()
                 .map_err(|_| "unable to read data dir")?,
@@ -337,7 +409,7 @@ where
             eth2_config.clone(),
             context.log,
         )
-        .map_err(|e| format!("Failed to start HTTP API: {:?}", e))?;
+        .map_err(|e| format!("Failed to start HTTP API: {}", e))?;
 
         self.exit_channels.push(exit_channel);
         self.http_listen_addr = Some(listening_addr);
@@ -485,6 +557,60 @@ where
     }
 }
 
+impl<TStore, TStoreMigrator, TSlotClock, TEth1Backend, TEthSpec>
+    ClientBuilder<
+        Witness<
+            TStore,
+            TStoreMigrator,
+            TSlotClock,
+            TEth1Backend,
+            TEthSpec,
+            TeeEventHandler<TEthSpec>,
+        >,
+    >
+where
+    TStore: Store<TEthSpec> + 'static,
+    TStoreMigrator: Migrate<TStore, TEthSpec>,
+    TSlotClock: SlotClock + 'static,
+    TEth1Backend: Eth1ChainBackend<TEthSpec, TStore> + 'static,
+    TEthSpec: EthSpec + 'static,
+{
+    /// Specifies that the `BeaconChain` should publish events using both the WebSocket server and
+    /// the event stream of the HTTP API.
+    pub fn tee_event_handler(mut self, config: WebSocketConfig) -> Result<Self, String> {
+        let context = self
+            .runtime_context
+            .as_ref()
+            .ok_or_else(|| "tee_event_handler requires a runtime_context")?
+            .service_context("ws".into());
+
+        let (sender, exit_channel, listening_addr): (
+            WebSocketSender<TEthSpec>,
+            Option<_>,
+            Option<_>,
+        ) = if config.enabled {
+            let (sender, exit, listening_addr) =
+                websocket_server::start_server(&config, &context.executor, &context.log)?;
+            (sender, Some(exit), Some(listening_addr))
+        } else {
+            (WebSocketSender::dummy(), None, None)
+        };
+
+        if let Some(channel) = exit_channel {
+            self.exit_channels.push(channel);
+        }
+        let server_sent_event_handler = Arc::new(ServerSentEventHandler::default());
+        self.event_handler = Some(TeeEventHandler::new(
+            sender,
+            server_sent_event_handler.clone(),
+        ));
+        self.server_sent_event_handler = Some(server_sent_event_handler);
+        self.websocket_listen_addr = listening_addr;
+
+        Ok(self)
+    }
+}
+
 impl<TStoreMigrator, TSlotClock, TEth1Backend, TEthSpec, TEventHandler>
     ClientBuilder<
         Witness<
@@ -766,3 +892,60 @@ where
         Ok(self)
     }
 }
+
+/// Downloads the most recent finalized state and block from `remote` which lie at the start of an
+/// epoch, for use as the anchor of a new chain.
+///
+/// If the finalized block does not lie on an epoch boundary, prior epochs are searched for one
+/// that does.
+fn fetch_finalized_checkpoint<E: EthSpec>(
+    remote: RemoteBeaconNode<E>,
+    log: Logger,
+) -> impl Future<Item = (BeaconState<E>, SignedBeaconBlock<E>), Error = String> {
+    let beacon = remote.http.beacon();
+
+    beacon
+        .get_head()
+        .map_err(|e| format!("Unable to get head from remote beacon node: {:?}", e))
+        .and_then(move |head| {
+            let finalized_epoch = head.finalized_slot.epoch(E::slots_per_epoch());
+
+            loop_fn(finalized_epoch, move |epoch: Epoch| {
+                let slot = epoch.start_slot(E::slots_per_epoch());
+                let log = log.clone();
+
+                beacon
+                    .get_block_by_slot(slot)
+                    .map_err(move |e| {
+                        format!("Unable to get block at slot {} from remote: {:?}", slot, e)
+                    })
+                    .and_then(move |(block, _root)| {
+                        if block.slot() == slot {
+                            Ok(Loop::Break(block))
+                        } else if epoch + CHECKPOINT_SYNC_MAX_EPOCHS_SEARCHED > finalized_epoch
+                            && epoch > 0
+                        {
+                            warn!(
+                                log,
+                                "Skipped slot at epoch boundary, trying previous epoch";
+                                "slot" => slot.as_u64(),
+                            );
+                            Ok(Loop::Continue(epoch - 1))
+                        } else {
+                            Err(format!(
+                                "No block at the start of the {} epochs prior to finalized epoch {}",
+                                CHECKPOINT_SYNC_MAX_EPOCHS_SEARCHED, finalized_epoch
+                            ))
+                        }
+                    })
+            })
+        })
+        .and_then(move |block| {
+            remote
+                .http
+                .beacon()
+                .get_state_by_root(block.state_root())
+                .map(|(state, _root)| (state, block))
+                .map_err(|e| format!("Unable to get finalized state from remote: {:?}", e))
+        })
+}
@@ -2,6 +2,7 @@ use network::NetworkConfig;
 use serde_derive::{Deserialize, Serialize};
 use std::fs;
 use std::path::PathBuf;
+use types::Checkpoint;
 
 pub const DEFAULT_DATADIR: &str = ".lighthouse";
 
@@ -29,6 +30,17 @@ pub enum ClientGenesis {
     /// We include the bytes instead of the `BeaconState<E>` because the `EthSpec` type
     /// parameter would be very annoying.
     SszBytes { genesis_state_bytes: Vec<u8> },
+    /// Starts from a trusted, finalized checkpoint rather than genesis, using the SSZ-encoded
+    /// `BeaconState` and the `SignedBeaconBlock` that produced it.
+    ///
+    /// The state must lie on an epoch boundary.
+    WeakSubjSszBytes {
+        anchor_state_bytes: Vec<u8>,
+        anchor_block_bytes: Vec<u8>,
+    },
+    /// Starts from the latest finalized checkpoint of a trusted beacon node, downloaded from its
+    /// HTTP API at `url`.
+    CheckpointSyncUrl { url: String },
 }
 
 impl Default for ClientGenesis {
@@ -55,6 +67,11 @@ pub struct Config {
     pub sync_eth1_chain: bool,
     /// A list of hard-coded forks that will be disabled.
     pub disabled_forks: Vec<String>,
+    /// A checkpoint that the finalized chain must include, protecting against long-range attacks.
+    ///
+    /// Not serialized, so that it must be provided via the CLI at runtime.
+    #[serde(skip)]
+    pub weak_subjectivity_checkpoint: Option<Checkpoint>,
     #[serde(skip)]
     /// The `genesis` field is not serialized or deserialized by `serde` to ensure it is defined
     /// via the CLI at runtime, instead of from a configuration file saved to disk.
@@ -84,6 +101,7 @@ impl Default for Config {
             sync_eth1_chain: false,
             eth1: <_>::default(),
             disabled_forks: Vec::new(),
+            weak_subjectivity_checkpoint: None,
         }
     }
 }
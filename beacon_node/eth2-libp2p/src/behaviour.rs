@@ -1,8 +1,12 @@
 use crate::discovery::{enr::Eth2Enr, Discovery};
-use crate::peer_manager::{PeerManager, PeerManagerEvent};
+use crate::nat::ExternalAddress;
+use crate::peer_manager::{PeerAction, PeerFilter, PeerManager, PeerManagerEvent};
+use crate::rpc::methods::GoodbyeReason;
 use crate::rpc::*;
 use crate::types::{GossipEncoding, GossipKind, GossipTopic};
-use crate::{error, Enr, NetworkConfig, NetworkGlobals, PubsubMessage, TopicHash};
+use crate::{
+    error, Enr, MessageAcceptance, NetworkConfig, NetworkGlobals, PubsubMessage, TopicHash,
+};
 use futures::prelude::*;
 use libp2p::{
     core::{identity::Keypair, ConnectedPoint},
@@ -17,9 +21,15 @@ use lru::LruCache;
 use slog::{crit, debug, o, warn};
 use std::marker::PhantomData;
 use std::sync::Arc;
+use std::time::Duration;
 use types::{EnrForkId, EthSpec, SubnetId};
 
 const MAX_IDENTIFY_ADDRESSES: usize = 10;
+/// The time in seconds that a peer disconnected for a low reputation is prevented from
+/// reconnecting.
+const DISCONNECT_PEER_TIMEOUT: u64 = 30;
+/// The time in seconds that a peer banned for a low reputation is prevented from reconnecting.
+const BAN_PEER_TIMEOUT: u64 = 3600;
 
 /// Builds the network behaviour that manages the core protocols of eth2.
 /// This core behaviour is managed by `Behaviour` which adds peer management to all core
@@ -43,6 +53,13 @@ pub struct Behaviour<TSubstream: AsyncRead + AsyncWrite, TSpec: EthSpec> {
     /// The events generated by this behaviour to be consumed in the swarm poll.
     #[behaviour(ignore)]
     events: Vec<BehaviourEvent<TSpec>>,
+    /// Peers that the peer manager has requested to be disconnected, along with the time they are
+    /// banned for. Peers without a ban time are free to reconnect.
+    #[behaviour(ignore)]
+    peers_to_disconnect: Vec<(PeerId, Option<Duration>)>,
+    /// The peers and IP ranges that are refused connections.
+    #[behaviour(ignore)]
+    peer_filter: PeerFilter,
     /// The current meta data of the node, so respond to pings and get metadata
     #[behaviour(ignore)]
     meta_data: MetaData<TSpec>,
@@ -103,8 +120,15 @@ impl<TSubstream: AsyncRead + AsyncWrite, TSpec: EthSpec> Behaviour<TSubstream, T
             gossipsub: Gossipsub::new(local_peer_id, net_conf.gs_config.clone()),
             discovery: Discovery::new(local_key, net_conf, network_globals.clone(), log)?,
             identify,
-            peer_manager: PeerManager::new(network_globals.clone(), log),
+            peer_manager: PeerManager::new(
+                network_globals.clone(),
+                net_conf.max_peers,
+                net_conf.gossip_score_params.clone(),
+                log,
+            ),
             events: Vec::new(),
+            peers_to_disconnect: Vec::new(),
+            peer_filter: PeerFilter::new(net_conf),
             seen_gossip_messages: LruCache::new(100_000),
             meta_data,
             network_globals,
@@ -149,6 +173,7 @@ impl<TSubstream: AsyncRead + AsyncWrite, TSpec: EthSpec> Behaviour<TSubstream, T
 
     /// Subscribes to a specific subnet id;
     pub fn subscribe_to_subnet(&mut self, subnet_id: SubnetId) -> bool {
+        self.peer_manager.subnet_subscribed(subnet_id);
         let topic = GossipTopic::new(
             subnet_id.into(),
             GossipEncoding::default(),
@@ -159,6 +184,7 @@ impl<TSubstream: AsyncRead + AsyncWrite, TSpec: EthSpec> Behaviour<TSubstream, T
 
     /// Un-Subscribes from a specific subnet id;
     pub fn unsubscribe_from_subnet(&mut self, subnet_id: SubnetId) -> bool {
+        self.peer_manager.subnet_unsubscribed(&subnet_id);
         let topic = GossipTopic::new(
             subnet_id.into(),
             GossipEncoding::default(),
@@ -212,6 +238,18 @@ impl<TSubstream: AsyncRead + AsyncWrite, TSpec: EthSpec> Behaviour<TSubstream, T
             .propagate_message(&message_id, propagation_source);
     }
 
+    /// Informs the peer manager of the result of validating a gossipsub message, so that the
+    /// peer that delivered it can be scored.
+    pub fn report_message_validation_result(
+        &mut self,
+        propagation_source: &PeerId,
+        kind: &GossipKind,
+        acceptance: MessageAcceptance,
+    ) {
+        self.peer_manager
+            .gossip_validation_result(propagation_source, kind, acceptance);
+    }
+
     /* Eth2 RPC behaviour functions */
 
     /// Sends an RPC Request/Response via the RPC protocol.
@@ -221,13 +259,36 @@ impl<TSubstream: AsyncRead + AsyncWrite, TSpec: EthSpec> Behaviour<TSubstream, T
 
     /* Discovery / Peer management functions */
 
-    /// Notify discovery that the peer has been banned.
+    /// Reports a peer's behaviour to the peer manager, adjusting its reputation.
+    pub fn report_peer(&mut self, peer_id: &PeerId, action: PeerAction) {
+        self.peer_manager.report_peer(peer_id, action);
+    }
+
+    /// Returns the peers that the peer manager has requested to be disconnected, along with the
+    /// time they are to be banned for, if any.
+    pub fn take_peers_to_disconnect(&mut self) -> Vec<(PeerId, Option<Duration>)> {
+        std::mem::replace(&mut self.peers_to_disconnect, Vec::new())
+    }
+
+    /// Returns true if the peer is a configured trusted peer.
+    pub fn is_trusted(&self, peer_id: &PeerId) -> bool {
+        self.network_globals.peers.read().is_trusted(peer_id)
+    }
+
+    /// Returns true if the peer is on the configured deny list.
+    pub fn is_denied(&self, peer_id: &PeerId) -> bool {
+        self.peer_filter.is_peer_denied(peer_id)
+    }
+
+    /// Notify discovery and the peer manager that the peer has been banned.
     pub fn peer_banned(&mut self, peer_id: PeerId) {
+        self.peer_manager.peer_banned(&peer_id);
         self.discovery.peer_banned(peer_id);
     }
 
-    /// Notify discovery that the peer has been unbanned.
+    /// Notify discovery and the peer manager that the peer has been unbanned.
     pub fn peer_unbanned(&mut self, peer_id: &PeerId) {
+        self.peer_manager.peer_unbanned(peer_id);
         self.discovery.peer_unbanned(peer_id);
     }
 
@@ -252,11 +313,18 @@ impl<TSubstream: AsyncRead + AsyncWrite, TSpec: EthSpec> Behaviour<TSubstream, T
         self.update_metadata();
     }
 
-    /// A request to search for peers connected to a long-lived subnet.
+    /// A request to search for peers connected to a subnet. The peers found are kept connected
+    /// whilst the subnet is needed.
     pub fn peers_request(&mut self, subnet_id: SubnetId) {
+        self.peer_manager.subnet_peers_requested(subnet_id);
         self.discovery.peers_request(subnet_id);
     }
 
+    /// Updates the local ENR with the external address mapped on the gateway.
+    pub fn update_external_address(&mut self, external_address: &ExternalAddress) {
+        self.discovery.update_enr_external_address(external_address);
+    }
+
     /// Updates the local ENR's "eth2" field with the latest EnrForkId.
     pub fn update_fork_version(&mut self, enr_fork_id: EnrForkId) {
         self.discovery.update_eth2_enr(enr_fork_id.clone());
@@ -311,6 +379,13 @@ impl<TSubstream: AsyncRead + AsyncWrite, TSpec: EthSpec> Behaviour<TSubstream, T
         self.send_rpc(peer_id, event);
     }
 
+    /// Sends a Goodbye request to a peer that is being disconnected.
+    fn send_goodbye(&mut self, peer_id: PeerId, reason: GoodbyeReason) {
+        let goodbye_request =
+            RPCEvent::Request(RequestId::from(0usize), RPCRequest::Goodbye(reason));
+        self.send_rpc(peer_id, goodbye_request);
+    }
+
     /// Sends a METADATA request to a peer.
     fn send_meta_data_request(&mut self, peer_id: PeerId) {
         let metadata_request =
@@ -364,10 +439,19 @@ impl<TSubstream: AsyncRead + AsyncWrite, TSpec: EthSpec>
                 }
             }
             GossipsubEvent::Subscribed { peer_id, topic } => {
+                if let Ok(gossip_topic) = GossipTopic::decode(topic.as_str()) {
+                    self.peer_manager
+                        .peer_subscribed(&peer_id, gossip_topic.kind().clone());
+                }
                 self.events
                     .push(BehaviourEvent::PeerSubscribed(peer_id, topic));
             }
-            GossipsubEvent::Unsubscribed { .. } => {}
+            GossipsubEvent::Unsubscribed { peer_id, topic } => {
+                if let Ok(gossip_topic) = GossipTopic::decode(topic.as_str()) {
+                    self.peer_manager
+                        .peer_unsubscribed(&peer_id, gossip_topic.kind());
+                }
+            }
         }
     }
 }
@@ -381,11 +465,31 @@ impl<TSubstream: AsyncRead + AsyncWrite, TSpec: EthSpec>
             // events to the
             // peer manager. After a behaviour re-write remove these:
             RPCMessage::PeerConnectedHack(peer_id, connected_point) => {
+                let remote_address = match &connected_point {
+                    ConnectedPoint::Dialer { address } => address,
+                    ConnectedPoint::Listener { send_back_addr, .. } => send_back_addr,
+                };
+                let is_peer_denied = self.peer_filter.is_peer_denied(&peer_id);
+                let is_denied =
+                    is_peer_denied || self.peer_filter.is_address_denied(remote_address);
+
                 match connected_point {
                     ConnectedPoint::Dialer { .. } => self.peer_manager.connect_outgoing(&peer_id),
                     ConnectedPoint::Listener { .. } => self.peer_manager.connect_ingoing(&peer_id),
                 };
 
+                if is_denied {
+                    debug!(self.log, "Disconnecting denied peer"; "peer_id" => format!("{}", peer_id), "address" => format!("{}", remote_address));
+                    // a peer denied only by its address may connect from an allowed address
+                    let ban_timeout = if is_peer_denied {
+                        Some(Duration::from_secs(BAN_PEER_TIMEOUT))
+                    } else {
+                        None
+                    };
+                    self.peers_to_disconnect.push((peer_id, ban_timeout));
+                    return;
+                }
+
                 // Find ENR info about a peer if possible.
                 if let Some(enr) = self.discovery.enr_of_peer(&peer_id) {
                     let bitfield = match enr.bitfield::<TSpec>() {
@@ -420,6 +524,11 @@ impl<TSubstream: AsyncRead + AsyncWrite, TSpec: EthSpec>
             RPCMessage::PeerDisconnected(peer_id) => {
                 self.events.push(BehaviourEvent::PeerDisconnected(peer_id))
             }
+            RPCMessage::RateLimited(peer_id, _protocol) => {
+                // the request has already been rejected, penalise the peer for sending it
+                self.peer_manager
+                    .report_peer(&peer_id, PeerAction::RateLimited);
+            }
             RPCMessage::RPC(peer_id, rpc_event) => {
                 // The METADATA and PING RPC responses are handled within the behaviour and not
                 // propagated
@@ -444,6 +553,11 @@ impl<TSubstream: AsyncRead + AsyncWrite, TSpec: EthSpec>
                     ) => {
                         self.peer_manager.meta_data_response(&peer_id, meta_data);
                     }
+                    RPCEvent::Error(_, ref error) => {
+                        // penalise the peer if it caused the error and propagate it upwards
+                        self.peer_manager.handle_rpc_error(&peer_id, error);
+                        self.events.push(BehaviourEvent::RPC(peer_id, rpc_event));
+                    }
                     RPCEvent::Request(_, RPCRequest::Status(_))
                     | RPCEvent::Response(_, RPCErrorResponse::Success(RPCResponse::Status(_))) => {
                         // inform the peer manager that we have received a status from a peer
@@ -484,11 +598,24 @@ impl<TSubstream: AsyncRead + AsyncWrite, TSpec: EthSpec> Behaviour<TSubstream, T
                     PeerManagerEvent::MetaData(peer_id) => {
                         self.send_meta_data_request(peer_id);
                     }
-                    PeerManagerEvent::_DisconnectPeer(_peer_id) => {
-                        //TODO: Implement
+                    PeerManagerEvent::DisconnectPeer(peer_id) => {
+                        // the libp2p service drops the connection once the goodbye is sent
+                        self.send_goodbye(peer_id.clone(), GoodbyeReason::Fault);
+                        self.peers_to_disconnect
+                            .push((peer_id, Some(Duration::from_secs(DISCONNECT_PEER_TIMEOUT))));
+                    }
+                    PeerManagerEvent::PrunePeer(peer_id) => {
+                        // the peer is free to reconnect once we have fewer peers
+                        self.send_goodbye(peer_id.clone(), GoodbyeReason::TooManyPeers);
+                        self.peers_to_disconnect.push((peer_id, None));
+                    }
+                    PeerManagerEvent::BanPeer(peer_id) => {
+                        self.send_goodbye(peer_id.clone(), GoodbyeReason::Fault);
+                        self.peers_to_disconnect
+                            .push((peer_id, Some(Duration::from_secs(BAN_PEER_TIMEOUT))));
                     }
-                    PeerManagerEvent::_BanPeer(_peer_id) => {
-                        //TODO: Implement
+                    PeerManagerEvent::DiscoverSubnetPeers(subnet_id) => {
+                        self.discovery.peers_request(subnet_id);
                     }
                 },
                 Ok(Async::NotReady) => break,
@@ -1,5 +1,7 @@
+use crate::peer_manager::{GossipScoreParams, IpCidr};
 use crate::types::GossipKind;
-use crate::Enr;
+use crate::{Enr, PeerId};
+use libp2p::core::multiaddr::Protocol;
 use libp2p::discv5::{Discv5Config, Discv5ConfigBuilder};
 use libp2p::gossipsub::{GossipsubConfig, GossipsubConfigBuilder, GossipsubMessage, MessageId};
 use libp2p::Multiaddr;
@@ -26,6 +28,10 @@ pub struct Config {
     /// UDP port that discovery listens on.
     pub discovery_port: u16,
 
+    /// The TCP port that libp2p listens on for WebSocket connections. None disables the WebSocket
+    /// transport, so WebSocket addresses are neither listened on nor dialed.
+    pub websocket_port: Option<u16>,
+
     /// The address to broadcast to peers about which address we are listening on. None indicates
     /// that no discovery address has been set in the CLI args.
     pub enr_address: Option<std::net::IpAddr>,
@@ -36,6 +42,10 @@ pub struct Config {
     /// The tcp port to broadcast to peers in order to reach back for libp2p services.
     pub enr_tcp_port: Option<u16>,
 
+    /// Map the libp2p TCP and discovery UDP ports on the local gateway with UPnP, and advertise
+    /// the mapped external address in the ENR.
+    pub upnp_enabled: bool,
+
     /// Target number of connected peers.
     pub max_peers: usize,
 
@@ -49,6 +59,10 @@ pub struct Config {
     #[serde(skip)]
     pub gs_config: GossipsubConfig,
 
+    /// The per-topic parameters used to score peers on the gossipsub messages they deliver.
+    #[serde(skip)]
+    pub gossip_score_params: GossipScoreParams,
+
     /// Discv5 configuration parameters.
     #[serde(skip)]
     pub discv5_config: Discv5Config,
@@ -59,6 +73,18 @@ pub struct Config {
     /// List of libp2p nodes to initially connect to.
     pub libp2p_nodes: Vec<Multiaddr>,
 
+    /// List of trusted peers, each ending in a `/p2p/<peer-id>` component. Trusted peers are
+    /// kept connected, are never disconnected or banned and don't count towards `max_peers`.
+    pub trusted_peers: Vec<Multiaddr>,
+
+    /// List of peers that are never connected to.
+    #[serde(skip)]
+    pub denied_peers: Vec<PeerId>,
+
+    /// List of IP ranges that peers are never connected to from or at.
+    #[serde(skip)]
+    pub denied_ip_ranges: Vec<IpCidr>,
+
     /// Client version
     pub client_version: String,
 
@@ -71,6 +97,27 @@ pub struct Config {
     pub propagation_percentage: Option<u8>,
 }
 
+impl Config {
+    /// Returns the address of each enabled transport that libp2p listens on.
+    ///
+    /// QUIC is not supported by the libp2p version in use, so only TCP and WebSocket addresses
+    /// are returned.
+    pub fn listen_multiaddrs(&self) -> Vec<Multiaddr> {
+        let mut tcp = Multiaddr::from(self.listen_address);
+        tcp.push(Protocol::Tcp(self.libp2p_port));
+        let mut multiaddrs = vec![tcp];
+
+        if let Some(port) = self.websocket_port {
+            let mut ws = Multiaddr::from(self.listen_address);
+            ws.push(Protocol::Tcp(port));
+            ws.push(Protocol::Ws);
+            multiaddrs.push(ws);
+        }
+
+        multiaddrs
+    }
+}
+
 impl Default for Config {
     /// Generate a default network configuration.
     fn default() -> Self {
@@ -126,18 +173,64 @@ impl Default for Config {
             listen_address: "0.0.0.0".parse().expect("valid ip address"),
             libp2p_port: 9000,
             discovery_port: 9000,
+            websocket_port: None,
             enr_address: None,
             enr_udp_port: None,
             enr_tcp_port: None,
+            upnp_enabled: false,
             max_peers: 50,
             secret_key_hex: None,
             gs_config,
+            gossip_score_params: GossipScoreParams::default(),
             discv5_config,
             boot_nodes: vec![],
             libp2p_nodes: vec![],
+            trusted_peers: vec![],
+            denied_peers: vec![],
+            denied_ip_ranges: vec![],
             client_version: version::version(),
             topics,
             propagation_percentage: None,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_listen_multiaddrs() {
+        let mut config = Config::default();
+        config.listen_address = "10.0.0.1".parse().unwrap();
+        config.libp2p_port = 9000;
+        assert_eq!(
+            config.listen_multiaddrs(),
+            vec!["/ip4/10.0.0.1/tcp/9000".parse::<Multiaddr>().unwrap()]
+        );
+
+        config.websocket_port = Some(9001);
+        assert_eq!(
+            config.listen_multiaddrs(),
+            vec![
+                "/ip4/10.0.0.1/tcp/9000".parse::<Multiaddr>().unwrap(),
+                "/ip4/10.0.0.1/tcp/9001/ws".parse::<Multiaddr>().unwrap(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_listen_multiaddrs_ipv6() {
+        let mut config = Config::default();
+        config.listen_address = "::1".parse().unwrap();
+        config.libp2p_port = 9000;
+        config.websocket_port = Some(9001);
+        assert_eq!(
+            config.listen_multiaddrs(),
+            vec![
+                "/ip6/::1/tcp/9000".parse::<Multiaddr>().unwrap(),
+                "/ip6/::1/tcp/9001/ws".parse::<Multiaddr>().unwrap(),
+            ]
+        );
+    }
+}
@@ -5,9 +5,12 @@ pub(crate) mod enr;
 pub use enr::{build_enr, CombinedKey, Keypair};
 
 use crate::metrics;
+use crate::nat::ExternalAddress;
+use crate::peer_manager::PeerFilter;
 use crate::{error, Enr, NetworkConfig, NetworkGlobals};
 use enr::{Eth2Enr, BITFIELD_ENR_KEY, ETH2_ENR_KEY};
 use futures::prelude::*;
+use hashmap_delay::HashSetDelay;
 use libp2p::core::{ConnectedPoint, Multiaddr, PeerId};
 use libp2p::discv5::enr::NodeId;
 use libp2p::discv5::{Discv5, Discv5Event};
@@ -31,8 +34,15 @@ const MAX_TIME_BETWEEN_PEER_SEARCHES: u64 = 120;
 const INITIAL_SEARCH_DELAY: u64 = 5;
 /// Local ENR storage filename.
 pub const ENR_FILENAME: &str = "enr.dat";
-/// Number of peers we'd like to have connected to a given long-lived subnet.
-const TARGET_SUBNET_PEERS: u64 = 3;
+/// Number of peers we'd like to have connected to a given long-lived subnet. The peer manager keeps
+/// this many peers on the subnets we need when pruning peers.
+pub(crate) const TARGET_SUBNET_PEERS: u64 = 3;
+/// The number of connections beyond `max_peers` that may be used to dial peers on subnets we are
+/// searching for. The peer manager prunes the excess peers that are not on a needed subnet.
+const SUBNET_PEER_EXCESS: usize = 5;
+/// The time in seconds that discovered peers on a subnet are dialed beyond `max_peers`, after
+/// a search for peers on the subnet started.
+const SUBNET_QUERY_TIMEOUT: u64 = 60;
 
 /// Lighthouse discovery behaviour. This provides peer management and discovery using the Discv5
 /// libp2p protocol.
@@ -46,6 +56,12 @@ pub struct Discovery<TSubstream, TSpec: EthSpec> {
     /// The target number of connected peers on the libp2p interface.
     max_peers: usize,
 
+    /// The subnets with an ongoing search for peers.
+    subnet_queries: HashSetDelay<SubnetId>,
+
+    /// The peers and IP ranges that must not be dialed.
+    peer_filter: PeerFilter,
+
     /// The directory where the ENR is stored.
     enr_dir: String,
 
@@ -119,10 +135,14 @@ impl<TSubstream, TSpec: EthSpec> Discovery<TSubstream, TSpec> {
             });
         }
 
+        let peer_filter = PeerFilter::new(config);
+
         Ok(Self {
             events: VecDeque::with_capacity(16),
-            banned_peers: HashSet::new(),
+            banned_peers: peer_filter.denied_peers().cloned().collect(),
             max_peers: config.max_peers,
+            subnet_queries: HashSetDelay::new(Duration::from_secs(SUBNET_QUERY_TIMEOUT)),
+            peer_filter,
             peer_discovery_delay: Delay::new(Instant::now()),
             past_discovery_delay: INITIAL_SEARCH_DELAY,
             tcp_port: config.libp2p_port,
@@ -249,6 +269,44 @@ impl<TSubstream, TSpec: EthSpec> Discovery<TSubstream, TSpec> {
         *self.network_globals.local_enr.write() = self.discovery.local_enr().clone();
     }
 
+    /// Updates the address and ports of our local ENR with the external address mapped on the
+    /// gateway.
+    pub fn update_enr_external_address(&mut self, external_address: &ExternalAddress) {
+        info!(self.log, "Updating the ENR external address";
+            "ip" => format!("{:?}", external_address.ip),
+            "tcp_port" => format!("{:?}", external_address.tcp_port),
+            "udp_port" => format!("{:?}", external_address.udp_port),
+        );
+
+        let mut fields = vec![];
+        if let Some(ip) = external_address.ip {
+            fields.push(("ip", ip.octets().to_vec()));
+        }
+        if let Some(tcp_port) = external_address.tcp_port {
+            fields.push(("tcp", tcp_port.to_be_bytes().to_vec()));
+        }
+        if let Some(udp_port) = external_address.udp_port {
+            fields.push(("udp", udp_port.to_be_bytes().to_vec()));
+        }
+
+        for (key, value) in fields {
+            let _ = self.discovery.enr_insert(key, value).map_err(|e| {
+                warn!(
+                    self.log,
+                    "Could not update ENR field";
+                    "key" => key,
+                    "error" => format!("{:?}", e)
+                )
+            });
+        }
+
+        let enr = self.discovery.local_enr();
+        enr::save_enr_to_disk(Path::new(&self.enr_dir), enr, &self.log);
+
+        // replace the global version with discovery version
+        *self.network_globals.local_enr.write() = self.discovery.local_enr().clone();
+    }
+
     /// A request to find peers on a given subnet.
     // TODO: This logic should be improved with added sophistication in peer management
     // This currently checks for currently connected peers and if we don't have
@@ -270,6 +328,8 @@ impl<TSubstream, TSpec: EthSpec> Discovery<TSubstream, TSpec> {
                 "peers_to_find" => target_peers
             );
 
+            self.subnet_queries.insert(subnet_id);
+
             let log_clone = self.log.clone();
 
             let subnet_predicate = move |enr: &Enr| {
@@ -305,6 +365,25 @@ impl<TSubstream, TSpec: EthSpec> Discovery<TSubstream, TSpec> {
 
     /* Internal Functions */
 
+    /// Returns true if the peer's ENR advertises a subnet that we are searching for peers on.
+    fn is_on_queried_subnet(&mut self, peer_id: &PeerId) -> bool {
+        if self.subnet_queries.len() == 0 {
+            return false;
+        }
+        let bitfield = match self
+            .discovery
+            .enr_of_peer(peer_id)
+            .and_then(|enr| enr.bitfield::<TSpec>().ok())
+        {
+            Some(bitfield) => bitfield,
+            None => return false,
+        };
+        self.subnet_queries
+            .keys_vec()
+            .into_iter()
+            .any(|subnet_id| bitfield.get(**subnet_id as usize).unwrap_or(false))
+    }
+
     /// Run a standard query to search for more peers.
     ///
     /// This searches for the standard kademlia bucket size (16) peers.
@@ -355,8 +434,13 @@ where
     }
 
     fn addresses_of_peer(&mut self, peer_id: &PeerId) -> Vec<Multiaddr> {
-        // Let discovery track possible known peers.
-        self.discovery.addresses_of_peer(peer_id)
+        // Let discovery track possible known peers, never dialing denied addresses.
+        let peer_filter = &self.peer_filter;
+        self.discovery
+            .addresses_of_peer(peer_id)
+            .into_iter()
+            .filter(|multiaddr| !peer_filter.is_address_denied(multiaddr))
+            .collect()
     }
 
     fn inject_connected(&mut self, _peer_id: PeerId, _endpoint: ConnectedPoint) {}
@@ -393,7 +477,7 @@ where
         loop {
             match self.peer_discovery_delay.poll() {
                 Ok(Async::Ready(_)) => {
-                    if self.network_globals.connected_peers() < self.max_peers {
+                    if self.network_globals.connected_untrusted_peers() < self.max_peers {
                         self.find_peers();
                     }
                     // Set to maximum, and update to earlier, once we get our results back.
@@ -408,6 +492,9 @@ where
             }
         }
 
+        // forget subnet searches that have completed
+        while let Ok(Async::Ready(Some(_))) = self.subnet_queries.poll() {}
+
         // Poll discovery
         loop {
             match self.discovery.poll(params) {
@@ -452,10 +539,16 @@ where
                                 .reset(Instant::now() + Duration::from_secs(delay));
 
                             for peer_id in closer_peers {
-                                // if we need more peers, attempt a connection
-
-                                if self.network_globals.connected_or_dialing_peers()
-                                    < self.max_peers
+                                // if we need more peers, attempt a connection. Peers on subnets
+                                // we are searching for may use the reserved excess connections.
+                                let peer_limit = if self.is_on_queried_subnet(&peer_id) {
+                                    self.max_peers + SUBNET_PEER_EXCESS
+                                } else {
+                                    self.max_peers
+                                };
+
+                                if self.network_globals.connected_or_dialing_untrusted_peers()
+                                    < peer_limit
                                     && !self
                                         .network_globals
                                         .peers
@@ -9,17 +9,21 @@ pub mod behaviour;
 mod config;
 pub mod discovery;
 mod metrics;
+mod nat;
 mod peer_manager;
 pub mod rpc;
 mod service;
 pub mod types;
 
-pub use crate::types::{error, Enr, GossipTopic, NetworkGlobals, PubsubMessage};
+pub use crate::types::{error, Enr, GossipTopic, MessageAcceptance, NetworkGlobals, PubsubMessage};
 pub use behaviour::BehaviourEvent;
 pub use config::Config as NetworkConfig;
 pub use libp2p::gossipsub::{MessageId, Topic, TopicHash};
 pub use libp2p::{multiaddr, Multiaddr};
 pub use libp2p::{PeerId, Swarm};
-pub use peer_manager::{PeerDB, PeerInfo, PeerSyncStatus, SyncInfo};
+pub use peer_manager::{
+    peer_id_of_multiaddr, Client, IpCidr, PeerAction, PeerDB, PeerInfo, PeerSyncStatus, Rep,
+    SyncInfo, DEFAULT_REPUTATION,
+};
 pub use rpc::RPCEvent;
 pub use service::{Service, NETWORK_KEY_FILENAME};
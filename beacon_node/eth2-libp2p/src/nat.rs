@@ -0,0 +1,474 @@
+//! Maps the libp2p and discovery ports on the local gateway with UPnP, so that nodes behind a NAT
+//! can accept inbound connections.
+//!
+//! Only UPnP IGD gateways are supported. Gateways that only offer NAT-PMP or PCP are not.
+
+use crate::NetworkConfig;
+use futures::sync::mpsc;
+use igd::{Gateway, PortMappingProtocol, SearchOptions};
+use slog::{debug, info, warn};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, SocketAddrV4, UdpSocket};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc as std_mpsc;
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+/// The lease in seconds requested for each port mapping.
+const MAPPING_LEASE_DURATION: u32 = 3600;
+/// The time in seconds between renewals of the port mappings.
+const MAPPING_RENEWAL_INTERVAL: u64 = 1800;
+/// The description given to the port mappings on the gateway.
+const MAPPING_DESCRIPTION: &str = "lighthouse";
+
+/// The parts of the local ENR to update once the ports have been mapped on the gateway. Fields
+/// that were set by the user are `None`, so that they are never overridden.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExternalAddress {
+    /// The external IP of the gateway.
+    pub ip: Option<Ipv4Addr>,
+    /// The mapped libp2p TCP port.
+    pub tcp_port: Option<u16>,
+    /// The mapped discovery UDP port.
+    pub udp_port: Option<u16>,
+}
+
+/// The ports to map on the gateway, along with the ENR fields that the user has set.
+#[derive(Debug, Clone)]
+struct MappingConfig {
+    listen_address: IpAddr,
+    tcp_port: u16,
+    udp_port: u16,
+    enr_address: Option<IpAddr>,
+    enr_tcp_port: Option<u16>,
+    enr_udp_port: Option<u16>,
+}
+
+impl MappingConfig {
+    fn new(config: &NetworkConfig) -> Self {
+        MappingConfig {
+            listen_address: config.listen_address,
+            tcp_port: config.libp2p_port,
+            udp_port: config.discovery_port,
+            enr_address: config.enr_address,
+            enr_tcp_port: config.enr_tcp_port,
+            enr_udp_port: config.enr_udp_port,
+        }
+    }
+
+    /// Returns the ENR fields to update once the ports are mapped on a gateway with the external
+    /// IP `ip`, or `None` if the user has set all of them.
+    fn external_address(&self, ip: Ipv4Addr) -> Option<ExternalAddress> {
+        let external_address = ExternalAddress {
+            ip: Some(ip).filter(|_| self.enr_address.is_none()),
+            tcp_port: Some(self.tcp_port).filter(|_| self.enr_tcp_port.is_none()),
+            udp_port: Some(self.udp_port).filter(|_| self.enr_udp_port.is_none()),
+        };
+
+        if external_address.ip.is_none()
+            && external_address.tcp_port.is_none()
+            && external_address.udp_port.is_none()
+        {
+            None
+        } else {
+            Some(external_address)
+        }
+    }
+}
+
+/// Maintains UPnP port mappings on a background thread. The mappings are renewed until this is
+/// dropped, at which point they are removed from the gateway.
+pub struct PortMapper {
+    /// Signals the mapping thread to remove the mappings and exit.
+    exit: Option<std_mpsc::Sender<()>>,
+    /// The mapping thread.
+    handle: Option<JoinHandle<()>>,
+    /// Set once the gateway has been found, after which the mapping thread exits promptly.
+    gateway_found: Arc<AtomicBool>,
+    /// Receives the external address each time it is established or changes.
+    pub external_addresses: mpsc::UnboundedReceiver<ExternalAddress>,
+}
+
+impl PortMapper {
+    /// Spawns a thread that searches for a UPnP gateway and maps the libp2p and discovery ports
+    /// on it.
+    pub fn spawn(config: &NetworkConfig, log: slog::Logger) -> Result<Self, String> {
+        let (exit, exit_rx) = std_mpsc::channel();
+        let (address_tx, external_addresses) = mpsc::unbounded();
+        let gateway_found = Arc::new(AtomicBool::new(false));
+
+        let mapping_config = MappingConfig::new(config);
+        let thread_gateway_found = gateway_found.clone();
+        let handle = thread::Builder::new()
+            .name("upnp".into())
+            .spawn(move || {
+                maintain_mappings(
+                    mapping_config,
+                    thread_gateway_found,
+                    address_tx,
+                    exit_rx,
+                    log,
+                )
+            })
+            .map_err(|e| format!("Unable to spawn UPnP thread: {:?}", e))?;
+
+        Ok(PortMapper {
+            exit: Some(exit),
+            handle: Some(handle),
+            gateway_found,
+            external_addresses,
+        })
+    }
+}
+
+impl Drop for PortMapper {
+    fn drop(&mut self) {
+        // dropping the sender wakes the thread, which removes the mappings before exiting
+        self.exit.take();
+        if let Some(handle) = self.handle.take() {
+            // a thread that is still searching for the gateway has no mappings to remove and is
+            // left to exit once the search times out
+            if self.gateway_found.load(Ordering::SeqCst) {
+                let _ = handle.join();
+            }
+        }
+    }
+}
+
+/// The operations of a UPnP gateway that are used to maintain the port mappings.
+trait PortMappingGateway {
+    fn external_ip(&self) -> Result<Ipv4Addr, String>;
+
+    /// Maps the external port to the same port at `local_address`.
+    fn add_port(
+        &self,
+        protocol: PortMappingProtocol,
+        local_address: SocketAddrV4,
+    ) -> Result<(), String>;
+
+    fn remove_port(&self, protocol: PortMappingProtocol, port: u16) -> Result<(), String>;
+}
+
+impl PortMappingGateway for Gateway {
+    fn external_ip(&self) -> Result<Ipv4Addr, String> {
+        self.get_external_ip().map_err(|e| format!("{}", e))
+    }
+
+    fn add_port(
+        &self,
+        protocol: PortMappingProtocol,
+        local_address: SocketAddrV4,
+    ) -> Result<(), String> {
+        Gateway::add_port(
+            self,
+            protocol,
+            local_address.port(),
+            local_address,
+            MAPPING_LEASE_DURATION,
+            MAPPING_DESCRIPTION,
+        )
+        .map_err(|e| format!("{}", e))
+    }
+
+    fn remove_port(&self, protocol: PortMappingProtocol, port: u16) -> Result<(), String> {
+        Gateway::remove_port(self, protocol, port).map_err(|e| format!("{}", e))
+    }
+}
+
+/// Maps the ports and renews them until `exit_rx` is signalled or dropped, then removes them.
+/// Ports that could not be mapped are retried at each renewal.
+fn maintain_mappings(
+    config: MappingConfig,
+    gateway_found: Arc<AtomicBool>,
+    address_tx: mpsc::UnboundedSender<ExternalAddress>,
+    exit_rx: std_mpsc::Receiver<()>,
+    log: slog::Logger,
+) {
+    let gateway = match igd::search_gateway(SearchOptions::default()) {
+        Ok(gateway) => gateway,
+        Err(e) => {
+            warn!(log, "UPnP gateway not found, ports have not been mapped"; "error" => format!("{}", e));
+            return;
+        }
+    };
+    gateway_found.store(true, Ordering::SeqCst);
+
+    // the port mapper may have been dropped during the search
+    if let Err(std_mpsc::TryRecvError::Disconnected) = exit_rx.try_recv() {
+        return;
+    }
+
+    let local_ip = match local_ip_towards(config.listen_address, &gateway) {
+        Ok(ip) => ip,
+        Err(e) => {
+            warn!(log, "Could not determine the local address for UPnP"; "error" => e);
+            return;
+        }
+    };
+
+    let mut mapped_ip = None;
+    loop {
+        match map_ports(&gateway, local_ip, config.tcp_port, config.udp_port) {
+            Ok(ip) => {
+                if mapped_ip != Some(ip) {
+                    info!(log, "UPnP port mappings established";
+                        "external_ip" => format!("{}", ip),
+                        "tcp_port" => config.tcp_port,
+                        "udp_port" => config.udp_port,
+                    );
+                    if let Some(external_address) = config.external_address(ip) {
+                        if address_tx.unbounded_send(external_address).is_err() {
+                            break;
+                        }
+                    }
+                    mapped_ip = Some(ip);
+                }
+            }
+            Err(e) => {
+                // existing mappings are kept and mapping is retried at the next interval
+                warn!(log, "Could not map ports with UPnP";
+                    "error" => e,
+                    "retry_secs" => MAPPING_RENEWAL_INTERVAL,
+                );
+            }
+        }
+
+        match exit_rx.recv_timeout(Duration::from_secs(MAPPING_RENEWAL_INTERVAL)) {
+            Err(std_mpsc::RecvTimeoutError::Timeout) => {
+                debug!(log, "Renewing UPnP port mappings"; "mapped" => mapped_ip.is_some());
+            }
+            _ => break,
+        }
+    }
+
+    if mapped_ip.is_some() {
+        remove_port(&gateway, PortMappingProtocol::TCP, config.tcp_port, &log);
+        remove_port(&gateway, PortMappingProtocol::UDP, config.udp_port, &log);
+    }
+}
+
+/// Maps the TCP and UDP ports to the same ports at `local_ip`, returning the external IP of the
+/// gateway once both are mapped.
+///
+/// Nothing is mapped if the external IP is not public, as the gateway is then itself behind a
+/// NAT. If only the TCP port could be mapped, its mapping is removed again, so that a port is
+/// never mapped without the other.
+fn map_ports<G: PortMappingGateway>(
+    gateway: &G,
+    local_ip: Ipv4Addr,
+    tcp_port: u16,
+    udp_port: u16,
+) -> Result<Ipv4Addr, String> {
+    let ip = gateway
+        .external_ip()
+        .map_err(|e| format!("Could not get the external address: {}", e))?;
+    if !is_public_ipv4(&ip) {
+        return Err(format!("The external address is not public: {}", ip));
+    }
+
+    gateway
+        .add_port(
+            PortMappingProtocol::TCP,
+            SocketAddrV4::new(local_ip, tcp_port),
+        )
+        .map_err(|e| format!("Could not map TCP port {}: {}", tcp_port, e))?;
+    if let Err(e) = gateway.add_port(
+        PortMappingProtocol::UDP,
+        SocketAddrV4::new(local_ip, udp_port),
+    ) {
+        let _ = gateway.remove_port(PortMappingProtocol::TCP, tcp_port);
+        return Err(format!("Could not map UDP port {}: {}", udp_port, e));
+    }
+
+    Ok(ip)
+}
+
+/// Returns true if `ip` is publicly routable. Private and carrier-grade NAT (100.64.0.0/10)
+/// addresses are not.
+fn is_public_ipv4(ip: &Ipv4Addr) -> bool {
+    let octets = ip.octets();
+    let is_shared = octets[0] == 100 && (octets[1] & 0b1100_0000) == 0b0100_0000;
+    !(ip.is_private()
+        || ip.is_loopback()
+        || ip.is_link_local()
+        || ip.is_unspecified()
+        || ip.is_broadcast()
+        || ip.is_documentation()
+        || is_shared)
+}
+
+/// Returns the local address that the gateway reaches this node at. If the node listens on all
+/// interfaces, this is the address of the interface that routes to the gateway.
+fn local_ip_towards(listen_address: IpAddr, gateway: &Gateway) -> Result<Ipv4Addr, String> {
+    match listen_address {
+        IpAddr::V4(ip) if !ip.is_unspecified() => Ok(ip),
+        IpAddr::V4(_) => {
+            // connecting a UDP socket sends no packets, it only selects the route
+            let socket = UdpSocket::bind("0.0.0.0:0").map_err(|e| format!("{:?}", e))?;
+            socket
+                .connect(gateway.addr)
+                .map_err(|e| format!("{:?}", e))?;
+            match socket.local_addr().map_err(|e| format!("{:?}", e))? {
+                SocketAddr::V4(addr) => Ok(*addr.ip()),
+                SocketAddr::V6(addr) => Err(format!("Unexpected IPv6 local address: {}", addr)),
+            }
+        }
+        IpAddr::V6(ip) => Err(format!("UPnP does not support IPv6 address: {}", ip)),
+    }
+}
+
+fn remove_port(gateway: &Gateway, protocol: PortMappingProtocol, port: u16, log: &slog::Logger) {
+    match PortMappingGateway::remove_port(gateway, protocol, port) {
+        Ok(()) => {
+            debug!(log, "Removed UPnP port mapping"; "protocol" => format!("{}", protocol), "port" => port)
+        }
+        Err(e) => {
+            warn!(log, "Could not remove UPnP port mapping"; "protocol" => format!("{}", protocol), "port" => port, "error" => e)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    /// A gateway that keeps its mappings in memory and refuses to map the `refused` ports.
+    struct MockGateway {
+        external_ip: Ipv4Addr,
+        refused: Vec<(PortMappingProtocol, u16)>,
+        mappings: RefCell<Vec<(PortMappingProtocol, u16)>>,
+    }
+
+    impl MockGateway {
+        fn new(external_ip: &str) -> Self {
+            MockGateway {
+                external_ip: external_ip.parse().unwrap(),
+                refused: vec![],
+                mappings: RefCell::new(vec![]),
+            }
+        }
+    }
+
+    impl PortMappingGateway for MockGateway {
+        fn external_ip(&self) -> Result<Ipv4Addr, String> {
+            Ok(self.external_ip)
+        }
+
+        fn add_port(
+            &self,
+            protocol: PortMappingProtocol,
+            local_address: SocketAddrV4,
+        ) -> Result<(), String> {
+            let mapping = (protocol, local_address.port());
+            if self.refused.contains(&mapping) {
+                return Err("refused".into());
+            }
+            self.mappings.borrow_mut().push(mapping);
+            Ok(())
+        }
+
+        fn remove_port(&self, protocol: PortMappingProtocol, port: u16) -> Result<(), String> {
+            self.mappings
+                .borrow_mut()
+                .retain(|mapping| *mapping != (protocol, port));
+            Ok(())
+        }
+    }
+
+    fn local_ip() -> Ipv4Addr {
+        "192.168.1.2".parse().unwrap()
+    }
+
+    #[test]
+    fn test_map_ports() {
+        let gateway = MockGateway::new("1.2.3.4");
+        assert_eq!(
+            map_ports(&gateway, local_ip(), 9000, 9001),
+            Ok("1.2.3.4".parse().unwrap())
+        );
+        assert_eq!(
+            *gateway.mappings.borrow(),
+            vec![
+                (PortMappingProtocol::TCP, 9000),
+                (PortMappingProtocol::UDP, 9001)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_map_ports_partial_failure() {
+        let mut gateway = MockGateway::new("1.2.3.4");
+        gateway.refused = vec![(PortMappingProtocol::UDP, 9001)];
+        assert!(map_ports(&gateway, local_ip(), 9000, 9001).is_err());
+        assert!(gateway.mappings.borrow().is_empty());
+
+        let mut gateway = MockGateway::new("1.2.3.4");
+        gateway.refused = vec![(PortMappingProtocol::TCP, 9000)];
+        assert!(map_ports(&gateway, local_ip(), 9000, 9001).is_err());
+        assert!(gateway.mappings.borrow().is_empty());
+    }
+
+    #[test]
+    fn test_map_ports_non_public_external_ip() {
+        for ip in &[
+            "192.168.0.1",
+            "10.1.2.3",
+            "172.16.0.1",
+            "100.64.0.1",
+            "100.127.255.254",
+        ] {
+            let gateway = MockGateway::new(ip);
+            assert!(map_ports(&gateway, local_ip(), 9000, 9001).is_err());
+            assert!(gateway.mappings.borrow().is_empty());
+        }
+    }
+
+    #[test]
+    fn test_is_public_ipv4() {
+        for ip in &["1.2.3.4", "100.63.255.255", "100.128.0.0", "172.32.0.1"] {
+            assert!(is_public_ipv4(&ip.parse().unwrap()), "{} is public", ip);
+        }
+        for ip in &["127.0.0.1", "169.254.0.1", "0.0.0.0", "100.100.100.100"] {
+            assert!(
+                !is_public_ipv4(&ip.parse().unwrap()),
+                "{} is not public",
+                ip
+            );
+        }
+    }
+
+    #[test]
+    fn test_external_address_keeps_user_enr_fields() {
+        let mut network_config = NetworkConfig::default();
+        network_config.libp2p_port = 9000;
+        network_config.discovery_port = 9001;
+        let ip: Ipv4Addr = "1.2.3.4".parse().unwrap();
+
+        assert_eq!(
+            MappingConfig::new(&network_config).external_address(ip),
+            Some(ExternalAddress {
+                ip: Some(ip),
+                tcp_port: Some(9000),
+                udp_port: Some(9001),
+            })
+        );
+
+        network_config.enr_address = Some("5.6.7.8".parse().unwrap());
+        network_config.enr_udp_port = Some(30303);
+        assert_eq!(
+            MappingConfig::new(&network_config).external_address(ip),
+            Some(ExternalAddress {
+                ip: None,
+                tcp_port: Some(9000),
+                udp_port: None,
+            })
+        );
+
+        network_config.enr_tcp_port = Some(30303);
+        assert_eq!(
+            MappingConfig::new(&network_config).external_address(ip),
+            None
+        );
+    }
+}
@@ -49,14 +49,19 @@ impl Default for Client {
 impl Client {
     /// Builds a `Client` from `IdentifyInfo`.
     pub fn from_identify_info(info: &IdentifyInfo) -> Self {
-        let (kind, version, os_version) = client_from_agent_version(&info.agent_version);
+        Client::from_agent_string(info.agent_version.clone(), info.protocol_version.clone())
+    }
+
+    /// Builds a `Client` from an identify agent string and protocol version.
+    pub fn from_agent_string(agent_string: String, protocol_version: String) -> Self {
+        let (kind, version, os_version) = client_from_agent_version(&agent_string);
 
         Client {
             kind,
             version,
             os_version,
-            protocol_version: info.protocol_version.clone(),
-            agent_string: Some(info.agent_version.clone()),
+            protocol_version,
+            agent_string: Some(agent_string),
         }
     }
 }
@@ -0,0 +1,190 @@
+//! Per-topic parameters for scoring peers on the gossipsub messages they deliver.
+
+use super::peerdb::Rep;
+use crate::types::GossipKind;
+use crate::PeerId;
+use std::collections::{HashMap, HashSet};
+
+/// The reputation changes applied to peers for the messages they deliver on a topic.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TopicScoreParams {
+    /// The reputation gained by a peer for being the first to deliver a valid message.
+    pub first_message_deliveries_reward: Rep,
+    /// The reputation lost by a peer for delivering a message that is rejected.
+    pub invalid_message_deliveries_penalty: Rep,
+    /// The number of valid messages a peer subscribed to the topic is expected to be the first
+    /// to deliver between peer manager heartbeats. A threshold of zero disables the check.
+    pub mesh_message_deliveries_threshold: u32,
+    /// The reputation lost every heartbeat by a subscribed peer that delivered fewer messages
+    /// than the threshold.
+    pub mesh_message_deliveries_penalty: Rep,
+}
+
+/// The scoring parameters of each kind of gossipsub topic.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GossipScoreParams {
+    pub beacon_block: TopicScoreParams,
+    pub beacon_aggregate_and_proof: TopicScoreParams,
+    /// The parameters shared by all committee subnets.
+    pub committee_index: TopicScoreParams,
+    /// The parameters shared by the voluntary exit and slashing topics.
+    pub operations: TopicScoreParams,
+}
+
+impl Default for GossipScoreParams {
+    /// Rewards peers for delivering the infrequent messages first and penalises invalid messages.
+    /// Attestations are too frequent to be rewarded without saturating reputations, and the
+    /// delivery rate checks are disabled as only first deliveries are observed.
+    fn default() -> Self {
+        GossipScoreParams {
+            beacon_block: TopicScoreParams {
+                first_message_deliveries_reward: 1,
+                invalid_message_deliveries_penalty: -20,
+                mesh_message_deliveries_threshold: 0,
+                mesh_message_deliveries_penalty: -5,
+            },
+            beacon_aggregate_and_proof: TopicScoreParams {
+                first_message_deliveries_reward: 0,
+                invalid_message_deliveries_penalty: -20,
+                mesh_message_deliveries_threshold: 0,
+                mesh_message_deliveries_penalty: -5,
+            },
+            committee_index: TopicScoreParams {
+                first_message_deliveries_reward: 0,
+                invalid_message_deliveries_penalty: -10,
+                mesh_message_deliveries_threshold: 0,
+                mesh_message_deliveries_penalty: -5,
+            },
+            operations: TopicScoreParams {
+                first_message_deliveries_reward: 1,
+                invalid_message_deliveries_penalty: -20,
+                mesh_message_deliveries_threshold: 0,
+                mesh_message_deliveries_penalty: 0,
+            },
+        }
+    }
+}
+
+impl GossipScoreParams {
+    /// Returns the parameters of the given kind of topic.
+    pub fn topic(&self, kind: &GossipKind) -> &TopicScoreParams {
+        match kind {
+            GossipKind::BeaconBlock => &self.beacon_block,
+            GossipKind::BeaconAggregateAndProof => &self.beacon_aggregate_and_proof,
+            GossipKind::CommitteeIndex(_) => &self.committee_index,
+            GossipKind::VoluntaryExit
+            | GossipKind::ProposerSlashing
+            | GossipKind::AttesterSlashing => &self.operations,
+        }
+    }
+}
+
+/// Tracks the topics peers are subscribed to and the number of valid messages they were the first
+/// to deliver on each of them since the last heartbeat.
+#[derive(Default)]
+pub struct MeshDeliveries {
+    subscriptions: HashMap<PeerId, HashSet<GossipKind>>,
+    deliveries: HashMap<(PeerId, GossipKind), u32>,
+}
+
+impl MeshDeliveries {
+    pub fn subscribe(&mut self, peer_id: PeerId, kind: GossipKind) {
+        self.subscriptions.entry(peer_id).or_default().insert(kind);
+    }
+
+    pub fn unsubscribe(&mut self, peer_id: &PeerId, kind: &GossipKind) {
+        if let Some(kinds) = self.subscriptions.get_mut(peer_id) {
+            kinds.remove(kind);
+        }
+    }
+
+    /// Forgets a disconnected peer.
+    pub fn remove_peer(&mut self, peer_id: &PeerId) {
+        self.subscriptions.remove(peer_id);
+        self.deliveries.retain(|(id, _), _| id != peer_id);
+    }
+
+    /// Records that the peer was the first to deliver a valid message on the topic.
+    pub fn record_delivery(&mut self, peer_id: PeerId, kind: GossipKind) {
+        *self.deliveries.entry((peer_id, kind)).or_default() += 1;
+    }
+
+    /// Returns the total penalty of each subscribed peer that delivered fewer messages than the
+    /// thresholds of its topics, and starts counting the deliveries of the next heartbeat.
+    pub fn take_penalties(&mut self, params: &GossipScoreParams) -> Vec<(PeerId, Rep)> {
+        let deliveries = std::mem::replace(&mut self.deliveries, HashMap::new());
+
+        self.subscriptions
+            .iter()
+            .filter_map(|(peer_id, kinds)| {
+                let penalty: Rep = kinds
+                    .iter()
+                    .map(|kind| (kind, params.topic(kind)))
+                    .filter(|(kind, topic_params)| {
+                        let delivered = deliveries
+                            .get(&(peer_id.clone(), (*kind).clone()))
+                            .cloned()
+                            .unwrap_or(0);
+                        delivered < topic_params.mesh_message_deliveries_threshold
+                    })
+                    .map(|(_, topic_params)| topic_params.mesh_message_deliveries_penalty)
+                    .sum();
+                if penalty != 0 {
+                    Some((peer_id.clone(), penalty))
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mesh_delivery_penalties() {
+        let mut params = GossipScoreParams::default();
+        params.beacon_block.mesh_message_deliveries_threshold = 2;
+        params.beacon_block.mesh_message_deliveries_penalty = -5;
+
+        let mut mesh_deliveries = MeshDeliveries::default();
+        let quiet_peer = PeerId::random();
+        let busy_peer = PeerId::random();
+        let unsubscribed_peer = PeerId::random();
+
+        mesh_deliveries.subscribe(quiet_peer.clone(), GossipKind::BeaconBlock);
+        mesh_deliveries.subscribe(busy_peer.clone(), GossipKind::BeaconBlock);
+        mesh_deliveries.subscribe(unsubscribed_peer.clone(), GossipKind::BeaconBlock);
+        mesh_deliveries.unsubscribe(&unsubscribed_peer, &GossipKind::BeaconBlock);
+
+        mesh_deliveries.record_delivery(quiet_peer.clone(), GossipKind::BeaconBlock);
+        mesh_deliveries.record_delivery(busy_peer.clone(), GossipKind::BeaconBlock);
+        mesh_deliveries.record_delivery(busy_peer.clone(), GossipKind::BeaconBlock);
+
+        assert_eq!(
+            mesh_deliveries.take_penalties(&params),
+            vec![(quiet_peer.clone(), -5)]
+        );
+
+        // deliveries are counted per heartbeat
+        let mut penalised: Vec<PeerId> = mesh_deliveries
+            .take_penalties(&params)
+            .into_iter()
+            .map(|(peer_id, _)| peer_id)
+            .collect();
+        penalised.sort_by_key(|peer_id| peer_id.to_base58());
+        let mut expected = vec![quiet_peer, busy_peer];
+        expected.sort_by_key(|peer_id| peer_id.to_base58());
+        assert_eq!(penalised, expected);
+    }
+
+    #[test]
+    fn test_thresholds_disabled_by_default() {
+        let params = GossipScoreParams::default();
+        let mut mesh_deliveries = MeshDeliveries::default();
+        mesh_deliveries.subscribe(PeerId::random(), GossipKind::BeaconBlock);
+        assert!(mesh_deliveries.take_penalties(&params).is_empty());
+    }
+}
@@ -1,8 +1,10 @@
 //! Implementation of a Lighthouse's peer management system.
 
 pub use self::peerdb::*;
+use crate::discovery::TARGET_SUBNET_PEERS;
 use crate::metrics;
-use crate::rpc::MetaData;
+use crate::rpc::{MetaData, RPCError};
+use crate::types::{GossipKind, MessageAcceptance};
 use crate::{NetworkGlobals, PeerId};
 use futures::prelude::*;
 use futures::Stream;
@@ -10,22 +12,45 @@ use hashmap_delay::HashSetDelay;
 use libp2p::identify::IdentifyInfo;
 use slog::{crit, debug, error, warn};
 use smallvec::SmallVec;
+use std::collections::HashSet;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
-use types::EthSpec;
+use tokio::timer::Interval;
+use types::{EthSpec, SubnetId};
 
 mod client;
+mod gossip_scoring;
+mod peer_filter;
 mod peer_info;
 mod peer_sync_status;
 mod peerdb;
 
+pub use client::Client;
+pub use gossip_scoring::{GossipScoreParams, TopicScoreParams};
+pub use peer_filter::{peer_id_of_multiaddr, IpCidr, PeerFilter};
 pub use peer_info::PeerInfo;
 pub use peer_sync_status::{PeerSyncStatus, SyncInfo};
+
+use gossip_scoring::MeshDeliveries;
 /// The minimum reputation before a peer is disconnected.
 // Most likely this needs tweaking
-const _MINIMUM_REPUTATION_BEFORE_BAN: Rep = 20;
+const MINIMUM_REPUTATION_BEFORE_DISCONNECT: Rep = 20;
+/// The minimum reputation before a peer is banned. A peer that is disconnected for a low
+/// reputation also receives the `PeerAction::Disconnected` penalty, so a peer that keeps
+/// misbehaving after reconnecting falls below this threshold.
+const MINIMUM_REPUTATION_BEFORE_BAN: Rep = -20;
+/// The maximum reputation a peer can earn through valid messages.
+const MAXIMUM_REPUTATION: Rep = 100;
+/// The time in seconds it takes for a peer's reputation to move one point back towards
+/// `DEFAULT_REPUTATION`.
+const REPUTATION_DECAY_INTERVAL: u64 = 60;
+/// The time in seconds between updates of the peers' reputations.
+const HEARTBEAT_INTERVAL: u64 = 30;
 /// The time in seconds between re-status's peers.
 const STATUS_INTERVAL: u64 = 300;
+/// The time in seconds that peers are kept on a subnet after a request to find peers for an
+/// upcoming duty on it.
+const UPCOMING_SUBNET_TIMEOUT: u64 = 768;
 /// The time in seconds between PING events. We do not send a ping if the other peer as PING'd us within
 /// this time frame (Seconds)
 const PING_INTERVAL: u64 = 30;
@@ -42,21 +67,39 @@ pub struct PeerManager<TSpec: EthSpec> {
     status_peers: HashSetDelay<PeerId>,
     /// Last updated moment.
     last_updated: Instant,
+    /// A timer that periodically decays the peers' reputations.
+    heartbeat: Interval,
+    /// The per-topic parameters used to score peers on the gossipsub messages they deliver.
+    gossip_score_params: GossipScoreParams,
+    /// The valid gossipsub messages peers were the first to deliver since the last heartbeat.
+    mesh_deliveries: MeshDeliveries,
+    /// The number of connected peers, excluding trusted peers, to prune down to.
+    target_peers: usize,
+    /// The subnets we are subscribed to.
+    subscribed_subnets: HashSet<SubnetId>,
+    /// The subnets peers have been requested for, ahead of upcoming duties.
+    upcoming_subnets: HashSetDelay<SubnetId>,
     /// The logger associated with the `PeerManager`.
     log: slog::Logger,
 }
 
 /// A collection of actions a peer can perform which will adjust its reputation
 /// Each variant has an associated reputation change.
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum PeerAction {
     /// The peer timed out on an RPC request/response.
-    _TimedOut = -10,
+    TimedOut = -10,
     /// The peer sent and invalid request/response or encoding.
-    _InvalidMessage = -20,
+    InvalidMessage = -20,
     /// The peer sent  something objectively malicious.
-    _Malicious = -50,
+    Malicious = -50,
+    /// The peer exceeded its quota of inbound RPC requests.
+    RateLimited = -5,
+    /// An RPC stream with the peer failed for a reason that may not be the fault of the peer
+    /// (e.g., the stream was closed early).
+    StreamError = -3,
     /// Received an expected message.
-    _ValidMessage = 20,
+    ValidMessage = 20,
     /// Peer disconnected.
     Disconnected = -30,
 }
@@ -70,17 +113,32 @@ pub enum PeerManagerEvent {
     /// Request METADATA from a peer.
     MetaData(PeerId),
     /// The peer should be disconnected.
-    _DisconnectPeer(PeerId),
+    DisconnectPeer(PeerId),
+    /// The peer should be disconnected, without a ban, as too many peers are connected.
+    PrunePeer(PeerId),
     /// The peer should be disconnected and banned.
-    _BanPeer(PeerId),
+    BanPeer(PeerId),
+    /// More peers are required on the subnet.
+    DiscoverSubnetPeers(SubnetId),
 }
 
 impl<TSpec: EthSpec> PeerManager<TSpec> {
-    pub fn new(network_globals: Arc<NetworkGlobals<TSpec>>, log: &slog::Logger) -> Self {
+    pub fn new(
+        network_globals: Arc<NetworkGlobals<TSpec>>,
+        target_peers: usize,
+        gossip_score_params: GossipScoreParams,
+        log: &slog::Logger,
+    ) -> Self {
         PeerManager {
             network_globals,
             events: SmallVec::new(),
             last_updated: Instant::now(),
+            heartbeat: Interval::new_interval(Duration::from_secs(HEARTBEAT_INTERVAL)),
+            gossip_score_params,
+            mesh_deliveries: MeshDeliveries::default(),
+            target_peers,
+            subscribed_subnets: HashSet::new(),
+            upcoming_subnets: HashSetDelay::new(Duration::from_secs(UPCOMING_SUBNET_TIMEOUT)),
             ping_peers: HashSetDelay::new(Duration::from_secs(PING_INTERVAL)),
             status_peers: HashSetDelay::new(Duration::from_secs(STATUS_INTERVAL)),
             log: log.clone(),
@@ -167,29 +225,23 @@ impl<TSpec: EthSpec> PeerManager<TSpec> {
         self.status_peers.insert(peer_id.clone());
     }
 
-    /// Checks the reputation of a peer and if it is too low, bans it and
-    /// sends the corresponding event. Informs if it got banned
-    fn _gets_banned(&mut self, peer_id: &PeerId) -> bool {
-        // if the peer was already banned don't inform again
-        let mut peerdb = self.network_globals.peers.write();
+    /// Requests that a peer get disconnected.
+    pub fn _disconnect_peer(&mut self, peer_id: &PeerId) {
+        self.events
+            .push(PeerManagerEvent::DisconnectPeer(peer_id.clone()));
+    }
 
-        if let Some(connection_status) = peerdb.connection_status(peer_id) {
-            if peerdb.reputation(peer_id) < _MINIMUM_REPUTATION_BEFORE_BAN
-                && !connection_status.is_banned()
-            {
-                peerdb.ban(peer_id);
-                self.events
-                    .push(PeerManagerEvent::_BanPeer(peer_id.clone()));
-                return true;
-            }
+    /// A peer has been banned and prevented from reconnecting.
+    pub fn peer_banned(&mut self, peer_id: &PeerId) {
+        let mut peerdb = self.network_globals.peers.write();
+        if peerdb.connection_status(peer_id).map(|c| c.is_banned()) != Some(true) {
+            peerdb.ban(peer_id);
         }
-        false
     }
 
-    /// Requests that a peer get disconnected.
-    pub fn _disconnect_peer(&mut self, peer_id: &PeerId) {
-        self.events
-            .push(PeerManagerEvent::_DisconnectPeer(peer_id.clone()));
+    /// A peer's ban has expired, allowing it to reconnect.
+    pub fn peer_unbanned(&mut self, peer_id: &PeerId) {
+        self.network_globals.peers.write().unban(peer_id);
     }
 
     /// Updates the state of the peer as disconnected.
@@ -204,6 +256,7 @@ impl<TSpec: EthSpec> PeerManager<TSpec> {
         // remove the ping and status timer for the peer
         self.ping_peers.remove(peer_id);
         self.status_peers.remove(peer_id);
+        self.mesh_deliveries.remove_peer(peer_id);
         metrics::inc_counter(&metrics::PEER_DISCONNECT_EVENT_COUNT);
         metrics::set_gauge(
             &metrics::PEERS_CONNECTED,
@@ -228,31 +281,96 @@ impl<TSpec: EthSpec> PeerManager<TSpec> {
         self.network_globals.peers.read().reputation(peer_id)
     }
 
-    /// Updates the reputation of known peers according to their connection
-    /// status and the time that has passed.
+    /// Updates the reputation of known peers according to the time that has passed.
+    ///
+    /// Reputations move one point towards `DEFAULT_REPUTATION` every
+    /// `REPUTATION_DECAY_INTERVAL` seconds, so both penalties and rewards are temporary.
     pub fn update_reputations(&mut self) {
-        let now = Instant::now();
-        let elapsed = (now - self.last_updated).as_secs();
-        // 0 seconds means now - last_updated < 0, but (most likely) not = 0.
-        // In this case, do nothing (updating last_updated would propagate
-        // rounding errors)
-        if elapsed > 0 {
-            self.last_updated = now;
-            // TODO decide how reputations change with time. If they get too low
-            // set the peers as banned
+        let elapsed = self.last_updated.elapsed().as_secs();
+        let steps = elapsed / REPUTATION_DECAY_INTERVAL;
+        // Only whole steps are applied. The remainder is carried to the next update to avoid
+        // accumulating rounding errors.
+        if steps > 0 {
+            self.last_updated += Duration::from_secs(steps * REPUTATION_DECAY_INTERVAL);
+            let steps = std::cmp::min(steps, Rep::max_value() as u64) as Rep;
+            self.network_globals.peers.write().decay_reputations(steps);
         }
     }
 
-    /// Reports a peer for some action.
+    /// Reports a peer for some action, disconnecting or banning it if its reputation becomes too
+    /// low.
     ///
     /// If the peer doesn't exist, log a warning and insert defaults.
-    pub fn _report_peer(&mut self, peer_id: &PeerId, action: PeerAction) {
-        self.update_reputations();
-        self.network_globals
-            .peers
-            .write()
-            .add_reputation(peer_id, action as Rep);
+    pub fn report_peer(&mut self, peer_id: &PeerId, action: PeerAction) {
         self.update_reputations();
+        self.change_reputation(peer_id, action as Rep, &format!("{:?}", action));
+    }
+
+    /// The validation of a gossipsub message delivered by the peer has completed. Scores the
+    /// peer according to the parameters of the message's topic.
+    pub fn gossip_validation_result(
+        &mut self,
+        peer_id: &PeerId,
+        kind: &GossipKind,
+        acceptance: MessageAcceptance,
+    ) {
+        let params = self.gossip_score_params.topic(kind).clone();
+        let change = match acceptance {
+            MessageAcceptance::Accept => {
+                self.mesh_deliveries
+                    .record_delivery(peer_id.clone(), kind.clone());
+                params.first_message_deliveries_reward
+            }
+            MessageAcceptance::Reject => params.invalid_message_deliveries_penalty,
+            MessageAcceptance::Ignore => 0,
+        };
+
+        if change != 0 {
+            self.update_reputations();
+            self.change_reputation(peer_id, change, &format!("{:?} on {}", acceptance, kind));
+        }
+    }
+
+    /// The peer has subscribed to a gossipsub topic.
+    pub fn peer_subscribed(&mut self, peer_id: &PeerId, kind: GossipKind) {
+        self.mesh_deliveries.subscribe(peer_id.clone(), kind);
+    }
+
+    /// The peer has unsubscribed from a gossipsub topic.
+    pub fn peer_unsubscribed(&mut self, peer_id: &PeerId, kind: &GossipKind) {
+        self.mesh_deliveries.unsubscribe(peer_id, kind);
+    }
+
+    /// We have subscribed to a subnet. Peers on the subnet are kept when pruning peers.
+    pub fn subnet_subscribed(&mut self, subnet_id: SubnetId) {
+        self.subscribed_subnets.insert(subnet_id);
+    }
+
+    /// We have unsubscribed from a subnet.
+    pub fn subnet_unsubscribed(&mut self, subnet_id: &SubnetId) {
+        self.subscribed_subnets.remove(subnet_id);
+    }
+
+    /// Peers have been requested on a subnet for an upcoming duty. Peers on the subnet are kept
+    /// when pruning peers until `UPCOMING_SUBNET_TIMEOUT` has passed.
+    pub fn subnet_peers_requested(&mut self, subnet_id: SubnetId) {
+        self.upcoming_subnets.insert(subnet_id);
+    }
+
+    /// An RPC request to or from a peer has failed. Reports the peer if the failure was caused by
+    /// the peer.
+    pub fn handle_rpc_error(&mut self, peer_id: &PeerId, error: &RPCError) {
+        let action = match error {
+            RPCError::StreamTimeout => PeerAction::TimedOut,
+            RPCError::Custom(_) => PeerAction::StreamError,
+            RPCError::SSZDecodeError(_)
+            | RPCError::SnappyError(_)
+            | RPCError::InvalidProtocol(_) => PeerAction::InvalidMessage,
+            // The connection failed or the peer responded with an error, neither of which are
+            // necessarily the fault of the peer.
+            RPCError::ReadError(_) | RPCError::IoError(_) | RPCError::RPCErrorResponse => return,
+        };
+        self.report_peer(peer_id, action);
     }
 
     /// Updates `PeerInfo` with `identify` information.
@@ -267,6 +385,89 @@ impl<TSpec: EthSpec> PeerManager<TSpec> {
 
     /* Internal functions */
 
+    /// The subnets that we need peers on, either because we are subscribed to them or because
+    /// of upcoming duties.
+    fn priority_subnets(&self) -> HashSet<SubnetId> {
+        self.subscribed_subnets
+            .iter()
+            .chain(self.upcoming_subnets.keys_vec())
+            .cloned()
+            .collect()
+    }
+
+    /// Disconnects the lowest reputation peers beyond `target_peers`, keeping peers on the
+    /// priority subnets, and requests more peers on priority subnets that have too few.
+    fn maintain_peer_count(&mut self) {
+        let priority_subnets = self.priority_subnets();
+        let (to_prune, subnets_without_peers) = {
+            let peerdb = self.network_globals.peers.read();
+            let to_prune = peerdb.peers_to_prune(
+                self.target_peers,
+                &priority_subnets,
+                TARGET_SUBNET_PEERS as usize,
+            );
+            let subnets_without_peers = priority_subnets
+                .iter()
+                .filter(|subnet_id| {
+                    peerdb.peers_on_subnet(subnet_id).count() < TARGET_SUBNET_PEERS as usize
+                })
+                .cloned()
+                .collect::<Vec<_>>();
+            (to_prune, subnets_without_peers)
+        };
+
+        for peer_id in to_prune {
+            debug!(self.log, "Disconnecting excess peer"; "peer_id" => format!("{}", peer_id), "target_peers" => self.target_peers);
+            self.events.push(PeerManagerEvent::PrunePeer(peer_id));
+        }
+
+        for subnet_id in subnets_without_peers {
+            debug!(self.log, "Too few peers on subnet"; "subnet_id" => *subnet_id, "target_peers" => TARGET_SUBNET_PEERS);
+            self.events
+                .push(PeerManagerEvent::DiscoverSubnetPeers(subnet_id));
+        }
+    }
+
+    /// Changes the reputation of a peer, up to `MAXIMUM_REPUTATION`.
+    ///
+    /// If the peer's reputation falls below `MINIMUM_REPUTATION_BEFORE_DISCONNECT` it is
+    /// disconnected and if it falls below `MINIMUM_REPUTATION_BEFORE_BAN` it is banned.
+    fn change_reputation(&mut self, peer_id: &PeerId, change: Rep, reason: &str) {
+        let mut peerdb = self.network_globals.peers.write();
+        peerdb.add_reputation(peer_id, change);
+        let reputation = peerdb.reputation(peer_id);
+        if reputation > MAXIMUM_REPUTATION {
+            peerdb.set_reputation(peer_id, MAXIMUM_REPUTATION);
+        }
+
+        debug!(self.log, "Peer reported"; "peer_id" => format!("{}", peer_id), "reason" => reason, "reputation" => reputation);
+
+        if peerdb.is_trusted(peer_id) {
+            // trusted peers are never disconnected or banned
+            return;
+        }
+
+        let connection_status = match peerdb.connection_status(peer_id) {
+            Some(connection_status) => connection_status,
+            None => return,
+        };
+
+        if !connection_status.is_connected() {
+            // the peer is banned or not connected, there is nothing to disconnect
+            return;
+        }
+
+        if reputation < MINIMUM_REPUTATION_BEFORE_BAN {
+            warn!(self.log, "Banning peer with low reputation"; "peer_id" => format!("{}", peer_id), "reputation" => reputation);
+            peerdb.ban(peer_id);
+            self.events.push(PeerManagerEvent::BanPeer(peer_id.clone()));
+        } else if reputation < MINIMUM_REPUTATION_BEFORE_DISCONNECT {
+            debug!(self.log, "Disconnecting peer with low reputation"; "peer_id" => format!("{}", peer_id), "reputation" => reputation);
+            self.events
+                .push(PeerManagerEvent::DisconnectPeer(peer_id.clone()));
+        }
+    }
+
     /// Registers a peer as connected. The `ingoing` parameter determines if the peer is being
     /// dialed or connecting to us.
     ///
@@ -275,7 +476,6 @@ impl<TSpec: EthSpec> PeerManager<TSpec> {
     /// This informs if the peer was accepted in to the db or not.
     // TODO: Drop peers if over max_peer limit
     fn connect_peer(&mut self, peer_id: &PeerId, outgoing: bool) -> bool {
-        // TODO: Call this on a timer
         self.update_reputations();
 
         {
@@ -288,7 +488,7 @@ impl<TSpec: EthSpec> PeerManager<TSpec> {
             if outgoing {
                 peerdb.connect_outgoing(peer_id);
             } else {
-                peerdb.connect_outgoing(peer_id);
+                peerdb.connect_ingoing(peer_id);
             }
         }
 
@@ -317,6 +517,26 @@ impl<TSpec: EthSpec> Stream for PeerManager<TSpec> {
     type Error = ();
 
     fn poll(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
+        // decay the reputations of peers on each heartbeat
+        while let Async::Ready(Some(_)) = self.heartbeat.poll().map_err(|e| {
+            error!(self.log, "Failed to poll the peer manager heartbeat"; "error" => format!("{}",e));
+        })? {
+            self.update_reputations();
+
+            // penalise subscribed peers that delivered too few messages since the last heartbeat
+            for (peer_id, penalty) in self.mesh_deliveries.take_penalties(&self.gossip_score_params)
+            {
+                self.change_reputation(&peer_id, penalty, "Too few mesh message deliveries");
+            }
+
+            self.maintain_peer_count();
+        }
+
+        // forget the subnets of duties that have passed
+        while let Async::Ready(Some(_)) = self.upcoming_subnets.poll().map_err(|e| {
+            error!(self.log, "Failed to check for expired subnets"; "error" => format!("{}",e));
+        })? {}
+
         // poll the timeouts for pings and status'
         // TODO: Remove task notifies and temporary vecs for stable futures
         // These exist to handle a bug in delayqueue
@@ -362,3 +582,192 @@ impl<TSpec: EthSpec> Stream for PeerManager<TSpec> {
         Ok(Async::NotReady)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::discovery::{build_enr, CombinedKey, Keypair};
+    use crate::NetworkConfig;
+    use slog::o;
+    use std::convert::TryInto;
+    use types::{EnrForkId, MinimalEthSpec};
+
+    type E = MinimalEthSpec;
+
+    fn build_peer_manager(trusted_peers: Vec<PeerId>) -> PeerManager<E> {
+        let log = slog::Logger::root(slog::Discard, o!());
+        let config = NetworkConfig::default();
+        let enr_key: CombinedKey = Keypair::generate_secp256k1()
+            .try_into()
+            .expect("should convert keypair");
+        let enr =
+            build_enr::<E>(&enr_key, &config, EnrForkId::default()).expect("should build enr");
+        let globals = NetworkGlobals::new(
+            enr,
+            config.libp2p_port,
+            config.discovery_port,
+            trusted_peers,
+            &log,
+        );
+        PeerManager::new(Arc::new(globals), 50, GossipScoreParams::default(), &log)
+    }
+
+    fn connected_peer(pm: &mut PeerManager<E>, reputation: Rep) -> PeerId {
+        let peer_id = PeerId::random();
+        assert!(pm.connect_ingoing(&peer_id));
+        pm.network_globals
+            .peers
+            .write()
+            .set_reputation(&peer_id, reputation);
+        peer_id
+    }
+
+    /// Returns the peers that the drained events disconnect and ban, respectively.
+    fn drain_events(pm: &mut PeerManager<E>) -> (Vec<PeerId>, Vec<PeerId>) {
+        let mut disconnected = vec![];
+        let mut banned = vec![];
+        for event in std::mem::replace(&mut pm.events, SmallVec::new()) {
+            match event {
+                PeerManagerEvent::DisconnectPeer(peer_id) => disconnected.push(peer_id),
+                PeerManagerEvent::BanPeer(peer_id) => banned.push(peer_id),
+                _ => {}
+            }
+        }
+        (disconnected, banned)
+    }
+
+    fn is_banned(pm: &PeerManager<E>, peer_id: &PeerId) -> bool {
+        pm.network_globals
+            .peers
+            .read()
+            .connection_status(peer_id)
+            .map_or(false, |status| status.is_banned())
+    }
+
+    #[test]
+    fn disconnect_threshold() {
+        let mut pm = build_peer_manager(vec![]);
+
+        let peer_id = connected_peer(&mut pm, MINIMUM_REPUTATION_BEFORE_DISCONNECT);
+        pm.change_reputation(&peer_id, 0, "test");
+        assert_eq!(drain_events(&mut pm), (vec![], vec![]));
+
+        pm.change_reputation(&peer_id, -1, "test");
+        assert_eq!(drain_events(&mut pm), (vec![peer_id.clone()], vec![]));
+        assert!(!is_banned(&pm, &peer_id));
+    }
+
+    #[test]
+    fn ban_threshold() {
+        let mut pm = build_peer_manager(vec![]);
+
+        let peer_id = connected_peer(&mut pm, MINIMUM_REPUTATION_BEFORE_BAN);
+        pm.change_reputation(&peer_id, 0, "test");
+        assert_eq!(drain_events(&mut pm), (vec![peer_id.clone()], vec![]));
+        assert!(!is_banned(&pm, &peer_id));
+
+        pm.change_reputation(&peer_id, -1, "test");
+        assert_eq!(drain_events(&mut pm), (vec![], vec![peer_id.clone()]));
+        assert!(is_banned(&pm, &peer_id));
+    }
+
+    #[test]
+    fn repeated_reports_disconnect_then_ban() {
+        let mut pm = build_peer_manager(vec![]);
+        let peer_id = connected_peer(&mut pm, DEFAULT_REPUTATION);
+
+        // 50 -> 30
+        pm.report_peer(&peer_id, PeerAction::InvalidMessage);
+        assert_eq!(drain_events(&mut pm), (vec![], vec![]));
+        // 30 -> 10
+        pm.report_peer(&peer_id, PeerAction::InvalidMessage);
+        assert_eq!(drain_events(&mut pm), (vec![peer_id.clone()], vec![]));
+        // 10 -> -40
+        pm.report_peer(&peer_id, PeerAction::Malicious);
+        assert_eq!(drain_events(&mut pm), (vec![], vec![peer_id.clone()]));
+
+        // A banned peer is not banned again.
+        pm.report_peer(&peer_id, PeerAction::Malicious);
+        assert_eq!(drain_events(&mut pm), (vec![], vec![]));
+    }
+
+    #[test]
+    fn reputation_is_capped() {
+        let mut pm = build_peer_manager(vec![]);
+        let peer_id = connected_peer(&mut pm, MAXIMUM_REPUTATION);
+
+        pm.report_peer(&peer_id, PeerAction::ValidMessage);
+        assert_eq!(pm._get_peer_rep(&peer_id), MAXIMUM_REPUTATION);
+    }
+
+    #[test]
+    fn trusted_peers_are_never_disconnected() {
+        let peer_id = PeerId::random();
+        let mut pm = build_peer_manager(vec![peer_id.clone()]);
+        assert!(pm.connect_ingoing(&peer_id));
+
+        pm.change_reputation(&peer_id, -1_000, "test");
+        assert_eq!(drain_events(&mut pm), (vec![], vec![]));
+        assert!(!is_banned(&pm, &peer_id));
+    }
+
+    #[test]
+    fn disconnected_peers_are_not_disconnected_again() {
+        let mut pm = build_peer_manager(vec![]);
+        let peer_id = connected_peer(&mut pm, DEFAULT_REPUTATION);
+        pm.notify_disconnect(&peer_id);
+
+        pm.change_reputation(&peer_id, -1_000, "test");
+        assert_eq!(drain_events(&mut pm), (vec![], vec![]));
+        assert!(!is_banned(&pm, &peer_id));
+    }
+
+    #[test]
+    fn rpc_error_actions() {
+        let mut pm = build_peer_manager(vec![]);
+        let peer_id = connected_peer(&mut pm, DEFAULT_REPUTATION);
+
+        pm.handle_rpc_error(&peer_id, &RPCError::RPCErrorResponse);
+        assert_eq!(pm._get_peer_rep(&peer_id), DEFAULT_REPUTATION);
+
+        pm.handle_rpc_error(&peer_id, &RPCError::Custom("Stream closed early".into()));
+        assert_eq!(
+            pm._get_peer_rep(&peer_id),
+            DEFAULT_REPUTATION + PeerAction::StreamError as Rep
+        );
+
+        pm.handle_rpc_error(&peer_id, &RPCError::StreamTimeout);
+        assert_eq!(
+            pm._get_peer_rep(&peer_id),
+            DEFAULT_REPUTATION + PeerAction::StreamError as Rep + PeerAction::TimedOut as Rep
+        );
+    }
+
+    #[test]
+    fn excess_peers_are_pruned_without_a_ban() {
+        // the peer manager targets 50 peers
+        let mut pm = build_peer_manager(vec![]);
+        let peers = (0..52)
+            .map(|i| connected_peer(&mut pm, DEFAULT_REPUTATION + i))
+            .collect::<Vec<_>>();
+
+        pm.maintain_peer_count();
+        let mut pruned = vec![];
+        for event in std::mem::replace(&mut pm.events, SmallVec::new()) {
+            match event {
+                PeerManagerEvent::PrunePeer(peer_id) => pruned.push(peer_id),
+                PeerManagerEvent::DisconnectPeer(_) | PeerManagerEvent::BanPeer(_) => {
+                    panic!("pruned peers should not be disconnected as faulty or banned")
+                }
+                _ => {}
+            }
+        }
+
+        // the peers with the lowest reputation are pruned
+        assert_eq!(pruned.len(), 2);
+        assert!(pruned.contains(&peers[0]));
+        assert!(pruned.contains(&peers[1]));
+        assert!(!is_banned(&pm, &peers[0]));
+        assert!(!is_banned(&pm, &peers[1]));
+    }
+}
@@ -0,0 +1,182 @@
+//! Deny lists of peers and IP ranges that are never connected to, and helpers for trusted peers.
+
+use crate::multiaddr::Protocol;
+use crate::{Multiaddr, NetworkConfig, PeerId};
+use std::collections::HashSet;
+use std::net::IpAddr;
+use std::str::FromStr;
+
+/// A range of IP addresses given in CIDR notation, e.g. `10.0.0.0/8`.
+///
+/// A plain IP address is a range that only contains that address.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IpCidr {
+    /// The address of the range.
+    address: IpAddr,
+    /// The number of leading bits of `address` that addresses in the range share.
+    prefix_len: u8,
+}
+
+impl IpCidr {
+    /// Returns true if `ip` is in the range.
+    pub fn contains(&self, ip: &IpAddr) -> bool {
+        match (self.address, ip) {
+            (IpAddr::V4(range), IpAddr::V4(ip)) => {
+                prefix_matches(&range.octets(), &ip.octets(), self.prefix_len)
+            }
+            (IpAddr::V6(range), IpAddr::V6(ip)) => {
+                prefix_matches(&range.octets(), &ip.octets(), self.prefix_len)
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Returns true if the first `prefix_len` bits of `a` and `b` are equal.
+fn prefix_matches(a: &[u8], b: &[u8], prefix_len: u8) -> bool {
+    let full_bytes = prefix_len as usize / 8;
+    let remaining_bits = prefix_len % 8;
+
+    if a[..full_bytes] != b[..full_bytes] {
+        return false;
+    }
+    if remaining_bits == 0 {
+        return true;
+    }
+    let mask = 0xff_u8 << (8 - remaining_bits);
+    a[full_bytes] & mask == b[full_bytes] & mask
+}
+
+impl FromStr for IpCidr {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.splitn(2, '/');
+        let address: IpAddr = parts
+            .next()
+            .unwrap_or("")
+            .parse()
+            .map_err(|_| format!("Invalid IP address in range: {}", s))?;
+        let max_prefix_len = if address.is_ipv4() { 32 } else { 128 };
+
+        let prefix_len = match parts.next() {
+            Some(prefix_len) => prefix_len
+                .parse::<u8>()
+                .ok()
+                .filter(|prefix_len| *prefix_len <= max_prefix_len)
+                .ok_or_else(|| format!("Invalid prefix length in IP range: {}", s))?,
+            None => max_prefix_len,
+        };
+
+        Ok(IpCidr {
+            address,
+            prefix_len,
+        })
+    }
+}
+
+impl std::fmt::Display for IpCidr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}", self.address, self.prefix_len)
+    }
+}
+
+/// The peers and IP ranges that this node refuses to connect to, in either direction.
+#[derive(Debug, Clone, Default)]
+pub struct PeerFilter {
+    denied_peers: HashSet<PeerId>,
+    denied_ip_ranges: Vec<IpCidr>,
+}
+
+impl PeerFilter {
+    pub fn new(config: &NetworkConfig) -> Self {
+        PeerFilter {
+            denied_peers: config.denied_peers.iter().cloned().collect(),
+            denied_ip_ranges: config.denied_ip_ranges.clone(),
+        }
+    }
+
+    /// The peers that must never be connected to.
+    pub fn denied_peers(&self) -> impl Iterator<Item = &PeerId> {
+        self.denied_peers.iter()
+    }
+
+    pub fn is_peer_denied(&self, peer_id: &PeerId) -> bool {
+        self.denied_peers.contains(peer_id)
+    }
+
+    pub fn is_ip_denied(&self, ip: &IpAddr) -> bool {
+        self.denied_ip_ranges.iter().any(|range| range.contains(ip))
+    }
+
+    /// Returns true if the address is in a denied IP range or belongs to a denied peer.
+    pub fn is_address_denied(&self, multiaddr: &Multiaddr) -> bool {
+        multiaddr.iter().any(|protocol| match protocol {
+            Protocol::Ip4(ip) => self.is_ip_denied(&IpAddr::V4(ip)),
+            Protocol::Ip6(ip) => self.is_ip_denied(&IpAddr::V6(ip)),
+            Protocol::P2p(_) => peer_id_of_multiaddr(multiaddr)
+                .map_or(false, |peer_id| self.is_peer_denied(&peer_id)),
+            _ => false,
+        })
+    }
+}
+
+/// Returns the `PeerId` in the `/p2p/` component of a multiaddr, if there is one.
+pub fn peer_id_of_multiaddr(multiaddr: &Multiaddr) -> Option<PeerId> {
+    multiaddr.iter().find_map(|protocol| match protocol {
+        Protocol::P2p(multihash) => PeerId::from_multihash(multihash).ok(),
+        _ => None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ip_cidr_contains() {
+        let range: IpCidr = "10.1.0.0/16".parse().unwrap();
+        assert!(range.contains(&"10.1.200.3".parse().unwrap()));
+        assert!(!range.contains(&"10.2.0.1".parse().unwrap()));
+        assert!(!range.contains(&"::1".parse().unwrap()));
+
+        let range: IpCidr = "192.168.0.128/25".parse().unwrap();
+        assert!(range.contains(&"192.168.0.200".parse().unwrap()));
+        assert!(!range.contains(&"192.168.0.100".parse().unwrap()));
+
+        let single: IpCidr = "127.0.0.1".parse().unwrap();
+        assert_eq!(single.to_string(), "127.0.0.1/32");
+        assert!(single.contains(&"127.0.0.1".parse().unwrap()));
+        assert!(!single.contains(&"127.0.0.2".parse().unwrap()));
+
+        let any: IpCidr = "::/0".parse().unwrap();
+        assert!(any.contains(&"2001:db8::1".parse().unwrap()));
+    }
+
+    #[test]
+    fn test_invalid_ip_cidr() {
+        assert!("10.0.0.0/33".parse::<IpCidr>().is_err());
+        assert!("10.0.0/8".parse::<IpCidr>().is_err());
+        assert!("::/129".parse::<IpCidr>().is_err());
+    }
+
+    #[test]
+    fn test_denied_addresses() {
+        let denied_peer = PeerId::random();
+        let mut config = NetworkConfig::default();
+        config.denied_peers = vec![denied_peer.clone()];
+        config.denied_ip_ranges = vec!["10.0.0.0/8".parse().unwrap()];
+        let filter = PeerFilter::new(&config);
+
+        let denied_ip: Multiaddr = "/ip4/10.3.2.1/tcp/9000".parse().unwrap();
+        let allowed_ip: Multiaddr = "/ip4/11.3.2.1/tcp/9000".parse().unwrap();
+        let mut denied_id = allowed_ip.clone();
+        denied_id.push(Protocol::P2p(denied_peer.clone().into()));
+
+        assert!(filter.is_address_denied(&denied_ip));
+        assert!(!filter.is_address_denied(&allowed_ip));
+        assert!(filter.is_address_denied(&denied_id));
+        assert!(filter.is_peer_denied(&denied_peer));
+        assert_eq!(peer_id_of_multiaddr(&denied_id), Some(denied_peer));
+    }
+}
@@ -3,7 +3,7 @@ use super::peer_sync_status::PeerSyncStatus;
 use crate::rpc::methods::MetaData;
 use crate::PeerId;
 use slog::{crit, warn};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::time::Instant;
 use types::{EthSpec, SubnetId};
 
@@ -26,11 +26,16 @@ pub struct PeerDB<TSpec: EthSpec> {
 }
 
 impl<TSpec: EthSpec> PeerDB<TSpec> {
-    pub fn new(log: &slog::Logger) -> Self {
+    pub fn new(trusted_peers: Vec<PeerId>, log: &slog::Logger) -> Self {
+        // Initialize the peers hashmap with trusted peers
+        let peers = trusted_peers
+            .into_iter()
+            .map(|peer_id| (peer_id, PeerInfo::trusted_peer_info()))
+            .collect();
         Self {
             log: log.clone(),
             n_dc: 0,
-            peers: HashMap::new(),
+            peers,
         }
     }
 
@@ -72,6 +77,13 @@ impl<TSpec: EthSpec> PeerDB<TSpec> {
         }
     }
 
+    /// Returns true if the peer is a configured trusted peer.
+    pub fn is_trusted(&self, peer_id: &PeerId) -> bool {
+        self.peers
+            .get(peer_id)
+            .map_or(false, |info| info.is_trusted)
+    }
+
     /// Gives the ids of all known connected peers.
     pub fn connected_peers(&self) -> impl Iterator<Item = (&PeerId, &PeerInfo<TSpec>)> {
         self.peers
@@ -121,6 +133,62 @@ impl<TSpec: EthSpec> PeerDB<TSpec> {
             .map(|(peer_id, _)| peer_id)
     }
 
+    /// Returns the connected peers to disconnect in order to bring the number of connected peers,
+    /// excluding trusted peers, down to `target_peers`.
+    ///
+    /// Peers with the lowest reputation are pruned first. Peers whose removal would leave fewer
+    /// than `min_subnet_peers` connected peers on one of the `priority_subnets` are kept, even if
+    /// this leaves more than `target_peers` peers connected.
+    pub fn peers_to_prune(
+        &self,
+        target_peers: usize,
+        priority_subnets: &HashSet<SubnetId>,
+        min_subnet_peers: usize,
+    ) -> Vec<PeerId> {
+        let mut candidates = self
+            .peers
+            .iter()
+            .filter(|(_, info)| info.connection_status.is_connected() && !info.is_trusted)
+            .collect::<Vec<_>>();
+        let mut excess = candidates.len().saturating_sub(target_peers);
+        if excess == 0 {
+            return Vec::new();
+        }
+
+        let mut subnet_peers: HashMap<SubnetId, usize> = priority_subnets
+            .iter()
+            .map(|subnet_id| (*subnet_id, self.peers_on_subnet(subnet_id).count()))
+            .collect();
+
+        candidates.sort_by_key(|(_, info)| info.reputation);
+
+        let mut to_prune = Vec::new();
+        for (peer_id, info) in candidates {
+            if excess == 0 {
+                break;
+            }
+            let peer_subnets = priority_subnets
+                .iter()
+                .filter(|subnet_id| info.on_subnet(**subnet_id))
+                .collect::<Vec<_>>();
+            if peer_subnets
+                .iter()
+                .any(|subnet_id| subnet_peers[*subnet_id] <= min_subnet_peers)
+            {
+                // the peer is needed on one of the subnets
+                continue;
+            }
+            for subnet_id in peer_subnets {
+                if let Some(count) = subnet_peers.get_mut(subnet_id) {
+                    *count -= 1;
+                }
+            }
+            to_prune.push(peer_id.clone());
+            excess -= 1;
+        }
+        to_prune
+    }
+
     /// Gives the ids of all known disconnected peers.
     pub fn disconnected_peers(&self) -> impl Iterator<Item = &PeerId> {
         self.peers
@@ -240,7 +308,8 @@ impl<TSpec: EthSpec> PeerDB<TSpec> {
             PeerInfo::default()
         });
 
-        if !info.connection_status.is_disconnected() {
+        // a banned peer remains banned until the ban expires
+        if !info.connection_status.is_disconnected() && !info.connection_status.is_banned() {
             info.connection_status.disconnect();
             self.n_dc += 1;
         }
@@ -248,22 +317,53 @@ impl<TSpec: EthSpec> PeerDB<TSpec> {
     }
 
     /// Drops the peers with the lowest reputation so that the number of
-    /// disconnected peers is less than MAX_DC_PEERS
+    /// disconnected peers is less than MAX_DC_PEERS. Trusted peers are never dropped.
     pub fn shrink_to_fit(&mut self) {
         // for caution, but the difference should never be > 1
         while self.n_dc > MAX_DC_PEERS {
-            let to_drop = self
+            let to_drop = match self
                 .peers
                 .iter()
-                .filter(|(_, info)| info.connection_status.is_disconnected())
+                .filter(|(_, info)| info.connection_status.is_disconnected() && !info.is_trusted)
                 .min_by_key(|(_, info)| info.reputation)
                 .map(|(id, _)| id.clone())
-                .unwrap(); // should be safe since n_dc > MAX_DC_PEERS > 0
+            {
+                Some(peer_id) => peer_id,
+                // only trusted peers are disconnected
+                None => break,
+            };
             self.peers.remove(&to_drop);
             self.n_dc -= 1;
         }
     }
 
+    /// Adds peers that were known prior to a restart, as banned if they are paired with `true`
+    /// and as disconnected otherwise. Peers that are already known are not modified.
+    ///
+    /// Excess disconnected peers are only dropped once all the peers have been added, so that the
+    /// peers with the lowest reputation are dropped regardless of the order of `peers`. Banned
+    /// peers are never dropped.
+    pub fn add_known_peers(
+        &mut self,
+        peers: impl IntoIterator<Item = (PeerId, PeerInfo<TSpec>, bool)>,
+    ) {
+        for (peer_id, mut info, banned) in peers {
+            if self.peers.contains_key(&peer_id) {
+                continue;
+            }
+            if banned {
+                info.connection_status.ban();
+            } else {
+                info.connection_status = PeerConnectionStatus::Disconnected {
+                    since: Instant::now(),
+                };
+                self.n_dc += 1;
+            }
+            self.peers.insert(peer_id, info);
+        }
+        self.shrink_to_fit();
+    }
+
     /// Sets a peer as banned
     pub fn ban(&mut self, peer_id: &PeerId) {
         let log_ref = &self.log;
@@ -278,6 +378,17 @@ impl<TSpec: EthSpec> PeerDB<TSpec> {
         info.connection_status.ban();
     }
 
+    /// Sets a banned peer as disconnected once its ban has expired.
+    pub fn unban(&mut self, peer_id: &PeerId) {
+        if let Some(info) = self.peers.get_mut(peer_id) {
+            if info.connection_status.is_banned() {
+                info.connection_status.disconnect();
+                self.n_dc += 1;
+                self.shrink_to_fit();
+            }
+        }
+    }
+
     /// Add the meta data of a peer.
     pub fn add_metadata(&mut self, peer_id: &PeerId, meta_data: MetaData<TSpec>) {
         if let Some(peer_info) = self.peers.get_mut(peer_id) {
@@ -316,6 +427,24 @@ impl<TSpec: EthSpec> PeerDB<TSpec> {
         });
         info.reputation = info.reputation.saturating_add(change);
     }
+
+    /// Moves the reputation of every known peer `steps` points back towards
+    /// DEFAULT_REPUTATION, so that past behaviour is gradually forgotten.
+    pub fn decay_reputations(&mut self, steps: Rep) {
+        for info in self.peers.values_mut() {
+            if info.reputation < DEFAULT_REPUTATION {
+                info.reputation = info
+                    .reputation
+                    .saturating_add(steps)
+                    .min(DEFAULT_REPUTATION);
+            } else {
+                info.reputation = info
+                    .reputation
+                    .saturating_sub(steps)
+                    .max(DEFAULT_REPUTATION);
+            }
+        }
+    }
 }
 
 #[cfg(test)]
@@ -339,7 +468,7 @@ mod tests {
 
     fn get_db() -> PeerDB<M> {
         let log = build_log(slog::Level::Debug, true);
-        PeerDB::new(&log)
+        PeerDB::new(vec![], &log)
     }
 
     #[test]
@@ -409,6 +538,180 @@ mod tests {
         assert_eq!(pdb.reputation(&random_peer), Rep::max_value());
     }
 
+    #[test]
+    fn test_reputation_decay() {
+        let mut pdb = get_db();
+
+        let low_peer = PeerId::random();
+        let high_peer = PeerId::random();
+        pdb.connect_ingoing(&low_peer);
+        pdb.connect_ingoing(&high_peer);
+        pdb.set_reputation(&low_peer, DEFAULT_REPUTATION - 10);
+        pdb.set_reputation(&high_peer, DEFAULT_REPUTATION + 10);
+
+        pdb.decay_reputations(4);
+        assert_eq!(pdb.reputation(&low_peer), DEFAULT_REPUTATION - 6);
+        assert_eq!(pdb.reputation(&high_peer), DEFAULT_REPUTATION + 6);
+
+        // reputations do not decay past the default
+        pdb.decay_reputations(Rep::max_value());
+        assert_eq!(pdb.reputation(&low_peer), DEFAULT_REPUTATION);
+        assert_eq!(pdb.reputation(&high_peer), DEFAULT_REPUTATION);
+    }
+
+    #[test]
+    fn test_trusted_peers_are_never_dropped() {
+        let log = build_log(slog::Level::Debug, false);
+        let trusted_peer = PeerId::random();
+        let mut pdb: PeerDB<M> = PeerDB::new(vec![trusted_peer.clone()], &log);
+        assert!(pdb.is_trusted(&trusted_peer));
+
+        pdb.connect_outgoing(&trusted_peer);
+        pdb.add_reputation(&trusted_peer, -100);
+        pdb.disconnect(&trusted_peer);
+
+        for _ in 0..MAX_DC_PEERS + 1 {
+            let p = PeerId::random();
+            pdb.connect_ingoing(&p);
+            pdb.disconnect(&p);
+        }
+        assert_eq!(pdb.n_dc, MAX_DC_PEERS);
+        assert!(pdb.peer_info(&trusted_peer).is_some());
+        assert!(!pdb.is_trusted(&PeerId::random()));
+    }
+
+    #[test]
+    fn test_add_known_peers() {
+        let mut pdb = get_db();
+
+        let known_peer = PeerId::random();
+        let banned_peer = PeerId::random();
+        let mut info = PeerInfo::default();
+        info.reputation = 80;
+        let mut banned_info = PeerInfo::default();
+        banned_info.reputation = -50;
+        pdb.add_known_peers(vec![
+            (known_peer.clone(), info, false),
+            (banned_peer.clone(), banned_info, true),
+        ]);
+        assert!(pdb
+            .connection_status(&known_peer)
+            .unwrap()
+            .is_disconnected());
+        assert_eq!(pdb.reputation(&known_peer), 80);
+        assert!(pdb.connection_status(&banned_peer).unwrap().is_banned());
+        assert_eq!(pdb.reputation(&banned_peer), -50);
+        assert_eq!(pdb.n_dc, 1);
+
+        // peers that are already known are left untouched
+        let connected_peer = PeerId::random();
+        pdb.connect_ingoing(&connected_peer);
+        pdb.add_known_peers(vec![(connected_peer.clone(), PeerInfo::default(), false)]);
+        assert!(pdb.is_connected(&connected_peer));
+        assert_eq!(pdb.n_dc, 1);
+    }
+
+    #[test]
+    fn test_add_known_peers_keeps_best_and_banned() {
+        let mut pdb = get_db();
+
+        let peers = (0..MAX_DC_PEERS as Rep + 10)
+            .map(|reputation| {
+                let mut info = PeerInfo::default();
+                info.reputation = reputation;
+                (PeerId::random(), info, false)
+            })
+            .collect::<Vec<_>>();
+        let banned = (0..5)
+            .map(|_| {
+                let mut info = PeerInfo::default();
+                info.reputation = -100;
+                (PeerId::random(), info, true)
+            })
+            .collect::<Vec<_>>();
+        // the banned peers have the lowest reputations, but must survive pruning
+        pdb.add_known_peers(banned.iter().chain(peers.iter()).cloned());
+
+        assert_eq!(pdb.n_dc, MAX_DC_PEERS);
+        for (peer_id, _, _) in &peers[..10] {
+            assert!(pdb.peer_info(peer_id).is_none());
+        }
+        for (peer_id, info, _) in &peers[10..] {
+            assert_eq!(pdb.reputation(peer_id), info.reputation);
+        }
+        for (peer_id, _, _) in &banned {
+            assert!(pdb.connection_status(peer_id).unwrap().is_banned());
+            assert_eq!(pdb.reputation(peer_id), -100);
+        }
+    }
+
+    #[test]
+    fn test_ban_survives_disconnect() {
+        let mut pdb = get_db();
+
+        let random_peer = PeerId::random();
+        pdb.connect_ingoing(&random_peer);
+        pdb.ban(&random_peer);
+        pdb.disconnect(&random_peer);
+        assert!(pdb.connection_status(&random_peer).unwrap().is_banned());
+        assert_eq!(pdb.n_dc, pdb.disconnected_peers().count());
+
+        pdb.unban(&random_peer);
+        assert!(pdb
+            .connection_status(&random_peer)
+            .unwrap()
+            .is_disconnected());
+        assert_eq!(pdb.n_dc, pdb.disconnected_peers().count());
+    }
+
+    #[test]
+    fn test_peers_on_priority_subnets_are_not_pruned() {
+        let mut pdb = get_db();
+        let subnet_id = SubnetId::new(3);
+        let mut attnets = crate::types::EnrBitfield::<M>::new();
+        attnets.set(*subnet_id as usize, true).unwrap();
+
+        let mut subnet_peers = Vec::new();
+        for _ in 0..2 {
+            let peer_id = PeerId::random();
+            pdb.connect_ingoing(&peer_id);
+            pdb.add_metadata(
+                &peer_id,
+                MetaData {
+                    seq_number: 1,
+                    attnets: attnets.clone(),
+                },
+            );
+            // subnet peers have the lowest reputation
+            pdb.set_reputation(&peer_id, 0);
+            subnet_peers.push(peer_id);
+        }
+        let mut other_peers = Vec::new();
+        for rep in 1..5 {
+            let peer_id = PeerId::random();
+            pdb.connect_ingoing(&peer_id);
+            pdb.set_reputation(&peer_id, rep);
+            other_peers.push(peer_id);
+        }
+
+        let mut priority_subnets = HashSet::new();
+        assert_eq!(
+            pdb.peers_to_prune(4, &priority_subnets, 2)
+                .into_iter()
+                .collect::<HashSet<_>>(),
+            subnet_peers.into_iter().collect::<HashSet<_>>()
+        );
+
+        priority_subnets.insert(subnet_id);
+        assert_eq!(
+            pdb.peers_to_prune(4, &priority_subnets, 2),
+            other_peers[..2].to_vec()
+        );
+        // at most the excess peers are pruned, even when that leaves too many connected
+        assert_eq!(pdb.peers_to_prune(1, &priority_subnets, 2), other_peers);
+        assert!(pdb.peers_to_prune(6, &priority_subnets, 2).is_empty());
+    }
+
     #[test]
     fn test_disconnected_are_bounded() {
         let mut pdb = get_db();
@@ -59,6 +59,7 @@ impl<TSpec: EthSpec> Encoder for SSZSnappyInboundCodec<TSpec> {
             },
             RPCErrorResponse::InvalidRequest(err) => err.as_ssz_bytes(),
             RPCErrorResponse::ServerError(err) => err.as_ssz_bytes(),
+            RPCErrorResponse::RateLimited(err) => err.as_ssz_bytes(),
             RPCErrorResponse::Unknown(err) => err.as_ssz_bytes(),
             RPCErrorResponse::StreamTermination(_) => {
                 unreachable!("Code error - attempting to encode a stream termination")
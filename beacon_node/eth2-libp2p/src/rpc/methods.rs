@@ -9,6 +9,10 @@ use types::{Epoch, EthSpec, Hash256, SignedBeaconBlock, Slot};
 
 /* Requests */
 
+/// The maximum number of blocks that may be requested in a single `BlocksByRange` or
+/// `BlocksByRoot` request.
+pub const MAX_REQUEST_BLOCKS: u64 = 1024;
+
 pub type RequestId = usize;
 
 /// The STATUS request/response handshake message.
@@ -63,6 +67,9 @@ pub enum GoodbyeReason {
     /// Error/fault in the RPC.
     Fault = 3,
 
+    /// Too many peers are connected.
+    TooManyPeers = 129,
+
     /// Unknown reason.
     Unknown = 0,
 }
@@ -73,6 +80,7 @@ impl From<u64> for GoodbyeReason {
             1 => GoodbyeReason::ClientShutdown,
             2 => GoodbyeReason::IrrelevantNetwork,
             3 => GoodbyeReason::Fault,
+            129 => GoodbyeReason::TooManyPeers,
             _ => GoodbyeReason::Unknown,
         }
     }
@@ -184,6 +192,9 @@ pub enum RPCErrorResponse<T: EthSpec> {
     /// The response indicates a server error.
     ServerError(ErrorMessage),
 
+    /// The request exceeded the peer's quota for the protocol.
+    RateLimited(ErrorMessage),
+
     /// There was an unknown response.
     Unknown(ErrorMessage),
 
@@ -198,6 +209,7 @@ impl<T: EthSpec> RPCErrorResponse<T> {
             RPCErrorResponse::Success(_) => Some(0),
             RPCErrorResponse::InvalidRequest(_) => Some(1),
             RPCErrorResponse::ServerError(_) => Some(2),
+            RPCErrorResponse::RateLimited(_) => Some(139),
             RPCErrorResponse::Unknown(_) => Some(255),
             RPCErrorResponse::StreamTermination(_) => None,
         }
@@ -216,6 +228,7 @@ impl<T: EthSpec> RPCErrorResponse<T> {
         match response_code {
             1 => RPCErrorResponse::InvalidRequest(err),
             2 => RPCErrorResponse::ServerError(err),
+            139 => RPCErrorResponse::RateLimited(err),
             _ => RPCErrorResponse::Unknown(err),
         }
     }
@@ -232,6 +245,7 @@ impl<T: EthSpec> RPCErrorResponse<T> {
             },
             RPCErrorResponse::InvalidRequest(_) => true,
             RPCErrorResponse::ServerError(_) => true,
+            RPCErrorResponse::RateLimited(_) => true,
             RPCErrorResponse::Unknown(_) => true,
             // Stream terminations are part of responses that have chunks
             RPCErrorResponse::StreamTermination(_) => true,
@@ -288,6 +302,7 @@ impl<T: EthSpec> std::fmt::Display for RPCErrorResponse<T> {
             RPCErrorResponse::Success(res) => write!(f, "{}", res),
             RPCErrorResponse::InvalidRequest(err) => write!(f, "Invalid Request: {:?}", err),
             RPCErrorResponse::ServerError(err) => write!(f, "Server Error: {:?}", err),
+            RPCErrorResponse::RateLimited(err) => write!(f, "Rate Limited: {:?}", err),
             RPCErrorResponse::Unknown(err) => write!(f, "Unknown Error: {:?}", err),
             RPCErrorResponse::StreamTermination(_) => write!(f, "Stream Termination"),
         }
@@ -300,6 +315,7 @@ impl std::fmt::Display for GoodbyeReason {
             GoodbyeReason::ClientShutdown => write!(f, "Client Shutdown"),
             GoodbyeReason::IrrelevantNetwork => write!(f, "Irrelevant Network"),
             GoodbyeReason::Fault => write!(f, "Fault"),
+            GoodbyeReason::TooManyPeers => write!(f, "Too Many Peers"),
             GoodbyeReason::Unknown => write!(f, "Unknown Reason"),
         }
     }
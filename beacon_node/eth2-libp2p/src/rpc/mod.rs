@@ -16,7 +16,8 @@ pub use methods::{
     ErrorMessage, MetaData, RPCErrorResponse, RPCResponse, RequestId, ResponseTermination,
     StatusMessage,
 };
-pub use protocol::{RPCError, RPCProtocol, RPCRequest};
+pub use protocol::{Protocol, RPCError, RPCProtocol, RPCRequest};
+use rate_limiter::RPCRateLimiter;
 use slog::{debug, o};
 use std::marker::PhantomData;
 use std::time::Duration;
@@ -27,6 +28,7 @@ pub(crate) mod codec;
 mod handler;
 pub mod methods;
 mod protocol;
+mod rate_limiter;
 
 /// The return type used in the behaviour and the resultant event from the protocols handler.
 #[derive(Debug)]
@@ -67,6 +69,8 @@ impl<T: EthSpec> std::fmt::Display for RPCEvent<T> {
 pub struct RPC<TSubstream, TSpec: EthSpec> {
     /// Queue of events to processed.
     events: Vec<NetworkBehaviourAction<RPCEvent<TSpec>, RPCMessage<TSpec>>>,
+    /// Limits the rate of inbound requests of each peer.
+    limiter: RPCRateLimiter,
     /// Pins the generic substream.
     marker: PhantomData<TSubstream>,
     /// Slog logger for RPC behaviour.
@@ -78,6 +82,7 @@ impl<TSubstream, TSpec: EthSpec> RPC<TSubstream, TSpec> {
         let log = log.new(o!("service" => "libp2p_rpc"));
         RPC {
             events: Vec::new(),
+            limiter: RPCRateLimiter::default(),
             marker: PhantomData,
             log,
         }
@@ -140,6 +145,8 @@ where
     }
 
     fn inject_disconnected(&mut self, peer_id: &PeerId, connected_point: ConnectedPoint) {
+        self.limiter.remove_peer(peer_id);
+
         // TODO: Remove this on proper peer discovery
         self.events.push(NetworkBehaviourAction::GenerateEvent(
             RPCMessage::PeerDisconnectedHack(peer_id.clone(), connected_point.clone()),
@@ -156,6 +163,25 @@ where
         source: PeerId,
         event: <Self::ProtocolsHandler as ProtocolsHandler>::OutEvent,
     ) {
+        // reject inbound requests that exceed the peer's quota
+        if let RPCEvent::Request(id, request) = &event {
+            if let Err(e) = self.limiter.allows(&source, request) {
+                let protocol = request.protocol();
+                debug!(self.log, "Request rate limited"; "peer_id" => format!("{}", source), "protocol" => format!("{}", protocol), "reason" => format!("{}", e));
+                let error = RPCErrorResponse::RateLimited(ErrorMessage {
+                    error_message: format!("{}", e).into_bytes(),
+                });
+                self.events.push(NetworkBehaviourAction::SendEvent {
+                    peer_id: source.clone(),
+                    event: RPCEvent::Response(*id, error),
+                });
+                self.events.push(NetworkBehaviourAction::GenerateEvent(
+                    RPCMessage::RateLimited(source, protocol),
+                ));
+                return;
+            }
+        }
+
         // send the event to the user
         self.events
             .push(NetworkBehaviourAction::GenerateEvent(RPCMessage::RPC(
@@ -184,6 +210,8 @@ pub enum RPCMessage<TSpec: EthSpec> {
     RPC(PeerId, RPCEvent<TSpec>),
     PeerDialed(PeerId),
     PeerDisconnected(PeerId),
+    /// An inbound request from the peer exceeded its quota and was rejected.
+    RateLimited(PeerId, Protocol),
     // TODO: This is a hack to give access to connections to peer manager. Remove this once
     // behaviour is re-written
     PeerConnectedHack(PeerId, ConnectedPoint),
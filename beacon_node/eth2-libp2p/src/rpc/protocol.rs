@@ -34,7 +34,7 @@ const TTFB_TIMEOUT: u64 = 5;
 const REQUEST_TIMEOUT: u64 = 15;
 
 /// Protocol names to be used.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Protocol {
     /// The Status protocol name.
     Status,
@@ -203,7 +203,7 @@ where
         socket: upgrade::Negotiated<TSocket>,
         protocol: ProtocolId,
     ) -> Self::Future {
-        let protocol_name = protocol.message_name.clone();
+        let protocol_name = protocol.message_name;
         let codec = match protocol.encoding {
             Encoding::SSZSnappy => {
                 let ssz_snappy_codec =
@@ -302,6 +302,30 @@ impl<TSpec: EthSpec> RPCRequest<TSpec> {
         }
     }
 
+    /// Returns the protocol of the request.
+    pub fn protocol(&self) -> Protocol {
+        match self {
+            RPCRequest::Status(_) => Protocol::Status,
+            RPCRequest::Goodbye(_) => Protocol::Goodbye,
+            RPCRequest::BlocksByRange(_) => Protocol::BlocksByRange,
+            RPCRequest::BlocksByRoot(_) => Protocol::BlocksByRoot,
+            RPCRequest::Ping(_) => Protocol::Ping,
+            RPCRequest::MetaData(_) => Protocol::MetaData,
+        }
+    }
+
+    /// Returns the maximum number of responses that may be sent for the request.
+    pub fn expected_responses(&self) -> u64 {
+        match self {
+            RPCRequest::Status(_) => 1,
+            RPCRequest::Goodbye(_) => 0,
+            RPCRequest::BlocksByRange(req) => req.count,
+            RPCRequest::BlocksByRoot(req) => req.block_roots.len() as u64,
+            RPCRequest::Ping(_) => 1,
+            RPCRequest::MetaData(_) => 1,
+        }
+    }
+
     /* These functions are used in the handler for stream management */
 
     /// This specifies whether a stream should remain open and await a response, given a request.
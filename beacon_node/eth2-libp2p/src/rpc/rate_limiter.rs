@@ -0,0 +1,256 @@
+//! Limits the rate at which peers can make inbound RPC requests.
+//!
+//! Each peer is given a token bucket for each protocol. A request consumes one token per response
+//! it may produce, so a `BlocksByRange` request for 64 blocks costs 64 tokens. Buckets are
+//! replenished continuously, refilling completely over the replenish period of the protocol's
+//! `Quota`.
+
+use super::methods::MAX_REQUEST_BLOCKS;
+use super::protocol::{Protocol, RPCRequest};
+use fnv::FnvHashMap;
+use libp2p::PeerId;
+use std::time::{Duration, Instant};
+use types::EthSpec;
+
+/// The number of tokens a bucket holds and the time it takes to refill an empty bucket.
+#[derive(Debug, Clone)]
+pub struct Quota {
+    /// The maximum number of tokens in a bucket.
+    max_tokens: u64,
+    /// The time it takes for an empty bucket to be completely refilled.
+    replenish_all_every: Duration,
+}
+
+impl Quota {
+    pub fn n_every(max_tokens: u64, seconds: u64) -> Self {
+        Quota {
+            max_tokens,
+            replenish_all_every: Duration::from_secs(seconds),
+        }
+    }
+}
+
+/// The reason a request was not allowed.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RateLimitedErr {
+    /// The request costs more tokens than the quota allows and can never be served.
+    TooLarge,
+    /// The peer has not got enough tokens left for the request.
+    TooSoon,
+}
+
+impl std::fmt::Display for RateLimitedErr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RateLimitedErr::TooLarge => write!(f, "Request exceeds the protocol quota"),
+            RateLimitedErr::TooSoon => write!(f, "Too many requests"),
+        }
+    }
+}
+
+/// The tokens available to a peer for a protocol.
+struct Bucket {
+    /// The number of tokens left, including fractions of tokens that have been replenished.
+    tokens: f64,
+    /// The last time the bucket was replenished.
+    last_updated: Instant,
+}
+
+/// Keeps a token bucket per peer and protocol for inbound RPC requests.
+pub struct RPCRateLimiter {
+    /// The quotas of the rate limited protocols. Protocols without a quota are not limited.
+    quotas: FnvHashMap<Protocol, Quota>,
+    /// The buckets of connected peers.
+    buckets: FnvHashMap<(PeerId, Protocol), Bucket>,
+}
+
+impl Default for RPCRateLimiter {
+    /// Quotas that comfortably allow for syncing from this node, whilst bounding the number of
+    /// blocks a single peer can make us read from the database.
+    fn default() -> Self {
+        let mut quotas = FnvHashMap::default();
+        quotas.insert(Protocol::Status, Quota::n_every(5, 15));
+        quotas.insert(Protocol::Ping, Quota::n_every(2, 10));
+        quotas.insert(Protocol::MetaData, Quota::n_every(2, 5));
+        quotas.insert(
+            Protocol::BlocksByRange,
+            Quota::n_every(MAX_REQUEST_BLOCKS, 10),
+        );
+        // Roots are looked up individually, so they are replenished more slowly than ranges. The
+        // bucket still holds enough tokens for the largest request a peer may make.
+        quotas.insert(
+            Protocol::BlocksByRoot,
+            Quota::n_every(MAX_REQUEST_BLOCKS, 80),
+        );
+        RPCRateLimiter::new(quotas)
+    }
+}
+
+impl RPCRateLimiter {
+    pub fn new(quotas: FnvHashMap<Protocol, Quota>) -> Self {
+        RPCRateLimiter {
+            quotas,
+            buckets: FnvHashMap::default(),
+        }
+    }
+
+    /// Consumes the tokens required by `request` if the peer has enough of them, otherwise
+    /// returns the reason the request is not allowed.
+    pub fn allows<T: EthSpec>(
+        &mut self,
+        peer_id: &PeerId,
+        request: &RPCRequest<T>,
+    ) -> Result<(), RateLimitedErr> {
+        // every request costs at least one token, even if it asks for no responses
+        let tokens = std::cmp::max(request.expected_responses(), 1);
+        self.allows_at(peer_id, request.protocol(), tokens, Instant::now())
+    }
+
+    /// Removes the buckets of a disconnected peer.
+    pub fn remove_peer(&mut self, peer_id: &PeerId) {
+        self.buckets.retain(|(id, _), _| id != peer_id);
+    }
+
+    fn allows_at(
+        &mut self,
+        peer_id: &PeerId,
+        protocol: Protocol,
+        tokens: u64,
+        now: Instant,
+    ) -> Result<(), RateLimitedErr> {
+        let quota = match self.quotas.get(&protocol) {
+            Some(quota) => quota,
+            None => return Ok(()),
+        };
+
+        if tokens > quota.max_tokens {
+            return Err(RateLimitedErr::TooLarge);
+        }
+
+        let bucket = self
+            .buckets
+            .entry((peer_id.clone(), protocol))
+            .or_insert_with(|| Bucket {
+                tokens: quota.max_tokens as f64,
+                last_updated: now,
+            });
+
+        // replenish the tokens accrued since the last request, up to the maximum
+        let elapsed = now.saturating_duration_since(bucket.last_updated);
+        let replenished = elapsed.as_secs_f64() / quota.replenish_all_every.as_secs_f64()
+            * quota.max_tokens as f64;
+        bucket.tokens = (bucket.tokens + replenished).min(quota.max_tokens as f64);
+        bucket.last_updated = now;
+
+        if bucket.tokens < tokens as f64 {
+            return Err(RateLimitedErr::TooSoon);
+        }
+
+        bucket.tokens -= tokens as f64;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limiter() -> RPCRateLimiter {
+        let mut quotas = FnvHashMap::default();
+        quotas.insert(Protocol::BlocksByRange, Quota::n_every(10, 10));
+        RPCRateLimiter::new(quotas)
+    }
+
+    #[test]
+    fn test_requests_are_limited() {
+        let mut limiter = limiter();
+        let peer_id = PeerId::random();
+        let now = Instant::now();
+
+        assert_eq!(
+            limiter.allows_at(&peer_id, Protocol::BlocksByRange, 6, now),
+            Ok(())
+        );
+        assert_eq!(
+            limiter.allows_at(&peer_id, Protocol::BlocksByRange, 6, now),
+            Err(RateLimitedErr::TooSoon)
+        );
+        assert_eq!(
+            limiter.allows_at(&peer_id, Protocol::BlocksByRange, 4, now),
+            Ok(())
+        );
+
+        // other peers have their own buckets
+        assert_eq!(
+            limiter.allows_at(&PeerId::random(), Protocol::BlocksByRange, 10, now),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_tokens_are_replenished() {
+        let mut limiter = limiter();
+        let peer_id = PeerId::random();
+        let now = Instant::now();
+
+        assert_eq!(
+            limiter.allows_at(&peer_id, Protocol::BlocksByRange, 10, now),
+            Ok(())
+        );
+        // one token is replenished every second
+        let later = now + Duration::from_secs(5);
+        assert_eq!(
+            limiter.allows_at(&peer_id, Protocol::BlocksByRange, 6, later),
+            Err(RateLimitedErr::TooSoon)
+        );
+        assert_eq!(
+            limiter.allows_at(&peer_id, Protocol::BlocksByRange, 5, later),
+            Ok(())
+        );
+
+        // buckets do not fill beyond the quota
+        let much_later = later + Duration::from_secs(100);
+        assert_eq!(
+            limiter.allows_at(&peer_id, Protocol::BlocksByRange, 10, much_later),
+            Ok(())
+        );
+        assert_eq!(
+            limiter.allows_at(&peer_id, Protocol::BlocksByRange, 1, much_later),
+            Err(RateLimitedErr::TooSoon)
+        );
+    }
+
+    #[test]
+    fn test_oversized_and_unlimited_requests() {
+        let mut limiter = limiter();
+        let peer_id = PeerId::random();
+        let now = Instant::now();
+
+        assert_eq!(
+            limiter.allows_at(&peer_id, Protocol::BlocksByRange, 11, now),
+            Err(RateLimitedErr::TooLarge)
+        );
+        assert_eq!(
+            limiter.allows_at(&peer_id, Protocol::Status, 1000, now),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_default_quotas_allow_maximum_requests() {
+        let mut limiter = RPCRateLimiter::default();
+        let peer_id = PeerId::random();
+        let now = Instant::now();
+
+        for protocol in &[Protocol::BlocksByRange, Protocol::BlocksByRoot] {
+            assert_eq!(
+                limiter.allows_at(&peer_id, *protocol, MAX_REQUEST_BLOCKS, now),
+                Ok(())
+            );
+            assert_eq!(
+                limiter.allows_at(&peer_id, *protocol, MAX_REQUEST_BLOCKS + 1, now),
+                Err(RateLimitedErr::TooLarge)
+            );
+        }
+    }
+}
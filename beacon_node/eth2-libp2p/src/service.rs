@@ -1,6 +1,8 @@
 use crate::behaviour::{Behaviour, BehaviourEvent};
 use crate::discovery::enr;
 use crate::multiaddr::Protocol;
+use crate::nat::PortMapper;
+use crate::peer_manager::{peer_id_of_multiaddr, PeerFilter};
 use crate::types::{error, GossipKind};
 use crate::{NetworkConfig, NetworkGlobals};
 use futures::prelude::*;
@@ -10,18 +12,19 @@ use libp2p::core::{
     multiaddr::Multiaddr,
     muxing::StreamMuxerBox,
     nodes::Substream,
-    transport::boxed::Boxed,
+    transport::{boxed::Boxed, OptionalTransport},
     upgrade::{InboundUpgradeExt, OutboundUpgradeExt},
     ConnectedPoint,
 };
 use libp2p::{core, noise, secio, swarm::NetworkBehaviour, PeerId, Swarm, Transport};
 use slog::{crit, debug, error, info, trace, warn};
+use std::collections::HashMap;
 use std::fs::File;
 use std::io::prelude::*;
 use std::io::{Error, ErrorKind};
 use std::sync::Arc;
-use std::time::Duration;
-use tokio::timer::DelayQueue;
+use std::time::{Duration, Instant};
+use tokio::timer::{delay_queue, DelayQueue};
 use types::{EnrForkId, EthSpec};
 
 type Libp2pStream = Boxed<(PeerId, StreamMuxerBox), Error>;
@@ -31,6 +34,11 @@ pub const NETWORK_KEY_FILENAME: &str = "key";
 /// The time in milliseconds to wait before banning a peer. This allows for any Goodbye messages to be
 /// flushed and protocols to be negotiated.
 const BAN_PEER_WAIT_TIMEOUT: u64 = 200;
+/// The time in seconds to wait before redialing a disconnected trusted peer. The wait doubles
+/// after each failed attempt, up to `TRUSTED_PEER_MAX_BACKOFF`.
+const TRUSTED_PEER_MIN_BACKOFF: u64 = 5;
+/// The maximum time in seconds to wait between attempts to redial a trusted peer.
+const TRUSTED_PEER_MAX_BACKOFF: u64 = 300;
 
 /// The configuration and state of the libp2p components for the beacon node.
 pub struct Service<TSpec: EthSpec> {
@@ -44,9 +52,37 @@ pub struct Service<TSpec: EthSpec> {
     /// A current list of peers to ban after a given timeout.
     peers_to_ban: DelayQueue<PeerId>,
 
+    /// A current list of peers to disconnect, without banning them, after a given timeout.
+    peers_to_disconnect: DelayQueue<PeerId>,
+
     /// A list of timeouts after which peers become unbanned.
     peer_ban_timeout: DelayQueue<PeerId>,
 
+    /// The keys and expiry times of the pending unban timeouts, so a peer's ban can be replaced
+    /// by a later one.
+    peer_ban_keys: HashMap<PeerId, (delay_queue::Key, Instant)>,
+
+    /// The addresses of the configured trusted peers.
+    trusted_peers: HashMap<PeerId, Multiaddr>,
+
+    /// The times at which disconnected trusted peers are redialed.
+    trusted_peer_redials: DelayQueue<PeerId>,
+
+    /// The keys of the pending redials, so a trusted peer is only ever scheduled once.
+    trusted_peer_redial_keys: HashMap<PeerId, delay_queue::Key>,
+
+    /// The current backoff of trusted peers that are being redialed.
+    trusted_peer_backoffs: HashMap<PeerId, Duration>,
+
+    /// The peers and IP ranges that are refused connections.
+    peer_filter: PeerFilter,
+
+    /// Maintains the UPnP port mappings of the node, if enabled.
+    port_mapper: Option<PortMapper>,
+
+    /// A collection of variables accessible outside of the network service.
+    network_globals: Arc<NetworkGlobals<TSpec>>,
+
     /// The libp2p logger handle.
     pub log: slog::Logger,
 }
@@ -71,51 +107,84 @@ impl<TSpec: EthSpec> Service<TSpec> {
             enr::build_or_load_enr::<TSpec>(local_keypair.clone(), config, enr_fork_id, &log)?;
 
         let local_peer_id = enr.peer_id();
+
+        let mut trusted_peers = HashMap::new();
+        for multiaddr in &config.trusted_peers {
+            match peer_id_of_multiaddr(multiaddr) {
+                Some(peer_id) => {
+                    trusted_peers.insert(peer_id, multiaddr.clone());
+                }
+                None => {
+                    warn!(log, "Ignoring trusted peer without a peer id"; "address" => format!("{}", multiaddr))
+                }
+            }
+        }
+
         // set up a collection of variables accessible outside of the network crate
         let network_globals = Arc::new(NetworkGlobals::new(
             enr.clone(),
             config.libp2p_port,
             config.discovery_port,
+            trusted_peers.keys().cloned().collect(),
             &log,
         ));
+        let peer_filter = PeerFilter::new(config);
 
         info!(log, "Libp2p Service"; "peer_id" => format!("{:?}", enr.peer_id()));
-        debug!(log, "Attempting to open listening ports"; "address" => format!("{}", config.listen_address), "tcp_port" => config.libp2p_port, "udp_port" => config.discovery_port);
+        debug!(log, "Attempting to open listening ports"; "address" => format!("{}", config.listen_address), "tcp_port" => config.libp2p_port, "udp_port" => config.discovery_port, "ws_port" => format!("{:?}", config.websocket_port));
 
         let mut swarm = {
             // Set up the transport - tcp/ws with noise/secio and mplex/yamux
-            let transport = build_transport(local_keypair.clone());
+            let transport = build_transport(local_keypair.clone(), config.websocket_port.is_some());
             // Lighthouse network behaviour
             let behaviour = Behaviour::new(&local_keypair, config, network_globals.clone(), &log)?;
             Swarm::new(transport, behaviour, local_peer_id.clone())
         };
 
-        // listen on the specified address
-        let listen_multiaddr = {
-            let mut m = Multiaddr::from(config.listen_address);
-            m.push(Protocol::Tcp(config.libp2p_port));
-            m
-        };
+        // listen on the address of each enabled transport
+        for listen_multiaddr in config.listen_multiaddrs() {
+            match Swarm::listen_on(&mut swarm, listen_multiaddr.clone()) {
+                Ok(_) => {
+                    let mut log_address = listen_multiaddr;
+                    log_address.push(Protocol::P2p(local_peer_id.clone().into()));
+                    info!(log, "Listening established"; "address" => format!("{}", log_address));
+                }
+                Err(err) => {
+                    crit!(
+                        log,
+                        "Unable to listen on libp2p address";
+                        "error" => format!("{:?}", err),
+                        "listen_multiaddr" => format!("{}", listen_multiaddr),
+                    );
+                    return Err("Libp2p was unable to listen on the given listen address.".into());
+                }
+            };
+        }
 
-        match Swarm::listen_on(&mut swarm, listen_multiaddr.clone()) {
-            Ok(_) => {
-                let mut log_address = listen_multiaddr;
-                log_address.push(Protocol::P2p(local_peer_id.clone().into()));
-                info!(log, "Listening established"; "address" => format!("{}", log_address));
-            }
-            Err(err) => {
-                crit!(
-                    log,
-                    "Unable to listen on libp2p address";
-                    "error" => format!("{:?}", err),
-                    "listen_multiaddr" => format!("{}", listen_multiaddr),
-                );
-                return Err("Libp2p was unable to listen on the given listen address.".into());
+        // map the listening ports on the local gateway
+        let port_mapper = if config.upnp_enabled {
+            match PortMapper::spawn(config, log.clone()) {
+                Ok(port_mapper) => Some(port_mapper),
+                Err(e) => {
+                    warn!(log, "Could not start UPnP port mapping"; "error" => e);
+                    None
+                }
             }
+        } else {
+            None
         };
 
+        // the deny list is enforced by the swarm for the lifetime of the service
+        for peer_id in peer_filter.denied_peers() {
+            Swarm::ban_peer_id(&mut swarm, peer_id.clone());
+        }
+
         // helper closure for dialing peers
         let mut dial_addr = |multiaddr: &Multiaddr| {
+            if peer_filter.is_address_denied(multiaddr) {
+                debug!(log, "Not dialing denied address"; "address" => format!("{}", multiaddr));
+                return;
+            }
             match Swarm::dial_addr(&mut swarm, multiaddr.clone()) {
                 Ok(()) => debug!(log, "Dialing libp2p peer"; "address" => format!("{}", multiaddr)),
                 Err(err) => debug!(
@@ -157,11 +226,30 @@ impl<TSpec: EthSpec> Service<TSpec> {
         }
         info!(log, "Subscribed to topics"; "topics" => format!("{:?}", subscribed_topics));
 
+        // dial the trusted peers as soon as the service is polled
+        let mut trusted_peer_redials = DelayQueue::new();
+        let trusted_peer_redial_keys = trusted_peers
+            .keys()
+            .map(|peer_id| {
+                let key = trusted_peer_redials.insert(peer_id.clone(), Duration::from_secs(0));
+                (peer_id.clone(), key)
+            })
+            .collect();
+
         let service = Service {
             local_peer_id,
             swarm,
             peers_to_ban: DelayQueue::new(),
+            peers_to_disconnect: DelayQueue::new(),
             peer_ban_timeout: DelayQueue::new(),
+            peer_ban_keys: HashMap::new(),
+            trusted_peers,
+            trusted_peer_redials,
+            trusted_peer_redial_keys,
+            trusted_peer_backoffs: HashMap::new(),
+            peer_filter,
+            port_mapper,
+            network_globals: network_globals.clone(),
             log,
         };
 
@@ -169,13 +257,118 @@ impl<TSpec: EthSpec> Service<TSpec> {
     }
 
     /// Adds a peer to be banned for a period of time, specified by a timeout.
+    ///
+    /// If the peer is already banned, the previous timeout is replaced.
     pub fn disconnect_and_ban_peer(&mut self, peer_id: PeerId, timeout: Duration) {
+        if self.trusted_peers.contains_key(&peer_id) {
+            debug!(self.log, "Not banning trusted peer"; "peer_id" => format!("{:?}", peer_id));
+            return;
+        }
         error!(self.log, "Disconnecting and banning peer"; "peer_id" => format!("{:?}", peer_id), "timeout" => format!("{:?}", timeout));
         self.peers_to_ban.insert(
             peer_id.clone(),
             Duration::from_millis(BAN_PEER_WAIT_TIMEOUT),
         );
-        self.peer_ban_timeout.insert(peer_id, timeout);
+        self.insert_ban_timeout(peer_id, timeout);
+    }
+
+    /// Disconnects a peer without preventing it from reconnecting.
+    pub fn disconnect_peer(&mut self, peer_id: PeerId) {
+        if self.trusted_peers.contains_key(&peer_id) {
+            debug!(self.log, "Not disconnecting trusted peer"; "peer_id" => format!("{:?}", peer_id));
+            return;
+        }
+        debug!(self.log, "Disconnecting peer"; "peer_id" => format!("{:?}", peer_id));
+        self.peers_to_disconnect
+            .insert(peer_id, Duration::from_millis(BAN_PEER_WAIT_TIMEOUT));
+    }
+
+    /// Bans a peer that is not connected for a period of time, specified by a timeout.
+    ///
+    /// This is used to restore the bans of peers that were banned prior to a restart.
+    pub fn ban_peer(&mut self, peer_id: PeerId, timeout: Duration) {
+        if self.trusted_peers.contains_key(&peer_id) {
+            return;
+        }
+        debug!(self.log, "Banning peer"; "peer_id" => format!("{:?}", peer_id), "timeout" => format!("{:?}", timeout));
+        Swarm::ban_peer_id(&mut self.swarm, peer_id.clone());
+        self.swarm.peer_banned(peer_id.clone());
+        self.insert_ban_timeout(peer_id, timeout);
+    }
+
+    /// Dials a known peer at one of its previously seen listening addresses.
+    ///
+    /// The caller is expected to have informed the peer manager that the peer is being dialed.
+    pub fn dial_peer(&mut self, peer_id: &PeerId, multiaddr: Multiaddr) {
+        if self.peer_filter.is_peer_denied(peer_id)
+            || self.peer_filter.is_address_denied(&multiaddr)
+        {
+            debug!(self.log, "Not dialing denied peer"; "peer_id" => format!("{}", peer_id), "address" => format!("{}", multiaddr));
+            return;
+        }
+        match Swarm::dial_addr(&mut self.swarm, multiaddr.clone()) {
+            Ok(()) => {
+                debug!(self.log, "Dialing known peer"; "peer_id" => format!("{}", peer_id), "address" => format!("{}", multiaddr))
+            }
+            Err(err) => debug!(
+                self.log,
+                "Could not connect to peer"; "address" => format!("{}", multiaddr), "error" => format!("{:?}", err)
+            ),
+        }
+    }
+
+    /// Returns the banned peers and the time remaining until they are unbanned.
+    pub fn banned_peers(&self) -> impl Iterator<Item = (&PeerId, Duration)> {
+        let now = Instant::now();
+        self.peer_ban_keys
+            .iter()
+            .map(move |(peer_id, (_, expiry))| (peer_id, expiry.saturating_duration_since(now)))
+    }
+
+    /// Dials a trusted peer if it is not connected and schedules the next attempt, backing off
+    /// exponentially while the peer stays unreachable.
+    fn redial_trusted_peer(&mut self, peer_id: PeerId) {
+        if self.network_globals.peers.read().is_connected(&peer_id) {
+            // the peer is connected, the next disconnect starts from the minimum backoff again
+            self.trusted_peer_backoffs.remove(&peer_id);
+            return;
+        }
+
+        if let Some(multiaddr) = self.trusted_peers.get(&peer_id).cloned() {
+            debug!(self.log, "Dialing trusted peer"; "peer_id" => format!("{}", peer_id));
+            self.network_globals.peers.write().dialing_peer(&peer_id);
+            self.dial_peer(&peer_id, multiaddr);
+        }
+
+        let backoff = self
+            .trusted_peer_backoffs
+            .entry(peer_id.clone())
+            .or_insert_with(|| Duration::from_secs(TRUSTED_PEER_MIN_BACKOFF));
+        let next_attempt = *backoff;
+        *backoff = std::cmp::min(
+            next_attempt * 2,
+            Duration::from_secs(TRUSTED_PEER_MAX_BACKOFF),
+        );
+        self.schedule_trusted_peer_redial(peer_id, next_attempt);
+    }
+
+    /// Schedules a trusted peer to be redialed after `delay`, replacing any pending redial.
+    fn schedule_trusted_peer_redial(&mut self, peer_id: PeerId, delay: Duration) {
+        if let Some(key) = self.trusted_peer_redial_keys.remove(&peer_id) {
+            self.trusted_peer_redials.remove(&key);
+        }
+        let key = self.trusted_peer_redials.insert(peer_id.clone(), delay);
+        self.trusted_peer_redial_keys.insert(peer_id, key);
+    }
+
+    /// Schedules the peer to be unbanned after `timeout`, replacing any previous timeout.
+    fn insert_ban_timeout(&mut self, peer_id: PeerId, timeout: Duration) {
+        if let Some((key, _)) = self.peer_ban_keys.remove(&peer_id) {
+            self.peer_ban_timeout.remove(&key);
+        }
+        let key = self.peer_ban_timeout.insert(peer_id.clone(), timeout);
+        self.peer_ban_keys
+            .insert(peer_id, (key, Instant::now() + timeout));
     }
 }
 
@@ -187,6 +380,15 @@ impl<TSpec: EthSpec> Stream for Service<TSpec> {
         loop {
             match self.swarm.poll() {
                 Ok(Async::Ready(Some(event))) => {
+                    if let BehaviourEvent::PeerDisconnected(peer_id) = &event {
+                        // keep trusted peers connected
+                        if self.trusted_peers.contains_key(peer_id) {
+                            self.schedule_trusted_peer_redial(
+                                peer_id.clone(),
+                                Duration::from_secs(TRUSTED_PEER_MIN_BACKOFF),
+                            );
+                        }
+                    }
                     return Ok(Async::Ready(Some(event)));
                 }
                 Ok(Async::Ready(None)) => unreachable!("Swarm stream shouldn't end"),
@@ -195,6 +397,42 @@ impl<TSpec: EthSpec> Stream for Service<TSpec> {
             }
         }
 
+        // disconnect and ban any peers the peer manager has rejected
+        for (peer_id, ban_timeout) in self.swarm.take_peers_to_disconnect() {
+            match ban_timeout {
+                Some(timeout) => self.disconnect_and_ban_peer(peer_id, timeout),
+                None => self.disconnect_peer(peer_id),
+            }
+        }
+
+        // disconnect peers that are free to reconnect
+        loop {
+            match self.peers_to_disconnect.poll() {
+                Ok(Async::Ready(Some(peer_id))) => {
+                    let peer_id = peer_id.into_inner();
+                    // banning the peer closes its connections, the ban is lifted immediately
+                    Swarm::ban_peer_id(&mut self.swarm, peer_id.clone());
+                    let dummy_connected_point = ConnectedPoint::Dialer {
+                        address: "/ip4/0.0.0.0"
+                            .parse::<Multiaddr>()
+                            .expect("valid multiaddr"),
+                    };
+                    self.swarm
+                        .inject_disconnected(&peer_id, dummy_connected_point);
+                    // the peer may have been banned in the meantime
+                    if !self.peer_ban_keys.contains_key(&peer_id)
+                        && !self.peer_filter.is_peer_denied(&peer_id)
+                    {
+                        Swarm::unban_peer_id(&mut self.swarm, peer_id);
+                    }
+                }
+                Ok(Async::NotReady) | Ok(Async::Ready(None)) => break,
+                Err(e) => {
+                    warn!(self.log, "Peer disconnection queue failed"; "error" => format!("{:?}", e));
+                }
+            }
+        }
+
         // check if peers need to be banned
         loop {
             match self.peers_to_ban.poll() {
@@ -225,6 +463,11 @@ impl<TSpec: EthSpec> Stream for Service<TSpec> {
             match self.peer_ban_timeout.poll() {
                 Ok(Async::Ready(Some(peer_id))) => {
                     let peer_id = peer_id.into_inner();
+                    self.peer_ban_keys.remove(&peer_id);
+                    if self.peer_filter.is_peer_denied(&peer_id) {
+                        // denied peers stay banned
+                        continue;
+                    }
                     debug!(self.log, "Peer has been unbanned"; "peer" => format!("{:?}", peer_id));
                     self.swarm.peer_unbanned(&peer_id);
                     Swarm::unban_peer_id(&mut self.swarm, peer_id);
@@ -236,21 +479,60 @@ impl<TSpec: EthSpec> Stream for Service<TSpec> {
             }
         }
 
+        // redial any trusted peers that are due
+        loop {
+            match self.trusted_peer_redials.poll() {
+                Ok(Async::Ready(Some(peer_id))) => {
+                    let peer_id = peer_id.into_inner();
+                    self.trusted_peer_redial_keys.remove(&peer_id);
+                    self.redial_trusted_peer(peer_id);
+                }
+                Ok(Async::NotReady) | Ok(Async::Ready(None)) => break,
+                Err(e) => {
+                    warn!(self.log, "Trusted peer redial queue failed"; "error" => format!("{:?}", e));
+                }
+            }
+        }
+
+        // advertise the external address of any new UPnP port mappings
+        if let Some(port_mapper) = self.port_mapper.as_mut() {
+            while let Ok(Async::Ready(Some(external_address))) =
+                port_mapper.external_addresses.poll()
+            {
+                self.swarm.update_external_address(&external_address);
+                if let (Some(ip), Some(tcp_port)) = (external_address.ip, external_address.tcp_port)
+                {
+                    let mut address = Multiaddr::from(ip);
+                    address.push(Protocol::Tcp(tcp_port));
+                    Swarm::add_external_address(&mut self.swarm, address);
+                }
+            }
+        }
+
         Ok(Async::NotReady)
     }
 }
 
 /// The implementation supports TCP/IP, WebSockets over TCP/IP, noise/secio as the encryption layer, and
 /// mplex or yamux as the multiplexing layer.
-fn build_transport(local_private_key: Keypair) -> Boxed<(PeerId, StreamMuxerBox), Error> {
+///
+/// The WebSocket transport is only included if `websocket` is set, in which case WebSocket
+/// addresses can be both dialed and listened on.
+fn build_transport(
+    local_private_key: Keypair,
+    websocket: bool,
+) -> Boxed<(PeerId, StreamMuxerBox), Error> {
     // TODO: The Wire protocol currently doesn't specify encryption and this will need to be customised
     // in the future.
     let transport = libp2p::tcp::TcpConfig::new().nodelay(true);
     let transport = libp2p::dns::DnsConfig::new(transport);
-    #[cfg(feature = "libp2p-websocket")]
     let transport = {
-        let trans_clone = transport.clone();
-        transport.or_transport(websocket::WsConfig::new(trans_clone))
+        let websocket_transport = if websocket {
+            OptionalTransport::some(libp2p::websocket::WsConfig::new(transport.clone()))
+        } else {
+            OptionalTransport::none()
+        };
+        transport.or_transport(websocket_transport)
     };
     // Authentication
     let transport = transport
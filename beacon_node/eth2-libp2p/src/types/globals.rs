@@ -30,7 +30,13 @@ pub struct NetworkGlobals<TSpec: EthSpec> {
 }
 
 impl<TSpec: EthSpec> NetworkGlobals<TSpec> {
-    pub fn new(enr: Enr, tcp_port: u16, udp_port: u16, log: &slog::Logger) -> Self {
+    pub fn new(
+        enr: Enr,
+        tcp_port: u16,
+        udp_port: u16,
+        trusted_peers: Vec<PeerId>,
+        log: &slog::Logger,
+    ) -> Self {
         // set up the local meta data of the node
         let meta_data = RwLock::new(MetaData {
             seq_number: 0,
@@ -46,7 +52,7 @@ impl<TSpec: EthSpec> NetworkGlobals<TSpec> {
             listen_multiaddrs: RwLock::new(Vec::new()),
             listen_port_tcp: AtomicU16::new(tcp_port),
             listen_port_udp: AtomicU16::new(udp_port),
-            peers: RwLock::new(PeerDB::new(log)),
+            peers: RwLock::new(PeerDB::new(trusted_peers, log)),
             gossipsub_subscriptions: RwLock::new(HashSet::new()),
             sync_state: RwLock::new(SyncState::Stalled),
         }
@@ -88,6 +94,25 @@ impl<TSpec: EthSpec> NetworkGlobals<TSpec> {
         self.peers.read().connected_or_dialing_peers().count()
     }
 
+    /// Returns the number of connected peers that count towards `max_peers`. Trusted peers are
+    /// exempt from the limit.
+    pub fn connected_untrusted_peers(&self) -> usize {
+        let peers = self.peers.read();
+        peers
+            .connected_peer_ids()
+            .filter(|peer_id| !peers.is_trusted(peer_id))
+            .count()
+    }
+
+    /// Returns the number of connected or dialing peers that count towards `max_peers`.
+    pub fn connected_or_dialing_untrusted_peers(&self) -> usize {
+        let peers = self.peers.read();
+        peers
+            .connected_or_dialing_peers()
+            .filter(|peer_id| !peers.is_trusted(peer_id))
+            .count()
+    }
+
     /// Returns in the node is syncing.
     pub fn is_syncing(&self) -> bool {
         self.sync_state.read().is_syncing()
@@ -39,6 +39,7 @@ fn build_secio_swarm(
         enr,
         config.libp2p_port,
         config.discovery_port,
+        vec![],
         &log,
     ));
 
@@ -4,6 +4,8 @@ pub mod service;
 
 mod attestation_service;
 mod persisted_dht;
+mod persisted_peers;
+mod persisted_sync;
 mod router;
 mod sync;
 
@@ -0,0 +1,338 @@
+use eth2_libp2p::{
+    Client, Multiaddr, NetworkGlobals, PeerDB, PeerId, PeerInfo, Rep, Service as LibP2PService,
+    DEFAULT_REPUTATION,
+};
+use slog::{debug, info};
+use ssz::{Decode, Encode};
+use ssz_derive::{Decode, Encode};
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use store::{DBColumn, Error as StoreError, SimpleStoreItem, Store};
+use types::{EthSpec, Hash256};
+
+/// 32-byte key for accessing the `Peers`.
+pub const PEERS_DB_KEY: &str = "PERSISTEDPEERSPERSISTEDPEERSPERS";
+
+pub fn load_peers<T: Store<E>, E: EthSpec>(store: Arc<T>) -> Vec<PersistedPeer> {
+    // Load the peers from the store
+    let key = Hash256::from_slice(&PEERS_DB_KEY.as_bytes());
+    match store.get(&key) {
+        Ok(Some(p)) => {
+            let p: PersistedPeers = p;
+            p.peers
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// Attempt to persist the known peers to `self.store`.
+pub fn persist_peers<T: Store<E>, E: EthSpec>(
+    store: Arc<T>,
+    peers: Vec<PersistedPeer>,
+) -> Result<(), store::Error> {
+    let key = Hash256::from_slice(&PEERS_DB_KEY.as_bytes());
+    store.put(&key, &PersistedPeers { peers })?;
+    Ok(())
+}
+
+/// Collects the peers in the peer database, along with the expiry times of their bans.
+pub fn collect_peers<E: EthSpec>(
+    libp2p: &LibP2PService<E>,
+    network_globals: &NetworkGlobals<E>,
+) -> Vec<PersistedPeer> {
+    let now = unix_time_secs();
+    let ban_expiries: HashMap<&PeerId, u64> = libp2p
+        .banned_peers()
+        .map(|(peer_id, remaining)| (peer_id, now + remaining.as_secs()))
+        .collect();
+
+    network_globals
+        .peers
+        .read()
+        .peers()
+        .map(|(peer_id, info)| PersistedPeer {
+            peer_id: peer_id.as_bytes().to_vec(),
+            reputation: info.reputation as u32,
+            ban_expiry: ban_expiries.get(peer_id).cloned().unwrap_or(0),
+            listening_addresses: info
+                .listening_addresses
+                .iter()
+                .map(Multiaddr::to_vec)
+                .collect(),
+            agent_string: info
+                .client
+                .agent_string
+                .clone()
+                .unwrap_or_default()
+                .into_bytes(),
+            protocol_version: info.client.protocol_version.clone().into_bytes(),
+        })
+        .collect()
+}
+
+/// Adds the persisted peers to the peer database, re-applies the bans that have not yet expired
+/// and dials up to `max_peers` of the peers with the highest reputation.
+pub fn restore_peers<E: EthSpec>(
+    libp2p: &mut LibP2PService<E>,
+    network_globals: &NetworkGlobals<E>,
+    peers: Vec<PersistedPeer>,
+    max_peers: usize,
+    log: &slog::Logger,
+) {
+    let (bans, dials) = add_persisted_peers(
+        &mut network_globals.peers.write(),
+        peers,
+        max_peers,
+        unix_time_secs(),
+        log,
+    );
+
+    info!(log, "Restored persisted peers"; "banned" => bans.len(), "dialed" => dials.len());
+
+    for (peer_id, timeout) in bans {
+        libp2p.ban_peer(peer_id, timeout);
+    }
+    for (peer_id, multiaddr) in dials {
+        libp2p.dial_peer(&peer_id, multiaddr);
+    }
+}
+
+/// Adds the persisted peers to `peerdb`, marking the peers to be dialed as dialing. Returns the
+/// peers whose bans have not expired at `now` with the remaining ban time, and the peers to dial
+/// with the address to dial them at.
+///
+/// Bans and reputations are applied before the excess disconnected peers are dropped, so banned
+/// peers are never dropped.
+fn add_persisted_peers<E: EthSpec>(
+    peerdb: &mut PeerDB<E>,
+    peers: Vec<PersistedPeer>,
+    max_peers: usize,
+    now: u64,
+    log: &slog::Logger,
+) -> (Vec<(PeerId, Duration)>, Vec<(PeerId, Multiaddr)>) {
+    let mut bans = vec![];
+    let mut candidates = vec![];
+    let mut known_peers = vec![];
+
+    for peer in peers {
+        let peer_id = match PeerId::from_bytes(peer.peer_id.clone()) {
+            Ok(peer_id) => peer_id,
+            Err(_) => {
+                debug!(log, "Ignoring persisted peer with an invalid id");
+                continue;
+            }
+        };
+        let banned = peer.ban_expiry > now;
+
+        if banned {
+            bans.push((peer_id.clone(), Duration::from_secs(peer.ban_expiry - now)));
+        } else if peer.reputation() >= DEFAULT_REPUTATION {
+            if let Some(multiaddr) = peer.listening_addresses().into_iter().next() {
+                candidates.push((peer.reputation(), peer_id.clone(), multiaddr));
+            }
+        }
+
+        known_peers.push((peer_id, peer.into_peer_info(), banned));
+    }
+
+    peerdb.add_known_peers(known_peers);
+
+    // dial the best peers first
+    candidates.sort_by_key(|(reputation, _, _)| std::cmp::Reverse(*reputation));
+    let mut dials = vec![];
+    for (_, peer_id, multiaddr) in candidates {
+        if dials.len() >= max_peers {
+            break;
+        }
+        // peers that were dropped or are already known to be connected are not dialed
+        let is_disconnected = peerdb
+            .connection_status(&peer_id)
+            .map_or(false, |status| status.is_disconnected());
+        if is_disconnected {
+            // inform the peer manager that we are currently dialing this peer
+            peerdb.dialing_peer(&peer_id);
+            dials.push((peer_id, multiaddr));
+        }
+    }
+
+    (bans, dials)
+}
+
+fn unix_time_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// A peer from the peer database, in a form that can be persisted to disk.
+#[derive(Encode, Decode, Clone, Debug, PartialEq)]
+pub struct PersistedPeer {
+    /// The bytes of the `PeerId`.
+    pub peer_id: Vec<u8>,
+    /// The two's complement of the peer's reputation, which may be negative.
+    pub reputation: u32,
+    /// The unix time in seconds at which the peer's ban expires, or 0 if it is not banned.
+    pub ban_expiry: u64,
+    /// The bytes of the known listening `Multiaddr`s of the peer.
+    pub listening_addresses: Vec<Vec<u8>>,
+    /// The agent string the peer identified with, which determines its client kind.
+    pub agent_string: Vec<u8>,
+    /// The protocol version the peer identified with.
+    pub protocol_version: Vec<u8>,
+}
+
+impl PersistedPeer {
+    pub fn reputation(&self) -> Rep {
+        self.reputation as Rep
+    }
+
+    /// The listening addresses of the peer, ignoring any that fail to parse.
+    pub fn listening_addresses(&self) -> Vec<Multiaddr> {
+        self.listening_addresses
+            .iter()
+            .filter_map(|bytes| Multiaddr::try_from(bytes.clone()).ok())
+            .collect()
+    }
+
+    pub fn into_peer_info<E: EthSpec>(self) -> PeerInfo<E> {
+        let mut info = PeerInfo::default();
+        info.reputation = self.reputation();
+        info.listening_addresses = self.listening_addresses();
+        if !self.agent_string.is_empty() {
+            info.client = Client::from_agent_string(
+                String::from_utf8_lossy(&self.agent_string).into_owned(),
+                String::from_utf8_lossy(&self.protocol_version).into_owned(),
+            );
+        }
+        info
+    }
+}
+
+/// Wrapper around the known peers for persistence to disk.
+#[derive(Encode, Decode)]
+pub struct PersistedPeers {
+    pub peers: Vec<PersistedPeer>,
+}
+
+impl SimpleStoreItem for PersistedPeers {
+    fn db_column() -> DBColumn {
+        DBColumn::Peers
+    }
+
+    fn as_store_bytes(&self) -> Vec<u8> {
+        self.as_ssz_bytes()
+    }
+
+    fn from_store_bytes(bytes: &[u8]) -> Result<Self, StoreError> {
+        Self::from_ssz_bytes(bytes).map_err(Into::into)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use store::{MemoryStore, Store};
+    use types::MinimalEthSpec;
+
+    #[test]
+    fn test_persisted_peers() {
+        let store = Arc::new(MemoryStore::<MinimalEthSpec>::open());
+        let multiaddr: Multiaddr = "/ip4/127.0.0.1/tcp/9000".parse().unwrap();
+        let peers = vec![PersistedPeer {
+            peer_id: PeerId::random().as_bytes().to_vec(),
+            reputation: -30_i32 as u32,
+            ban_expiry: 1_000,
+            listening_addresses: vec![multiaddr.to_vec()],
+            agent_string: b"Lighthouse/v0.2.0/x86_64-linux".to_vec(),
+            protocol_version: b"lighthouse/libp2p".to_vec(),
+        }];
+        persist_peers::<_, MinimalEthSpec>(store.clone(), peers.clone()).unwrap();
+
+        let loaded = load_peers::<_, MinimalEthSpec>(store);
+        assert_eq!(loaded, peers);
+        assert_eq!(loaded[0].reputation(), -30);
+        assert_eq!(loaded[0].listening_addresses(), vec![multiaddr]);
+
+        let info: PeerInfo<MinimalEthSpec> = loaded[0].clone().into_peer_info();
+        assert_eq!(info.reputation, -30);
+        assert_eq!(
+            info.client.agent_string,
+            Some("Lighthouse/v0.2.0/x86_64-linux".to_string())
+        );
+    }
+
+    fn persisted_peer(reputation: Rep, ban_expiry: u64) -> PersistedPeer {
+        let multiaddr: Multiaddr = "/ip4/127.0.0.1/tcp/9000".parse().unwrap();
+        PersistedPeer {
+            peer_id: PeerId::random().as_bytes().to_vec(),
+            reputation: reputation as u32,
+            ban_expiry,
+            listening_addresses: vec![multiaddr.to_vec()],
+            agent_string: vec![],
+            protocol_version: vec![],
+        }
+    }
+
+    fn peer_id(peer: &PersistedPeer) -> PeerId {
+        PeerId::from_bytes(peer.peer_id.clone()).unwrap()
+    }
+
+    #[test]
+    fn test_restore_many_peers() {
+        let log = slog::Logger::root(slog::Discard, slog::o!());
+        let mut peerdb = PeerDB::<MinimalEthSpec>::new(vec![], &log);
+        let now = 1_000;
+
+        // more peers than the peer database keeps disconnected, the worst of which are banned
+        let banned = (0..5)
+            .map(|_| persisted_peer(-100, now + 60))
+            .collect::<Vec<_>>();
+        let expired = persisted_peer(-100, now);
+        let peers = (0..40)
+            .map(|i| persisted_peer(10 + i * 3, 0))
+            .collect::<Vec<_>>();
+        let all = banned
+            .iter()
+            .chain(std::iter::once(&expired))
+            .chain(peers.iter())
+            .cloned()
+            .collect();
+
+        let (bans, dials) = add_persisted_peers(&mut peerdb, all, 10, now, &log);
+
+        assert_eq!(
+            bans,
+            banned
+                .iter()
+                .map(|peer| (peer_id(peer), Duration::from_secs(60)))
+                .collect::<Vec<_>>()
+        );
+        for peer in &banned {
+            let peer_id = peer_id(peer);
+            assert!(peerdb.connection_status(&peer_id).unwrap().is_banned());
+            assert_eq!(peerdb.reputation(&peer_id), -100);
+        }
+
+        // the best peers are dialed
+        let dialed = dials
+            .into_iter()
+            .map(|(peer_id, _)| peer_id)
+            .collect::<Vec<_>>();
+        let best = peers.iter().rev().take(10).map(peer_id).collect::<Vec<_>>();
+        assert_eq!(dialed, best);
+
+        // the worst of the peers that are not banned are dropped
+        assert!(peerdb.peer_info(&peer_id(&expired)).is_none());
+        assert_eq!(peerdb.disconnected_peers().count(), 20);
+        for peer in &peers[..10] {
+            assert!(peerdb.peer_info(&peer_id(peer)).is_none());
+        }
+        for peer in &peers[10..] {
+            assert_eq!(peerdb.reputation(&peer_id(peer)), peer.reputation());
+        }
+    }
+}
@@ -0,0 +1,89 @@
+use ssz::{Decode, Encode};
+use ssz_derive::{Decode, Encode};
+use std::sync::Arc;
+use store::{DBColumn, Error as StoreError, SimpleStoreItem, Store};
+use types::{Epoch, EthSpec, Hash256, Slot};
+
+/// 32-byte key for accessing the `SyncProgress`.
+pub const SYNC_DB_KEY: &str = "PERSISTEDSYNCPERSISTEDSYNCPERSIS";
+
+pub fn load_sync_progress<T: Store<E>, E: EthSpec>(store: Arc<T>) -> Option<PersistedSyncProgress> {
+    // Load the sync progress from the store
+    let key = Hash256::from_slice(&SYNC_DB_KEY.as_bytes());
+    match store.get(&key) {
+        Ok(Some(p)) => Some(p),
+        _ => None,
+    }
+}
+
+/// Attempt to persist the progress of range sync to `self.store`.
+pub fn persist_sync_progress<T: Store<E>, E: EthSpec>(
+    store: Arc<T>,
+    progress: &PersistedSyncProgress,
+) -> Result<(), store::Error> {
+    let key = Hash256::from_slice(&SYNC_DB_KEY.as_bytes());
+    store.put(&key, progress)?;
+    Ok(())
+}
+
+/// Removes any persisted range sync progress from `self.store`.
+pub fn clear_sync_progress<T: Store<E>, E: EthSpec>(store: Arc<T>) -> Result<(), store::Error> {
+    let key = Hash256::from_slice(&SYNC_DB_KEY.as_bytes());
+    store.delete::<PersistedSyncProgress>(&key)
+}
+
+/// The target of the finalized chain being synced, along with the batches it has processed.
+#[derive(Encode, Decode, Clone, Debug, PartialEq)]
+pub struct PersistedSyncProgress {
+    pub target_head_root: Hash256,
+    pub target_head_slot: Slot,
+    /// The epoch the batch ids of the chain are counted from.
+    pub start_epoch: Epoch,
+    /// The id of the next batch to be processed.
+    pub to_be_processed_id: u64,
+    /// The root of the last block imported from the processed batches.
+    pub processed_root: Hash256,
+}
+
+impl SimpleStoreItem for PersistedSyncProgress {
+    fn db_column() -> DBColumn {
+        DBColumn::SyncProgress
+    }
+
+    fn as_store_bytes(&self) -> Vec<u8> {
+        self.as_ssz_bytes()
+    }
+
+    fn from_store_bytes(bytes: &[u8]) -> Result<Self, StoreError> {
+        Self::from_ssz_bytes(bytes).map_err(Into::into)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use store::{MemoryStore, Store};
+    use types::MinimalEthSpec;
+
+    #[test]
+    fn test_persisted_sync_progress() {
+        let store = Arc::new(MemoryStore::<MinimalEthSpec>::open());
+        let progress = PersistedSyncProgress {
+            target_head_root: Hash256::repeat_byte(1),
+            target_head_slot: Slot::new(1024),
+            start_epoch: Epoch::new(3),
+            to_be_processed_id: 7,
+            processed_root: Hash256::repeat_byte(2),
+        };
+
+        assert_eq!(load_sync_progress::<_, MinimalEthSpec>(store.clone()), None);
+        persist_sync_progress(store.clone(), &progress).unwrap();
+        assert_eq!(
+            load_sync_progress::<_, MinimalEthSpec>(store.clone()),
+            Some(progress)
+        );
+        clear_sync_progress::<_, MinimalEthSpec>(store.clone()).unwrap();
+        assert_eq!(load_sync_progress::<_, MinimalEthSpec>(store), None);
+    }
+}
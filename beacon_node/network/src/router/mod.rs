@@ -11,7 +11,8 @@ use crate::service::NetworkMessage;
 use beacon_chain::{AttestationType, BeaconChain, BeaconChainTypes, BlockError};
 use eth2_libp2p::{
     rpc::{RPCError, RPCErrorResponse, RPCRequest, RPCResponse, RequestId, ResponseTermination},
-    MessageId, NetworkGlobals, PeerId, PubsubMessage, RPCEvent,
+    types::GossipKind,
+    MessageAcceptance, MessageId, NetworkGlobals, PeerId, PubsubMessage, RPCEvent,
 };
 use futures::future::Future;
 use futures::stream::Stream;
@@ -176,6 +177,10 @@ impl<T: BeaconChainTypes> Router<T> {
                 warn!(self.log, "Peer internal server error";"peer_id" => format!("{:?}", peer_id), "error" => error.as_string());
                 self.handle_rpc_error(peer_id, request_id, RPCError::RPCErrorResponse);
             }
+            RPCErrorResponse::RateLimited(error) => {
+                warn!(self.log, "Peer rate limited request";"peer_id" => format!("{:?}", peer_id), "error" => error.as_string());
+                self.handle_rpc_error(peer_id, request_id, RPCError::RPCErrorResponse);
+            }
             RPCErrorResponse::Unknown(error) => {
                 warn!(self.log, "Unknown peer error";"peer" => format!("{:?}", peer_id), "error" => error.as_string());
                 self.handle_rpc_error(peer_id, request_id, RPCError::RPCErrorResponse);
@@ -227,87 +232,94 @@ impl<T: BeaconChainTypes> Router<T> {
         self.processor.on_rpc_error(peer_id, request_id);
     }
 
-    /// Handle RPC messages
+    /// Handle gossipsub messages, informing the network service whether each message is valid
+    /// and should be propagated.
     fn handle_gossip(
         &mut self,
         id: MessageId,
         peer_id: PeerId,
         gossip_message: PubsubMessage<T::EthSpec>,
     ) {
+        let kind = gossip_message.kind();
         match gossip_message {
             // Attestations should never reach the router.
             PubsubMessage::AggregateAndProofAttestation(aggregate_and_proof) => {
-                if self
+                let acceptance = self
                     .processor
-                    .should_forward_aggregate_attestation(&aggregate_and_proof)
-                {
-                    self.propagate_message(id, peer_id.clone());
-                }
-                self.processor.process_attestation_gossip(
-                    peer_id,
-                    aggregate_and_proof.message.aggregate,
-                    AttestationType::Aggregated,
-                );
+                    .process_aggregate_gossip(peer_id.clone(), *aggregate_and_proof);
+                self.validation_result(id, peer_id, kind, acceptance);
             }
             PubsubMessage::Attestation(subnet_attestation) => {
-                if self
-                    .processor
-                    .should_forward_attestation(&subnet_attestation.1)
-                {
-                    self.propagate_message(id, peer_id.clone());
-                }
-                self.processor.process_attestation_gossip(
-                    peer_id,
+                let acceptance = self.processor.process_attestation_gossip(
+                    peer_id.clone(),
                     subnet_attestation.1,
                     AttestationType::Unaggregated { should_store: true },
                 );
+                self.validation_result(id, peer_id, kind, acceptance);
             }
             PubsubMessage::BeaconBlock(block) => {
                 match self.processor.should_forward_block(&peer_id, block) {
                     Ok(verified_block) => {
-                        self.propagate_message(id, peer_id.clone());
+                        self.validation_result(
+                            id,
+                            peer_id.clone(),
+                            kind,
+                            MessageAcceptance::Accept,
+                        );
                         self.processor.on_block_gossip(peer_id, verified_block);
                     }
-                    Err(BlockError::ParentUnknown { .. }) => {} // performing a parent lookup
                     Err(e) => {
-                        // performing a parent lookup
-                        warn!(self.log, "Could not verify block for gossip";
-                            "error" => format!("{:?}", e));
+                        match e {
+                            BlockError::ParentUnknown { .. } => {} // performing a parent lookup
+                            _ => warn!(self.log, "Could not verify block for gossip";
+                                "error" => format!("{:?}", e)),
+                        }
+                        let acceptance = processor::block_error_acceptance(&e);
+                        self.validation_result(id, peer_id, kind, acceptance);
                     }
                 }
             }
-            PubsubMessage::VoluntaryExit(_exit) => {
-                // TODO: Apply more sophisticated validation
-                self.propagate_message(id, peer_id.clone());
-                // TODO: Handle exits
-                debug!(self.log, "Received a voluntary exit"; "peer_id" => format!("{}", peer_id) );
+            PubsubMessage::VoluntaryExit(exit) => {
+                let acceptance = self
+                    .processor
+                    .process_voluntary_exit_gossip(peer_id.clone(), *exit);
+                self.validation_result(id, peer_id, kind, acceptance);
             }
-            PubsubMessage::ProposerSlashing(_proposer_slashing) => {
-                // TODO: Apply more sophisticated validation
-                self.propagate_message(id, peer_id.clone());
-                // TODO: Handle proposer slashings
-                debug!(self.log, "Received a proposer slashing"; "peer_id" => format!("{}", peer_id) );
+            PubsubMessage::ProposerSlashing(proposer_slashing) => {
+                let acceptance = self
+                    .processor
+                    .process_proposer_slashing_gossip(peer_id.clone(), *proposer_slashing);
+                self.validation_result(id, peer_id, kind, acceptance);
             }
-            PubsubMessage::AttesterSlashing(_attester_slashing) => {
-                // TODO: Apply more sophisticated validation
-                self.propagate_message(id, peer_id.clone());
-                // TODO: Handle attester slashings
-                debug!(self.log, "Received an attester slashing"; "peer_id" => format!("{}", peer_id) );
+            PubsubMessage::AttesterSlashing(attester_slashing) => {
+                let acceptance = self
+                    .processor
+                    .process_attester_slashing_gossip(peer_id.clone(), *attester_slashing);
+                self.validation_result(id, peer_id, kind, acceptance);
             }
         }
     }
 
-    /// Informs the network service that the message should be forwarded to other peers.
-    fn propagate_message(&mut self, message_id: MessageId, propagation_source: PeerId) {
+    /// Informs the network service of the result of validating a gossipsub message. Only accepted
+    /// messages are forwarded to other peers.
+    fn validation_result(
+        &mut self,
+        message_id: MessageId,
+        propagation_source: PeerId,
+        kind: GossipKind,
+        acceptance: MessageAcceptance,
+    ) {
         self.network_send
-            .try_send(NetworkMessage::Propagate {
+            .try_send(NetworkMessage::ValidationResult {
                 propagation_source,
                 message_id,
+                kind,
+                acceptance,
             })
             .unwrap_or_else(|_| {
                 warn!(
                     self.log,
-                    "Could not send propagation request to the network service"
+                    "Could not send validation result to the network service"
                 )
             });
     }
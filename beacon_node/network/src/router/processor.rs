@@ -1,20 +1,21 @@
 use crate::service::NetworkMessage;
 use crate::sync::{PeerSyncInfo, SyncMessage};
 use beacon_chain::{
-    AttestationProcessingOutcome, AttestationType, BeaconChain, BeaconChainTypes, BlockError,
-    BlockProcessingOutcome, GossipVerifiedBlock,
+    AttestationProcessingOutcome, AttestationType, BeaconChain, BeaconChainError, BeaconChainTypes,
+    BlockError, BlockProcessingOutcome, GossipVerifiedBlock, InvalidAggregator,
+    OperationProcessingOutcome,
 };
 use eth2_libp2p::rpc::methods::*;
 use eth2_libp2p::rpc::{RPCEvent, RPCRequest, RPCResponse, RequestId};
-use eth2_libp2p::{NetworkGlobals, PeerId};
+use eth2_libp2p::{MessageAcceptance, NetworkGlobals, PeerAction, PeerId};
 use slog::{debug, error, o, trace, warn};
 use ssz::Encode;
 use std::sync::Arc;
 use store::Store;
 use tokio::sync::{mpsc, oneshot};
 use types::{
-    Attestation, ChainSpec, Epoch, EthSpec, Hash256, SignedAggregateAndProof, SignedBeaconBlock,
-    Slot,
+    Attestation, AttesterSlashing, ChainSpec, Epoch, EthSpec, Hash256, ProposerSlashing,
+    SignedAggregateAndProof, SignedBeaconBlock, SignedVoluntaryExit, Slot,
 };
 
 //TODO: Rate limit requests
@@ -338,7 +339,8 @@ impl<T: BeaconChainTypes> Processor<T> {
             warn!(self.log,
                 "Peer sent invalid range request";
                 "error" => "Step sent was 0");
-            self.network.disconnect(peer_id, GoodbyeReason::Fault);
+            self.network
+                .report_peer(peer_id, PeerAction::InvalidMessage);
             return;
         }
 
@@ -473,6 +475,15 @@ impl<T: BeaconChainTypes> Processor<T> {
             debug!(self.log, "Unknown block received. Starting a parent lookup"; "block_slot" => block.message.slot, "block_hash" => format!("{}", block_hash));
             self.send_to_sync(SyncMessage::UnknownBlock(peer_id.clone(), block));
         }
+
+        // invalid blocks are penalised when they are rejected, forged signatures additionally
+        // mark the peer as malicious
+        if let Err(BlockError::ProposalSignatureInvalid) | Err(BlockError::InvalidSignature) =
+            result
+        {
+            self.network
+                .report_peer(peer_id.clone(), PeerAction::Malicious);
+        }
         result
     }
 
@@ -533,6 +544,21 @@ impl<T: BeaconChainTypes> Processor<T> {
                         "Invalid gossip beacon block ssz";
                         "ssz" => format!("0x{}", hex::encode(block.as_ssz_bytes())),
                     );
+                    match other {
+                        BlockProcessingOutcome::InvalidSignature
+                        | BlockProcessingOutcome::ProposalSignatureInvalid => {
+                            self.network.report_peer(peer_id, PeerAction::Malicious);
+                        }
+                        BlockProcessingOutcome::StateRootMismatch { .. }
+                        | BlockProcessingOutcome::GenesisBlock
+                        | BlockProcessingOutcome::BlockIsNotLaterThanParent { .. }
+                        | BlockProcessingOutcome::PerBlockProcessingError(_)
+                        | BlockProcessingOutcome::WeakSubjectivityConflict { .. } => {
+                            self.network
+                                .report_peer(peer_id, PeerAction::InvalidMessage);
+                        }
+                        _ => {}
+                    }
                 }
             },
             Err(_) => {
@@ -548,32 +574,43 @@ impl<T: BeaconChainTypes> Processor<T> {
         true
     }
 
-    /// Verifies the Aggregate attestation before propagating.
-    pub fn should_forward_aggregate_attestation(
-        &self,
-        _aggregate_and_proof: &Box<SignedAggregateAndProof<T::EthSpec>>,
-    ) -> bool {
-        // TODO: Implement
-        true
-    }
-
-    /// Verifies the attestation before propagating.
-    pub fn should_forward_attestation(&self, _aggregate: &Attestation<T::EthSpec>) -> bool {
-        // TODO: Implement
-        true
-    }
-
     /// Process a new attestation received from gossipsub.
+    ///
+    /// Returns whether the attestation should be forwarded to our peers.
     pub fn process_attestation_gossip(
         &mut self,
         peer_id: PeerId,
         msg: Attestation<T::EthSpec>,
         attestation_type: AttestationType,
-    ) {
-        match self
+    ) -> MessageAcceptance {
+        let result = self
             .chain
-            .process_attestation(msg.clone(), attestation_type)
-        {
+            .process_attestation(msg.clone(), attestation_type);
+        self.attestation_acceptance(peer_id, &msg, result)
+    }
+
+    /// Process a new aggregate received from gossipsub. The aggregate is only imported once its
+    /// aggregator is verified to have been selected to aggregate and to have signed it.
+    ///
+    /// Returns whether the aggregate should be forwarded to our peers.
+    pub fn process_aggregate_gossip(
+        &mut self,
+        peer_id: PeerId,
+        signed_aggregate: SignedAggregateAndProof<T::EthSpec>,
+    ) -> MessageAcceptance {
+        let result = self.chain.process_signed_aggregate(&signed_aggregate);
+        self.attestation_acceptance(peer_id, &signed_aggregate.message.aggregate, result)
+    }
+
+    /// Returns whether an attestation received from gossipsub should be forwarded to our peers,
+    /// given the result of processing it.
+    fn attestation_acceptance(
+        &mut self,
+        peer_id: PeerId,
+        msg: &Attestation<T::EthSpec>,
+        result: Result<AttestationProcessingOutcome, BeaconChainError>,
+    ) -> MessageAcceptance {
+        match result {
             Ok(outcome) => match outcome {
                 AttestationProcessingOutcome::Processed => {
                     debug!(
@@ -584,6 +621,7 @@ impl<T: BeaconChainTypes> Processor<T> {
                         "block_root" => format!("{}", msg.data.beacon_block_root),
                         "slot" => format!("{}", msg.data.slot),
                     );
+                    MessageAcceptance::Accept
                 }
                 AttestationProcessingOutcome::UnknownHeadBlock { beacon_block_root } => {
                     // TODO: Maintain this attestation and re-process once sync completes
@@ -595,19 +633,40 @@ impl<T: BeaconChainTypes> Processor<T> {
                     );
                     // we don't know the block, get the sync manager to handle the block lookup
                     self.send_to_sync(SyncMessage::UnknownBlockHash(peer_id, beacon_block_root));
+                    MessageAcceptance::Ignore
                 }
                 AttestationProcessingOutcome::FutureEpoch { .. }
                 | AttestationProcessingOutcome::PastEpoch { .. }
                 | AttestationProcessingOutcome::UnknownTargetRoot { .. }
-                | AttestationProcessingOutcome::FinalizedSlot { .. } => {} // ignore the attestation
-                AttestationProcessingOutcome::Invalid { .. }
+                | AttestationProcessingOutcome::FinalizedSlot { .. } => MessageAcceptance::Ignore,
+                AttestationProcessingOutcome::InvalidAggregator(
+                    InvalidAggregator::UnknownValidator(aggregator_index),
+                ) => {
+                    debug!(
+                        self.log,
+                        "Unknown aggregator";
+                        "peer" => format!("{:?}", peer_id),
+                        "aggregator_index" => aggregator_index,
+                    );
+                    MessageAcceptance::Ignore
+                }
+                AttestationProcessingOutcome::InvalidAggregator(invalid) => {
+                    debug!(
+                        self.log,
+                        "Invalid aggregator";
+                        "peer" => format!("{:?}", peer_id),
+                        "reason" => format!("{:?}", invalid),
+                    );
+                    MessageAcceptance::Reject
+                }
+                AttestationProcessingOutcome::InvalidSignature
+                | AttestationProcessingOutcome::Invalid { .. }
                 | AttestationProcessingOutcome::EmptyAggregationBitfield { .. }
                 | AttestationProcessingOutcome::AttestsToFutureBlock { .. }
-                | AttestationProcessingOutcome::InvalidSignature
                 | AttestationProcessingOutcome::NoCommitteeForSlotAndIndex { .. }
                 | AttestationProcessingOutcome::BadTargetEpoch { .. } => {
-                    // the peer has sent a bad attestation. Remove them.
-                    self.network.disconnect(peer_id, GoodbyeReason::Fault);
+                    // the peer has sent a bad attestation, it is penalised by the rejection
+                    MessageAcceptance::Reject
                 }
             },
             Err(_) => {
@@ -617,8 +676,154 @@ impl<T: BeaconChainTypes> Processor<T> {
                     "Erroneous gossip attestation ssz";
                     "ssz" => format!("0x{}", hex::encode(msg.as_ssz_bytes())),
                 );
+                MessageAcceptance::Ignore
             }
-        };
+        }
+    }
+
+    /// Verifies a voluntary exit received from gossipsub and adds it to the operation pool.
+    ///
+    /// Returns whether the exit should be propagated to other peers.
+    pub fn process_voluntary_exit_gossip(
+        &mut self,
+        peer_id: PeerId,
+        exit: SignedVoluntaryExit,
+    ) -> MessageAcceptance {
+        let validator_index = exit.message.validator_index;
+        match self.chain.process_voluntary_exit(exit) {
+            Ok(OperationProcessingOutcome::Processed) => {
+                debug!(
+                    self.log,
+                    "Processed voluntary exit";
+                    "source" => "gossip",
+                    "peer" => format!("{:?}", peer_id),
+                    "validator_index" => validator_index,
+                );
+                MessageAcceptance::Accept
+            }
+            // The exit may well be valid, but we must not vouch for it to our peers.
+            Ok(OperationProcessingOutcome::NotVerified) => {
+                debug!(
+                    self.log,
+                    "Unable to verify voluntary exit";
+                    "source" => "gossip",
+                    "peer" => format!("{:?}", peer_id),
+                    "validator_index" => validator_index,
+                );
+                MessageAcceptance::Ignore
+            }
+            Err(e) => {
+                debug!(
+                    self.log,
+                    "Invalid voluntary exit";
+                    "source" => "gossip",
+                    "peer" => format!("{:?}", peer_id),
+                    "validator_index" => validator_index,
+                    "error" => format!("{:?}", e),
+                );
+                MessageAcceptance::Reject
+            }
+        }
+    }
+
+    /// Verifies a proposer slashing received from gossipsub and adds it to the operation pool.
+    ///
+    /// Returns whether the slashing should be propagated to other peers.
+    pub fn process_proposer_slashing_gossip(
+        &mut self,
+        peer_id: PeerId,
+        proposer_slashing: ProposerSlashing,
+    ) -> MessageAcceptance {
+        let proposer_index = proposer_slashing.signed_header_1.message.proposer_index;
+        match self.chain.process_proposer_slashing(proposer_slashing) {
+            Ok(OperationProcessingOutcome::Processed) => {
+                debug!(
+                    self.log,
+                    "Processed proposer slashing";
+                    "source" => "gossip",
+                    "peer" => format!("{:?}", peer_id),
+                    "proposer_index" => proposer_index,
+                );
+                MessageAcceptance::Accept
+            }
+            Ok(OperationProcessingOutcome::NotVerified) => {
+                debug!(
+                    self.log,
+                    "Unable to verify proposer slashing";
+                    "source" => "gossip",
+                    "peer" => format!("{:?}", peer_id),
+                    "proposer_index" => proposer_index,
+                );
+                MessageAcceptance::Ignore
+            }
+            Err(e) => {
+                debug!(
+                    self.log,
+                    "Invalid proposer slashing";
+                    "source" => "gossip",
+                    "peer" => format!("{:?}", peer_id),
+                    "proposer_index" => proposer_index,
+                    "error" => format!("{:?}", e),
+                );
+                MessageAcceptance::Reject
+            }
+        }
+    }
+
+    /// Verifies an attester slashing received from gossipsub and adds it to the operation pool.
+    ///
+    /// Returns whether the slashing should be propagated to other peers.
+    pub fn process_attester_slashing_gossip(
+        &mut self,
+        peer_id: PeerId,
+        attester_slashing: AttesterSlashing<T::EthSpec>,
+    ) -> MessageAcceptance {
+        match self.chain.process_attester_slashing(attester_slashing) {
+            Ok(OperationProcessingOutcome::Processed) => {
+                debug!(
+                    self.log,
+                    "Processed attester slashing";
+                    "source" => "gossip",
+                    "peer" => format!("{:?}", peer_id),
+                );
+                MessageAcceptance::Accept
+            }
+            Ok(OperationProcessingOutcome::NotVerified) => {
+                debug!(
+                    self.log,
+                    "Unable to verify attester slashing";
+                    "source" => "gossip",
+                    "peer" => format!("{:?}", peer_id),
+                );
+                MessageAcceptance::Ignore
+            }
+            Err(e) => {
+                debug!(
+                    self.log,
+                    "Invalid attester slashing";
+                    "source" => "gossip",
+                    "peer" => format!("{:?}", peer_id),
+                    "error" => format!("{:?}", e),
+                );
+                MessageAcceptance::Reject
+            }
+        }
+    }
+}
+
+/// Determines whether a gossip block that failed verification is rejected, penalising the
+/// peer that delivered it, or ignored.
+pub(crate) fn block_error_acceptance(error: &BlockError) -> MessageAcceptance {
+    match error {
+        BlockError::ProposalSignatureInvalid
+        | BlockError::InvalidSignature
+        | BlockError::GenesisBlock
+        | BlockError::StateRootMismatch { .. }
+        | BlockError::BlockIsNotLaterThanParent { .. }
+        | BlockError::PerBlockProcessingError(_)
+        | BlockError::WeakSubjectivityConflict { .. } => MessageAcceptance::Reject,
+        // the block may be valid, but it is not useful to forward it now
+        _ => MessageAcceptance::Ignore,
     }
 }
 
@@ -675,6 +880,25 @@ impl<T: EthSpec> HandlerNetworkContext<T> {
             });
     }
 
+    /// Reports a peer to the peer manager. Peers that repeatedly misbehave are disconnected and
+    /// banned by the peer manager.
+    pub fn report_peer(&mut self, peer_id: PeerId, action: PeerAction) {
+        debug!(
+            &self.log,
+            "Peer reported";
+            "peer_id" => format!("{:?}", peer_id),
+            "action" => format!("{:?}", action),
+        );
+        self.network_send
+            .try_send(NetworkMessage::ReportPeer { peer_id, action })
+            .unwrap_or_else(|_| {
+                warn!(
+                    self.log,
+                    "Could not send a peer report to the network service"
+                )
+            });
+    }
+
     pub fn send_rpc_request(&mut self, peer_id: PeerId, rpc_request: RPCRequest<T>) {
         // the message handler cannot send requests with ids. Id's are managed by the sync
         // manager.
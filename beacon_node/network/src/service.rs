@@ -1,5 +1,6 @@
 use crate::error;
 use crate::persisted_dht::{load_dht, persist_dht};
+use crate::persisted_peers::{collect_peers, load_peers, persist_peers, restore_peers};
 use crate::router::{Router, RouterMessage};
 use crate::{
     attestation_service::{AttServiceMessage, AttestationService},
@@ -7,7 +8,10 @@ use crate::{
 };
 use beacon_chain::{BeaconChain, BeaconChainTypes};
 use eth2_libp2p::Service as LibP2PService;
-use eth2_libp2p::{rpc::RPCRequest, BehaviourEvent, Enr, MessageId, NetworkGlobals, PeerId, Swarm};
+use eth2_libp2p::{
+    rpc::RPCRequest, types::GossipKind, BehaviourEvent, Enr, MessageAcceptance, MessageId,
+    NetworkGlobals, PeerAction, PeerId, Swarm,
+};
 use eth2_libp2p::{PubsubMessage, RPCEvent};
 use futures::prelude::*;
 use futures::Stream;
@@ -84,6 +88,14 @@ impl<T: BeaconChainTypes> NetworkService<T> {
             libp2p.swarm.add_enr(enr);
         }
 
+        restore_peers(
+            &mut libp2p,
+            &network_globals,
+            load_peers::<T::Store, T::EthSpec>(store.clone()),
+            config.max_peers,
+            &network_log,
+        );
+
         // A delay used to initialise code after the network has started
         // This is currently used to obtain the listening addresses from the libp2p service.
         let initial_delay = Delay::new(Instant::now() + Duration::from_secs(1));
@@ -166,6 +178,25 @@ fn spawn_service<T: BeaconChainTypes>(
                         ),
                     }
 
+                    let peers = collect_peers(&service.libp2p, &service.network_globals);
+                    debug!(
+                        log,
+                        "Persisting peers to store";
+                        "Number of peers" => format!("{}", peers.len()),
+                    );
+
+                    match persist_peers::<T::Store, T::EthSpec>(service.store.clone(), peers) {
+                        Err(e) => error!(
+                            log,
+                            "Failed to persist peers on drop";
+                            "error" => format!("{:?}", e)
+                        ),
+                        Ok(_) => info!(
+                            log,
+                            "Saved peer database";
+                        ),
+                    }
+
                     info!(log.clone(), "Network service shutdown");
                     return Ok(Async::Ready(()));
         }
@@ -179,10 +210,22 @@ fn spawn_service<T: BeaconChainTypes>(
                         trace!(log, "Sending RPC"; "rpc" => format!("{}", rpc_event));
                         service.libp2p.swarm.send_rpc(peer_id, rpc_event);
                     }
-                    NetworkMessage::Propagate {
+                    NetworkMessage::ValidationResult {
                         propagation_source,
                         message_id,
+                        kind,
+                        acceptance,
                     } => {
+                        service.libp2p.swarm.report_message_validation_result(
+                            &propagation_source,
+                            &kind,
+                            acceptance,
+                        );
+                        // only accepted messages are forwarded, the others expire from the cache
+                        if acceptance != MessageAcceptance::Accept {
+                            continue;
+                        }
+
                         // TODO: Remove this for mainnet
                         // randomly prevents propagation
                         let mut should_send = true;
@@ -237,6 +280,9 @@ fn spawn_service<T: BeaconChainTypes>(
                             std::time::Duration::from_secs(BAN_PEER_TIMEOUT),
                         );
                     }
+                    NetworkMessage::ReportPeer { peer_id, action } => {
+                        service.libp2p.swarm.report_peer(&peer_id, action);
+                    }
                     NetworkMessage::Subscribe { subscriptions } =>
                     {
                        // the result is dropped as it used solely for ergonomics
@@ -395,11 +441,17 @@ pub enum NetworkMessage<T: EthSpec> {
     RPC(PeerId, RPCEvent<T>),
     /// Publish a list of messages to the gossipsub protocol.
     Publish { messages: Vec<PubsubMessage<T>> },
-    /// Propagate a received gossipsub message.
-    Propagate {
+    /// The result of validating a received gossipsub message. Accepted messages are propagated
+    /// and the peer that delivered the message is scored.
+    ValidationResult {
         propagation_source: PeerId,
         message_id: MessageId,
+        kind: GossipKind,
+        acceptance: MessageAcceptance,
     },
     /// Disconnect and bans a peer id.
     Disconnect { peer_id: PeerId },
+    /// Reports a peer's behaviour to the peer manager, which disconnects or bans the peer if its
+    /// reputation becomes too low.
+    ReportPeer { peer_id: PeerId, action: PeerAction },
 }
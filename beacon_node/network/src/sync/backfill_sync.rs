@@ -0,0 +1,279 @@
+//! Downloads the blocks prior to the anchor of a node that was started from a checkpoint.
+//!
+//! A node started from a finalized checkpoint state holds no blocks prior to the checkpoint. Once
+//! running, the `BackfillSync` requests these blocks from synced peers, in descending batches of
+//! `BlocksByRange` requests, until genesis is reached. Each batch must form a chain of parent
+//! roots ending at the parent of the oldest block already stored, so the downloaded history is
+//! verified against the anchor block without processing any state transitions.
+//!
+//! Backfilling runs alongside the other sync strategies and only downloads a single batch at a
+//! time, so it does not delay syncing to the head of the chain.
+
+use super::network_context::SyncNetworkContext;
+use beacon_chain::{BeaconChain, BeaconChainTypes};
+use eth2_libp2p::rpc::{methods::BlocksByRangeRequest, RequestId};
+use eth2_libp2p::{NetworkGlobals, PeerAction, PeerId};
+use rand::seq::IteratorRandom;
+use slog::{debug, error, info, warn};
+use std::sync::Arc;
+use store::Store;
+use types::{EthSpec, SignedBeaconBlock, Slot};
+
+/// The number of epochs of blocks requested in a single backfill batch.
+const EPOCHS_PER_BATCH: u64 = 2;
+
+/// A `BlocksByRange` request for blocks prior to the oldest stored block.
+struct BackfillBatch<E: EthSpec> {
+    /// The id of the request.
+    request_id: RequestId,
+    /// The peer the blocks were requested from.
+    peer_id: PeerId,
+    /// The first slot of the request.
+    start_slot: Slot,
+    /// The slot following the last slot of the request.
+    end_slot: Slot,
+    /// The blocks received so far.
+    downloaded_blocks: Vec<SignedBeaconBlock<E>>,
+}
+
+/// Downloads blocks backwards from the anchor of a checkpoint-synced node towards genesis.
+pub struct BackfillSync<T: BeaconChainTypes> {
+    /// The beacon chain whose store the blocks are written to.
+    beacon_chain: Arc<BeaconChain<T>>,
+    /// A reference to the network globals and peer-db.
+    network_globals: Arc<NetworkGlobals<T::EthSpec>>,
+    /// The batch currently being downloaded, if any.
+    pending_batch: Option<BackfillBatch<T::EthSpec>>,
+    /// The slot up to which the next batch is requested.
+    ///
+    /// Batches without blocks only move this cursor, the anchor in the store is updated once a
+    /// block is found. If a later batch fails verification, the cursor is reset to the anchor in
+    /// case the empty batches were incorrect.
+    next_end_slot: Option<Slot>,
+    /// The last peer that failed a request, which is avoided for the following request.
+    failed_peer: Option<PeerId>,
+    /// The syncing logger.
+    log: slog::Logger,
+}
+
+impl<T: BeaconChainTypes> BackfillSync<T> {
+    pub fn new(
+        beacon_chain: Arc<BeaconChain<T>>,
+        network_globals: Arc<NetworkGlobals<T::EthSpec>>,
+        log: slog::Logger,
+    ) -> Self {
+        BackfillSync {
+            beacon_chain,
+            network_globals,
+            pending_batch: None,
+            next_end_slot: None,
+            failed_peer: None,
+            log,
+        }
+    }
+
+    /// Requests the next batch of blocks from a synced peer, if blocks remain to be backfilled and
+    /// no batch is already being downloaded.
+    pub fn resume(&mut self, network: &mut SyncNetworkContext<T::EthSpec>) {
+        if self.pending_batch.is_some() {
+            return;
+        }
+
+        let anchor = match self.beacon_chain.store.get_anchor_info() {
+            Some(anchor) if !anchor.block_backfill_complete() => anchor,
+            _ => return,
+        };
+
+        let end_slot = self.next_end_slot.unwrap_or(anchor.oldest_block_slot);
+        if end_slot == 0 {
+            // Every batch down to genesis was empty, which cannot be correct since the genesis
+            // block exists. Start again from the oldest stored block.
+            self.next_end_slot = None;
+            return;
+        }
+
+        let peer_id = {
+            let peers = self.network_globals.peers.read();
+            let failed_peer = self.failed_peer.as_ref();
+            match peers
+                .synced_peers()
+                .filter(|peer_id| Some(*peer_id) != failed_peer)
+                .choose(&mut rand::thread_rng())
+            {
+                Some(peer_id) => peer_id.clone(),
+                None => return,
+            }
+        };
+
+        let batch_size = T::EthSpec::slots_per_epoch() * EPOCHS_PER_BATCH;
+        let start_slot = end_slot.saturating_sub(batch_size);
+        let request = BlocksByRangeRequest {
+            start_slot: start_slot.as_u64(),
+            count: (end_slot - start_slot).as_u64(),
+            step: 1,
+        };
+
+        if let Ok(request_id) = network.blocks_by_range_request(peer_id.clone(), request) {
+            debug!(
+                self.log,
+                "Requesting backfill batch";
+                "start_slot" => start_slot,
+                "end_slot" => end_slot,
+                "peer" => format!("{}", peer_id),
+            );
+            self.pending_batch = Some(BackfillBatch {
+                request_id,
+                peer_id,
+                start_slot,
+                end_slot,
+                downloaded_blocks: vec![],
+            });
+        }
+    }
+
+    /// Returns `true` if `request_id` belongs to the batch being downloaded.
+    pub fn is_backfill_request(&self, request_id: RequestId) -> bool {
+        self.pending_batch
+            .as_ref()
+            .map_or(false, |batch| batch.request_id == request_id)
+    }
+
+    /// Handles a block from a `BlocksByRange` response to the pending batch.
+    ///
+    /// The batch is verified and stored once the stream terminates.
+    pub fn blocks_by_range_response(
+        &mut self,
+        network: &mut SyncNetworkContext<T::EthSpec>,
+        request_id: RequestId,
+        beacon_block: Option<SignedBeaconBlock<T::EthSpec>>,
+    ) {
+        if !self.is_backfill_request(request_id) {
+            return;
+        }
+
+        match beacon_block {
+            Some(block) => {
+                if let Some(batch) = self.pending_batch.as_mut() {
+                    batch.downloaded_blocks.push(block);
+                }
+            }
+            None => {
+                if let Some(batch) = self.pending_batch.take() {
+                    self.process_batch(network, batch);
+                }
+                self.resume(network);
+            }
+        }
+    }
+
+    /// Handles a failed request, returning `false` if the request was not made by the backfill
+    /// sync.
+    pub fn inject_error(
+        &mut self,
+        network: &mut SyncNetworkContext<T::EthSpec>,
+        request_id: RequestId,
+    ) -> bool {
+        if !self.is_backfill_request(request_id) {
+            return false;
+        }
+
+        if let Some(batch) = self.pending_batch.take() {
+            debug!(self.log, "Backfill batch request failed"; "peer" => format!("{}", batch.peer_id));
+            self.failed_peer = Some(batch.peer_id);
+        }
+        self.resume(network);
+        true
+    }
+
+    /// Abandons the pending batch if it was requested from the disconnected peer.
+    pub fn peer_disconnect(
+        &mut self,
+        network: &mut SyncNetworkContext<T::EthSpec>,
+        peer_id: &PeerId,
+    ) {
+        if self
+            .pending_batch
+            .as_ref()
+            .map_or(false, |batch| batch.peer_id == *peer_id)
+        {
+            self.pending_batch = None;
+            self.resume(network);
+        }
+    }
+
+    /// Verifies that the blocks of `batch` chain to the oldest stored block and stores them.
+    fn process_batch(
+        &mut self,
+        network: &mut SyncNetworkContext<T::EthSpec>,
+        batch: BackfillBatch<T::EthSpec>,
+    ) {
+        let anchor = match self.beacon_chain.store.get_anchor_info() {
+            Some(anchor) => anchor,
+            None => return,
+        };
+
+        let mut blocks = batch.downloaded_blocks;
+        blocks.sort_by_key(|block| block.slot());
+
+        // Walk backwards from the oldest stored block, each block must be the parent of the one
+        // after it.
+        let mut expected_root = anchor.oldest_block_parent;
+        let mut valid = true;
+        for block in blocks.iter().rev() {
+            if block.slot() < batch.start_slot
+                || block.slot() >= batch.end_slot
+                || block.canonical_root() != expected_root
+            {
+                valid = false;
+                break;
+            }
+            expected_root = block.parent_root();
+        }
+
+        if !valid {
+            warn!(
+                self.log,
+                "Backfill batch does not chain to the oldest block";
+                "start_slot" => batch.start_slot,
+                "end_slot" => batch.end_slot,
+                "peer" => format!("{}", batch.peer_id),
+            );
+            self.next_end_slot = None;
+            self.failed_peer = Some(batch.peer_id.clone());
+            network.report_peer(batch.peer_id, PeerAction::InvalidMessage);
+            return;
+        }
+
+        self.failed_peer = None;
+        if blocks.is_empty() {
+            self.next_end_slot = Some(batch.start_slot);
+            return;
+        }
+
+        let imported_blocks = blocks.len();
+        match self.beacon_chain.store.put_backfilled_blocks(blocks) {
+            Ok(()) => {
+                self.next_end_slot = None;
+                match self.beacon_chain.store.get_anchor_info() {
+                    Some(anchor) if anchor.block_backfill_complete() => {
+                        info!(self.log, "Historical block backfill complete")
+                    }
+                    Some(anchor) => debug!(
+                        self.log,
+                        "Backfilled historical blocks";
+                        "imported_blocks" => imported_blocks,
+                        "oldest_block_slot" => anchor.oldest_block_slot,
+                    ),
+                    None => {}
+                }
+            }
+            Err(e) => {
+                error!(
+                    self.log,
+                    "Unable to store backfilled blocks";
+                    "error" => format!("{:?}", e),
+                );
+            }
+        }
+    }
+}
@@ -27,21 +27,29 @@
 //! fully sync'd peers. If `PARENT_FAIL_TOLERANCE` attempts at requesting the block fails, we
 //! drop the propagated block and downvote the peer that sent it to us.
 //!
+//! ## Backfill
+//!
+//! A node started from a checkpoint state downloads the blocks prior to the checkpoint in the
+//! background, from fully sync'd peers. See `BackfillSync` for further details.
+//!
 //! Block Lookup
 //!
 //! To keep the logic maintained to the syncing thread (and manage the request_ids), when a block needs to be searched for (i.e
 //! if an attestation references an unknown block) this manager can search for the block and
 //! subsequently search for parents if needed.
 
+use super::backfill_sync::BackfillSync;
 use super::block_processor::{spawn_block_processor, BatchProcessResult, ProcessId};
 use super::network_context::SyncNetworkContext;
 use super::peer_sync_info::{PeerSyncInfo, PeerSyncType};
 use super::range_sync::{BatchId, ChainId, RangeSync};
 use crate::service::NetworkMessage;
-use beacon_chain::{BeaconChain, BeaconChainTypes, BlockProcessingOutcome};
+use beacon_chain::{
+    events::EventKind, BeaconChain, BeaconChainTypes, BlockProcessingOutcome, EventHandler,
+};
 use eth2_libp2p::rpc::{methods::*, RequestId};
-use eth2_libp2p::types::NetworkGlobals;
-use eth2_libp2p::PeerId;
+use eth2_libp2p::types::{NetworkGlobals, SyncState};
+use eth2_libp2p::{PeerAction, PeerId};
 use fnv::FnvHashMap;
 use futures::prelude::*;
 use slog::{crit, debug, error, info, trace, warn, Logger};
@@ -145,6 +153,9 @@ pub struct SyncManager<T: BeaconChainTypes> {
     /// The object handling long-range batch load-balanced syncing.
     range_sync: RangeSync<T>,
 
+    /// The object downloading blocks prior to the anchor of a checkpoint-synced node.
+    backfill_sync: BackfillSync<T>,
+
     /// A collection of parent block lookups.
     parent_queue: SmallVec<[ParentRequests<T::EthSpec>; 3]>,
 
@@ -204,6 +215,11 @@ pub fn spawn<T: BeaconChainTypes>(
             sync_send.clone(),
             log.clone(),
         ),
+        backfill_sync: BackfillSync::new(
+            beacon_chain.clone(),
+            network_globals.clone(),
+            log.clone(),
+        ),
         network: SyncNetworkContext::new(network_send, log.clone()),
         chain: beacon_chain,
         network_globals,
@@ -227,6 +243,19 @@ pub fn spawn<T: BeaconChainTypes>(
     (sync_send, sync_exit)
 }
 
+/// Publishes an event if the node has started or finished a long-range sync.
+pub(crate) fn register_sync_state_change<T: BeaconChainTypes>(
+    chain: &BeaconChain<T>,
+    old_state: &SyncState,
+    new_state: &SyncState,
+) {
+    if old_state.is_syncing() != new_state.is_syncing() {
+        let _ = chain.event_handler.register(EventKind::SyncStateChanged {
+            syncing: new_state.is_syncing(),
+        });
+    }
+}
+
 impl<T: BeaconChainTypes> SyncManager<T> {
     /* Input Handling Functions */
 
@@ -262,6 +291,8 @@ impl<T: BeaconChainTypes> SyncManager<T> {
                 self.synced_peer(&peer_id, remote);
                 // notify the range sync that a peer has been added
                 self.range_sync.fully_synced_peer_found();
+                // the peer may be used to backfill historical blocks
+                self.backfill_sync.resume(&mut self.network);
             }
             PeerSyncType::Advanced => {
                 trace!(self.log, "Useful peer for sync found";
@@ -349,7 +380,8 @@ impl<T: BeaconChainTypes> SyncManager<T> {
                     // the peer didn't respond with a block that it referenced
                     if !single_block_request.block_returned {
                         warn!(self.log, "Peer didn't respond with a block it referenced"; "referenced_block_hash" => format!("{}", single_block_request.hash), "peer_id" =>  format!("{}", peer_id));
-                        self.network.downvote_peer(peer_id);
+                        self.network
+                            .report_peer(peer_id, PeerAction::InvalidMessage);
                     }
                     return;
                 }
@@ -390,7 +422,8 @@ impl<T: BeaconChainTypes> SyncManager<T> {
         if expected_block_hash != block.canonical_root() {
             // the peer that sent this, sent us the wrong block
             warn!(self.log, "Peer sent incorrect block for single block lookup"; "peer_id" => format!("{}", peer_id));
-            self.network.downvote_peer(peer_id);
+            self.network
+                .report_peer(peer_id, PeerAction::InvalidMessage);
             return;
         }
 
@@ -424,7 +457,8 @@ impl<T: BeaconChainTypes> SyncManager<T> {
                     }
                     _ => {
                         warn!(self.log, "Single block lookup failed"; "outcome" => format!("{:?}", outcome));
-                        self.network.downvote_peer(peer_id);
+                        self.network
+                            .report_peer(peer_id, PeerAction::InvalidMessage);
                     }
                 }
             }
@@ -529,6 +563,13 @@ impl<T: BeaconChainTypes> SyncManager<T> {
             return;
         }
 
+        if self
+            .backfill_sync
+            .inject_error(&mut self.network, request_id)
+        {
+            return;
+        }
+
         // otherwise, this is a range sync issue, notify the range sync
         self.range_sync
             .inject_error(&mut self.network, peer_id.clone(), request_id);
@@ -536,6 +577,8 @@ impl<T: BeaconChainTypes> SyncManager<T> {
 
     fn peer_disconnect(&mut self, peer_id: &PeerId) {
         self.range_sync.peer_disconnect(&mut self.network, peer_id);
+        self.backfill_sync
+            .peer_disconnect(&mut self.network, peer_id);
         self.update_sync_state();
     }
 
@@ -586,6 +629,7 @@ impl<T: BeaconChainTypes> SyncManager<T> {
     fn update_sync_state(&mut self) {
         if let Some((old_state, new_state)) = self.network_globals.update_sync_state() {
             info!(self.log, "Sync state updated"; "old_state" => format!("{}", old_state), "new_state" => format!("{}",new_state));
+            register_sync_state_change(&self.chain, &old_state, &new_state);
         }
     }
     /* Processing State Functions */
@@ -626,7 +670,7 @@ impl<T: BeaconChainTypes> SyncManager<T> {
             );
 
             self.request_parent(parent_request);
-            self.network.downvote_peer(peer);
+            self.network.report_peer(peer, PeerAction::InvalidMessage);
         } else {
             // The last block in the queue is the only one that has not attempted to be processed yet.
             //
@@ -668,8 +712,10 @@ impl<T: BeaconChainTypes> SyncManager<T> {
                         "outcome" => format!("{:?}", outcome),
                         "last_peer" => format!("{:?}", parent_request.last_submitted_peer),
                     );
-                    self.network
-                        .downvote_peer(parent_request.last_submitted_peer.clone());
+                    self.network.report_peer(
+                        parent_request.last_submitted_peer.clone(),
+                        PeerAction::InvalidMessage,
+                    );
                     return;
                 }
                 Err(e) => {
@@ -678,8 +724,10 @@ impl<T: BeaconChainTypes> SyncManager<T> {
                         "error" => format!("{:?}", e),
                         "last_peer" => format!("{:?}", parent_request.last_submitted_peer),
                     );
-                    self.network
-                        .downvote_peer(parent_request.last_submitted_peer.clone());
+                    self.network.report_peer(
+                        parent_request.last_submitted_peer.clone(),
+                        PeerAction::InvalidMessage,
+                    );
                     return;
                 }
             }
@@ -749,12 +797,20 @@ impl<T: BeaconChainTypes> Future for SyncManager<T> {
                         request_id,
                         beacon_block,
                     } => {
-                        self.range_sync.blocks_by_range_response(
-                            &mut self.network,
-                            peer_id,
-                            request_id,
-                            beacon_block.map(|b| *b),
-                        );
+                        if self.backfill_sync.is_backfill_request(request_id) {
+                            self.backfill_sync.blocks_by_range_response(
+                                &mut self.network,
+                                request_id,
+                                beacon_block.map(|b| *b),
+                            );
+                        } else {
+                            self.range_sync.blocks_by_range_response(
+                                &mut self.network,
+                                peer_id,
+                                request_id,
+                                beacon_block.map(|b| *b),
+                            );
+                        }
                     }
                     SyncMessage::BlocksByRootResponse {
                         peer_id,
@@ -790,7 +846,8 @@ impl<T: BeaconChainTypes> Future for SyncManager<T> {
                         );
                     }
                     SyncMessage::ParentLookupFailed(peer_id) => {
-                        self.network.downvote_peer(peer_id);
+                        self.network
+                            .report_peer(peer_id, PeerAction::InvalidMessage);
                     }
                 },
                 Ok(Async::NotReady) => break,
@@ -806,3 +863,62 @@ impl<T: BeaconChainTypes> Future for SyncManager<T> {
         Ok(Async::NotReady)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use beacon_chain::test_utils::BeaconChainHarness;
+    use types::{test_utils::generate_deterministic_keypairs, MinimalEthSpec};
+
+    fn syncing_head() -> SyncState {
+        SyncState::SyncingHead {
+            start_slot: Slot::new(0),
+            head_slot: Slot::new(64),
+        }
+    }
+
+    #[test]
+    fn test_sync_state_change_events() {
+        let harness = BeaconChainHarness::new_with_event_recorder(
+            MinimalEthSpec,
+            generate_deterministic_keypairs(8),
+        );
+        let chain = &harness.chain;
+
+        register_sync_state_change(chain, &SyncState::Stalled, &syncing_head());
+        register_sync_state_change(chain, &syncing_head(), &SyncState::Synced);
+
+        let events = chain.event_handler.take();
+        assert_eq!(events.len(), 2);
+        match (&events[0], &events[1]) {
+            (
+                EventKind::SyncStateChanged { syncing: true },
+                EventKind::SyncStateChanged { syncing: false },
+            ) => {}
+            other => panic!("unexpected events: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_sync_state_change_ignores_non_syncing_transitions() {
+        let harness = BeaconChainHarness::new_with_event_recorder(
+            MinimalEthSpec,
+            generate_deterministic_keypairs(8),
+        );
+        let chain = &harness.chain;
+
+        register_sync_state_change(chain, &SyncState::Synced, &SyncState::Stalled);
+        register_sync_state_change(chain, &SyncState::Stalled, &SyncState::Synced);
+        register_sync_state_change(
+            chain,
+            &SyncState::SyncingFinalized {
+                start_slot: Slot::new(0),
+                head_slot: Slot::new(64),
+                head_root: Hash256::zero(),
+            },
+            &syncing_head(),
+        );
+
+        assert!(chain.event_handler.take().is_empty());
+    }
+}
@@ -6,7 +6,7 @@ use crate::service::NetworkMessage;
 use beacon_chain::{BeaconChain, BeaconChainTypes};
 use eth2_libp2p::rpc::methods::*;
 use eth2_libp2p::rpc::{RPCEvent, RPCRequest, RequestId};
-use eth2_libp2p::PeerId;
+use eth2_libp2p::{PeerAction, PeerId};
 use slog::{debug, trace, warn};
 use std::sync::Arc;
 use tokio::sync::mpsc;
@@ -83,32 +83,21 @@ impl<T: EthSpec> SyncNetworkContext<T> {
         self.send_rpc_request(peer_id, RPCRequest::BlocksByRoot(request))
     }
 
-    pub fn downvote_peer(&mut self, peer_id: PeerId) {
+    /// Reports a peer to the peer manager. Peers that repeatedly misbehave are disconnected and
+    /// banned by the peer manager.
+    pub fn report_peer(&mut self, peer_id: PeerId, action: PeerAction) {
         debug!(
             self.log,
-            "Peer downvoted";
-            "peer" => format!("{:?}", peer_id)
+            "Peer reported";
+            "peer" => format!("{:?}", peer_id),
+            "action" => format!("{:?}", action)
         );
-        // TODO: Implement reputation
-        self.disconnect(peer_id, GoodbyeReason::Fault);
-    }
-
-    fn disconnect(&mut self, peer_id: PeerId, reason: GoodbyeReason) {
-        warn!(
-            &self.log,
-            "Disconnecting peer (RPC)";
-            "reason" => format!("{:?}", reason),
-            "peer_id" => format!("{:?}", peer_id),
-        );
-
-        // ignore the error if the channel send fails
-        let _ = self.send_rpc_request(peer_id.clone(), RPCRequest::Goodbye(reason));
         self.network_send
-            .try_send(NetworkMessage::Disconnect { peer_id })
+            .try_send(NetworkMessage::ReportPeer { peer_id, action })
             .unwrap_or_else(|_| {
                 warn!(
                     self.log,
-                    "Could not send a Disconnect to the network service"
+                    "Could not send a peer report to the network service"
                 )
             });
     }
@@ -9,8 +9,19 @@ use std::collections::hash_map::Entry;
 use std::collections::{HashMap, HashSet};
 use std::hash::{Hash, Hasher};
 use std::ops::Sub;
+use std::time::{Duration, Instant};
 use types::{EthSpec, SignedBeaconBlock, Slot};
 
+/// The time in which a peer is expected to return all of its pending batches. Each peer is given as
+/// many concurrent batches as its observed throughput allows it to download in this time.
+const TARGET_BATCH_DOWNLOAD_TIME: Duration = Duration::from_secs(10);
+
+/// The maximum number of concurrent batch requests to a single peer, regardless of its throughput.
+const MAX_PENDING_BATCHES_PER_PEER: usize = 3;
+
+/// The weight given to the latest completed batch when updating a peer's throughput estimate.
+const THROUGHPUT_SMOOTHING_FACTOR: f64 = 0.5;
+
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub struct BatchId(pub u64);
 
@@ -106,15 +117,71 @@ impl<T: EthSpec> PartialOrd for Batch<T> {
     }
 }
 
+/// Estimates the rate at which peers return the blocks of the batches requested from them.
+#[derive(Default)]
+pub struct PeerThroughput {
+    /// A moving average of the number of blocks per second each peer returns.
+    blocks_per_second: HashMap<PeerId, f64>,
+}
+
+impl PeerThroughput {
+    /// Updates the estimate of a peer that returned a batch of `blocks` blocks in `elapsed` time.
+    pub fn record_batch(&mut self, peer_id: &PeerId, blocks: usize, elapsed: Duration) {
+        // empty batches say nothing about the bandwidth of the peer
+        if blocks == 0 {
+            return;
+        }
+        let sample = blocks as f64 / elapsed.as_secs_f64().max(0.001);
+        let estimate = self
+            .blocks_per_second
+            .entry(peer_id.clone())
+            .or_insert(sample);
+        *estimate =
+            THROUGHPUT_SMOOTHING_FACTOR * sample + (1.0 - THROUGHPUT_SMOOTHING_FACTOR) * *estimate;
+    }
+
+    /// Halves the estimate of a peer that failed to return a batch.
+    pub fn record_failure(&mut self, peer_id: &PeerId) {
+        if let Some(estimate) = self.blocks_per_second.get_mut(peer_id) {
+            *estimate /= 2.0;
+        }
+    }
+
+    pub fn remove_peer(&mut self, peer_id: &PeerId) {
+        self.blocks_per_second.remove(peer_id);
+    }
+
+    /// The number of batches of `blocks_per_batch` blocks that may be requested from the peer at
+    /// once. Peers that have not yet returned a batch are given a single batch.
+    pub fn max_pending_batches(&self, peer_id: &PeerId, blocks_per_batch: u64) -> usize {
+        self.blocks_per_second
+            .get(peer_id)
+            .map(|blocks_per_second| {
+                let batches = blocks_per_second * TARGET_BATCH_DOWNLOAD_TIME.as_secs_f64()
+                    / blocks_per_batch as f64;
+                std::cmp::min(
+                    std::cmp::max(batches as usize, 1),
+                    MAX_PENDING_BATCHES_PER_PEER,
+                )
+            })
+            .unwrap_or(1)
+    }
+}
+
 /// A structure that contains a mapping of pending batch requests, that also keeps track of which
 /// peers are currently making batch requests.
 ///
-/// This is used to optimise searches for idle peers (peers that have no outbound batch requests).
+/// This is used to find peers that can be sent more batch requests, based on the number of
+/// requests they have outstanding and the throughput they have shown so far.
 pub struct PendingBatches<T: EthSpec> {
     /// The current pending batches.
     batches: FnvHashMap<RequestId, Batch<T>>,
     /// A mapping of peers to the number of pending requests.
     peer_requests: HashMap<PeerId, HashSet<RequestId>>,
+    /// The time each pending request was sent.
+    request_times: FnvHashMap<RequestId, Instant>,
+    /// The observed throughput of the peers batches have been requested from.
+    throughput: PeerThroughput,
 }
 
 impl<T: EthSpec> PendingBatches<T> {
@@ -122,6 +189,8 @@ impl<T: EthSpec> PendingBatches<T> {
         PendingBatches {
             batches: FnvHashMap::default(),
             peer_requests: HashMap::new(),
+            request_times: FnvHashMap::default(),
+            throughput: PeerThroughput::default(),
         }
     }
 
@@ -131,10 +200,12 @@ impl<T: EthSpec> PendingBatches<T> {
             .entry(peer_request)
             .or_insert_with(HashSet::new)
             .insert(request_id);
+        self.request_times.insert(request_id, Instant::now());
         self.batches.insert(request_id, batch)
     }
 
     pub fn remove(&mut self, request_id: RequestId) -> Option<Batch<T>> {
+        self.request_times.remove(&request_id);
         if let Some(batch) = self.batches.remove(&request_id) {
             if let Entry::Occupied(mut entry) = self.peer_requests.entry(batch.current_peer.clone())
             {
@@ -150,6 +221,29 @@ impl<T: EthSpec> PendingBatches<T> {
         }
     }
 
+    /// Removes a batch whose response has been fully received and updates the throughput of the
+    /// peer that sent it.
+    pub fn complete(&mut self, request_id: RequestId) -> Option<Batch<T>> {
+        let sent = self.request_times.get(&request_id).cloned();
+        let batch = self.remove(request_id)?;
+        if let Some(sent) = sent {
+            self.throughput.record_batch(
+                &batch.current_peer,
+                batch.downloaded_blocks.len(),
+                sent.elapsed(),
+            );
+        }
+        Some(batch)
+    }
+
+    /// Removes a batch whose request has failed and lowers the throughput of the peer it was
+    /// requested from.
+    pub fn fail(&mut self, request_id: RequestId) -> Option<Batch<T>> {
+        let batch = self.remove(request_id)?;
+        self.throughput.record_failure(&batch.current_peer);
+        Some(batch)
+    }
+
     /// The number of current pending batch requests.
     pub fn len(&self) -> usize {
         self.batches.len()
@@ -163,10 +257,21 @@ impl<T: EthSpec> PendingBatches<T> {
         Some(())
     }
 
-    /// Returns true if there the peer does not exist in the peer_requests mapping. Indicating it
-    /// has no pending outgoing requests.
-    pub fn peer_is_idle(&self, peer_id: &PeerId) -> bool {
-        self.peer_requests.get(peer_id).is_none()
+    /// Returns true if the peer has fewer pending requests than its throughput allows for.
+    pub fn peer_has_capacity(&self, peer_id: &PeerId, blocks_per_batch: u64) -> bool {
+        let pending = self
+            .peer_requests
+            .get(peer_id)
+            .map_or(0, |requests| requests.len());
+        pending
+            < self
+                .throughput
+                .max_pending_batches(peer_id, blocks_per_batch)
+    }
+
+    /// Forgets the throughput of a peer that has left the chain.
+    pub fn remove_peer(&mut self, peer_id: &PeerId) {
+        self.throughput.remove_peer(peer_id);
     }
 
     /// Removes a batch for a given peer.
@@ -177,3 +282,38 @@ impl<T: EthSpec> PendingBatches<T> {
         self.remove(request_id)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pending_batches_scale_with_throughput() {
+        let mut throughput = PeerThroughput::default();
+        let slow_peer = PeerId::random();
+        let fast_peer = PeerId::random();
+
+        // peers without any completed batches get a single batch
+        assert_eq!(throughput.max_pending_batches(&slow_peer, 64), 1);
+
+        throughput.record_batch(&slow_peer, 64, Duration::from_secs(30));
+        throughput.record_batch(&fast_peer, 64, Duration::from_secs(2));
+        assert_eq!(throughput.max_pending_batches(&slow_peer, 64), 1);
+        assert_eq!(
+            throughput.max_pending_batches(&fast_peer, 64),
+            MAX_PENDING_BATCHES_PER_PEER
+        );
+
+        // empty batches do not change the estimate
+        throughput.record_batch(&fast_peer, 0, Duration::from_secs(60));
+        assert_eq!(
+            throughput.max_pending_batches(&fast_peer, 64),
+            MAX_PENDING_BATCHES_PER_PEER
+        );
+
+        // 32 blocks per second halves to 16, which allows 2 batches every 10 seconds
+        throughput.record_batch(&fast_peer, 64, Duration::from_secs(2));
+        throughput.record_failure(&fast_peer);
+        assert_eq!(throughput.max_pending_batches(&fast_peer, 64), 2);
+    }
+}
@@ -1,10 +1,11 @@
 use super::batch::{Batch, BatchId, PendingBatches};
+use crate::persisted_sync::PersistedSyncProgress;
 use crate::sync::block_processor::{spawn_block_processor, BatchProcessResult, ProcessId};
 use crate::sync::network_context::SyncNetworkContext;
 use crate::sync::SyncMessage;
 use beacon_chain::{BeaconChain, BeaconChainTypes};
 use eth2_libp2p::rpc::RequestId;
-use eth2_libp2p::PeerId;
+use eth2_libp2p::{PeerAction, PeerId};
 use rand::prelude::*;
 use slog::{crit, debug, warn};
 use std::collections::HashSet;
@@ -78,6 +79,10 @@ pub struct SyncingChain<T: BeaconChainTypes> {
     /// The next batch id that needs to be processed.
     to_be_processed_id: BatchId,
 
+    /// The root of the last block imported from a processed batch, or zero if no blocks have been
+    /// imported by this chain.
+    processed_root: Hash256,
+
     /// The current state of the chain.
     pub state: ChainSyncingState,
 
@@ -128,6 +133,7 @@ impl<T: BeaconChainTypes> SyncingChain<T> {
             peer_pool,
             to_be_downloaded_id: BatchId(1),
             to_be_processed_id: BatchId(1),
+            processed_root: Hash256::zero(),
             state: ChainSyncingState::Stopped,
             current_processing_batch: None,
             sync_send,
@@ -164,7 +170,7 @@ impl<T: BeaconChainTypes> SyncingChain<T> {
             self.pending_batches.add_block(request_id, block.clone())
         } else {
             // A stream termination has been sent. This batch has ended. Process a completed batch.
-            let batch = self.pending_batches.remove(request_id)?;
+            let batch = self.pending_batches.complete(request_id)?;
             self.handle_completed_batch(network, batch);
             Some(())
         }
@@ -192,7 +198,7 @@ impl<T: BeaconChainTypes> SyncingChain<T> {
                 warn!(self.log, "BlocksByRange response returned out of range blocks";
                           "response_initial_slot" => first_slot,
                           "requested_initial_slot" => batch.start_slot);
-                network.downvote_peer(batch.current_peer);
+                network.report_peer(batch.current_peer, PeerAction::InvalidMessage);
                 self.to_be_processed_id = batch.id; // reset the id back to here, when incrementing, it will check against completed batches
                 return;
             }
@@ -321,17 +327,25 @@ impl<T: BeaconChainTypes> SyncingChain<T> {
             BatchProcessResult::Success => {
                 *self.to_be_processed_id += 1;
 
+                // We are only uncertain about this batch if it has not returned all blocks.
+                let is_complete = batch.downloaded_blocks.last().map(|block| block.slot())
+                    == Some(batch.end_slot.saturating_sub(1u64));
+
                 // If the processed batch was not empty, we can validate previous invalidated
-                // blocks
-                if !batch.downloaded_blocks.is_empty() {
+                // blocks and reward the peer that sent them
+                if let Some(last_block) = batch.downloaded_blocks.last() {
+                    self.processed_root = last_block.canonical_root();
                     self.mark_processed_batches_as_valid(network, &batch);
+                    // An incomplete batch is validated along with the other processed batches
+                    // once a later batch is processed.
+                    if is_complete {
+                        self.validate_reprocessed_batch(network, &batch);
+                    }
+                    network.report_peer(batch.current_peer.clone(), PeerAction::ValidMessage);
                 }
 
-                // Add the current batch to processed batches to be verified in the future. We are
-                // only uncertain about this batch, if it has not returned all blocks.
-                if batch.downloaded_blocks.last().map(|block| block.slot())
-                    != Some(batch.end_slot.saturating_sub(1u64))
-                {
+                // Add the current batch to processed batches to be verified in the future.
+                if !is_complete {
                     self.processed_batches.push(batch);
                 }
 
@@ -370,7 +384,7 @@ impl<T: BeaconChainTypes> SyncingChain<T> {
                     warn!(self.log, "Batch failed to download. Dropping chain and downvoting peers";
                         "chain_id" => self.id, "id"=> *batch.id);
                     for peer_id in self.peer_pool.drain() {
-                        network.downvote_peer(peer_id);
+                        network.report_peer(peer_id, PeerAction::InvalidMessage);
                     }
                     ProcessingResult::RemoveChain
                 } else {
@@ -396,7 +410,7 @@ impl<T: BeaconChainTypes> SyncingChain<T> {
                     warn!(self.log, "Batch failed to download. Dropping chain and downvoting peers";
                         "chain_id" => self.id, "id"=> *batch.id);
                     for peer_id in self.peer_pool.drain() {
-                        network.downvote_peer(peer_id);
+                        network.report_peer(peer_id, PeerAction::InvalidMessage);
                     }
                     ProcessingResult::RemoveChain
                 } else {
@@ -431,25 +445,36 @@ impl<T: BeaconChainTypes> SyncingChain<T> {
                     "current_id" => *last_batch.id);
             }
 
-            if let Some(prev_hash) = processed_batch.original_hash {
-                // The validated batch has been re-processed
-                if prev_hash != processed_batch.hash() {
-                    // The re-downloaded version was different
-                    if processed_batch.current_peer != processed_batch.original_peer {
-                        // A new peer sent the correct batch, the previous peer did not
-                        // downvote the original peer
-                        //
-                        // If the same peer corrected it's mistake, we allow it.... for
-                        // now.
-                        debug!(
-                            self.log, "Re-processed batch validated. Downvoting original peer";
-                                "chain_id" => self.id,
-                                "batch_id" => *processed_batch.id,
-                                "original_peer" => format!("{}",processed_batch.original_peer),
-                                "new_peer" => format!("{}", processed_batch.current_peer)
-                        );
-                        network.downvote_peer(processed_batch.original_peer);
-                    }
+            self.validate_reprocessed_batch(network, &processed_batch);
+        }
+    }
+
+    /// Handles a batch that is known to be valid. If it had been re-downloaded because its
+    /// original version could not be processed and the two versions differ, the original peer
+    /// sent an invalid batch and is downvoted.
+    fn validate_reprocessed_batch(
+        &self,
+        network: &mut SyncNetworkContext<T::EthSpec>,
+        batch: &Batch<T::EthSpec>,
+    ) {
+        if let Some(prev_hash) = batch.original_hash {
+            // The validated batch has been re-processed
+            if prev_hash != batch.hash() {
+                // The re-downloaded version was different
+                if batch.current_peer != batch.original_peer {
+                    // A new peer sent the correct batch, the previous peer did not
+                    // downvote the original peer
+                    //
+                    // If the same peer corrected it's mistake, we allow it.... for
+                    // now.
+                    debug!(
+                        self.log, "Re-processed batch validated. Downvoting original peer";
+                            "chain_id" => self.id,
+                            "batch_id" => *batch.id,
+                            "original_peer" => format!("{}", batch.original_peer),
+                            "new_peer" => format!("{}", batch.current_peer)
+                    );
+                    network.report_peer(batch.original_peer.clone(), PeerAction::InvalidMessage);
                 }
             }
         }
@@ -511,16 +536,9 @@ impl<T: BeaconChainTypes> SyncingChain<T> {
         // increment the re-process counter
         batch.reprocess_retries += 1;
 
-        // attempt to find another peer to download the batch from (this potentially doubles up
-        // requests on a single peer)
-        let current_peer = &batch.current_peer;
-        let new_peer = self
-            .peer_pool
-            .iter()
-            .find(|peer| *peer != current_peer)
-            .unwrap_or_else(|| current_peer);
-
-        batch.current_peer = new_peer.clone();
+        // attempt to find another peer to download the batch from, so that the two versions of
+        // the batch can be compared (this potentially doubles up requests on a single peer)
+        batch.current_peer = self.get_retry_peer(&batch);
 
         debug!(self.log, "Re-requesting batch";
             "chain_id" => self.id,
@@ -533,6 +551,49 @@ impl<T: BeaconChainTypes> SyncingChain<T> {
         self.send_batch(network, batch);
     }
 
+    /// Returns the progress of this chain in a form that can be persisted to disk.
+    ///
+    /// Only the id of the next batch to be processed is kept. Batches that have been downloaded
+    /// but not yet processed are requested again once the chain is resumed.
+    pub fn persisted_progress(&self) -> PersistedSyncProgress {
+        PersistedSyncProgress {
+            target_head_root: self.target_head_root,
+            target_head_slot: self.target_head_slot,
+            start_epoch: self.start_epoch,
+            to_be_processed_id: *self.to_be_processed_id,
+            processed_root: self.processed_root,
+        }
+    }
+
+    /// Resumes the progress of a chain that was persisted before a restart, so that the batches
+    /// that were already processed are not downloaded again.
+    ///
+    /// The progress is ignored if the last block it processed is not known to fork choice.
+    /// Returns `true` if the progress was restored.
+    pub fn restore_progress(&mut self, progress: PersistedSyncProgress) -> bool {
+        if progress.to_be_processed_id > 1
+            && !self
+                .chain
+                .fork_choice
+                .contains_block(&progress.processed_root)
+        {
+            debug!(self.log, "Ignoring persisted sync progress";
+                "chain_id" => self.id,
+                "processed_root" => format!("{}", progress.processed_root));
+            return false;
+        }
+
+        self.start_epoch = progress.start_epoch;
+        self.to_be_processed_id = BatchId(progress.to_be_processed_id);
+        self.to_be_downloaded_id = BatchId(progress.to_be_processed_id);
+        self.processed_root = progress.processed_root;
+
+        debug!(self.log, "Restored persisted sync progress";
+            "chain_id" => self.id,
+            "processed_slot" => self.current_processed_slot());
+        true
+    }
+
     pub fn stop_syncing(&mut self) {
         self.state = ChainSyncingState::Stopped;
     }
@@ -619,7 +680,7 @@ impl<T: BeaconChainTypes> SyncingChain<T> {
         peer_id: &PeerId,
         request_id: RequestId,
     ) -> Option<ProcessingResult> {
-        if let Some(batch) = self.pending_batches.remove(request_id) {
+        if let Some(batch) = self.pending_batches.fail(request_id) {
             warn!(self.log, "Batch failed. RPC Error";
                 "chain_id" => self.id,
                 "id" => *batch.id,
@@ -650,14 +711,7 @@ impl<T: BeaconChainTypes> SyncingChain<T> {
             ProcessingResult::RemoveChain
         } else {
             // try to re-process the request using a different peer, if possible
-            let current_peer = &batch.current_peer;
-            let new_peer = self
-                .peer_pool
-                .iter()
-                .find(|peer| *peer != current_peer)
-                .unwrap_or_else(|| current_peer);
-
-            batch.current_peer = new_peer.clone();
+            batch.current_peer = self.get_retry_peer(&batch);
             debug!(self.log, "Re-Requesting batch";
                 "chain_id" => self.id,
                 "start_slot" => batch.start_slot,
@@ -697,7 +751,8 @@ impl<T: BeaconChainTypes> SyncingChain<T> {
         false
     }
 
-    /// Returns a peer if there exists a peer which does not currently have a pending request.
+    /// Returns a peer if there exists a peer which has fewer pending requests than its observed
+    /// throughput allows for.
     ///
     /// This is used to create the next request.
     fn get_next_peer(&self) -> Option<PeerId> {
@@ -707,18 +762,52 @@ impl<T: BeaconChainTypes> SyncingChain<T> {
         let mut peers = self.peer_pool.iter().collect::<Vec<_>>();
         peers.shuffle(&mut rng);
         for peer in peers {
-            if self.pending_batches.peer_is_idle(peer) {
+            if self
+                .pending_batches
+                .peer_has_capacity(peer, Self::blocks_per_batch())
+            {
                 return Some(peer.clone());
             }
         }
         None
     }
 
+    /// Returns the peer a failed batch should be requested from next.
+    ///
+    /// Peers that have not yet sent this batch and have spare capacity are preferred, followed by
+    /// any other peer. The current peer is only returned if it is the only peer in the pool.
+    fn get_retry_peer(&self, batch: &Batch<T::EthSpec>) -> PeerId {
+        let mut rng = rand::thread_rng();
+        let mut peers = self
+            .peer_pool
+            .iter()
+            .filter(|peer| **peer != batch.current_peer)
+            .collect::<Vec<_>>();
+        peers.shuffle(&mut rng);
+
+        peers
+            .iter()
+            .find(|peer| {
+                **peer != &batch.original_peer
+                    && self
+                        .pending_batches
+                        .peer_has_capacity(peer, Self::blocks_per_batch())
+            })
+            .or_else(|| peers.first())
+            .map(|peer| (*peer).clone())
+            .unwrap_or_else(|| batch.current_peer.clone())
+    }
+
+    /// The maximum number of blocks requested in a single batch.
+    fn blocks_per_batch() -> u64 {
+        T::EthSpec::slots_per_epoch() * EPOCHS_PER_BATCH
+    }
+
     /// Returns the next required batch from the chain if it exists. If there are no more batches
     /// required, `None` is returned.
     fn get_next_batch(&mut self, peer_id: PeerId) -> Option<Batch<T::EthSpec>> {
         let slots_per_epoch = T::EthSpec::slots_per_epoch();
-        let blocks_per_batch = slots_per_epoch * EPOCHS_PER_BATCH;
+        let blocks_per_batch = Self::blocks_per_batch();
 
         // only request batches up to the buffer size limit
         if self
@@ -787,3 +876,95 @@ impl<T: BeaconChainTypes> SyncingChain<T> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use beacon_chain::test_utils::{BeaconChainHarness, HarnessType};
+    use sloggers::{null::NullLoggerBuilder, Build};
+    use types::{test_utils::generate_deterministic_keypairs, MinimalEthSpec};
+
+    type E = MinimalEthSpec;
+
+    fn get_logger() -> slog::Logger {
+        NullLoggerBuilder.build().expect("should build logger")
+    }
+
+    fn get_beacon_chain() -> Arc<BeaconChain<HarnessType<E>>> {
+        Arc::new(BeaconChainHarness::new(MinimalEthSpec, generate_deterministic_keypairs(8)).chain)
+    }
+
+    fn build_chain(
+        beacon_chain: Arc<BeaconChain<HarnessType<E>>>,
+        peer_id: &PeerId,
+    ) -> SyncingChain<HarnessType<E>> {
+        let (sync_send, _) = mpsc::unbounded_channel();
+        SyncingChain::new(
+            1,
+            Epoch::new(0),
+            Slot::new(1024),
+            Hash256::repeat_byte(1),
+            peer_id.clone(),
+            sync_send,
+            beacon_chain,
+            get_logger(),
+        )
+    }
+
+    #[test]
+    fn test_persisted_progress() {
+        let peer_id = PeerId::random();
+        let mut chain = build_chain(get_beacon_chain(), &peer_id);
+        chain.start_epoch = Epoch::new(2);
+        chain.to_be_processed_id = BatchId(3);
+        chain.to_be_downloaded_id = BatchId(6);
+        chain.processed_root = Hash256::repeat_byte(7);
+
+        let progress = chain.persisted_progress();
+        assert_eq!(progress.target_head_root, chain.target_head_root);
+        assert_eq!(progress.target_head_slot, chain.target_head_slot);
+        assert_eq!(progress.start_epoch, Epoch::new(2));
+        assert_eq!(progress.to_be_processed_id, 3);
+        assert_eq!(progress.processed_root, Hash256::repeat_byte(7));
+    }
+
+    #[test]
+    fn test_restore_progress() {
+        let beacon_chain = get_beacon_chain();
+        let peer_id = PeerId::random();
+        let mut chain = build_chain(beacon_chain.clone(), &peer_id);
+        chain.start_epoch = Epoch::new(2);
+        chain.to_be_processed_id = BatchId(3);
+        chain.to_be_downloaded_id = BatchId(6);
+        chain.processed_root = beacon_chain.genesis_block_root;
+        let progress = chain.persisted_progress();
+
+        let mut restored_chain = build_chain(beacon_chain, &peer_id);
+        assert!(restored_chain.restore_progress(progress));
+        assert_eq!(restored_chain.start_epoch, Epoch::new(2));
+        assert_eq!(*restored_chain.to_be_processed_id, 3);
+        // the downloaded batches that were not processed are requested again
+        assert_eq!(*restored_chain.to_be_downloaded_id, 3);
+        assert_eq!(
+            restored_chain.current_processed_slot(),
+            chain.current_processed_slot()
+        );
+    }
+
+    #[test]
+    fn test_restore_progress_ignores_unknown_processed_root() {
+        let beacon_chain = get_beacon_chain();
+        let peer_id = PeerId::random();
+        let mut chain = build_chain(beacon_chain.clone(), &peer_id);
+        chain.start_epoch = Epoch::new(2);
+        chain.to_be_processed_id = BatchId(3);
+        chain.processed_root = Hash256::repeat_byte(42);
+        let progress = chain.persisted_progress();
+
+        let mut restored_chain = build_chain(beacon_chain, &peer_id);
+        assert!(!restored_chain.restore_progress(progress));
+        assert_eq!(restored_chain.start_epoch, Epoch::new(0));
+        assert_eq!(*restored_chain.to_be_processed_id, 1);
+        assert_eq!(*restored_chain.to_be_downloaded_id, 1);
+    }
+}
@@ -4,7 +4,7 @@
 //! with this struct to to simplify the logic of the other layers of sync.
 
 use super::chain::{ChainSyncingState, SyncingChain};
-use crate::sync::manager::SyncMessage;
+use crate::sync::manager::{register_sync_state_change, SyncMessage};
 use crate::sync::network_context::SyncNetworkContext;
 use crate::sync::PeerSyncInfo;
 use beacon_chain::{BeaconChain, BeaconChainTypes};
@@ -127,6 +127,7 @@ impl<T: BeaconChainTypes> ChainCollection<T> {
             let mut peer_state = self.network_globals.sync_state.write();
             if new_state != *peer_state {
                 info!(self.log, "Sync state updated"; "old_state" => format!("{}",peer_state), "new_state" => format!("{}",new_state));
+                register_sync_state_change(&self.beacon_chain, &peer_state, &new_state);
             }
             *peer_state = new_state;
         } else {
@@ -136,6 +137,7 @@ impl<T: BeaconChainTypes> ChainCollection<T> {
             if *node_sync_state != new_state {
                 // we are updating the state, inform the user
                 info!(self.log, "Sync state updated"; "old_state" => format!("{}",node_sync_state), "new_state" => format!("{}",new_state));
+                register_sync_state_change(&self.beacon_chain, &node_sync_state, &new_state);
             }
             *node_sync_state = new_state;
         }
@@ -487,6 +489,12 @@ impl<T: BeaconChainTypes> ChainCollection<T> {
         self.update_finalized(network);
     }
 
+    /// Returns the finalized chain that is currently syncing, if any.
+    pub fn finalized_syncing_chain(&self) -> Option<&SyncingChain<T>> {
+        self.finalized_syncing_index()
+            .map(|index| &self.finalized_chains[index])
+    }
+
     /// Returns the index of finalized chain that is currently syncing. Returns `None` if no
     /// finalized chain is currently syncing.
     fn finalized_syncing_index(&self) -> Option<usize> {
@@ -43,6 +43,9 @@ use super::chain::{ChainId, ProcessingResult};
 use super::chain_collection::{ChainCollection, RangeSyncState};
 use super::sync_type::RangeSyncType;
 use super::BatchId;
+use crate::persisted_sync::{
+    clear_sync_progress, load_sync_progress, persist_sync_progress, PersistedSyncProgress,
+};
 use crate::sync::block_processor::BatchProcessResult;
 use crate::sync::manager::SyncMessage;
 use crate::sync::network_context::SyncNetworkContext;
@@ -50,7 +53,7 @@ use crate::sync::PeerSyncInfo;
 use beacon_chain::{BeaconChain, BeaconChainTypes};
 use eth2_libp2p::rpc::RequestId;
 use eth2_libp2p::{NetworkGlobals, PeerId};
-use slog::{debug, error, trace};
+use slog::{debug, error, info, trace};
 use std::collections::HashSet;
 use std::sync::Arc;
 use tokio::sync::mpsc;
@@ -69,6 +72,9 @@ pub struct RangeSync<T: BeaconChainTypes> {
     /// finalized chain(s) complete, these peer's get STATUS'ed to update their head slot before
     /// the head chains are formed and downloaded.
     awaiting_head_peers: HashSet<PeerId>,
+    /// The progress of the finalized chain that was syncing before a restart. It is restored into
+    /// the first finalized chain that reaches its target.
+    persisted_progress: Option<PersistedSyncProgress>,
     /// The sync manager channel, allowing the batch processor thread to callback the sync task
     /// once complete.
     sync_send: mpsc::UnboundedSender<SyncMessage<T::EthSpec>>,
@@ -83,10 +89,19 @@ impl<T: BeaconChainTypes> RangeSync<T> {
         sync_send: mpsc::UnboundedSender<SyncMessage<T::EthSpec>>,
         log: slog::Logger,
     ) -> Self {
+        let persisted_progress =
+            load_sync_progress::<T::Store, T::EthSpec>(beacon_chain.store.clone());
+        if let Some(progress) = &persisted_progress {
+            info!(log, "Loaded persisted sync progress";
+                "target_slot" => progress.target_head_slot,
+                "next_batch" => progress.to_be_processed_id);
+        }
+
         RangeSync {
             beacon_chain: beacon_chain.clone(),
             chains: ChainCollection::new(beacon_chain, network_globals, log.clone()),
             awaiting_head_peers: HashSet::new(),
+            persisted_progress,
             sync_send,
             log,
         }
@@ -180,9 +195,26 @@ impl<T: BeaconChainTypes> RangeSync<T> {
                         local_info.finalized_epoch,
                         remote_info.finalized_root,
                         remote_finalized_slot,
-                        peer_id,
+                        peer_id.clone(),
                         self.sync_send.clone(),
                     );
+
+                    // resume the progress made before a restart if this chain reaches its target
+                    if self.persisted_progress.as_ref().map_or(false, |progress| {
+                        progress.target_head_slot < remote_finalized_slot
+                            || progress.target_head_root == remote_info.finalized_root
+                    }) {
+                        if let (Some(progress), Some(chain)) = (
+                            self.persisted_progress.take(),
+                            self.chains.get_finalized_mut(
+                                remote_info.finalized_root,
+                                remote_finalized_slot,
+                            ),
+                        ) {
+                            chain.restore_progress(progress);
+                        }
+                    }
+
                     self.chains.update_finalized(network);
                     // update the global sync state
                     self.chains.update_sync_state();
@@ -278,6 +310,7 @@ impl<T: BeaconChainTypes> RangeSync<T> {
     ) {
         // build an option for passing the downloaded_blocks to each chain
         let mut downloaded_blocks = Some(downloaded_blocks);
+        let previous_progress = self.finalized_progress();
 
         match self.chains.finalized_request(|chain| {
             chain.on_batch_process_result(
@@ -345,6 +378,51 @@ impl<T: BeaconChainTypes> RangeSync<T> {
                 }
             }
         }
+
+        // only write to disk once the finalized chain has processed a batch
+        if self.finalized_progress() != previous_progress {
+            self.checkpoint_progress();
+        }
+    }
+
+    /// Returns the progress of the syncing finalized chain, if any.
+    fn finalized_progress(&self) -> Option<PersistedSyncProgress> {
+        self.chains
+            .finalized_syncing_chain()
+            .map(|chain| chain.persisted_progress())
+    }
+
+    /// Checkpoints the progress of the syncing finalized chain, so that it can be resumed after a
+    /// restart.
+    ///
+    /// Progress loaded from disk that has not been resumed yet is kept, unless its target has
+    /// been reached. Returns the persisted progress, if any.
+    fn persist_progress(&self) -> Result<Option<PersistedSyncProgress>, store::Error> {
+        let progress = self.finalized_progress().or_else(|| {
+            self.persisted_progress
+                .as_ref()
+                .filter(|progress| {
+                    !self
+                        .beacon_chain
+                        .fork_choice
+                        .contains_block(&progress.target_head_root)
+                })
+                .cloned()
+        });
+
+        let store = self.beacon_chain.store.clone();
+        match &progress {
+            Some(progress) => persist_sync_progress::<T::Store, T::EthSpec>(store, progress)?,
+            None => clear_sync_progress::<T::Store, T::EthSpec>(store)?,
+        }
+        Ok(progress)
+    }
+
+    /// Persists the progress of range sync, logging any failure.
+    fn checkpoint_progress(&self) {
+        if let Err(e) = self.persist_progress() {
+            error!(self.log, "Failed to persist sync progress"; "error" => format!("{:?}", e));
+        }
     }
 
     /// A peer has disconnected. This removes the peer from any ongoing chains and mappings. A
@@ -380,6 +458,7 @@ impl<T: BeaconChainTypes> RangeSync<T> {
                             return Some(ProcessingResult::RemoveChain);
                         }
                     }
+                    chain.pending_batches.remove_peer(peer_id);
                     // peer removed from chain, no batch failed
                     Some(ProcessingResult::KeepChain)
                 } else {
@@ -417,3 +496,23 @@ impl<T: BeaconChainTypes> RangeSync<T> {
         }
     }
 }
+
+impl<T: BeaconChainTypes> Drop for RangeSync<T> {
+    /// Checkpoints the progress of range sync one last time before shutting down.
+    fn drop(&mut self) {
+        match self.persist_progress() {
+            Ok(Some(progress)) => info!(
+                self.log,
+                "Saved sync progress";
+                "target_slot" => progress.target_head_slot,
+                "next_batch" => progress.to_be_processed_id
+            ),
+            Ok(None) => {}
+            Err(e) => error!(
+                self.log,
+                "Failed to persist sync progress";
+                "error" => format!("{:?}", e)
+            ),
+        }
+    }
+}
@@ -0,0 +1,266 @@
+use crate::config::Config;
+use crate::error::ApiError;
+use eth2_libp2p::IpCidr;
+use hyper::{header, Body, Method, Request};
+use std::fs;
+use std::net::IpAddr;
+use std::path::Path;
+
+/// The prefix of the value of the `Authorization` header.
+const BEARER_PREFIX: &str = "Bearer ";
+
+/// The access a request requires.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ApiScope {
+    /// Requests which only read the state of the node.
+    ReadOnly,
+    /// Requests which are used by validators to obtain duties and publish messages.
+    Validator,
+}
+
+impl ApiScope {
+    /// Returns the scope required by the request.
+    ///
+    /// All `/validator` endpoints and all `POST` requests require the `Validator` scope.
+    pub fn of_request(method: &Method, path: &str) -> Self {
+        if *method == Method::POST || path == "/validator" || path.starts_with("/validator/") {
+            ApiScope::Validator
+        } else {
+            ApiScope::ReadOnly
+        }
+    }
+}
+
+/// Restricts access to the REST API by source address and bearer token.
+#[derive(Debug, Clone, Default)]
+pub struct ApiAuth {
+    /// The token that grants the `ReadOnly` scope.
+    read_only_token: Option<String>,
+    /// The token that grants both the `ReadOnly` and `Validator` scopes.
+    validator_token: Option<String>,
+    /// The IP ranges that are allowed to connect. All addresses are allowed if this is empty.
+    allowed_ips: Vec<IpCidr>,
+}
+
+impl ApiAuth {
+    pub fn new(
+        read_only_token: Option<String>,
+        validator_token: Option<String>,
+        allowed_ips: Vec<IpCidr>,
+    ) -> Self {
+        Self {
+            read_only_token,
+            validator_token,
+            allowed_ips,
+        }
+    }
+
+    /// Reads the tokens from the files given in `config`.
+    pub fn from_config(config: &Config) -> Result<Self, String> {
+        let read_token = |path: &Option<_>| -> Result<Option<String>, String> {
+            path.as_ref().map(|path| read_token_file(path)).transpose()
+        };
+
+        Ok(Self::new(
+            read_token(&config.read_only_token_file)?,
+            read_token(&config.validator_token_file)?,
+            config.allowed_ips.clone(),
+        ))
+    }
+
+    /// Returns true if requests must present a bearer token.
+    pub fn requires_token(&self) -> bool {
+        self.read_only_token.is_some() || self.validator_token.is_some()
+    }
+
+    /// Returns an error if the request from `remote_ip` is not allowed.
+    pub fn authorize(&self, req: &Request<Body>, remote_ip: IpAddr) -> Result<(), ApiError> {
+        let remote_ip = unmap_ipv4(remote_ip);
+        if !self.allowed_ips.is_empty()
+            && !self
+                .allowed_ips
+                .iter()
+                .any(|range| range.contains(&remote_ip))
+        {
+            return Err(ApiError::Forbidden(format!(
+                "Address {} is not allowed to access the API",
+                remote_ip
+            )));
+        }
+
+        if !self.requires_token() {
+            return Ok(());
+        }
+
+        let token = req
+            .headers()
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .filter(|value| value.starts_with(BEARER_PREFIX))
+            .map(|value| &value[BEARER_PREFIX.len()..])
+            .ok_or_else(|| {
+                ApiError::Unauthorized("A bearer token is required to access the API".to_string())
+            })?;
+
+        let is_token = |expected: &Option<String>| {
+            expected
+                .as_ref()
+                .map_or(false, |expected| constant_time_eq(expected, token))
+        };
+
+        let authorized = match ApiScope::of_request(req.method(), req.uri().path()) {
+            ApiScope::ReadOnly => {
+                is_token(&self.read_only_token) || is_token(&self.validator_token)
+            }
+            ApiScope::Validator => is_token(&self.validator_token),
+        };
+
+        if authorized {
+            Ok(())
+        } else if is_token(&self.read_only_token) {
+            Err(ApiError::Forbidden(
+                "The token does not grant access to the validator endpoints".to_string(),
+            ))
+        } else {
+            Err(ApiError::Unauthorized("Invalid bearer token".to_string()))
+        }
+    }
+}
+
+/// Returns the IPv4 address of an IPv4-mapped IPv6 address (`::ffff:a.b.c.d`), as received by a
+/// server listening on an IPv6 socket. Other addresses are returned unchanged.
+fn unmap_ipv4(ip: IpAddr) -> IpAddr {
+    match ip {
+        IpAddr::V6(v6) if v6.segments()[..6] == [0, 0, 0, 0, 0, 0xffff] => {
+            v6.to_ipv4().map(IpAddr::V4).unwrap_or(ip)
+        }
+        ip => ip,
+    }
+}
+
+/// Reads a token from the first line of a file.
+fn read_token_file(path: &Path) -> Result<String, String> {
+    let contents = fs::read_to_string(path)
+        .map_err(|e| format!("Unable to read API token file {:?}: {:?}", path, e))?;
+    let token = contents.lines().next().unwrap_or("").trim().to_string();
+
+    if token.is_empty() {
+        Err(format!("API token file {:?} is empty", path))
+    } else {
+        Ok(token)
+    }
+}
+
+/// Compares the strings in a time that only depends on their lengths.
+fn constant_time_eq(a: &str, b: &str) -> bool {
+    a.len() == b.len()
+        && a.bytes()
+            .zip(b.bytes())
+            .fold(0, |acc, (a, b)| acc | (a ^ b))
+            == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(method: Method, path: &str, token: Option<&str>) -> Request<Body> {
+        let mut builder = Request::builder();
+        builder.method(method).uri(path);
+        if let Some(token) = token {
+            builder.header(header::AUTHORIZATION, format!("Bearer {}", token));
+        }
+        builder.body(Body::empty()).unwrap()
+    }
+
+    fn localhost() -> IpAddr {
+        "127.0.0.1".parse().unwrap()
+    }
+
+    #[test]
+    fn open_api_allows_all_requests() {
+        let auth = ApiAuth::default();
+        assert_eq!(
+            auth.authorize(
+                &request(Method::POST, "/validator/block", None),
+                localhost()
+            ),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn tokens_are_scoped() {
+        let auth = ApiAuth::new(Some("read".into()), Some("validator".into()), vec![]);
+        let head = |token| request(Method::GET, "/beacon/head", token);
+        let publish = |token| request(Method::POST, "/validator/block", token);
+
+        assert!(auth.authorize(&head(Some("read")), localhost()).is_ok());
+        assert!(auth
+            .authorize(&head(Some("validator")), localhost())
+            .is_ok());
+        assert!(auth
+            .authorize(&publish(Some("validator")), localhost())
+            .is_ok());
+
+        match auth.authorize(&publish(Some("read")), localhost()) {
+            Err(ApiError::Forbidden(_)) => {}
+            other => panic!("expected forbidden, got {:?}", other),
+        }
+        match auth.authorize(&head(None), localhost()) {
+            Err(ApiError::Unauthorized(_)) => {}
+            other => panic!("expected unauthorized, got {:?}", other),
+        }
+        match auth.authorize(&head(Some("reads")), localhost()) {
+            Err(ApiError::Unauthorized(_)) => {}
+            other => panic!("expected unauthorized, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn requests_are_restricted_by_source_address() {
+        let auth = ApiAuth::new(None, None, vec!["10.0.0.0/8".parse().unwrap()]);
+        let req = request(Method::GET, "/beacon/head", None);
+
+        assert!(auth.authorize(&req, "10.1.2.3".parse().unwrap()).is_ok());
+        match auth.authorize(&req, localhost()) {
+            Err(ApiError::Forbidden(_)) => {}
+            other => panic!("expected forbidden, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn ipv4_mapped_addresses_match_ipv4_ranges() {
+        let auth = ApiAuth::new(None, None, vec!["10.0.0.0/8".parse().unwrap()]);
+        let req = request(Method::GET, "/beacon/head", None);
+
+        assert!(auth
+            .authorize(&req, "::ffff:10.1.2.3".parse().unwrap())
+            .is_ok());
+        match auth.authorize(&req, "::ffff:127.0.0.1".parse().unwrap()) {
+            Err(ApiError::Forbidden(_)) => {}
+            other => panic!("expected forbidden, got {:?}", other),
+        }
+        // IPv4-compatible addresses are not IPv4 addresses
+        match auth.authorize(&req, "::10.1.2.3".parse().unwrap()) {
+            Err(ApiError::Forbidden(_)) => {}
+            other => panic!("expected forbidden, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn validator_scope_covers_publishing() {
+        assert_eq!(
+            ApiScope::of_request(&Method::GET, "/validator/duties"),
+            ApiScope::Validator
+        );
+        assert_eq!(
+            ApiScope::of_request(&Method::POST, "/beacon/validators"),
+            ApiScope::Validator
+        );
+        assert_eq!(
+            ApiScope::of_request(&Method::GET, "/advanced/operation_pool"),
+            ApiScope::ReadOnly
+        );
+    }
+}
@@ -1,21 +1,29 @@
 use crate::helpers::*;
 use crate::response_builder::ResponseBuilder;
 use crate::validator::get_state_for_epoch;
-use crate::{ApiError, ApiResult, BoxFut, UrlQuery};
-use beacon_chain::{BeaconChain, BeaconChainTypes, StateSkipConfig};
+use crate::{ApiError, ApiResult, BoxFut, NetworkChannel, UrlQuery};
+use beacon_chain::{BeaconChain, BeaconChainTypes, OperationProcessingOutcome, StateSkipConfig};
 use futures::{Future, Stream};
 use hyper::{Body, Request};
 use rest_types::{
-    BlockResponse, CanonicalHeadResponse, Committee, HeadBeaconBlock, StateResponse,
-    ValidatorRequest, ValidatorResponse,
+    BlockResponse, CanonicalHeadResponse, Committee, EpochRewards, FinalityCheckpointsResponse,
+    HeadBeaconBlock, StateId, StateResponse, ValidatorBalance, ValidatorRequest, ValidatorResponse,
+    ValidatorRewardsResponse,
+};
+use state_processing::per_epoch_processing::{
+    get_reward_breakdowns, get_slashing_penalties, process_justification_and_finalization,
+    ValidatorStatuses,
 };
 use std::sync::Arc;
 use store::Store;
 use types::{
-    AttesterSlashing, BeaconState, EthSpec, Hash256, ProposerSlashing, PublicKeyBytes,
-    RelativeEpoch, Slot,
+    AttesterSlashing, BeaconState, Epoch, EthSpec, Hash256, ProposerSlashing, PublicKeyBytes,
+    RelativeEpoch, SignedVoluntaryExit, Slot,
 };
 
+/// The maximum number of epochs in a single `get_validator_rewards` request.
+const MAX_REWARD_HISTORY_EPOCHS: u64 = 64;
+
 /// HTTP handler to return a `BeaconBlock` at a given `root` or `slot`.
 pub fn get_head<T: BeaconChainTypes>(
     req: Request<Body>,
@@ -67,15 +75,18 @@ pub fn get_heads<T: BeaconChainTypes>(
     ResponseBuilder::new(&req)?.body(&heads)
 }
 
-/// HTTP handler to return a `BeaconBlock` at a given `root` or `slot`.
+/// HTTP handler to return a `BeaconBlock` at a given `block_id`, `root` or `slot`.
 pub fn get_block<T: BeaconChainTypes>(
     req: Request<Body>,
     beacon_chain: Arc<BeaconChain<T>>,
 ) -> ApiResult {
-    let query_params = ["root", "slot"];
+    let query_params = ["block_id", "root", "slot"];
     let (key, value) = UrlQuery::from_request(&req)?.first_of(&query_params)?;
 
     let block_root = match (key.as_ref(), value) {
+        ("block_id", value) => {
+            block_root_by_id(&beacon_chain, value.parse().map_err(ApiError::BadRequest)?)?
+        }
         ("slot", value) => {
             let target = parse_slot(&value)?;
 
@@ -148,14 +159,9 @@ pub fn get_validators<T: BeaconChainTypes>(
         .map(|validator_pubkey_str| parse_pubkey_bytes(validator_pubkey_str))
         .collect::<Result<Vec<_>, _>>()?;
 
-    let state_root_opt = if let Some((_key, value)) = query.first_of_opt(&["state_root"]) {
-        Some(parse_root(&value)?)
-    } else {
-        None
-    };
+    let state_id_opt = state_id_from_query(query)?;
 
-    let validators =
-        validator_responses_by_pubkey(beacon_chain, state_root_opt, validator_pubkeys)?;
+    let validators = validator_responses_by_pubkey(beacon_chain, state_id_opt, validator_pubkeys)?;
 
     ResponseBuilder::new(&req)?.body(&validators)
 }
@@ -167,13 +173,7 @@ pub fn get_all_validators<T: BeaconChainTypes>(
 ) -> ApiResult {
     let query = UrlQuery::from_request(&req)?;
 
-    let state_root_opt = if let Some((_key, value)) = query.first_of_opt(&["state_root"]) {
-        Some(parse_root(&value)?)
-    } else {
-        None
-    };
-
-    let mut state = get_state_from_root_opt(&beacon_chain, state_root_opt)?;
+    let mut state = get_state_from_id_opt(&beacon_chain, state_id_from_query(query)?)?;
     state.update_pubkey_cache()?;
 
     let validators = state
@@ -192,13 +192,7 @@ pub fn get_active_validators<T: BeaconChainTypes>(
 ) -> ApiResult {
     let query = UrlQuery::from_request(&req)?;
 
-    let state_root_opt = if let Some((_key, value)) = query.first_of_opt(&["state_root"]) {
-        Some(parse_root(&value)?)
-    } else {
-        None
-    };
-
-    let mut state = get_state_from_root_opt(&beacon_chain, state_root_opt)?;
+    let mut state = get_state_from_id_opt(&beacon_chain, state_id_from_query(query)?)?;
     state.update_pubkey_cache()?;
 
     let validators = state
@@ -237,7 +231,7 @@ pub fn post_validators<T: BeaconChainTypes>(
         .and_then(|bulk_request| {
             validator_responses_by_pubkey(
                 beacon_chain,
-                bulk_request.state_root,
+                bulk_request.state_root.map(StateId::Root),
                 bulk_request.pubkeys,
             )
         })
@@ -246,35 +240,194 @@ pub fn post_validators<T: BeaconChainTypes>(
     Box::new(future)
 }
 
-/// Returns either the state given by `state_root_opt`, or the canonical head state if it is
+/// HTTP handler to return the balance, effective balance and rewards and penalties by source of
+/// the validators at the given `validator_indices` and `validator_pubkeys`, for each epoch from
+/// `start_epoch` to `end_epoch` (inclusive).
+///
+/// The rewards and penalties are those of the transition at the end of each epoch, so
+/// `end_epoch` must be prior to the current epoch. `end_epoch` defaults to `start_epoch`.
+pub fn get_validator_rewards<T: BeaconChainTypes>(
+    req: Request<Body>,
+    beacon_chain: Arc<BeaconChain<T>>,
+) -> ApiResult {
+    let query = UrlQuery::from_request(&req)?;
+    let spec = &beacon_chain.spec;
+    let slots_per_epoch = T::EthSpec::slots_per_epoch();
+
+    let start_epoch = query
+        .first_of(&["start_epoch"])
+        .and_then(|(_key, value)| parse_epoch(&value))?;
+    let end_epoch = match query.first_of_opt(&["end_epoch"]) {
+        Some((_key, value)) => parse_epoch(&value)?,
+        None => start_epoch,
+    };
+    let current_epoch = beacon_chain.epoch()?;
+
+    if end_epoch < start_epoch {
+        return Err(ApiError::BadRequest(format!(
+            "end_epoch {} is prior to start_epoch {}",
+            end_epoch, start_epoch
+        )));
+    }
+    if end_epoch >= current_epoch {
+        return Err(ApiError::BadRequest(format!(
+            "Rewards for epoch {} are not applied until the end of the epoch, the current epoch \
+             is {}",
+            end_epoch, current_epoch
+        )));
+    }
+    if (end_epoch - start_epoch).as_u64() >= MAX_REWARD_HISTORY_EPOCHS {
+        return Err(ApiError::BadRequest(format!(
+            "At most {} epochs may be requested at once",
+            MAX_REWARD_HISTORY_EPOCHS
+        )));
+    }
+
+    let mut head_state = beacon_chain.head()?.beacon_state;
+    let mut responses = validator_indices_from_query(query, &mut head_state)?
+        .ok_or_else(|| {
+            ApiError::BadRequest(
+                "URL query must contain validator_indices or validator_pubkeys".to_string(),
+            )
+        })?
+        .into_iter()
+        .filter_map(|validator_index| {
+            Some(ValidatorRewardsResponse {
+                validator_index,
+                pubkey: head_state.validators.get(validator_index)?.pubkey.clone(),
+                epochs: vec![],
+            })
+        })
+        .collect::<Vec<_>>();
+
+    for epoch in (start_epoch.as_u64()..=end_epoch.as_u64()).map(Epoch::new) {
+        // The state at the last slot of the epoch is the input to the epoch transition.
+        let (_root, mut state) = state_at_slot(&beacon_chain, epoch.end_slot(slots_per_epoch))?;
+        state.build_committee_cache(RelativeEpoch::Previous, spec)?;
+        state.build_committee_cache(RelativeEpoch::Current, spec)?;
+
+        let mut validator_statuses = ValidatorStatuses::new(&state, spec)?;
+        validator_statuses.process_attestations(&state, spec)?;
+
+        // The inactivity penalty depends on the finalized checkpoint, which is updated by the
+        // epoch transition before the rewards are applied.
+        process_justification_and_finalization(&mut state, &validator_statuses.total_balances)?;
+
+        let breakdowns = get_reward_breakdowns(&state, &validator_statuses, spec)?;
+        let slashing_penalties = get_slashing_penalties(
+            &state,
+            validator_statuses.total_balances.current_epoch(),
+            spec,
+        )?;
+
+        for response in &mut responses {
+            let index = response.validator_index;
+            let (validator, balance, breakdown) = match (
+                state.validators.get(index),
+                state.balances.get(index),
+                breakdowns.get(index),
+            ) {
+                (Some(validator), Some(balance), Some(breakdown)) => {
+                    (validator, *balance, breakdown)
+                }
+                // The validator had not yet deposited.
+                _ => continue,
+            };
+
+            let slashing_penalty = slashing_penalties
+                .iter()
+                .find(|(slashed_index, _penalty)| *slashed_index == index)
+                .map_or(0, |(_index, penalty)| *penalty);
+
+            response.epochs.push(EpochRewards {
+                epoch,
+                balance,
+                effective_balance: validator.effective_balance,
+                source_reward: breakdown.source.rewards(),
+                source_penalty: breakdown.source.penalties(),
+                target_reward: breakdown.target.rewards(),
+                target_penalty: breakdown.target.penalties(),
+                head_reward: breakdown.head.rewards(),
+                head_penalty: breakdown.head.penalties(),
+                inclusion_delay_reward: breakdown.inclusion_delay.rewards(),
+                inactivity_penalty: breakdown.inactivity.penalties(),
+                proposer_reward: breakdown.proposer.rewards(),
+                slashing_penalty,
+            });
+        }
+    }
+
+    ResponseBuilder::new(&req)?.body(&responses)
+}
+
+/// Returns the indices of the validators given by the `validator_indices` and
+/// `validator_pubkeys` query parameters, or `None` if neither is present.
+///
+/// The pubkeys are resolved with the pubkey cache of `state`. Unknown pubkeys are omitted.
+fn validator_indices_from_query<E: EthSpec>(
+    query: UrlQuery,
+    state: &mut BeaconState<E>,
+) -> Result<Option<Vec<usize>>, ApiError> {
+    let mut validator_indices = query
+        .all_of("validator_indices")?
+        .iter()
+        .map(|index| {
+            index.parse::<usize>().map_err(|e| {
+                ApiError::BadRequest(format!("Unable to parse validator index: {:?}", e))
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let validator_pubkeys = query
+        .all_of("validator_pubkeys")?
+        .iter()
+        .map(|validator_pubkey_str| parse_pubkey_bytes(validator_pubkey_str))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if validator_indices.is_empty() && validator_pubkeys.is_empty() {
+        return Ok(None);
+    }
+
+    state.update_pubkey_cache()?;
+    for pubkey in &validator_pubkeys {
+        if let Some(index) = state
+            .get_validator_index(pubkey)
+            .map_err(|e| ApiError::ServerError(format!("Unable to read pubkey cache: {:?}", e)))?
+        {
+            validator_indices.push(index)
+        }
+    }
+
+    Ok(Some(validator_indices))
+}
+
+/// Returns the state selected by the `state_id` or `state_root` query parameter, if any.
+fn state_id_from_query(query: UrlQuery) -> Result<Option<StateId>, ApiError> {
+    if let Some(state_id) = query.state_id_opt()? {
+        Ok(Some(state_id))
+    } else if let Some((_key, value)) = query.first_of_opt(&["state_root"]) {
+        Ok(Some(StateId::Root(parse_root(&value)?)))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Returns either the state given by `state_id_opt`, or the canonical head state if it is
 /// `None`.
-fn get_state_from_root_opt<T: BeaconChainTypes>(
+fn get_state_from_id_opt<T: BeaconChainTypes>(
     beacon_chain: &BeaconChain<T>,
-    state_root_opt: Option<Hash256>,
+    state_id_opt: Option<StateId>,
 ) -> Result<BeaconState<T::EthSpec>, ApiError> {
-    if let Some(state_root) = state_root_opt {
-        beacon_chain
-            .get_state(&state_root, None)
-            .map_err(|e| {
-                ApiError::ServerError(format!(
-                    "Database error when reading state root {}: {:?}",
-                    state_root, e
-                ))
-            })?
-            .ok_or_else(|| ApiError::NotFound(format!("No state exists with root: {}", state_root)))
-    } else {
-        Ok(beacon_chain.head()?.beacon_state)
-    }
+    state_by_id(beacon_chain, state_id_opt.unwrap_or(StateId::Head)).map(|(_root, state)| state)
 }
 
-/// Maps a vec of `validator_pubkey` to a vec of `ValidatorResponse`, using the state at the given
-/// `state_root`. If `state_root.is_none()`, uses the canonial head state.
+/// Maps a vec of `validator_pubkey` to a vec of `ValidatorResponse`, using the state given by
+/// `state_id_opt`. If `state_id_opt.is_none()`, uses the canonial head state.
 fn validator_responses_by_pubkey<T: BeaconChainTypes>(
     beacon_chain: Arc<BeaconChain<T>>,
-    state_root_opt: Option<Hash256>,
+    state_id_opt: Option<StateId>,
     validator_pubkeys: Vec<PublicKeyBytes>,
 ) -> Result<Vec<ValidatorResponse>, ApiError> {
-    let mut state = get_state_from_root_opt(&beacon_chain, state_root_opt)?;
+    let mut state = get_state_from_id_opt(&beacon_chain, state_id_opt)?;
     state.update_pubkey_cache()?;
 
     validator_pubkeys
@@ -323,19 +476,34 @@ fn validator_response_by_pubkey<E: EthSpec>(
     }
 }
 
-/// HTTP handler
+/// HTTP handler to return the committees at the given `epoch`.
+///
+/// When `state_id` is provided, the committees are read from that state and `epoch` defaults to
+/// its current epoch. Otherwise, `epoch` is required.
 pub fn get_committees<T: BeaconChainTypes>(
     req: Request<Body>,
     beacon_chain: Arc<BeaconChain<T>>,
 ) -> ApiResult {
     let query = UrlQuery::from_request(&req)?;
 
-    let epoch = query.epoch()?;
-
-    let mut state = get_state_for_epoch(&beacon_chain, epoch, StateSkipConfig::WithoutStateRoots)?;
+    let (epoch, mut state) = if let Some(state_id) = query.state_id_opt()? {
+        let (_root, state) = state_by_id(&beacon_chain, state_id)?;
+        let epoch = match query.first_of_opt(&["epoch"]) {
+            Some((_key, value)) => parse_epoch(&value)?,
+            None => state.current_epoch(),
+        };
+        (epoch, state)
+    } else {
+        let epoch = query.epoch()?;
+        let state = get_state_for_epoch(&beacon_chain, epoch, StateSkipConfig::WithoutStateRoots)?;
+        (epoch, state)
+    };
 
     let relative_epoch = RelativeEpoch::from_epoch(state.current_epoch(), epoch).map_err(|e| {
-        ApiError::ServerError(format!("Failed to get state suitable for epoch: {:?}", e))
+        ApiError::BadRequest(format!(
+            "Epoch {} is not within one epoch of the state at slot {}: {:?}",
+            epoch, state.slot, e
+        ))
     })?;
 
     state
@@ -356,7 +524,7 @@ pub fn get_committees<T: BeaconChainTypes>(
     ResponseBuilder::new(&req)?.body(&committees)
 }
 
-/// HTTP handler to return a `BeaconState` at a given `root` or `slot`.
+/// HTTP handler to return a `BeaconState` at a given `state_id`, `root` or `slot`.
 ///
 /// Will not return a state if the request slot is in the future. Will return states higher than
 /// the current head by skipping slots.
@@ -369,7 +537,7 @@ pub fn get_state<T: BeaconChainTypes>(
     let (key, value) = match UrlQuery::from_request(&req) {
         Ok(query) => {
             // We have *some* parameters, just check them.
-            let query_params = ["root", "slot"];
+            let query_params = ["state_id", "root", "slot"];
             query.first_of(&query_params)?
         }
         Err(ApiError::BadRequest(_)) => {
@@ -382,6 +550,9 @@ pub fn get_state<T: BeaconChainTypes>(
     };
 
     let (root, state): (Hash256, BeaconState<T::EthSpec>) = match (key.as_ref(), value) {
+        ("state_id", value) => {
+            state_by_id(&beacon_chain, value.parse().map_err(ApiError::BadRequest)?)?
+        }
         ("slot", value) => state_at_slot(&beacon_chain, parse_slot(&value)?)?,
         ("root", value) => {
             let root = &parse_root(&value)?;
@@ -404,6 +575,97 @@ pub fn get_state<T: BeaconChainTypes>(
     ResponseBuilder::new(&req)?.body(&response)
 }
 
+/// Returns the state given by the `state_id` query parameter, or the canonical head state if it is
+/// not provided.
+fn state_from_request<T: BeaconChainTypes>(
+    req: &Request<Body>,
+    beacon_chain: &BeaconChain<T>,
+) -> Result<BeaconState<T::EthSpec>, ApiError> {
+    let state_id_opt = UrlQuery::from_request(req)?.state_id_opt()?;
+    get_state_from_id_opt(beacon_chain, state_id_opt)
+}
+
+/// HTTP handler to return the `Fork` of the state at the given `state_id`.
+pub fn get_state_fork<T: BeaconChainTypes>(
+    req: Request<Body>,
+    beacon_chain: Arc<BeaconChain<T>>,
+) -> ApiResult {
+    let state = state_from_request(&req, &beacon_chain)?;
+
+    ResponseBuilder::new(&req)?.body(&state.fork)
+}
+
+/// HTTP handler to return the justified and finalized checkpoints of the state at the given
+/// `state_id`.
+pub fn get_state_finality_checkpoints<T: BeaconChainTypes>(
+    req: Request<Body>,
+    beacon_chain: Arc<BeaconChain<T>>,
+) -> ApiResult {
+    let state = state_from_request(&req, &beacon_chain)?;
+
+    let response = FinalityCheckpointsResponse {
+        previous_justified: state.previous_justified_checkpoint.clone(),
+        current_justified: state.current_justified_checkpoint.clone(),
+        finalized: state.finalized_checkpoint.clone(),
+    };
+
+    ResponseBuilder::new(&req)?.body(&response)
+}
+
+/// HTTP handler to return the balances of the validators at the given `validator_indices` and
+/// `validator_pubkeys` in the state at the given `state_id`. Returns all balances if no validators
+/// are given.
+///
+/// Unknown validators are omitted from the response.
+pub fn get_state_validator_balances<T: BeaconChainTypes>(
+    req: Request<Body>,
+    beacon_chain: Arc<BeaconChain<T>>,
+) -> ApiResult {
+    let mut state = state_from_request(&req, &beacon_chain)?;
+
+    let indices = validator_indices_from_query(UrlQuery::from_request(&req)?, &mut state)?
+        .unwrap_or_else(|| (0..state.validators.len()).collect());
+
+    let balances = indices
+        .into_iter()
+        .filter_map(|validator_index| {
+            let validator = state.validators.get(validator_index)?;
+            let balance = state.balances.get(validator_index)?;
+
+            Some(ValidatorBalance {
+                validator_index,
+                pubkey: validator.pubkey.clone(),
+                balance: *balance,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    ResponseBuilder::new(&req)?.body(&balances)
+}
+
+/// HTTP handler to return the RANDAO mix of the given `epoch` in the state at the given
+/// `state_id`. The `epoch` defaults to the current epoch of the state.
+pub fn get_state_randao_mix<T: BeaconChainTypes>(
+    req: Request<Body>,
+    beacon_chain: Arc<BeaconChain<T>>,
+) -> ApiResult {
+    let state = state_from_request(&req, &beacon_chain)?;
+
+    let epoch = match UrlQuery::from_request(&req)?.first_of_opt(&["epoch"]) {
+        Some((_key, value)) => parse_epoch(&value)?,
+        None => state.current_epoch(),
+    };
+
+    let randao_mix = state.get_randao_mix(epoch).map_err(|e| {
+        ApiError::BadRequest(format!(
+            "RANDAO mix for epoch {} is not available in the state at slot {}: {:?}",
+            epoch, state.slot, e
+        ))
+    })?;
+
+    ResponseBuilder::new(&req)?.body(randao_mix)
+}
+
 /// HTTP handler to return a `BeaconState` root at a given `slot`.
 ///
 /// Will not return a state if the request slot is in the future. Will return states higher than
@@ -468,22 +730,15 @@ pub fn proposer_slashing<T: BeaconChainTypes>(
             })
         })
         .and_then(move |proposer_slashing| {
-            let spec = &beacon_chain.spec;
-            let state = &beacon_chain.head().unwrap().beacon_state;
-            if beacon_chain.eth1_chain.is_some() {
-                beacon_chain
-                    .op_pool
-                    .insert_proposer_slashing(proposer_slashing, state, spec)
-                    .map_err(|e| {
-                        ApiError::BadRequest(format!(
-                            "Error while inserting proposer slashing: {:?}",
-                            e
-                        ))
-                    })
-            } else {
-                Err(ApiError::BadRequest(
+            match beacon_chain.process_proposer_slashing(proposer_slashing) {
+                Ok(OperationProcessingOutcome::Processed) => Ok(()),
+                Ok(OperationProcessingOutcome::NotVerified) => Err(ApiError::BadRequest(
                     "Cannot insert proposer slashing on node without Eth1 connection.".to_string(),
-                ))
+                )),
+                Err(e) => Err(ApiError::BadRequest(format!(
+                    "Error while inserting proposer slashing: {:?}",
+                    e
+                ))),
             }
         })
         .and_then(|_| response_builder?.body(&true));
@@ -510,25 +765,59 @@ pub fn attester_slashing<T: BeaconChainTypes>(
             })
         })
         .and_then(move |attester_slashing| {
-            let spec = &beacon_chain.spec;
-            let state = &beacon_chain.head().unwrap().beacon_state;
-            if beacon_chain.eth1_chain.is_some() {
-                beacon_chain
-                    .op_pool
-                    .insert_attester_slashing(attester_slashing, state, spec)
-                    .map_err(|e| {
-                        ApiError::BadRequest(format!(
-                            "Error while inserting attester slashing: {:?}",
-                            e
-                        ))
-                    })
-            } else {
-                Err(ApiError::BadRequest(
+            match beacon_chain.process_attester_slashing(attester_slashing) {
+                Ok(OperationProcessingOutcome::Processed) => Ok(()),
+                Ok(OperationProcessingOutcome::NotVerified) => Err(ApiError::BadRequest(
                     "Cannot insert attester slashing on node without Eth1 connection.".to_string(),
-                ))
+                )),
+                Err(e) => Err(ApiError::BadRequest(format!(
+                    "Error while inserting attester slashing: {:?}",
+                    e
+                ))),
             }
         })
         .and_then(|_| response_builder?.body(&true));
 
     Box::new(future)
 }
+
+/// HTTP handler to accept a `SignedVoluntaryExit`, add it to the operation pool and publish it to
+/// the network.
+pub fn voluntary_exit<T: BeaconChainTypes>(
+    req: Request<Body>,
+    beacon_chain: Arc<BeaconChain<T>>,
+    network_chan: NetworkChannel<T::EthSpec>,
+) -> BoxFut {
+    let response_builder = ResponseBuilder::new(&req);
+
+    let future = req
+        .into_body()
+        .concat2()
+        .map_err(|e| ApiError::ServerError(format!("Unable to get request body: {:?}", e)))
+        .and_then(|chunks| {
+            serde_json::from_slice::<SignedVoluntaryExit>(&chunks).map_err(|e| {
+                ApiError::BadRequest(format!(
+                    "Unable to parse JSON into SignedVoluntaryExit: {:?}",
+                    e
+                ))
+            })
+        })
+        .and_then(
+            move |exit| match beacon_chain.process_voluntary_exit(exit.clone()) {
+                Ok(OperationProcessingOutcome::Processed) => {
+                    publish_voluntary_exit_to_network::<T>(network_chan, exit)
+                }
+                Ok(OperationProcessingOutcome::NotVerified) => Err(ApiError::ServerError(
+                    "Unable to verify voluntary exit; the node may lack an Eth1 connection."
+                        .to_string(),
+                )),
+                Err(e) => Err(ApiError::BadRequest(format!(
+                    "Error while inserting voluntary exit: {:?}",
+                    e
+                ))),
+            },
+        )
+        .and_then(|_| response_builder?.body(&true));
+
+    Box::new(future)
+}
@@ -1,5 +1,7 @@
+use eth2_libp2p::IpCidr;
 use serde::{Deserialize, Serialize};
 use std::net::Ipv4Addr;
+use std::path::PathBuf;
 
 /// Defines the encoding for the API.
 #[derive(Clone, Serialize, Deserialize, Copy)]
@@ -38,6 +40,19 @@ pub struct Config {
     pub listen_address: Ipv4Addr,
     /// The port the REST API HTTP server will listen on.
     pub port: u16,
+    /// The PEM-encoded certificate chain to serve the REST API over TLS with. TLS is enabled if
+    /// both this and `tls_key` are set.
+    pub tls_cert: Option<PathBuf>,
+    /// The PEM-encoded private key of `tls_cert`.
+    pub tls_key: Option<PathBuf>,
+    /// A file containing the bearer token that grants access to the read-only endpoints.
+    pub read_only_token_file: Option<PathBuf>,
+    /// A file containing the bearer token that grants access to all endpoints, including those
+    /// which publish validator messages.
+    pub validator_token_file: Option<PathBuf>,
+    /// The IP ranges that are allowed to connect. All addresses are allowed if this is empty.
+    #[serde(skip)]
+    pub allowed_ips: Vec<IpCidr>,
 }
 
 impl Default for Config {
@@ -46,6 +61,11 @@ impl Default for Config {
             enabled: false,
             listen_address: Ipv4Addr::new(127, 0, 0, 1),
             port: 5052,
+            tls_cert: None,
+            tls_key: None,
+            read_only_token_file: None,
+            validator_token_file: None,
+            allowed_ips: vec![],
         }
     }
 }
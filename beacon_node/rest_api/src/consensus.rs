@@ -4,10 +4,13 @@ use crate::{ApiError, ApiResult, BoxFut, UrlQuery};
 use beacon_chain::{BeaconChain, BeaconChainTypes};
 use futures::{Future, Stream};
 use hyper::{Body, Request};
-use rest_types::{IndividualVotesRequest, IndividualVotesResponse};
+use rest_types::{
+    IndividualVotesRequest, IndividualVotesResponse, LivenessRequest, LivenessResponse,
+};
 use serde::{Deserialize, Serialize};
 use ssz_derive::{Decode, Encode};
 use state_processing::per_epoch_processing::{TotalBalances, ValidatorStatuses};
+use std::collections::HashSet;
 use std::sync::Arc;
 use types::EthSpec;
 
@@ -140,3 +143,94 @@ pub fn post_individual_votes<T: BeaconChainTypes>(
 
     Box::new(future)
 }
+
+/// HTTP handler to return whether each of the given validators was live (i.e., attested or
+/// proposed a block) during some `Epoch`.
+///
+/// An attestation for `epoch` may be included in a block during `epoch` or the following epoch, so
+/// the state at the end of the following epoch is used (or the head state, if that is earlier).
+pub fn post_liveness<T: BeaconChainTypes>(
+    req: Request<Body>,
+    beacon_chain: Arc<BeaconChain<T>>,
+) -> BoxFut {
+    let response_builder = ResponseBuilder::new(&req);
+
+    let future = req
+        .into_body()
+        .concat2()
+        .map_err(|e| ApiError::ServerError(format!("Unable to get request body: {:?}", e)))
+        .and_then(|chunks| {
+            serde_json::from_slice::<LivenessRequest>(&chunks).map_err(|e| {
+                ApiError::BadRequest(format!(
+                    "Unable to parse JSON into LivenessRequest: {:?}",
+                    e
+                ))
+            })
+        })
+        .and_then(move |body| {
+            let epoch = body.epoch;
+            let slots_per_epoch = T::EthSpec::slots_per_epoch();
+            let head_slot = beacon_chain.head_info()?.slot;
+
+            if epoch > head_slot.epoch(slots_per_epoch) {
+                return Err(ApiError::BadRequest(format!(
+                    "Epoch {} is later than the head epoch",
+                    epoch
+                )));
+            }
+
+            // This is the last slot of the epoch following the given epoch.
+            let target_slot = std::cmp::min((epoch + 2).start_slot(slots_per_epoch) - 1, head_slot);
+
+            let (_root, state) = state_at_slot(&beacon_chain, target_slot)?;
+            let spec = &beacon_chain.spec;
+
+            let mut validator_statuses = ValidatorStatuses::new(&state, spec)?;
+            validator_statuses.process_attestations(&state, spec)?;
+
+            let start_slot = epoch.start_slot(slots_per_epoch);
+            let end_slot = epoch.end_slot(slots_per_epoch);
+            let block_roots = beacon_chain
+                .rev_iter_block_roots()?
+                .skip_while(|(_, slot)| *slot > end_slot)
+                .take_while(|(_, slot)| *slot >= start_slot)
+                .map(|(root, _)| root)
+                .collect::<HashSet<_>>();
+
+            let mut proposers = HashSet::new();
+            for root in block_roots {
+                if let Some(block) = beacon_chain.get_block(&root)? {
+                    if block.slot().epoch(slots_per_epoch) == epoch {
+                        proposers.insert(block.message.proposer_index);
+                    }
+                }
+            }
+
+            Ok(body
+                .indices
+                .into_iter()
+                .map(|index| {
+                    let attested =
+                        validator_statuses
+                            .statuses
+                            .get(index as usize)
+                            .map_or(false, |status| {
+                                if state.current_epoch() == epoch {
+                                    status.is_current_epoch_attester
+                                } else {
+                                    status.is_previous_epoch_attester
+                                }
+                            });
+
+                    LivenessResponse {
+                        index,
+                        epoch,
+                        is_live: attested || proposers.contains(&index),
+                    }
+                })
+                .collect::<Vec<_>>())
+        })
+        .and_then(|liveness| response_builder?.body_no_ssz(&liveness));
+
+    Box::new(future)
+}
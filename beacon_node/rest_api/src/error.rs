@@ -9,6 +9,8 @@ pub enum ApiError {
     NotImplemented(String),
     BadRequest(String),
     NotFound(String),
+    Unauthorized(String),
+    Forbidden(String),
     UnsupportedType(String),
     ImATeapot(String),       // Just in case.
     ProcessingError(String), // A 202 error, for when a block/attestation cannot be processed, but still transmitted.
@@ -24,6 +26,8 @@ impl ApiError {
             ApiError::NotImplemented(desc) => (StatusCode::NOT_IMPLEMENTED, desc),
             ApiError::BadRequest(desc) => (StatusCode::BAD_REQUEST, desc),
             ApiError::NotFound(desc) => (StatusCode::NOT_FOUND, desc),
+            ApiError::Unauthorized(desc) => (StatusCode::UNAUTHORIZED, desc),
+            ApiError::Forbidden(desc) => (StatusCode::FORBIDDEN, desc),
             ApiError::UnsupportedType(desc) => (StatusCode::UNSUPPORTED_MEDIA_TYPE, desc),
             ApiError::ImATeapot(desc) => (StatusCode::IM_A_TEAPOT, desc),
             ApiError::ProcessingError(desc) => (StatusCode::ACCEPTED, desc),
@@ -72,6 +76,12 @@ impl From<state_processing::per_slot_processing::Error> for ApiError {
     }
 }
 
+impl From<state_processing::per_epoch_processing::errors::EpochProcessingError> for ApiError {
+    fn from(e: state_processing::per_epoch_processing::errors::EpochProcessingError) -> ApiError {
+        ApiError::ServerError(format!("PerEpochProcessing error: {:?}", e))
+    }
+}
+
 impl From<hyper::error::Error> for ApiError {
     fn from(e: hyper::error::Error) -> ApiError {
         ApiError::ServerError(format!("Networking error: {:?}", e))
@@ -0,0 +1,46 @@
+use crate::url_query::UrlQuery;
+use crate::{ApiError, ApiResult};
+use beacon_chain::events::ServerSentEventHandler;
+use futures::Stream;
+use hyper::{Body, Request, Response, StatusCode};
+use std::collections::HashSet;
+use std::io::{Error, ErrorKind};
+use std::sync::Arc;
+use types::EthSpec;
+
+/// HTTP handler to stream the events of the requested topics as server-sent events.
+///
+/// The `topics` query parameter is a comma-separated list of topics.
+pub fn get_events<T: EthSpec>(
+    req: Request<Body>,
+    event_handler: Option<Arc<ServerSentEventHandler<T>>>,
+) -> ApiResult {
+    let event_handler = event_handler.ok_or_else(|| {
+        ApiError::NotImplemented("The event stream is not enabled on this node".to_string())
+    })?;
+
+    let topics: HashSet<String> = UrlQuery::from_request(&req)?
+        .only_one("topics")?
+        .split(',')
+        .map(|topic| topic.trim().to_string())
+        .filter(|topic| !topic.is_empty())
+        .collect();
+
+    if topics.is_empty() {
+        return Err(ApiError::BadRequest(
+            "At least one event topic is required".to_string(),
+        ));
+    }
+
+    let events = event_handler
+        .subscribe(topics)
+        .map_err(ApiError::BadRequest)?
+        .map_err(|()| Error::new(ErrorKind::Other, "Event stream closed"));
+
+    Response::builder()
+        .status(StatusCode::OK)
+        .header("content-type", "text/event-stream")
+        .header("cache-control", "no-cache")
+        .body(Body::wrap_stream(events))
+        .map_err(|e| ApiError::ServerError(format!("Failed to build response: {:?}", e)))
+}
@@ -6,11 +6,12 @@ use hex;
 use http::header;
 use hyper::{Body, Request};
 use network::NetworkMessage;
+use rest_types::{BlockId, StateId};
 use ssz::Decode;
 use store::{iter::AncestorIter, Store};
 use types::{
     Attestation, BeaconState, ChainSpec, CommitteeIndex, Epoch, EthSpec, Hash256, RelativeEpoch,
-    SignedAggregateAndProof, SignedBeaconBlock, Slot,
+    SignedAggregateAndProof, SignedBeaconBlock, SignedVoluntaryExit, Slot,
 };
 
 /// Parse a slot.
@@ -119,11 +120,96 @@ pub fn block_root_at_slot<T: BeaconChainTypes>(
     beacon_chain: &BeaconChain<T>,
     target: Slot,
 ) -> Result<Option<Hash256>, ApiError> {
-    Ok(beacon_chain
+    if let Some(anchor) = beacon_chain.store.get_anchor_info() {
+        if target < anchor.oldest_block_slot {
+            return Err(ApiError::NotFound(format!(
+                "Blocks prior to slot {} have not yet been backfilled",
+                anchor.oldest_block_slot
+            )));
+        }
+    }
+
+    let root = beacon_chain
         .rev_iter_block_roots()?
         .take_while(|(_root, slot)| *slot >= target)
         .find(|(_root, slot)| *slot == target)
-        .map(|(root, _slot)| root))
+        .map(|(root, _slot)| root);
+
+    match root {
+        Some(root) => Ok(Some(root)),
+        // The states required to iterate back from the head are not available prior to the
+        // anchor of a checkpoint-synced node, so look up backfilled blocks in the freezer.
+        None => Ok(beacon_chain.store.get_frozen_block_root(target)?),
+    }
+}
+
+/// Returns the root of the `SignedBeaconBlock` identified by `block_id`.
+pub fn block_root_by_id<T: BeaconChainTypes>(
+    beacon_chain: &BeaconChain<T>,
+    block_id: BlockId,
+) -> Result<Hash256, ApiError> {
+    // The checkpoints of the genesis epoch have a zero root.
+    let checkpoint_root = |root: Hash256| {
+        if root == Hash256::zero() {
+            beacon_chain.genesis_block_root
+        } else {
+            root
+        }
+    };
+
+    match block_id {
+        BlockId::Head => Ok(beacon_chain.head_info()?.block_root),
+        BlockId::Genesis => Ok(beacon_chain.genesis_block_root),
+        BlockId::Finalized => Ok(checkpoint_root(
+            beacon_chain.head_info()?.finalized_checkpoint.root,
+        )),
+        BlockId::Justified => Ok(checkpoint_root(
+            beacon_chain.head_info()?.current_justified_checkpoint.root,
+        )),
+        BlockId::Slot(slot) => block_root_at_slot(beacon_chain, slot)?.ok_or_else(|| {
+            ApiError::NotFound(format!(
+                "Unable to find SignedBeaconBlock for slot {:?}",
+                slot
+            ))
+        }),
+        BlockId::Root(root) => Ok(root),
+    }
+}
+
+/// Returns the `BeaconState` identified by `state_id` and its root.
+///
+/// States prior to the split slot are reconstructed from the nearest restore point in the
+/// freezer database.
+pub fn state_by_id<T: BeaconChainTypes>(
+    beacon_chain: &BeaconChain<T>,
+    state_id: StateId,
+) -> Result<(Hash256, BeaconState<T::EthSpec>), ApiError> {
+    let slots_per_epoch = T::EthSpec::slots_per_epoch();
+
+    match state_id {
+        StateId::Head => {
+            let head = beacon_chain.head()?;
+            Ok((head.beacon_state_root, head.beacon_state))
+        }
+        StateId::Genesis => state_at_slot(beacon_chain, beacon_chain.spec.genesis_slot),
+        StateId::Finalized => {
+            let epoch = beacon_chain.head_info()?.finalized_checkpoint.epoch;
+            state_at_slot(beacon_chain, epoch.start_slot(slots_per_epoch))
+        }
+        StateId::Justified => {
+            let epoch = beacon_chain.head_info()?.current_justified_checkpoint.epoch;
+            state_at_slot(beacon_chain, epoch.start_slot(slots_per_epoch))
+        }
+        StateId::Slot(slot) => state_at_slot(beacon_chain, slot),
+        StateId::Root(root) => {
+            let state = beacon_chain
+                .store
+                .get_state(&root, None)?
+                .ok_or_else(|| ApiError::NotFound(format!("No state for root: {:?}", root)))?;
+
+            Ok((root, state))
+        }
+    }
 }
 
 /// Returns a `BeaconState` and it's root in the canonical chain of `beacon_chain` at the given
@@ -300,6 +386,24 @@ pub fn publish_aggregate_attestations_to_network<T: BeaconChainTypes + 'static>(
     Ok(())
 }
 
+/// Publishes a voluntary exit to the network.
+pub fn publish_voluntary_exit_to_network<T: BeaconChainTypes + 'static>(
+    mut chan: NetworkChannel<T::EthSpec>,
+    exit: SignedVoluntaryExit,
+) -> Result<(), ApiError> {
+    let messages = vec![PubsubMessage::VoluntaryExit(Box::new(exit))];
+
+    // Publish the exit to the p2p network via gossipsub.
+    if let Err(e) = chan.try_send(NetworkMessage::Publish { messages }) {
+        return Err(ApiError::ServerError(format!(
+            "Unable to send voluntary exit to network: {:?}",
+            e
+        )));
+    }
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
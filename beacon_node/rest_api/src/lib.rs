@@ -5,10 +5,12 @@ extern crate lazy_static;
 extern crate network as client_network;
 
 mod advanced;
+mod auth;
 mod beacon;
 pub mod config;
 mod consensus;
 mod error;
+mod events;
 mod helpers;
 mod lighthouse;
 mod metrics;
@@ -17,26 +19,31 @@ mod node;
 mod response_builder;
 mod router;
 mod spec;
+mod tls;
 mod url_query;
 mod validator;
 
-use beacon_chain::{BeaconChain, BeaconChainTypes};
+use auth::ApiAuth;
+use beacon_chain::{events::ServerSentEventHandler, BeaconChain, BeaconChainTypes};
 use client_network::NetworkMessage;
 pub use config::ApiEncodingFormat;
 use error::{ApiError, ApiResult};
 use eth2_config::Eth2Config;
 use eth2_libp2p::NetworkGlobals;
+use futures::future::{self, Either};
 use hyper::rt::Future;
 use hyper::server::conn::AddrStream;
 use hyper::service::{make_service_fn, service_fn};
 use hyper::{Body, Request, Response, Server};
-use slog::{info, warn};
+use slog::{debug, info, warn};
 use std::net::SocketAddr;
 use std::ops::Deref;
 use std::path::PathBuf;
 use std::sync::Arc;
+use tokio::net::{TcpListener, TcpStream};
 use tokio::runtime::TaskExecutor;
 use tokio::sync::{mpsc, oneshot};
+use tokio_rustls::{server::TlsStream, TlsAcceptor};
 use url_query::UrlQuery;
 
 pub use crate::helpers::parse_pubkey_bytes;
@@ -57,46 +64,55 @@ pub fn start_server<T: BeaconChainTypes>(
     executor: &TaskExecutor,
     beacon_chain: Arc<BeaconChain<T>>,
     network_info: NetworkInfo<T>,
+    event_handler: Option<Arc<ServerSentEventHandler<T::EthSpec>>>,
     db_path: PathBuf,
     freezer_db_path: PathBuf,
     eth2_config: Eth2Config,
     log: slog::Logger,
-) -> Result<(oneshot::Sender<()>, SocketAddr), hyper::Error> {
+) -> Result<(oneshot::Sender<()>, SocketAddr), String> {
     let inner_log = log.clone();
     let eth2_config = Arc::new(eth2_config);
+    let auth = Arc::new(ApiAuth::from_config(config)?);
+    let auth_enabled = auth.requires_token();
 
-    // Define the function that will build the request handler.
-    let make_service = make_service_fn(move |_socket: &AddrStream| {
+    // Define the function that will build the request handler for a connection.
+    let new_service = move |remote_addr: SocketAddr| {
         let beacon_chain = beacon_chain.clone();
         let log = inner_log.clone();
         let eth2_config = eth2_config.clone();
         let network_globals = network_info.network_globals.clone();
         let network_channel = network_info.network_chan.clone();
+        let event_handler = event_handler.clone();
+        let auth = auth.clone();
         let db_path = db_path.clone();
         let freezer_db_path = freezer_db_path.clone();
 
         service_fn(move |req: Request<Body>| {
-            router::route(
+            if let Err(e) = auth.authorize(&req, remote_addr.ip()) {
+                debug!(
+                    log,
+                    "HTTP API request rejected";
+                    "path" => req.uri().path(),
+                    "remote_addr" => format!("{}", remote_addr),
+                    "error" => format!("{:?}", e)
+                );
+                let response: Response<Body> = e.into();
+                return Either::A(future::ok(response));
+            }
+
+            Either::B(router::route(
                 req,
                 beacon_chain.clone(),
                 network_globals.clone(),
                 network_channel.clone(),
+                event_handler.clone(),
                 eth2_config.clone(),
                 log.clone(),
                 db_path.clone(),
                 freezer_db_path.clone(),
-            )
+            ))
         })
-    });
-
-    let bind_addr = (config.listen_address, config.port).into();
-    let server = Server::bind(&bind_addr).serve(make_service);
-
-    // Determine the address the server is actually listening on.
-    //
-    // This may be different to `bind_addr` if bind port was 0 (this allows the OS to choose a free
-    // port).
-    let actual_listen_addr = server.local_addr();
+    };
 
     // Build a channel to kill the HTTP server.
     let (exit_signal, exit) = oneshot::channel();
@@ -105,22 +121,76 @@ pub fn start_server<T: BeaconChainTypes>(
         info!(inner_log, "HTTP service shutdown");
         Ok(())
     });
-    // Configure the `hyper` server to gracefully shutdown when the shutdown channel is triggered.
+
+    let bind_addr = (config.listen_address, config.port).into();
+    let (server, actual_listen_addr): (Box<dyn Future<Item = (), Error = hyper::Error> + Send>, _) =
+        match (&config.tls_cert, &config.tls_key) {
+            (Some(cert), Some(key)) => {
+                let acceptor = TlsAcceptor::from(Arc::new(tls::server_config(cert, key)?));
+                let listener = TcpListener::bind(&bind_addr)
+                    .map_err(|e| format!("Unable to bind HTTP API to {}: {:?}", bind_addr, e))?;
+                let actual_listen_addr = listener
+                    .local_addr()
+                    .map_err(|e| format!("Unable to read HTTP API listen address: {:?}", e))?;
+
+                let server = Server::builder(tls::incoming(listener, acceptor)).serve(
+                    make_service_fn(move |socket: &TlsStream<TcpStream>| {
+                        let remote_addr = socket
+                            .get_ref()
+                            .0
+                            .peer_addr()
+                            .unwrap_or_else(|_| ([0, 0, 0, 0], 0).into());
+                        new_service(remote_addr)
+                    }),
+                );
+
+                // Configure the `hyper` server to gracefully shutdown when the shutdown channel is
+                // triggered.
+                (
+                    Box::new(server.with_graceful_shutdown(server_exit)),
+                    actual_listen_addr,
+                )
+            }
+            (None, None) => {
+                let server =
+                    Server::bind(&bind_addr).serve(make_service_fn(move |socket: &AddrStream| {
+                        new_service(socket.remote_addr())
+                    }));
+
+                // Determine the address the server is actually listening on.
+                //
+                // This may be different to `bind_addr` if bind port was 0 (this allows the OS to
+                // choose a free port).
+                let actual_listen_addr = server.local_addr();
+
+                (
+                    Box::new(server.with_graceful_shutdown(server_exit)),
+                    actual_listen_addr,
+                )
+            }
+            _ => {
+                return Err(
+                    "Both a TLS certificate and key are required to serve the HTTP API over TLS"
+                        .to_string(),
+                )
+            }
+        };
+
     let inner_log = log.clone();
-    let server_future = server
-        .with_graceful_shutdown(server_exit)
-        .map_err(move |e| {
-            warn!(
-            inner_log,
-            "HTTP server failed to start, Unable to bind"; "address" => format!("{:?}", e)
-            )
-        });
+    let server_future = server.map_err(move |e| {
+        warn!(
+        inner_log,
+        "HTTP server failed to start, Unable to bind"; "address" => format!("{:?}", e)
+        )
+    });
 
     info!(
         log,
         "HTTP API started";
         "address" => format!("{}", actual_listen_addr.ip()),
         "port" => actual_listen_addr.port(),
+        "tls" => config.tls_cert.is_some(),
+        "auth" => auth_enabled,
     );
 
     executor.spawn(server_future);
@@ -1,8 +1,8 @@
 use crate::{
-    advanced, beacon, consensus, error::ApiError, helpers, lighthouse, metrics, network, node,
-    spec, validator, BoxFut, NetworkChannel,
+    advanced, beacon, consensus, error::ApiError, events, helpers, lighthouse, metrics, network,
+    node, spec, validator, BoxFut, NetworkChannel,
 };
-use beacon_chain::{BeaconChain, BeaconChainTypes};
+use beacon_chain::{events::ServerSentEventHandler, BeaconChain, BeaconChainTypes};
 use eth2_config::Eth2Config;
 use eth2_libp2p::NetworkGlobals;
 use futures::{Future, IntoFuture};
@@ -28,6 +28,7 @@ pub fn route<T: BeaconChainTypes>(
     beacon_chain: Arc<BeaconChain<T>>,
     network_globals: Arc<NetworkGlobals<T::EthSpec>>,
     network_channel: NetworkChannel<T::EthSpec>,
+    event_handler: Option<Arc<ServerSentEventHandler<T::EthSpec>>>,
     eth2_config: Arc<Eth2Config>,
     local_log: slog::Logger,
     db_path: PathBuf,
@@ -58,6 +59,11 @@ pub fn route<T: BeaconChainTypes>(
                 ))
             }
 
+            // Methods for Events
+            (&Method::GET, "/events") => {
+                into_boxfut(events::get_events::<T::EthSpec>(req, event_handler))
+            }
+
             // Methods for Network
             (&Method::GET, "/network/enr") => {
                 into_boxfut(network::get_enr::<T>(req, network_globals))
@@ -105,6 +111,9 @@ pub fn route<T: BeaconChainTypes>(
             (&Method::GET, "/beacon/validators/all") => {
                 into_boxfut(beacon::get_all_validators::<T>(req, beacon_chain))
             }
+            (&Method::GET, "/beacon/validators/rewards") => {
+                into_boxfut(beacon::get_validator_rewards::<T>(req, beacon_chain))
+            }
             (&Method::GET, "/beacon/validators/active") => {
                 into_boxfut(beacon::get_active_validators::<T>(req, beacon_chain))
             }
@@ -114,6 +123,18 @@ pub fn route<T: BeaconChainTypes>(
             (&Method::GET, "/beacon/state_root") => {
                 into_boxfut(beacon::get_state_root::<T>(req, beacon_chain))
             }
+            (&Method::GET, "/beacon/state/fork") => {
+                into_boxfut(beacon::get_state_fork::<T>(req, beacon_chain))
+            }
+            (&Method::GET, "/beacon/state/finality_checkpoints") => into_boxfut(
+                beacon::get_state_finality_checkpoints::<T>(req, beacon_chain),
+            ),
+            (&Method::GET, "/beacon/state/validator_balances") => {
+                into_boxfut(beacon::get_state_validator_balances::<T>(req, beacon_chain))
+            }
+            (&Method::GET, "/beacon/state/randao_mix") => {
+                into_boxfut(beacon::get_state_randao_mix::<T>(req, beacon_chain))
+            }
             (&Method::GET, "/beacon/state/genesis") => {
                 into_boxfut(beacon::get_genesis_state::<T>(req, beacon_chain))
             }
@@ -126,6 +147,9 @@ pub fn route<T: BeaconChainTypes>(
             (&Method::POST, "/beacon/attester_slashing") => {
                 into_boxfut(beacon::attester_slashing::<T>(req, beacon_chain))
             }
+            (&Method::POST, "/beacon/voluntary_exit") => {
+                beacon::voluntary_exit::<T>(req, beacon_chain, network_channel)
+            }
 
             // Methods for Validator
             (&Method::POST, "/validator/duties") => {
@@ -183,6 +207,9 @@ pub fn route<T: BeaconChainTypes>(
             (&Method::POST, "/consensus/individual_votes") => {
                 consensus::post_individual_votes::<T>(req, beacon_chain)
             }
+            (&Method::POST, "/consensus/liveness") => {
+                consensus::post_liveness::<T>(req, beacon_chain)
+            }
 
             // Methods for bootstrap and checking configuration
             (&Method::GET, "/spec") => into_boxfut(spec::get_spec::<T>(req, beacon_chain)),
@@ -0,0 +1,68 @@
+use futures::{Future, Stream};
+use std::fs::File;
+use std::io::{self, BufReader};
+use std::path::Path;
+use std::time::Duration;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::timer::Timeout;
+use tokio_rustls::rustls::internal::pemfile::{certs, pkcs8_private_keys, rsa_private_keys};
+use tokio_rustls::rustls::{NoClientAuth, ServerConfig};
+use tokio_rustls::{server::TlsStream, TlsAcceptor};
+
+/// The maximum number of TLS handshakes performed at once.
+const MAX_CONCURRENT_HANDSHAKES: usize = 64;
+
+/// The time a connection is given to complete the TLS handshake before it is dropped, so that
+/// idle connections cannot hold up the accepting of others.
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Builds a TLS configuration that serves the PEM-encoded certificate chain and private key.
+pub fn server_config(cert_path: &Path, key_path: &Path) -> Result<ServerConfig, String> {
+    let open = |path: &Path| {
+        File::open(path)
+            .map(BufReader::new)
+            .map_err(|e| format!("Unable to open {:?}: {:?}", path, e))
+    };
+
+    let cert_chain = certs(&mut open(cert_path)?)
+        .map_err(|()| format!("Unable to parse TLS certificates in {:?}", cert_path))?;
+    if cert_chain.is_empty() {
+        return Err(format!("No TLS certificates found in {:?}", cert_path));
+    }
+
+    // accept both PKCS #8 and RSA private keys
+    let mut keys = pkcs8_private_keys(&mut open(key_path)?)
+        .map_err(|()| format!("Unable to parse TLS private key in {:?}", key_path))?;
+    if keys.is_empty() {
+        keys = rsa_private_keys(&mut open(key_path)?)
+            .map_err(|()| format!("Unable to parse TLS private key in {:?}", key_path))?;
+    }
+    let key = keys
+        .into_iter()
+        .next()
+        .ok_or_else(|| format!("No TLS private key found in {:?}", key_path))?;
+
+    let mut config = ServerConfig::new(NoClientAuth::new());
+    config
+        .set_single_cert(cert_chain, key)
+        .map_err(|e| format!("Invalid TLS certificate or key: {:?}", e))?;
+    Ok(config)
+}
+
+/// Returns the TLS streams of the connections accepted by `listener`. Connections that fail to
+/// be accepted or to complete the TLS handshake within `HANDSHAKE_TIMEOUT` are dropped.
+pub fn incoming(
+    listener: TcpListener,
+    acceptor: TlsAcceptor,
+) -> impl Stream<Item = TlsStream<TcpStream>, Error = io::Error> + Send {
+    listener
+        .incoming()
+        .then(|stream| Ok::<_, io::Error>(stream.ok()))
+        .filter_map(|stream| stream)
+        .map(move |stream| {
+            Timeout::new(acceptor.accept(stream), HANDSHAKE_TIMEOUT)
+                .then(|stream| Ok::<_, io::Error>(stream.ok()))
+        })
+        .buffer_unordered(MAX_CONCURRENT_HANDSHAKES)
+        .filter_map(|stream| stream)
+}
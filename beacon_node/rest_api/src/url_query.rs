@@ -1,6 +1,7 @@
 use crate::helpers::{parse_committee_index, parse_epoch, parse_hex_ssz_bytes, parse_slot};
 use crate::ApiError;
 use hyper::Request;
+use rest_types::{BlockId, StateId};
 use types::{AttestationData, CommitteeIndex, Epoch, Signature, Slot};
 
 /// Provides handy functions for parsing the query parameters of a URL.
@@ -97,6 +98,20 @@ impl<'a> UrlQuery<'a> {
             .and_then(|(_key, value)| parse_slot(&value))
     }
 
+    /// Returns the value of the first occurrence of the `state_id` key, if any.
+    pub fn state_id_opt(self) -> Result<Option<StateId>, ApiError> {
+        self.first_of_opt(&["state_id"])
+            .map(|(_key, value)| value.parse().map_err(ApiError::BadRequest))
+            .transpose()
+    }
+
+    /// Returns the value of the first occurrence of the `block_id` key, if any.
+    pub fn block_id_opt(self) -> Result<Option<BlockId>, ApiError> {
+        self.first_of_opt(&["block_id"])
+            .map(|(_key, value)| value.parse().map_err(ApiError::BadRequest))
+            .transpose()
+    }
+
     /// Returns the value of the first occurrence of the `committee_index` key.
     pub fn committee_index(self) -> Result<CommitteeIndex, ApiError> {
         self.first_of(&["committee_index"])
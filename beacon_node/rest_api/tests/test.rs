@@ -6,9 +6,11 @@ use node_test_rig::{
     testing_client_config, ClientConfig, ClientGenesis, LocalBeaconNode,
 };
 use remote_beacon_node::{
-    Committee, HeadBeaconBlock, PersistedOperationPool, PublishStatus, ValidatorResponse,
+    BlockId, Committee, EpochRewards, HeadBeaconBlock, PersistedOperationPool, PublishStatus,
+    StateId, ValidatorResponse,
 };
 use rest_types::ValidatorDutyBytes;
+use std::borrow::Cow;
 use std::convert::TryInto;
 use std::sync::Arc;
 use types::{
@@ -18,7 +20,7 @@ use types::{
     },
     BeaconBlock, BeaconState, ChainSpec, Domain, Epoch, EthSpec, MinimalEthSpec, PublicKey,
     RelativeEpoch, Signature, SignedAggregateAndProof, SignedBeaconBlock, SignedRoot, Slot,
-    Validator,
+    Validator, VoluntaryExit,
 };
 use version;
 
@@ -77,6 +79,68 @@ fn sign_block<T: BeaconChainTypes>(
     block.sign(&keypair.sk, &fork, genesis_validators_root, spec)
 }
 
+/// Produces and imports a block at every slot from `1` to `end_slot` (inclusive). Every block
+/// includes the attestations of the `attesters` to the block prior to it.
+fn extend_chain_with_attestations<T: BeaconChainTypes>(
+    beacon_chain: Arc<BeaconChain<T>>,
+    end_slot: Slot,
+    attesters: &[usize],
+    spec: &ChainSpec,
+) {
+    for slot in (1..=end_slot.as_u64()).map(Slot::new) {
+        let randao_reveal = get_randao_reveal(beacon_chain.clone(), slot, spec);
+        let (block, mut state) = beacon_chain
+            .produce_block(randao_reveal, slot)
+            .expect("should produce block");
+        let block_root = beacon_chain
+            .process_block(sign_block(beacon_chain.clone(), block, spec))
+            .expect("should import block");
+        beacon_chain.fork_choice().expect("should run fork choice");
+
+        state
+            .build_committee_cache(RelativeEpoch::Current, spec)
+            .expect("should build committee cache");
+        let committees = state
+            .get_beacon_committees_at_slot(slot)
+            .expect("should get committees");
+
+        for committee in committees {
+            for (position, validator_index) in committee.committee.iter().enumerate() {
+                if !attesters.contains(validator_index) {
+                    continue;
+                }
+
+                let mut attestation = beacon_chain
+                    .produce_attestation_for_block(
+                        slot,
+                        committee.index,
+                        block_root,
+                        Cow::Borrowed(&state),
+                    )
+                    .expect("should produce attestation");
+                attestation
+                    .sign(
+                        &generate_deterministic_keypair(*validator_index).sk,
+                        position,
+                        &state.fork,
+                        state.genesis_validators_root,
+                        spec,
+                    )
+                    .expect("should sign attestation");
+                beacon_chain
+                    .op_pool
+                    .insert_attestation(
+                        attestation,
+                        &state.fork,
+                        state.genesis_validators_root,
+                        spec,
+                    )
+                    .expect("should insert attestation");
+            }
+        }
+    }
+}
+
 #[test]
 fn validator_produce_attestation() {
     let mut env = build_env();
@@ -609,6 +673,115 @@ fn beacon_block() {
     );
 }
 
+#[test]
+fn beacon_state_and_block_ids() {
+    let mut env = build_env();
+
+    let node = build_node(&mut env, testing_client_config());
+    let remote_node = node.remote_node().expect("should produce remote node");
+    let chain = node
+        .client
+        .beacon_chain()
+        .expect("client should have beacon chain");
+    let head = chain.head().expect("should get head");
+
+    let (state_by_slot, root) = env
+        .runtime()
+        .block_on(remote_node.http.beacon().get_state_by_slot(Slot::new(0)))
+        .expect("should fetch state by slot");
+    for state_id in &[
+        StateId::Genesis,
+        StateId::Slot(Slot::new(0)),
+        StateId::Root(root),
+    ] {
+        let (state, state_root) = env
+            .runtime()
+            .block_on(remote_node.http.beacon().get_state_by_id(*state_id))
+            .expect("should fetch state by id");
+        assert_eq!(state_root, root, "state root should match for {}", state_id);
+        assert_eq!(state, state_by_slot, "state should match for {}", state_id);
+    }
+
+    let (_state, head_state_root) = env
+        .runtime()
+        .block_on(remote_node.http.beacon().get_state_by_id(StateId::Head))
+        .expect("should fetch head state");
+    assert_eq!(head_state_root, head.beacon_state_root);
+
+    let (_block, genesis_block_root) = env
+        .runtime()
+        .block_on(remote_node.http.beacon().get_block_by_id(BlockId::Genesis))
+        .expect("should fetch genesis block");
+    assert_eq!(genesis_block_root, chain.genesis_block_root);
+
+    let (_block, head_block_root) = env
+        .runtime()
+        .block_on(remote_node.http.beacon().get_block_by_id(BlockId::Head))
+        .expect("should fetch head block");
+    assert_eq!(head_block_root, head.beacon_block_root);
+
+    let fork = env
+        .runtime()
+        .block_on(remote_node.http.beacon().get_state_fork(StateId::Head))
+        .expect("should fetch fork");
+    assert_eq!(fork, head.beacon_state.fork);
+
+    let checkpoints = env
+        .runtime()
+        .block_on(
+            remote_node
+                .http
+                .beacon()
+                .get_state_finality_checkpoints(StateId::Head),
+        )
+        .expect("should fetch finality checkpoints");
+    assert_eq!(
+        checkpoints.finalized,
+        head.beacon_state.finalized_checkpoint
+    );
+    assert_eq!(
+        checkpoints.current_justified,
+        head.beacon_state.current_justified_checkpoint
+    );
+
+    let balances = env
+        .runtime()
+        .block_on(
+            remote_node
+                .http
+                .beacon()
+                .get_state_validator_balances(StateId::Genesis, vec![0, 1]),
+        )
+        .expect("should fetch balances");
+    assert_eq!(balances.len(), 2);
+    for balance in balances {
+        assert_eq!(
+            balance.balance,
+            state_by_slot.balances[balance.validator_index]
+        );
+        assert_eq!(
+            balance.pubkey,
+            state_by_slot.validators[balance.validator_index].pubkey
+        );
+    }
+
+    let randao_mix = env
+        .runtime()
+        .block_on(
+            remote_node
+                .http
+                .beacon()
+                .get_state_randao_mix(StateId::Genesis, None),
+        )
+        .expect("should fetch randao mix");
+    assert_eq!(
+        randao_mix,
+        *state_by_slot
+            .get_randao_mix(state_by_slot.current_epoch())
+            .expect("should get randao mix")
+    );
+}
+
 #[test]
 fn genesis_time() {
     let mut env = build_env();
@@ -1122,3 +1295,318 @@ fn attester_slashing() {
     assert_eq!(attester_slashings.len(), 1);
     assert_eq!(attester_slashing, attester_slashings[0]);
 }
+
+#[test]
+fn voluntary_exit() {
+    let mut env = build_env();
+
+    let node = build_node(&mut env, testing_client_config());
+    let remote_node = node.remote_node().expect("should produce remote node");
+    let chain = node
+        .client
+        .beacon_chain()
+        .expect("node should have beacon chain");
+
+    let state = chain
+        .head()
+        .expect("should have retrieved state")
+        .beacon_state;
+
+    let spec = &chain.spec;
+
+    let validator_index = 0;
+    let keypair = generate_deterministic_keypair(validator_index);
+    let exit = VoluntaryExit {
+        epoch: state.current_epoch(),
+        validator_index: validator_index as u64,
+    }
+    .sign(
+        &keypair.sk,
+        &state.fork,
+        state.genesis_validators_root,
+        spec,
+    );
+
+    // The validator has not been active for `persistent_committee_period` epochs, so the exit
+    // must be rejected.
+    let result = env
+        .runtime()
+        .block_on(remote_node.http.beacon().voluntary_exit(exit));
+    assert!(result.is_err());
+
+    assert_eq!(chain.op_pool.get_voluntary_exits(&state, spec).len(), 0);
+}
+
+#[test]
+fn voluntary_exit_success() {
+    let mut env = build_env();
+    // Allow validators to exit immediately after genesis.
+    env.eth2_config.spec.persistent_committee_period = 0;
+
+    let node = build_node(&mut env, testing_client_config());
+    let remote_node = node.remote_node().expect("should produce remote node");
+    let chain = node
+        .client
+        .beacon_chain()
+        .expect("node should have beacon chain");
+
+    let state = chain
+        .head()
+        .expect("should have retrieved state")
+        .beacon_state;
+
+    let spec = &chain.spec;
+
+    let validator_index = 0;
+    let keypair = generate_deterministic_keypair(validator_index);
+    let exit = VoluntaryExit {
+        epoch: state.current_epoch(),
+        validator_index: validator_index as u64,
+    }
+    .sign(
+        &keypair.sk,
+        &state.fork,
+        state.genesis_validators_root,
+        spec,
+    );
+
+    let result = env
+        .runtime()
+        .block_on(remote_node.http.beacon().voluntary_exit(exit.clone()))
+        .expect("should fetch from http api");
+    assert!(result);
+
+    assert_eq!(chain.op_pool.get_voluntary_exits(&state, spec), vec![exit]);
+}
+
+#[test]
+fn liveness() {
+    let mut env = build_env();
+
+    let node = build_node(&mut env, testing_client_config());
+    let remote_node = node.remote_node().expect("should produce remote node");
+    let chain = node
+        .client
+        .beacon_chain()
+        .expect("node should have beacon chain");
+
+    let head_epoch = chain
+        .head_info()
+        .expect("should get head info")
+        .slot
+        .epoch(E::slots_per_epoch());
+    let indices = vec![0, 1, 2];
+
+    // No blocks or attestations have been applied to the genesis state, so no validator has been
+    // live.
+    let liveness = env
+        .runtime()
+        .block_on(
+            remote_node
+                .http
+                .consensus()
+                .get_liveness(head_epoch, indices.clone()),
+        )
+        .expect("should fetch liveness from http api");
+
+    assert_eq!(liveness.len(), indices.len());
+    for (response, index) in liveness.iter().zip(indices.iter()) {
+        assert_eq!(response.index, *index);
+        assert_eq!(response.epoch, head_epoch);
+        assert!(!response.is_live);
+    }
+
+    // Liveness cannot be known for epochs later than the head.
+    let result = env.runtime().block_on(
+        remote_node
+            .http
+            .consensus()
+            .get_liveness(head_epoch + 1, indices),
+    );
+    assert!(result.is_err());
+}
+
+/// Returns the balance of a validator after the rewards and penalties of `rewards` are applied.
+fn balance_after(rewards: &EpochRewards) -> u64 {
+    let total_rewards = rewards.source_reward
+        + rewards.target_reward
+        + rewards.head_reward
+        + rewards.inclusion_delay_reward
+        + rewards.proposer_reward;
+    let total_penalties = rewards.source_penalty
+        + rewards.target_penalty
+        + rewards.head_penalty
+        + rewards.inactivity_penalty
+        + rewards.slashing_penalty;
+
+    rewards.balance + total_rewards - total_penalties
+}
+
+#[test]
+fn get_validator_rewards() {
+    let mut env = build_env();
+
+    let spec = &E::default_spec();
+
+    let mut config = testing_client_config();
+    config.genesis = ClientGenesis::Interop {
+        validator_count: 8,
+        genesis_time: 13_371_337,
+    };
+
+    let node = build_node(&mut env, config);
+    let remote_node = node.remote_node().expect("should produce remote node");
+
+    let beacon_chain = node
+        .client
+        .beacon_chain()
+        .expect("client should have beacon chain");
+
+    let start_epoch = Epoch::new(1);
+    let end_epoch = Epoch::new(3);
+    let indices = (0..8).collect::<Vec<_>>();
+    extend_chain_with_attestations(
+        beacon_chain.clone(),
+        end_epoch.end_slot(E::slots_per_epoch()),
+        &indices,
+        spec,
+    );
+
+    let responses = env
+        .runtime()
+        .block_on(remote_node.http.beacon().get_validator_rewards(
+            indices.clone(),
+            start_epoch,
+            end_epoch,
+        ))
+        .expect("should fetch rewards from http api");
+
+    assert_eq!(responses.len(), indices.len());
+    for (response, index) in responses.iter().zip(indices.iter()) {
+        assert_eq!(response.validator_index, *index);
+        assert_eq!(
+            response
+                .epochs
+                .iter()
+                .map(|rewards| rewards.epoch)
+                .collect::<Vec<_>>(),
+            vec![Epoch::new(1), Epoch::new(2), Epoch::new(3)]
+        );
+
+        // Every validator attested throughout epoch 1, which is rewarded at the end of epoch 2.
+        let epoch_2 = &response.epochs[1];
+        assert!(epoch_2.source_reward > 0, "validator {}", index);
+        assert!(epoch_2.target_reward > 0, "validator {}", index);
+        assert!(epoch_2.head_reward > 0, "validator {}", index);
+        assert_eq!(epoch_2.target_penalty, 0, "validator {}", index);
+
+        // The balance of each epoch is the balance of the previous one after its rewards and
+        // penalties.
+        for pair in response.epochs.windows(2) {
+            assert_eq!(
+                balance_after(&pair[0]),
+                pair[1].balance,
+                "validator {} in epoch {}",
+                index,
+                pair[0].epoch
+            );
+        }
+    }
+
+    // Rewards are not known until the end of the epoch.
+    let current_epoch = beacon_chain.epoch().expect("should get current epoch");
+    let result = env
+        .runtime()
+        .block_on(remote_node.http.beacon().get_validator_rewards(
+            indices,
+            current_epoch,
+            current_epoch,
+        ));
+    assert!(result.is_err());
+}
+
+#[test]
+fn get_validator_rewards_without_finality() {
+    let mut env = build_env();
+
+    let spec = &E::default_spec();
+
+    let mut config = testing_client_config();
+    config.genesis = ClientGenesis::Interop {
+        validator_count: 8,
+        genesis_time: 13_371_337,
+    };
+
+    let node = build_node(&mut env, config);
+    let remote_node = node.remote_node().expect("should produce remote node");
+
+    let beacon_chain = node
+        .client
+        .beacon_chain()
+        .expect("client should have beacon chain");
+
+    // Too few validators attest for the chain to justify or finalize any epoch.
+    let attesters = vec![0, 1];
+    let start_epoch = Epoch::new(5);
+    let end_epoch = Epoch::new(7);
+    extend_chain_with_attestations(
+        beacon_chain.clone(),
+        end_epoch.end_slot(E::slots_per_epoch()),
+        &attesters,
+        spec,
+    );
+    assert_eq!(
+        beacon_chain
+            .head()
+            .expect("should get head")
+            .beacon_state
+            .finalized_checkpoint
+            .epoch,
+        Epoch::new(0),
+        "chain should not finalize"
+    );
+
+    let indices = (0..8).collect::<Vec<_>>();
+    let responses = env
+        .runtime()
+        .block_on(remote_node.http.beacon().get_validator_rewards(
+            indices.clone(),
+            start_epoch,
+            end_epoch,
+        ))
+        .expect("should fetch rewards from http api");
+
+    assert_eq!(responses.len(), indices.len());
+    for response in &responses {
+        let index = response.validator_index;
+        assert_eq!(response.epochs.len(), 3);
+
+        // The inactivity penalty applies once the previous epoch is more than
+        // `min_epochs_to_inactivity_penalty` epochs past the finalized epoch.
+        let (epoch_5, epoch_7) = (&response.epochs[0], &response.epochs[2]);
+        assert_eq!(epoch_5.inactivity_penalty, 0, "validator {}", index);
+        assert!(epoch_7.inactivity_penalty > 0, "validator {}", index);
+
+        // Validators that missed the target are penalized further.
+        let attester_penalty = responses[attesters[0]].epochs[2].inactivity_penalty;
+        if attesters.contains(&index) {
+            assert_eq!(epoch_7.inactivity_penalty, attester_penalty);
+        } else {
+            assert!(
+                epoch_7.inactivity_penalty > attester_penalty,
+                "validator {}",
+                index
+            );
+        }
+
+        for pair in response.epochs.windows(2) {
+            assert_eq!(
+                balance_after(&pair[0]),
+                pair[1].balance,
+                "validator {} in epoch {}",
+                index,
+                pair[0].epoch
+            );
+        }
+    }
+}
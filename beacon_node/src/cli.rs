@@ -60,6 +60,14 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .help("The UDP port that discovery will listen on. Defaults to `port`")
                 .takes_value(true),
         )
+        .arg(
+            Arg::with_name("libp2p-ws-port")
+                .long("libp2p-ws-port")
+                .value_name("PORT")
+                .help("The TCP port to listen on for libp2p WebSocket connections, in addition to \
+                       --port. The libp2p WebSocket transport is disabled if this is not set.")
+                .takes_value(true),
+        )
         .arg(
             Arg::with_name("maxpeers")
                 .long("maxpeers")
@@ -115,6 +123,15 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 This disables this feature, fixing the ENR's IP/PORT to those specified on boot.")
                 .takes_value(true),
         )
+        .arg(
+            Arg::with_name("enable-upnp")
+                .long("enable-upnp")
+                .help("Maps the TCP and discovery UDP ports on the local router with UPnP, and \
+                       advertises the router's external address in the local ENR. The mappings \
+                       are renewed while the node runs and removed on shutdown. If mapping \
+                       fails, it is retried every 30 minutes. NAT-PMP and PCP are not \
+                       supported.")
+        )
         .arg(
             Arg::with_name("libp2p-addresses")
                 .long("libp2p-addresses")
@@ -123,6 +140,31 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                        without an ENR.")
                 .takes_value(true),
         )
+        .arg(
+            Arg::with_name("trusted-peers")
+                .long("trusted-peers")
+                .value_name("MULTIADDR")
+                .help("One or more comma-delimited multiaddrs of trusted peers, each ending in \
+                       /p2p/<peer-id>. Trusted peers are kept connected, are never disconnected \
+                       or banned and do not count towards --maxpeers.")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("deny-peers")
+                .long("deny-peers")
+                .value_name("PEER-ID")
+                .help("One or more comma-delimited peer ids that are refused inbound and \
+                       outbound connections.")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("deny-ips")
+                .long("deny-ips")
+                .value_name("CIDR")
+                .help("One or more comma-delimited IP addresses or CIDR ranges (e.g. \
+                       10.0.0.0/8) that peers are refused inbound and outbound connections from.")
+                .takes_value(true),
+        )
         .arg(
             Arg::with_name("p2p-priv-key")
                 .long("p2p-priv-key")
@@ -164,6 +206,49 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .default_value("5052")
                 .takes_value(true),
         )
+        .arg(
+            Arg::with_name("http-tls-cert")
+                .long("http-tls-cert")
+                .value_name("FILE")
+                .help("Path to a PEM-encoded TLS certificate chain. When provided with \
+                       --http-tls-key, the HTTP API is served over HTTPS.")
+                .requires("http-tls-key")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("http-tls-key")
+                .long("http-tls-key")
+                .value_name("FILE")
+                .help("Path to the PEM-encoded private key of the HTTP API TLS certificate.")
+                .requires("http-tls-cert")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("http-read-token-file")
+                .long("http-read-token-file")
+                .value_name("FILE")
+                .help("Path to a file containing a bearer token that grants read-only access to \
+                       the HTTP API. When any token is set, requests without a valid token are \
+                       rejected.")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("http-validator-token-file")
+                .long("http-validator-token-file")
+                .value_name("FILE")
+                .help("Path to a file containing a bearer token that grants full access to the \
+                       HTTP API, including the validator endpoints. When any token is set, \
+                       requests without a valid token are rejected.")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("http-allow-ips")
+                .long("http-allow-ips")
+                .value_name("CIDR_LIST")
+                .help("One or more comma-delimited IP ranges (e.g. 10.0.0.0/8) allowed to connect \
+                       to the HTTP API. All addresses are allowed by default.")
+                .takes_value(true),
+        )
         /* Websocket related arguments */
         .arg(
             Arg::with_name("ws")
@@ -235,6 +320,47 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .help("Specifies how many states the database should cache in memory [default: 5]")
                 .takes_value(true)
         )
+        /*
+         * Checkpoint sync.
+         */
+        .arg(
+            Arg::with_name("checkpoint-state")
+                .long("checkpoint-state")
+                .value_name("STATE_SSZ")
+                .help("Set a checkpoint state to start syncing from. Must be aligned and match \
+                       --checkpoint-block. Using --checkpoint-sync-url instead is recommended.")
+                .takes_value(true)
+                .requires("checkpoint-block")
+        )
+        .arg(
+            Arg::with_name("checkpoint-block")
+                .long("checkpoint-block")
+                .value_name("BLOCK_SSZ")
+                .help("Set a checkpoint block to start syncing from. Must be aligned and match \
+                       --checkpoint-state. Using --checkpoint-sync-url instead is recommended.")
+                .takes_value(true)
+                .requires("checkpoint-state")
+        )
+        .arg(
+            Arg::with_name("checkpoint-sync-url")
+                .long("checkpoint-sync-url")
+                .value_name("BEACON_NODE")
+                .help("Set the HTTP API address of a TRUSTED beacon node. The node will start \
+                       from its latest finalized state rather than syncing from genesis. Only \
+                       used when the database is empty.")
+                .takes_value(true)
+                .conflicts_with("checkpoint-state")
+        )
+        .arg(
+            Arg::with_name("wss-checkpoint")
+                .long("wss-checkpoint")
+                .value_name("BLOCK_ROOT:EPOCH")
+                .help("Set a weak subjectivity checkpoint, e.g. 0x1234...abcd:1024. The node will \
+                       refuse to sync or finalize any chain which does not include the block at \
+                       the start of the epoch, and will fail to start if the stored chain \
+                       conflicts with it.")
+                .takes_value(true)
+        )
         /*
          * Purge.
          */
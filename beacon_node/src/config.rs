@@ -2,7 +2,7 @@ use beacon_chain::builder::PUBKEY_CACHE_FILENAME;
 use clap::ArgMatches;
 use clap_utils::BAD_TESTNET_DIR_MESSAGE;
 use client::{config::DEFAULT_DATADIR, ClientConfig, ClientGenesis};
-use eth2_libp2p::{Enr, Multiaddr};
+use eth2_libp2p::{peer_id_of_multiaddr, Enr, IpCidr, Multiaddr, PeerId};
 use eth2_testnet_config::Eth2TestnetConfig;
 use slog::{crit, info, Logger};
 use ssz::Encode;
@@ -12,7 +12,7 @@ use std::io::prelude::*;
 use std::net::{IpAddr, Ipv4Addr};
 use std::net::{TcpListener, UdpSocket};
 use std::path::PathBuf;
-use types::{ChainSpec, EthSpec};
+use types::{ChainSpec, Checkpoint, Epoch, EthSpec};
 
 pub const CLIENT_CONFIG_FILENAME: &str = "beacon-node.toml";
 pub const BEACON_NODE_DIR: &str = "beacon";
@@ -123,6 +123,13 @@ pub fn get_config<E: EthSpec>(
         client_config.network.discovery_port = port;
     }
 
+    if let Some(port_str) = cli_args.value_of("libp2p-ws-port") {
+        let port = port_str
+            .parse::<u16>()
+            .map_err(|_| format!("Invalid WebSocket port: {}", port_str))?;
+        client_config.network.websocket_port = Some(port);
+    }
+
     if let Some(boot_enr_str) = cli_args.value_of("boot-nodes") {
         client_config.network.boot_nodes = boot_enr_str
             .split(',')
@@ -141,6 +148,40 @@ pub fn get_config<E: EthSpec>(
             .collect::<Result<Vec<Multiaddr>, _>>()?;
     }
 
+    if let Some(trusted_peers_str) = cli_args.value_of("trusted-peers") {
+        client_config.network.trusted_peers = trusted_peers_str
+            .split(',')
+            .map(|multiaddr| {
+                let multiaddr: Multiaddr = multiaddr
+                    .parse()
+                    .map_err(|_| format!("Invalid Multiaddr: {}", multiaddr))?;
+                if peer_id_of_multiaddr(&multiaddr).is_none() {
+                    return Err(format!("Trusted peer has no /p2p/ peer id: {}", multiaddr));
+                }
+                Ok(multiaddr)
+            })
+            .collect::<Result<Vec<Multiaddr>, _>>()?;
+    }
+
+    if let Some(deny_peers_str) = cli_args.value_of("deny-peers") {
+        client_config.network.denied_peers = deny_peers_str
+            .split(',')
+            .map(|peer_id| {
+                peer_id
+                    .parse()
+                    .map_err(|_| format!("Invalid peer id: {}", peer_id))
+            })
+            .collect::<Result<Vec<PeerId>, _>>()?;
+    }
+
+    if let Some(deny_ips_str) = cli_args.value_of("deny-ips") {
+        client_config.network.denied_ip_ranges = deny_ips_str
+            .split(',')
+            .map(str::trim)
+            .map(|range| range.parse())
+            .collect::<Result<Vec<IpCidr>, _>>()?;
+    }
+
     if let Some(enr_address_str) = cli_args.value_of("enr-address") {
         client_config.network.enr_address = Some(
             enr_address_str
@@ -178,6 +219,10 @@ pub fn get_config<E: EthSpec>(
         client_config.network.enr_udp_port = Some(client_config.network.discovery_port);
     }
 
+    if cli_args.is_present("enable-upnp") {
+        client_config.network.upnp_enabled = true;
+    }
+
     if cli_args.is_present("disable_enr_auto_update") {
         client_config.network.discv5_config.enr_update = false;
     }
@@ -220,6 +265,29 @@ pub fn get_config<E: EthSpec>(
             .map_err(|_| "http-port is not a valid u16.")?;
     }
 
+    if let Some(cert) = cli_args.value_of("http-tls-cert") {
+        client_config.rest_api.tls_cert = Some(PathBuf::from(cert));
+    }
+
+    if let Some(key) = cli_args.value_of("http-tls-key") {
+        client_config.rest_api.tls_key = Some(PathBuf::from(key));
+    }
+
+    if let Some(token_file) = cli_args.value_of("http-read-token-file") {
+        client_config.rest_api.read_only_token_file = Some(PathBuf::from(token_file));
+    }
+
+    if let Some(token_file) = cli_args.value_of("http-validator-token-file") {
+        client_config.rest_api.validator_token_file = Some(PathBuf::from(token_file));
+    }
+
+    if let Some(allow_ips_str) = cli_args.value_of("http-allow-ips") {
+        client_config.rest_api.allowed_ips = allow_ips_str
+            .split(',')
+            .map(|range| range.parse())
+            .collect::<Result<Vec<IpCidr>, _>>()?;
+    }
+
     /*
      * Websocket server
      */
@@ -316,6 +384,12 @@ pub fn get_config<E: EthSpec>(
             unused_port("tcp").map_err(|e| format!("Failed to get port for libp2p: {}", e))?;
         client_config.network.discovery_port =
             unused_port("udp").map_err(|e| format!("Failed to get port for discovery: {}", e))?;
+        if client_config.network.websocket_port.is_some() {
+            client_config.network.websocket_port = Some(
+                unused_port("tcp")
+                    .map_err(|e| format!("Failed to get port for WebSocket: {}", e))?,
+            );
+        }
         client_config.rest_api.port = 0;
         client_config.websocket_server.port = 0;
     }
@@ -349,6 +423,33 @@ pub fn get_config<E: EthSpec>(
         client_config.genesis = ClientGenesis::DepositContract;
     }
 
+    /*
+     * Checkpoint sync.
+     *
+     * Replaces the genesis method, it is only used if there is no existing chain in the database.
+     */
+    if let (Some(state_path), Some(block_path)) = (
+        cli_args.value_of("checkpoint-state"),
+        cli_args.value_of("checkpoint-block"),
+    ) {
+        let read = |path: &str| {
+            fs::read(path).map_err(|e| format!("Unable to read checkpoint file {}: {:?}", path, e))
+        };
+
+        client_config.genesis = ClientGenesis::WeakSubjSszBytes {
+            anchor_state_bytes: read(state_path)?,
+            anchor_block_bytes: read(block_path)?,
+        };
+    } else if let Some(url) = cli_args.value_of("checkpoint-sync-url") {
+        client_config.genesis = ClientGenesis::CheckpointSyncUrl {
+            url: url.to_string(),
+        };
+    }
+
+    if let Some(checkpoint) = cli_args.value_of("wss-checkpoint") {
+        client_config.weak_subjectivity_checkpoint = Some(parse_wss_checkpoint(checkpoint)?);
+    }
+
     if !config_file_existed {
         write_to_file(config_file_path, &client_config)?;
     }
@@ -356,6 +457,35 @@ pub fn get_config<E: EthSpec>(
     Ok(client_config)
 }
 
+/// Parses a weak subjectivity checkpoint in the form `0x<block_root>:<epoch>`.
+fn parse_wss_checkpoint(string: &str) -> Result<Checkpoint, String> {
+    const PREFIX: &str = "0x";
+
+    let parts = string.split(':').collect::<Vec<_>>();
+    if parts.len() != 2 {
+        return Err("Weak subjectivity checkpoint must be in the form block_root:epoch".into());
+    }
+
+    if !parts[0].starts_with(PREFIX) {
+        return Err("Weak subjectivity checkpoint root must have a 0x prefix".into());
+    }
+    let root = parts[0]
+        .trim_start_matches(PREFIX)
+        .parse()
+        .map_err(|e| format!("Unable to parse weak subjectivity checkpoint root: {:?}", e))?;
+    let epoch = parts[1].parse::<u64>().map_err(|e| {
+        format!(
+            "Unable to parse weak subjectivity checkpoint epoch: {:?}",
+            e
+        )
+    })?;
+
+    Ok(Checkpoint {
+        epoch: Epoch::new(epoch),
+        root,
+    })
+}
+
 /// Gets the datadir which should be used.
 pub fn get_data_dir(cli_args: &ArgMatches) -> PathBuf {
     // Read the `--datadir` flag.
@@ -12,7 +12,7 @@ pub use eth2_config::Eth2Config;
 
 use beacon_chain::migrate::{BackgroundMigrator, DiskStore};
 use beacon_chain::{
-    builder::Witness, eth1_chain::CachingEth1Backend, events::WebSocketSender,
+    builder::Witness, eth1_chain::CachingEth1Backend, events::TeeEventHandler,
     slot_clock::SystemTimeSlotClock,
 };
 use clap::ArgMatches;
@@ -31,7 +31,7 @@ pub type ProductionClient<E> = Client<
         SystemTimeSlotClock,
         CachingEth1Backend<E, DiskStore<E>>,
         E,
-        WebSocketSender<E>,
+        TeeEventHandler<E>,
     >,
 >;
 
@@ -120,7 +120,7 @@ impl<E: EthSpec> ProductionBeaconNode<E> {
 
                 let builder = builder
                     .system_time_slot_clock()?
-                    .websocket_event_handler(client_config.websocket_server.clone())?
+                    .tee_event_handler(client_config.websocket_server.clone())?
                     .build_beacon_chain()?
                     .network(&mut client_config.network)?
                     .notifier()?;
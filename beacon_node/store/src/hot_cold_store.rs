@@ -1,5 +1,6 @@
 use crate::chunked_vector::{
-    store_updated_vector, BlockRoots, HistoricalRoots, RandaoMixes, StateRoots,
+    chunk_key, store_updated_vector, BlockRoots, Chunk, Field, HistoricalRoots, RandaoMixes,
+    StateRoots,
 };
 use crate::config::StoreConfig;
 use crate::forwards_iter::HybridForwardsBlockRootsIterator;
@@ -26,6 +27,8 @@ use types::*;
 
 /// 32-byte key for accessing the `split` of the freezer DB.
 pub const SPLIT_DB_KEY: &str = "FREEZERDBSPLITFREEZERDBSPLITFREE";
+/// 32-byte key for accessing the `anchor` of a database started from a checkpoint.
+pub const ANCHOR_INFO_DB_KEY: &str = "FREEZERDBANCHORFREEZERDBANCHORFR";
 
 /// On-disk database that stores finalized states efficiently.
 ///
@@ -37,6 +40,8 @@ pub struct HotColdDB<E: EthSpec> {
     /// States with slots less than `split.slot` are in the cold DB, while states with slots
     /// greater than or equal are in the hot DB.
     split: RwLock<Split>,
+    /// The range of blocks that are missing from a database started from a checkpoint, if any.
+    anchor: RwLock<Option<AnchorInfo>>,
     config: StoreConfig,
     /// Cold database containing compact historical data.
     pub(crate) cold_db: LevelDB<E>,
@@ -59,6 +64,17 @@ pub enum HotColdDBError {
     /// Recoverable error indicating that the database freeze point couldn't be updated
     /// due to the finalized block not lying on an epoch boundary (should be infrequent).
     FreezeSlotUnaligned(Slot),
+    /// The anchor state of a database must lie on an epoch boundary.
+    AnchorStateUnaligned(Slot),
+    /// An anchor state can only be stored in an empty database.
+    AnchorStateAlreadyInitialized(Slot),
+    /// Backfilled blocks can only be stored in a database started from a checkpoint.
+    MissingAnchorInfo,
+    /// Backfilled blocks must be in ascending slot order and older than the oldest stored block.
+    BackfillBlockOutOfOrder {
+        block_slot: Slot,
+        next_block_slot: Slot,
+    },
     FreezeSlotError {
         current_split_slot: Slot,
         proposed_split_slot: Slot,
@@ -278,6 +294,117 @@ impl<E: EthSpec> Store<E> for HotColdDB<E> {
         Ok(())
     }
 
+    fn get_split_slot(&self) -> Slot {
+        HotColdDB::get_split_slot(self)
+    }
+
+    /// Move the split point of an empty database to the anchor `state`.
+    ///
+    /// States prior to the anchor are unknown, so they are treated as if they had been frozen. No
+    /// states are stored in the freezer until the first migration after the anchor.
+    fn init_anchor_state(&self, state_root: &Hash256, state: &BeaconState<E>) -> Result<(), Error> {
+        if state.slot % E::slots_per_epoch() != 0 {
+            return Err(HotColdDBError::AnchorStateUnaligned(state.slot).into());
+        }
+
+        let current_split_slot = self.get_split_slot();
+        if current_split_slot != 0 {
+            return Err(HotColdDBError::AnchorStateAlreadyInitialized(current_split_slot).into());
+        }
+
+        *self.split.write() = Split {
+            slot: state.slot,
+            state_root: *state_root,
+        };
+        self.store_split()?;
+
+        *self.anchor.write() = Some(AnchorInfo {
+            anchor_slot: state.slot,
+            oldest_block_slot: state.latest_block_header.slot,
+            oldest_block_parent: state.latest_block_header.parent_root,
+        });
+        self.store_anchor_info()?;
+
+        self.store_hot_state(state_root, state)
+    }
+
+    fn get_anchor_info(&self) -> Option<AnchorInfo> {
+        *self.anchor.read()
+    }
+
+    /// Store blocks downloaded by backfill sync, recording their roots in the freezer so that
+    /// they can be looked up by slot.
+    fn put_backfilled_blocks(&self, blocks: Vec<SignedBeaconBlock<E>>) -> Result<(), Error> {
+        let anchor = self
+            .get_anchor_info()
+            .ok_or(HotColdDBError::MissingAnchorInfo)?;
+
+        let blocks = blocks
+            .into_iter()
+            .map(|block| (block.canonical_root(), block))
+            .collect::<Vec<_>>();
+
+        let (oldest_block_slot, oldest_block_parent) = match blocks.first() {
+            Some((_, block)) => (block.slot(), block.parent_root()),
+            None => return Ok(()),
+        };
+
+        // Each block is the canonical block for every slot up until the next block.
+        let mut block_roots = vec![];
+        for (i, (block_root, block)) in blocks.iter().enumerate() {
+            let next_block_slot = blocks
+                .get(i + 1)
+                .map_or(anchor.oldest_block_slot, |(_, next)| next.slot());
+
+            if block.slot() >= next_block_slot {
+                return Err(HotColdDBError::BackfillBlockOutOfOrder {
+                    block_slot: block.slot(),
+                    next_block_slot,
+                }
+                .into());
+            }
+
+            let num_slots = (next_block_slot - block.slot()).as_usize();
+            block_roots.extend(std::iter::repeat(*block_root).take(num_slots));
+        }
+
+        for (block_root, block) in &blocks {
+            // Bypass the block cache, historical blocks are unlikely to be read again soon.
+            self.put(block_root, block)?;
+        }
+        self.store_frozen_block_roots(oldest_block_slot, &block_roots)?;
+
+        *self.anchor.write() = Some(AnchorInfo {
+            oldest_block_slot,
+            oldest_block_parent,
+            ..anchor
+        });
+        self.store_anchor_info()
+    }
+
+    fn get_frozen_block_root(&self, slot: Slot) -> Result<Option<Hash256>, Error> {
+        if slot >= self.get_split_slot() {
+            return Ok(None);
+        }
+        if let Some(anchor) = self.get_anchor_info() {
+            if slot < anchor.oldest_block_slot {
+                return Ok(None);
+            }
+        }
+
+        let chunk_size = <BlockRoots as Field<E>>::chunk_size();
+        let vindex = slot.as_usize();
+        let chunk = Chunk::<Hash256>::load(
+            &self.cold_db,
+            DBColumn::BeaconBlockRoots,
+            &chunk_key((vindex / chunk_size) as u64),
+        )?;
+
+        Ok(chunk
+            .and_then(|chunk| chunk.values.get(vindex % chunk_size).copied())
+            .filter(|block_root| !block_root.is_zero()))
+    }
+
     fn forwards_block_roots_iterator(
         store: Arc<Self>,
         start_slot: Slot,
@@ -336,6 +463,7 @@ impl<E: EthSpec> HotColdDB<E> {
 
         let db = HotColdDB {
             split: RwLock::new(Split::default()),
+            anchor: RwLock::new(None),
             cold_db: LevelDB::open(cold_path)?,
             hot_db: LevelDB::open(hot_path)?,
             block_cache: Mutex::new(LruCache::new(config.block_cache_size)),
@@ -350,6 +478,7 @@ impl<E: EthSpec> HotColdDB<E> {
         if let Some(split) = db.load_split()? {
             *db.split.write() = split;
         }
+        *db.anchor.write() = db.load_anchor_info()?;
         Ok(db)
     }
 
@@ -652,6 +781,55 @@ impl<E: EthSpec> HotColdDB<E> {
         Ok(())
     }
 
+    /// Load the anchor info from disk.
+    fn load_anchor_info(&self) -> Result<Option<AnchorInfo>, Error> {
+        let key = Hash256::from_slice(ANCHOR_INFO_DB_KEY.as_bytes());
+        self.hot_db.get(&key)
+    }
+
+    /// Store the anchor info on disk.
+    fn store_anchor_info(&self) -> Result<(), Error> {
+        let key = Hash256::from_slice(ANCHOR_INFO_DB_KEY.as_bytes());
+        if let Some(anchor) = *self.anchor.read() {
+            self.hot_db.put(&key, &anchor)?;
+        }
+        Ok(())
+    }
+
+    /// Write the `block_roots` of consecutive slots starting at `start_slot` into the freezer's
+    /// block roots vector.
+    fn store_frozen_block_roots(
+        &self,
+        start_slot: Slot,
+        block_roots: &[Hash256],
+    ) -> Result<(), Error> {
+        if block_roots.is_empty() {
+            return Ok(());
+        }
+
+        let chunk_size = <BlockRoots as Field<E>>::chunk_size();
+        let start_vindex = start_slot.as_usize();
+        let end_vindex = start_vindex + block_roots.len();
+
+        for chunk_index in start_vindex / chunk_size..=(end_vindex - 1) / chunk_size {
+            let key = chunk_key(chunk_index as u64);
+            let mut chunk =
+                Chunk::load(&self.cold_db, DBColumn::BeaconBlockRoots, &key)?.unwrap_or_default();
+            chunk.values.resize(chunk_size, Hash256::zero());
+
+            for (i, value) in chunk.values.iter_mut().enumerate() {
+                let vindex = chunk_index * chunk_size + i;
+                if vindex >= start_vindex && vindex < end_vindex {
+                    *value = block_roots[vindex - start_vindex];
+                }
+            }
+
+            chunk.store(&self.cold_db, DBColumn::BeaconBlockRoots, &key)?;
+        }
+
+        Ok(())
+    }
+
     /// Load the state root of a restore point.
     fn load_restore_point_hash(&self, restore_point_index: u64) -> Result<Hash256, Error> {
         let key = Self::restore_point_key(restore_point_index);
@@ -749,6 +927,38 @@ impl SimpleStoreItem for Split {
     }
 }
 
+/// Struct for storing the range of blocks missing from a database started from a checkpoint.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Encode, Decode)]
+pub struct AnchorInfo {
+    /// The slot of the state that the database was started from.
+    pub anchor_slot: Slot,
+    /// The slot of the oldest block in the database.
+    pub oldest_block_slot: Slot,
+    /// The parent root of the oldest block in the database, which is the next block to backfill.
+    pub oldest_block_parent: Hash256,
+}
+
+impl AnchorInfo {
+    /// Returns `true` once all blocks back to genesis have been stored.
+    pub fn block_backfill_complete(&self) -> bool {
+        self.oldest_block_parent.is_zero()
+    }
+}
+
+impl SimpleStoreItem for AnchorInfo {
+    fn db_column() -> DBColumn {
+        DBColumn::BeaconMeta
+    }
+
+    fn as_store_bytes(&self) -> Vec<u8> {
+        self.as_ssz_bytes()
+    }
+
+    fn from_store_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        Ok(Self::from_ssz_bytes(bytes)?)
+    }
+}
+
 /// Struct for summarising a state in the hot database.
 ///
 /// Allows full reconstruction by replaying blocks.
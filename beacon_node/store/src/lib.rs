@@ -28,7 +28,7 @@ pub mod iter;
 use std::sync::Arc;
 
 pub use self::config::StoreConfig;
-pub use self::hot_cold_store::{HotColdDB as DiskStore, HotStateSummary};
+pub use self::hot_cold_store::{AnchorInfo, HotColdDB as DiskStore, HotStateSummary};
 pub use self::leveldb_store::LevelDB as SimpleDiskStore;
 pub use self::memory_store::MemoryStore;
 pub use self::partial_beacon_state::PartialBeaconState;
@@ -138,6 +138,47 @@ pub trait Store<E: EthSpec>: Sync + Send + Sized + 'static {
         Ok(())
     }
 
+    /// Returns the slot prior to which states are no longer held in the hot database.
+    ///
+    /// For a chain started from a checkpoint this is never less than the slot of the anchor
+    /// state, since no blocks or states prior to the anchor are known.
+    fn get_split_slot(&self) -> Slot {
+        Slot::new(0)
+    }
+
+    /// Stores the finalized `state` that an empty database will be started from, when that state
+    /// is not the genesis state.
+    fn init_anchor_state(&self, state_root: &Hash256, state: &BeaconState<E>) -> Result<(), Error> {
+        self.put_state(state_root, state)
+    }
+
+    /// Returns the range of blocks missing from a database that was started from a checkpoint.
+    ///
+    /// Returns `None` if the database holds all blocks back to genesis.
+    fn get_anchor_info(&self) -> Option<AnchorInfo> {
+        None
+    }
+
+    /// Stores `blocks` that precede the oldest block in the database, as downloaded by backfill
+    /// sync.
+    ///
+    /// The blocks must be in ascending slot order and must already have been verified to form a
+    /// chain ending at the parent of the current oldest block.
+    fn put_backfilled_blocks(&self, blocks: Vec<SignedBeaconBlock<E>>) -> Result<(), Error> {
+        for block in blocks {
+            self.put_block(&block.canonical_root(), block)?;
+        }
+        Ok(())
+    }
+
+    /// Returns the root of the canonical block at `slot`, from the frozen portion of the database.
+    ///
+    /// Returns `None` if the slot has not been frozen, or if it predates the anchor and has not
+    /// yet been backfilled.
+    fn get_frozen_block_root(&self, _slot: Slot) -> Result<Option<Hash256>, Error> {
+        Ok(None)
+    }
+
     /// Get a forwards (slot-ascending) iterator over the beacon block roots since `start_slot`.
     ///
     /// Will be efficient for frozen portions of the database if using `DiskStore`.
@@ -201,6 +242,10 @@ pub enum DBColumn {
     BeaconHistoricalRoots,
     BeaconRandaoMixes,
     DhtEnrs,
+    /// For the peers known to the network service prior to a restart.
+    Peers,
+    /// For the progress of range sync prior to a restart.
+    SyncProgress,
 }
 
 impl Into<&'static str> for DBColumn {
@@ -221,6 +266,8 @@ impl Into<&'static str> for DBColumn {
             DBColumn::BeaconHistoricalRoots => "bhr",
             DBColumn::BeaconRandaoMixes => "brm",
             DBColumn::DhtEnrs => "dht",
+            DBColumn::Peers => "prs",
+            DBColumn::SyncProgress => "syn",
         }
     }
 }
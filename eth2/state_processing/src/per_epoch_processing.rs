@@ -10,8 +10,10 @@ pub mod registry_updates;
 pub mod tests;
 pub mod validator_statuses;
 
-pub use apply_rewards::process_rewards_and_penalties;
-pub use process_slashings::process_slashings;
+pub use apply_rewards::{
+    get_reward_breakdowns, process_rewards_and_penalties, Delta, RewardBreakdown,
+};
+pub use process_slashings::{get_slashing_penalties, process_slashings};
 pub use registry_updates::process_registry_updates;
 pub use validator_statuses::{TotalBalances, ValidatorStatus, ValidatorStatuses};
 
@@ -6,13 +6,23 @@ use safe_arith::SafeArith;
 use types::*;
 
 /// Use to track the changes to a validators balance.
-#[derive(Default, Clone)]
+#[derive(Debug, Default, Clone, PartialEq)]
 pub struct Delta {
     rewards: u64,
     penalties: u64,
 }
 
 impl Delta {
+    /// The sum of the rewards.
+    pub fn rewards(&self) -> u64 {
+        self.rewards
+    }
+
+    /// The sum of the penalties.
+    pub fn penalties(&self) -> u64 {
+        self.penalties
+    }
+
     /// Reward the validator with the `reward`.
     pub fn reward(&mut self, reward: u64) -> Result<(), Error> {
         self.rewards = self.rewards.safe_add(reward)?;
@@ -26,12 +36,110 @@ impl Delta {
     }
 
     /// Combine two deltas.
-    fn combine(&mut self, other: Delta) -> Result<(), Error> {
+    fn combine(&mut self, other: &Delta) -> Result<(), Error> {
         self.reward(other.rewards)?;
         self.penalize(other.penalties)
     }
 }
 
+/// The changes to a validators balance during `process_rewards_and_penalties`, by source.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct RewardBreakdown {
+    /// Attesting to the expected FFG source.
+    pub source: Delta,
+    /// Attesting to the expected FFG target.
+    pub target: Delta,
+    /// Attesting to the expected head.
+    pub head: Delta,
+    /// The bonus for the speed with which the attestation was included.
+    pub inclusion_delay: Delta,
+    /// The penalty applied while the chain is not finalizing.
+    pub inactivity: Delta,
+    /// Including attestations in a proposed block.
+    pub proposer: Delta,
+}
+
+/// The changes to a validator's balance, recorded by source. `Delta` records every source into
+/// itself, so that the consensus path only keeps one `Delta` per validator.
+trait ValidatorDelta {
+    fn source(&mut self) -> &mut Delta;
+    fn target(&mut self) -> &mut Delta;
+    fn head(&mut self) -> &mut Delta;
+    fn inclusion_delay(&mut self) -> &mut Delta;
+    fn inactivity(&mut self) -> &mut Delta;
+    fn proposer(&mut self) -> &mut Delta;
+}
+
+impl ValidatorDelta for Delta {
+    fn source(&mut self) -> &mut Delta {
+        self
+    }
+
+    fn target(&mut self) -> &mut Delta {
+        self
+    }
+
+    fn head(&mut self) -> &mut Delta {
+        self
+    }
+
+    fn inclusion_delay(&mut self) -> &mut Delta {
+        self
+    }
+
+    fn inactivity(&mut self) -> &mut Delta {
+        self
+    }
+
+    fn proposer(&mut self) -> &mut Delta {
+        self
+    }
+}
+
+impl ValidatorDelta for RewardBreakdown {
+    fn source(&mut self) -> &mut Delta {
+        &mut self.source
+    }
+
+    fn target(&mut self) -> &mut Delta {
+        &mut self.target
+    }
+
+    fn head(&mut self) -> &mut Delta {
+        &mut self.head
+    }
+
+    fn inclusion_delay(&mut self) -> &mut Delta {
+        &mut self.inclusion_delay
+    }
+
+    fn inactivity(&mut self) -> &mut Delta {
+        &mut self.inactivity
+    }
+
+    fn proposer(&mut self) -> &mut Delta {
+        &mut self.proposer
+    }
+}
+
+impl RewardBreakdown {
+    /// Returns the sum of all the deltas.
+    pub fn total(&self) -> Result<Delta, Error> {
+        let mut total = Delta::default();
+        for delta in &[
+            &self.source,
+            &self.target,
+            &self.head,
+            &self.inclusion_delay,
+            &self.inactivity,
+            &self.proposer,
+        ] {
+            total.combine(delta)?;
+        }
+        Ok(total)
+    }
+}
+
 /// Apply attester and proposer rewards.
 ///
 /// Spec v0.11.1
@@ -53,7 +161,7 @@ pub fn process_rewards_and_penalties<T: EthSpec>(
 
     let mut deltas = vec![Delta::default(); state.balances.len()];
 
-    get_attestation_deltas(&mut deltas, state, &validator_statuses, spec)?;
+    get_attestation_deltas(&mut deltas, state, validator_statuses, spec)?;
 
     get_proposer_deltas(&mut deltas, state, validator_statuses, spec)?;
 
@@ -67,13 +175,42 @@ pub fn process_rewards_and_penalties<T: EthSpec>(
     Ok(())
 }
 
+/// Returns the rewards and penalties that `process_rewards_and_penalties` applies to each
+/// validator, by source.
+///
+/// The `validator_statuses` must have processed the attestations of `state`. This is not used
+/// by `process_rewards_and_penalties`, which only tracks the total change to each balance.
+pub fn get_reward_breakdowns<T: EthSpec>(
+    state: &BeaconState<T>,
+    validator_statuses: &ValidatorStatuses,
+    spec: &ChainSpec,
+) -> Result<Vec<RewardBreakdown>, Error> {
+    let mut breakdowns = vec![RewardBreakdown::default(); state.balances.len()];
+
+    if state.current_epoch() == T::genesis_epoch() {
+        return Ok(breakdowns);
+    }
+
+    if validator_statuses.statuses.len() != state.balances.len()
+        || validator_statuses.statuses.len() != state.validators.len()
+    {
+        return Err(Error::ValidatorStatusesInconsistent);
+    }
+
+    get_attestation_deltas(&mut breakdowns, state, validator_statuses, spec)?;
+
+    get_proposer_deltas(&mut breakdowns, state, validator_statuses, spec)?;
+
+    Ok(breakdowns)
+}
+
 /// For each attesting validator, reward the proposer who was first to include their attestation.
 ///
 /// Spec v0.11.1
-fn get_proposer_deltas<T: EthSpec>(
-    deltas: &mut Vec<Delta>,
+fn get_proposer_deltas<T: EthSpec, D: ValidatorDelta>(
+    deltas: &mut Vec<D>,
     state: &BeaconState<T>,
-    validator_statuses: &mut ValidatorStatuses,
+    validator_statuses: &ValidatorStatuses,
     spec: &ChainSpec,
 ) -> Result<(), Error> {
     for (index, validator) in validator_statuses.statuses.iter().enumerate() {
@@ -94,6 +231,7 @@ fn get_proposer_deltas<T: EthSpec>(
             }
 
             deltas[inclusion.proposer_index]
+                .proposer()
                 .reward(base_reward.safe_div(spec.proposer_reward_quotient)?)?;
         }
     }
@@ -104,8 +242,8 @@ fn get_proposer_deltas<T: EthSpec>(
 /// Apply rewards for participation in attestations during the previous epoch.
 ///
 /// Spec v0.11.1
-fn get_attestation_deltas<T: EthSpec>(
-    deltas: &mut Vec<Delta>,
+fn get_attestation_deltas<T: EthSpec, D: ValidatorDelta>(
+    deltas: &mut Vec<D>,
     state: &BeaconState<T>,
     validator_statuses: &ValidatorStatuses,
     spec: &ChainSpec,
@@ -120,15 +258,14 @@ fn get_attestation_deltas<T: EthSpec>(
             spec,
         )?;
 
-        let delta = get_attestation_delta::<T>(
+        get_attestation_delta::<T, D>(
+            &mut deltas[index],
             &validator,
             &validator_statuses.total_balances,
             base_reward,
             finality_delay,
             spec,
         )?;
-
-        deltas[index].combine(delta)?;
     }
 
     Ok(())
@@ -137,22 +274,21 @@ fn get_attestation_deltas<T: EthSpec>(
 /// Determine the delta for a single validator, sans proposer rewards.
 ///
 /// Spec v0.11.1
-fn get_attestation_delta<T: EthSpec>(
+fn get_attestation_delta<T: EthSpec, D: ValidatorDelta>(
+    delta: &mut D,
     validator: &ValidatorStatus,
     total_balances: &TotalBalances,
     base_reward: u64,
     finality_delay: u64,
     spec: &ChainSpec,
-) -> Result<Delta, Error> {
-    let mut delta = Delta::default();
-
+) -> Result<(), Error> {
     // Is this validator eligible to be rewarded or penalized?
     // Spec: validator index in `eligible_validator_indices`
     let is_eligible = validator.is_active_in_previous_epoch
         || (validator.is_slashed && !validator.is_withdrawable_in_current_epoch);
 
     if !is_eligible {
-        return Ok(delta);
+        return Ok(());
     }
 
     // Handle integer overflow by dividing these quantities by EFFECTIVE_BALANCE_INCREMENT
@@ -177,7 +313,7 @@ fn get_attestation_delta<T: EthSpec>(
     // Spec:
     // - validator index in `get_unslashed_attesting_indices(state, matching_source_attestations)`
     if validator.is_previous_epoch_attester && !validator.is_slashed {
-        delta.reward(
+        delta.source().reward(
             base_reward
                 .safe_mul(total_attesting_balance_ebi)?
                 .safe_div(total_balance_ebi)?,
@@ -188,45 +324,49 @@ fn get_attestation_delta<T: EthSpec>(
         let inclusion = validator
             .inclusion_info
             .expect("It is a logic error for an attester not to have an inclusion delay.");
-        delta.reward(max_attester_reward.safe_div(inclusion.delay)?)?;
+        delta
+            .inclusion_delay()
+            .reward(max_attester_reward.safe_div(inclusion.delay)?)?;
     } else {
-        delta.penalize(base_reward)?;
+        delta.source().penalize(base_reward)?;
     }
 
     // Expected FFG target.
     // Spec:
     // - validator index in `get_unslashed_attesting_indices(state, matching_target_attestations)`
     if validator.is_previous_epoch_target_attester && !validator.is_slashed {
-        delta.reward(
+        delta.target().reward(
             base_reward
                 .safe_mul(matching_target_balance_ebi)?
                 .safe_div(total_balance_ebi)?,
         )?;
     } else {
-        delta.penalize(base_reward)?;
+        delta.target().penalize(base_reward)?;
     }
 
     // Expected head.
     // Spec:
     // - validator index in `get_unslashed_attesting_indices(state, matching_head_attestations)`
     if validator.is_previous_epoch_head_attester && !validator.is_slashed {
-        delta.reward(
+        delta.head().reward(
             base_reward
                 .safe_mul(matching_head_balance_ebi)?
                 .safe_div(total_balance_ebi)?,
         )?;
     } else {
-        delta.penalize(base_reward)?;
+        delta.head().penalize(base_reward)?;
     }
 
     // Inactivity penalty
     if finality_delay > spec.min_epochs_to_inactivity_penalty {
         // All eligible validators are penalized
-        delta.penalize(spec.base_rewards_per_epoch.safe_mul(base_reward)?)?;
+        delta
+            .inactivity()
+            .penalize(spec.base_rewards_per_epoch.safe_mul(base_reward)?)?;
 
         // Additionally, all validators whose FFG target didn't match are penalized extra
         if !validator.is_previous_epoch_target_attester {
-            delta.penalize(
+            delta.inactivity().penalize(
                 validator
                     .current_epoch_effective_balance
                     .safe_mul(finality_delay)?
@@ -240,5 +380,5 @@ fn get_attestation_delta<T: EthSpec>(
     // This function only computes the delta for a single validator, so it cannot also return a
     // delta for a validator.
 
-    Ok(delta)
+    Ok(())
 }
@@ -9,9 +9,25 @@ pub fn process_slashings<T: EthSpec>(
     total_balance: u64,
     spec: &ChainSpec,
 ) -> Result<(), Error> {
+    for (index, penalty) in get_slashing_penalties(state, total_balance, spec)? {
+        safe_sub_assign!(state.balances[index], penalty);
+    }
+
+    Ok(())
+}
+
+/// Returns the index and penalty of each validator that `process_slashings` penalizes.
+///
+/// Spec v0.11.1
+pub fn get_slashing_penalties<T: EthSpec>(
+    state: &BeaconState<T>,
+    total_balance: u64,
+    spec: &ChainSpec,
+) -> Result<Vec<(usize, u64)>, Error> {
     let epoch = state.current_epoch();
     let sum_slashings = state.get_all_slashings().iter().sum::<u64>();
 
+    let mut penalties = vec![];
     for (index, validator) in state.validators.iter().enumerate() {
         if validator.slashed
             && epoch + T::EpochsPerSlashingsVector::to_u64().safe_div(2)?
@@ -26,9 +42,9 @@ pub fn process_slashings<T: EthSpec>(
                 .safe_div(total_balance)?
                 .safe_mul(increment)?;
 
-            safe_sub_assign!(state.balances[index], penalty);
+            penalties.push((index, penalty));
         }
     }
 
-    Ok(())
+    Ok(penalties)
 }
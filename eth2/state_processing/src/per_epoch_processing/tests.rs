@@ -1,5 +1,7 @@
 #![cfg(test)]
-use crate::per_epoch_processing::per_epoch_processing;
+use crate::per_epoch_processing::{
+    get_reward_breakdowns, per_epoch_processing, process_rewards_and_penalties, ValidatorStatuses,
+};
 use env_logger::{Builder, Env};
 use types::test_utils::TestingBeaconStateBuilder;
 use types::*;
@@ -21,3 +23,48 @@ fn runs_without_error() {
 
     per_epoch_processing(&mut state, &spec).unwrap();
 }
+
+#[test]
+fn reward_breakdowns_match_rewards_and_penalties() {
+    let spec = MinimalEthSpec::default_spec();
+
+    let mut builder: TestingBeaconStateBuilder<MinimalEthSpec> =
+        TestingBeaconStateBuilder::from_deterministic_keypairs(8, &spec);
+
+    let target_slot =
+        (MinimalEthSpec::genesis_epoch() + 4).end_slot(MinimalEthSpec::slots_per_epoch());
+    builder.teleport_to_slot(target_slot);
+
+    let (mut state, _keypairs) = builder.build();
+    state
+        .build_committee_cache(RelativeEpoch::Previous, &spec)
+        .unwrap();
+    state
+        .build_committee_cache(RelativeEpoch::Current, &spec)
+        .unwrap();
+
+    let mut validator_statuses = ValidatorStatuses::new(&state, &spec).unwrap();
+    validator_statuses
+        .process_attestations(&state, &spec)
+        .unwrap();
+
+    let breakdowns = get_reward_breakdowns(&state, &validator_statuses, &spec).unwrap();
+    let balances_before = state.balances.clone();
+
+    process_rewards_and_penalties(&mut state, &mut validator_statuses, &spec).unwrap();
+
+    for (index, breakdown) in breakdowns.iter().enumerate() {
+        // No attestations were included, so every validator missed each vote.
+        assert_eq!(breakdown.source.rewards(), 0);
+        assert!(breakdown.source.penalties() > 0);
+        assert_eq!(breakdown.target.penalties(), breakdown.source.penalties());
+        assert_eq!(breakdown.head.penalties(), breakdown.source.penalties());
+        assert_eq!(breakdown.proposer.rewards(), 0);
+
+        let total = breakdown.total().unwrap();
+        assert_eq!(
+            state.balances[index],
+            (balances_before[index] + total.rewards()).saturating_sub(total.penalties())
+        );
+    }
+}
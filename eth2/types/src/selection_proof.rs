@@ -1,7 +1,7 @@
 use crate::{ChainSpec, Domain, EthSpec, Fork, Hash256, SecretKey, Signature, SignedRoot, Slot};
+use eth2_hashing::hash;
 use safe_arith::{ArithError, SafeArith};
 use std::convert::TryInto;
-use tree_hash::TreeHash;
 
 #[derive(PartialEq, Debug, Clone)]
 pub struct SelectionProof(Signature);
@@ -25,11 +25,16 @@ impl SelectionProof {
         Self(Signature::new(message.as_bytes(), secret_key))
     }
 
+    /// Returns `true` if the validator that produced the proof is selected to aggregate for a
+    /// committee with the given `modulo`, i.e. `max(1, len(committee) //
+    /// TARGET_AGGREGATORS_PER_COMMITTEE)`.
+    ///
+    /// As in the spec, the selection is made on the SHA-256 hash of the serialized proof, not on
+    /// its tree hash root.
     pub fn is_aggregator(&self, modulo: u64) -> Result<bool, ArithError> {
-        let signature_hash = self.0.tree_hash_root();
+        let signature_hash = hash(&self.0.as_bytes());
         let signature_hash_int = u64::from_le_bytes(
             signature_hash[0..8]
-                .as_ref()
                 .try_into()
                 .expect("first 8 bytes of signature should always convert to fixed array"),
         );
@@ -38,8 +43,64 @@ impl SelectionProof {
     }
 }
 
+impl From<Signature> for SelectionProof {
+    fn from(signature: Signature) -> Self {
+        Self(signature)
+    }
+}
+
 impl Into<Signature> for SelectionProof {
     fn into(self) -> Signature {
         self.0
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns the compressed point at infinity, decoded as a non-empty signature.
+    fn infinity_signature() -> Signature {
+        let mut bytes = vec![0; 96];
+        bytes[0] = 0xc0;
+        Signature::from_bytes(&bytes).expect("should decode point at infinity")
+    }
+
+    #[test]
+    fn is_aggregator_spec_vectors() {
+        // `bytes_to_int(hash(signature)[0:8])` of the 96 zero bytes of the empty signature is
+        // 9251468512758311214, i.e. `hash = 0x2ea9ab9198d16380...`.
+        let empty = SelectionProof::from(Signature::empty_signature());
+        for modulo in &[1, 2, 3, 6] {
+            assert_eq!(empty.is_aggregator(*modulo), Ok(true), "modulo {}", modulo);
+        }
+        for modulo in &[4, 5, 7, 8, 16] {
+            assert_eq!(empty.is_aggregator(*modulo), Ok(false), "modulo {}", modulo);
+        }
+
+        // `0xc000...00` hashes to `0x086b9e46f2003ede...`, i.e. 16014238365542935304.
+        let infinity = SelectionProof::from(infinity_signature());
+        for modulo in &[1, 2, 4, 8, 23] {
+            assert_eq!(
+                infinity.is_aggregator(*modulo),
+                Ok(true),
+                "modulo {}",
+                modulo
+            );
+        }
+        for modulo in &[3, 5, 6, 16] {
+            assert_eq!(
+                infinity.is_aggregator(*modulo),
+                Ok(false),
+                "modulo {}",
+                modulo
+            );
+        }
+    }
+
+    #[test]
+    fn is_aggregator_zero_modulo() {
+        let proof = SelectionProof::from(Signature::empty_signature());
+        assert!(proof.is_aggregator(0).is_err());
+    }
+}
@@ -0,0 +1,246 @@
+use crate::{lamport_secret_key::LamportSecretKey, PlainText};
+use hkdf::Hkdf;
+use num_bigint::BigUint;
+use sha2::{Digest, Sha256};
+use zeroize::Zeroize;
+
+/// The byte size of a SHA256 hash.
+pub const HASH_SIZE: usize = 32;
+
+/// The size of the lamport array.
+///
+/// Indirectly defined in EIP-2333.
+pub const LAMPORT_ARRAY_SIZE: u8 = 255;
+
+/// The order of the BLS 12-381 curve.
+///
+/// Defined in EIP-2333.
+pub const R: &str = "52435875175126190479447740508185965837690552500527637822603658699938581184513";
+
+/// The `L` value used in the `hdkf_mod_r` function.
+///
+/// In EIP-2333 this value is defined as:
+///
+/// `ceil((3 * ceil(log2(r))) / 16)`
+pub const MOD_R_L: usize = 48;
+
+/// The salt used in the `hkdf_mod_r` function.
+pub const SALT: &[u8] = b"BLS-SIG-KEYGEN-SALT-";
+
+/// The minimum byte-length of a seed, as per EIP-2333.
+pub const MIN_SEED_LEN: usize = 32;
+
+#[derive(Debug, PartialEq)]
+pub enum Error {
+    /// The seed was shorter than `MIN_SEED_LEN` bytes.
+    EmptySeed,
+    SeedTooShort { len: usize, min: usize },
+}
+
+/// A BLS secret key that is derived from a `seed`, or derived as a child from another
+/// `DerivedKey`.
+///
+/// Implements `Zeroize` on `Drop`.
+#[derive(Zeroize)]
+#[zeroize(drop)]
+pub struct DerivedKey(ZeroizeHash);
+
+impl DerivedKey {
+    /// Instantiates `Self` from some secret seed bytes.
+    ///
+    /// The key is generated deterministically; the same `seed` will always return the same
+    /// `Self`.
+    ///
+    /// ## Errors
+    ///
+    /// Returns an error if the seed is shorter than `MIN_SEED_LEN` bytes.
+    pub fn from_seed(seed: &[u8]) -> Result<Self, Error> {
+        if seed.is_empty() {
+            Err(Error::EmptySeed)
+        } else if seed.len() < MIN_SEED_LEN {
+            Err(Error::SeedTooShort {
+                len: seed.len(),
+                min: MIN_SEED_LEN,
+            })
+        } else {
+            Ok(Self(derive_master_sk(seed)))
+        }
+    }
+
+    /// Derives a child key from the secret `Self` at some `index`.
+    pub fn child(&self, index: u32) -> DerivedKey {
+        Self(derive_child_sk(self.0.as_bytes(), index))
+    }
+
+    /// Derives the key at each of the `nodes` in turn, starting from `self`.
+    pub fn descend(&self, nodes: &[u32]) -> DerivedKey {
+        let mut key = Self(self.0.clone());
+        for node in nodes {
+            key = key.child(*node);
+        }
+        key
+    }
+
+    /// Returns the secret BLS key in `self`, as big-endian bytes.
+    pub fn secret(&self) -> &[u8] {
+        self.0.as_bytes()
+    }
+
+    /// Returns a copy of the secret BLS key in `self` which will be zeroized on drop.
+    pub fn to_plain_text(&self) -> PlainText {
+        PlainText::from(self.0.as_bytes().to_vec())
+    }
+}
+
+/// A 32-byte hash which implements `Zeroize` on `Drop`.
+#[derive(Zeroize, Clone)]
+#[zeroize(drop)]
+struct ZeroizeHash([u8; HASH_SIZE]);
+
+impl ZeroizeHash {
+    fn zero() -> Self {
+        Self([0; HASH_SIZE])
+    }
+
+    fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    fn as_mut_bytes(&mut self) -> &mut [u8] {
+        &mut self.0
+    }
+}
+
+/// Derives the "master" BLS secret key from some `seed` bytes.
+///
+/// Equivalent to `derive_master_SK` in EIP-2333.
+fn derive_master_sk(seed: &[u8]) -> ZeroizeHash {
+    hkdf_mod_r(seed)
+}
+
+/// From the given `parent_sk`, derives a child key at index`.
+///
+/// Equivalent to `derive_child_SK` in EIP-2333.
+fn derive_child_sk(parent_sk: &[u8], index: u32) -> ZeroizeHash {
+    let compressed_lamport_pk = parent_sk_to_lamport_pk(parent_sk, index);
+    hkdf_mod_r(compressed_lamport_pk.as_bytes())
+}
+
+/// From the `ikm` (initial key material), performs a HKDF-Extract and HKDF-Expand to generate a
+/// BLS private key within the order of the BLS-381 curve.
+///
+/// Equivalent to `HKDF_mod_r` in EIP-2333.
+fn hkdf_mod_r(ikm: &[u8]) -> ZeroizeHash {
+    let r = BigUint::parse_bytes(R.as_bytes(), 10).expect("must be able to parse R");
+
+    // The salt is hashed before use, and hashed again in the (incredibly unlikely) event that the
+    // derived key is zero.
+    let mut salt = Sha256::digest(SALT);
+
+    loop {
+        let okm = &mut PlainText::zero(MOD_R_L);
+        let prk = hkdf_extract(&salt, &[ikm, &[0]].concat());
+        prk.expand(&[0, MOD_R_L as u8], okm.as_mut_bytes())
+            .expect("MOD_R_L is a valid length for hkdf_expand");
+
+        let sk = BigUint::from_bytes_be(okm.as_bytes()) % &r;
+
+        if sk != BigUint::from(0_u8) {
+            return mod_r(sk);
+        }
+
+        salt = Sha256::digest(&salt);
+    }
+}
+
+/// Converts `n`, which must be less than `R`, into 32 big-endian bytes.
+fn mod_r(n: BigUint) -> ZeroizeHash {
+    let bytes = n.to_bytes_be();
+
+    let mut output = ZeroizeHash::zero();
+    output.as_mut_bytes()[HASH_SIZE - bytes.len()..].copy_from_slice(&bytes);
+    output
+}
+
+/// Generates a Lamport public key from the given `ikm` (which is assumed to be a BLS secret key).
+///
+/// Equivalent to `parent_SK_to_lamport_PK` in EIP-2333.
+fn parent_sk_to_lamport_pk(ikm: &[u8], index: u32) -> ZeroizeHash {
+    let salt = index.to_be_bytes();
+    let not_ikm = flip_bits(ikm);
+
+    let lamports = [
+        ikm_to_lamport_sk(&salt, ikm),
+        ikm_to_lamport_sk(&salt, not_ikm.as_bytes()),
+    ];
+
+    let mut hasher = Sha256::new();
+    for lamport in lamports.iter() {
+        for chunk in lamport.iter_chunks() {
+            hasher.update(Sha256::digest(chunk));
+        }
+    }
+
+    let mut compressed_lamport_pk = ZeroizeHash::zero();
+    compressed_lamport_pk
+        .as_mut_bytes()
+        .copy_from_slice(&hasher.finalize());
+    compressed_lamport_pk
+}
+
+/// Generates a Lamport secret key from the `ikm` (initial key material).
+///
+/// Equivalent to `IKM_to_lamport_SK` in EIP-2333.
+fn ikm_to_lamport_sk(salt: &[u8], ikm: &[u8]) -> LamportSecretKey {
+    let prk = hkdf_extract(salt, ikm);
+    let okm = &mut PlainText::zero(HASH_SIZE * LAMPORT_ARRAY_SIZE as usize);
+    prk.expand(&[], okm.as_mut_bytes())
+        .expect("lamport array size is a valid length for hkdf_expand");
+    LamportSecretKey::from_bytes(okm.as_bytes())
+}
+
+/// Peforms a `HKDF-Extract` on the `ikm` (initial key material) based up on the `salt`.
+///
+/// Defined in [RFC5869](https://tools.ietf.org/html/rfc5869).
+fn hkdf_extract(salt: &[u8], ikm: &[u8]) -> Hkdf<Sha256> {
+    Hkdf::<Sha256>::new(Some(salt), ikm)
+}
+
+/// Flips each bit in the `input`.
+///
+/// Equivalent to `flip_bits` in EIP-2333.
+fn flip_bits(input: &[u8]) -> ZeroizeHash {
+    assert_eq!(input.len(), HASH_SIZE);
+
+    let mut output = ZeroizeHash::zero();
+    for (out, byte) in output.as_mut_bytes().iter_mut().zip(input.iter()) {
+        *out = !byte;
+    }
+    output
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn flip_bits_is_self_inverse() {
+        let input = [42; HASH_SIZE];
+        let flipped = flip_bits(&input);
+        assert_eq!(flipped.as_bytes(), &[!42; HASH_SIZE][..]);
+        assert_eq!(flip_bits(flipped.as_bytes()).as_bytes(), &input[..]);
+    }
+
+    #[test]
+    fn seed_length() {
+        assert_eq!(DerivedKey::from_seed(&[]).err(), Some(Error::EmptySeed));
+        assert_eq!(
+            DerivedKey::from_seed(&[42; MIN_SEED_LEN - 1]).err(),
+            Some(Error::SeedTooShort {
+                len: MIN_SEED_LEN - 1,
+                min: MIN_SEED_LEN
+            })
+        );
+        assert!(DerivedKey::from_seed(&[42; MIN_SEED_LEN]).is_ok());
+    }
+}
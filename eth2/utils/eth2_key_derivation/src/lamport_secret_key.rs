@@ -0,0 +1,40 @@
+use crate::derived_key::{HASH_SIZE, LAMPORT_ARRAY_SIZE};
+use zeroize::Zeroize;
+
+/// A Lamport secret key as specified in [EIP-2333](https://eips.ethereum.org/EIPS/eip-2333).
+///
+/// Implements `Zeroize` on `Drop`.
+#[derive(Zeroize)]
+#[zeroize(drop)]
+pub struct LamportSecretKey(Vec<[u8; HASH_SIZE]>);
+
+impl LamportSecretKey {
+    /// Instantiates `Self` with all chunks set to `0`.
+    pub fn zero() -> Self {
+        Self(vec![[0; HASH_SIZE]; LAMPORT_ARRAY_SIZE as usize])
+    }
+
+    /// Instantiates `Self` from a flat buffer of `HASH_SIZE * LAMPORT_ARRAY_SIZE` bytes.
+    ///
+    /// ## Panics
+    ///
+    /// If `bytes.len() != HASH_SIZE * LAMPORT_ARRAY_SIZE`.
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        assert_eq!(
+            bytes.len(),
+            HASH_SIZE * LAMPORT_ARRAY_SIZE as usize,
+            "incorrect lamport secret key length"
+        );
+
+        let mut this = Self::zero();
+        for (chunk, bytes) in this.0.iter_mut().zip(bytes.chunks(HASH_SIZE)) {
+            chunk.copy_from_slice(bytes);
+        }
+        this
+    }
+
+    /// Returns an iterator over the `LAMPORT_ARRAY_SIZE` chunks of `self`.
+    pub fn iter_chunks(&self) -> impl Iterator<Item = &[u8; HASH_SIZE]> {
+        self.0.iter()
+    }
+}
@@ -0,0 +1,12 @@
+//! Provides path-based hierarchical BLS key derivation, as specified by
+//! [EIP-2333](https://eips.ethereum.org/EIPS/eip-2333) and
+//! [EIP-2334](https://eips.ethereum.org/EIPS/eip-2334).
+
+mod derived_key;
+mod lamport_secret_key;
+mod path;
+mod plain_text;
+
+pub use derived_key::{DerivedKey, Error, MIN_SEED_LEN};
+pub use path::{KeyType, ValidatorPath, ETH2_PURPOSE, ETH2_COIN_TYPE};
+pub use plain_text::PlainText;
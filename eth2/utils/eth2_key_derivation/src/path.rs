@@ -0,0 +1,69 @@
+use std::fmt;
+
+/// The `purpose` node of an EIP-2334 path.
+pub const ETH2_PURPOSE: u32 = 12381;
+/// The `coin_type` node of an EIP-2334 path.
+pub const ETH2_COIN_TYPE: u32 = 3600;
+
+/// The type of validator key, as defined in EIP-2334.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum KeyType {
+    Voting,
+    Withdrawal,
+}
+
+/// The path to a validator key, as defined in [EIP-2334](https://eips.ethereum.org/EIPS/eip-2334).
+///
+/// - Withdrawal keys are at `m/12381/3600/{index}/0`.
+/// - Voting (signing) keys are at `m/12381/3600/{index}/0/0`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ValidatorPath {
+    index: u32,
+    key_type: KeyType,
+}
+
+impl ValidatorPath {
+    pub fn new(index: u32, key_type: KeyType) -> Self {
+        Self { index, key_type }
+    }
+
+    /// Returns the nodes of the path, excluding the master node `m`.
+    pub fn nodes(&self) -> Vec<u32> {
+        let mut nodes = vec![ETH2_PURPOSE, ETH2_COIN_TYPE, self.index, 0];
+
+        if self.key_type == KeyType::Voting {
+            nodes.push(0)
+        }
+
+        nodes
+    }
+}
+
+impl fmt::Display for ValidatorPath {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "m")?;
+
+        for node in self.nodes() {
+            write!(f, "/{}", node)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn display() {
+        assert_eq!(
+            format!("{}", ValidatorPath::new(0, KeyType::Withdrawal)),
+            "m/12381/3600/0/0"
+        );
+        assert_eq!(
+            format!("{}", ValidatorPath::new(42, KeyType::Voting)),
+            "m/12381/3600/42/0/0"
+        );
+    }
+}
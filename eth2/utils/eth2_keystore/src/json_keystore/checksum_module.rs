@@ -0,0 +1,69 @@
+//! Defines the JSON representation of the "checksum" module.
+//!
+//! This file **MUST NOT** contain any logic beyond what is required to serialize/deserialize the
+//! data structures. Specifically, there should not be any actual crypto logic in this file.
+
+use super::hex_bytes::HexBytes;
+use serde_derive::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::convert::TryFrom;
+
+/// Used for ensuring that serde only decodes valid checksum functions.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Sha256Checksum(String);
+
+impl Sha256Checksum {
+    pub fn function() -> Self {
+        Self("sha256".into())
+    }
+}
+
+impl From<Sha256Checksum> for String {
+    fn from(from: Sha256Checksum) -> String {
+        from.0
+    }
+}
+
+impl TryFrom<String> for Sha256Checksum {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        if s == "sha256" {
+            Ok(Self(s))
+        } else {
+            Err(format!("Unsupported checksum function: {}", s))
+        }
+    }
+}
+
+/// Checksum module for `Keystore`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ChecksumModule {
+    pub function: Sha256Checksum,
+    pub params: EmptyMap,
+    pub message: HexBytes,
+}
+
+/// Used for ensuring serde only decodes an empty map.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "Value", into = "Value")]
+pub struct EmptyMap;
+
+impl From<EmptyMap> for Value {
+    fn from(_from: EmptyMap) -> Value {
+        Value::Object(Map::default())
+    }
+}
+
+impl TryFrom<Value> for EmptyMap {
+    type Error = String;
+
+    fn try_from(v: Value) -> Result<Self, Self::Error> {
+        match v {
+            Value::Object(map) if map.is_empty() => Ok(Self),
+            _ => Err("Checksum params must be an empty map".into()),
+        }
+    }
+}
@@ -0,0 +1,51 @@
+use serde::de::{Deserialize, Deserializer, Error as SerdeError};
+use serde::ser::{Serialize, Serializer};
+
+/// To allow serde to encode/decode byte arrays from HEX ASCII strings (without a `0x` prefix).
+#[derive(Debug, PartialEq, Clone)]
+pub struct HexBytes(Vec<u8>);
+
+impl HexBytes {
+    pub fn empty() -> Self {
+        Self(vec![])
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl From<Vec<u8>> for HexBytes {
+    fn from(vec: Vec<u8>) -> Self {
+        Self(vec)
+    }
+}
+
+impl Serialize for HexBytes {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&hex::encode(&self.0))
+    }
+}
+
+impl<'de> Deserialize<'de> for HexBytes {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let string = String::deserialize(deserializer)?;
+        hex::decode(&string)
+            .map(Self)
+            .map_err(|e| SerdeError::custom(format!("Invalid hex string {}: {:?}", string, e)))
+    }
+}
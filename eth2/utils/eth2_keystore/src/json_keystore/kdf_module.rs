@@ -0,0 +1,114 @@
+//! Defines the JSON representation of the "kdf" module.
+//!
+//! This file **MUST NOT** contain any logic beyond what is required to serialize/deserialize the
+//! data structures. Specifically, there should not be any actual crypto logic in this file.
+
+use super::hex_bytes::HexBytes;
+use serde_derive::{Deserialize, Serialize};
+use std::convert::TryFrom;
+
+/// KDF module representation.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct KdfModule {
+    pub function: KdfFunction,
+    pub params: Kdf,
+    pub message: EmptyString,
+}
+
+/// Used for ensuring serde only decodes an empty string.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct EmptyString;
+
+impl From<EmptyString> for String {
+    fn from(_from: EmptyString) -> String {
+        "".into()
+    }
+}
+
+impl TryFrom<String> for EmptyString {
+    type Error = &'static str;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        if s.is_empty() {
+            Ok(Self)
+        } else {
+            Err("kdf message must be an empty string")
+        }
+    }
+}
+
+/// Parameters for the supported key derivation functions.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged, deny_unknown_fields)]
+pub enum Kdf {
+    Scrypt(Scrypt),
+    Pbkdf2(Pbkdf2),
+}
+
+impl Kdf {
+    pub fn function(&self) -> KdfFunction {
+        match &self {
+            Kdf::Pbkdf2(_) => KdfFunction::Pbkdf2,
+            Kdf::Scrypt(_) => KdfFunction::Scrypt,
+        }
+    }
+}
+
+/// PRF for use in `pbkdf2`.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub enum Prf {
+    #[serde(rename = "hmac-sha256")]
+    HmacSha256,
+}
+
+/// Parameters for `pbkdf2` key derivation.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Pbkdf2 {
+    pub c: u32,
+    pub dklen: u32,
+    pub prf: Prf,
+    pub salt: HexBytes,
+}
+
+/// Used for ensuring that serde only decodes valid KDF functions.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum KdfFunction {
+    Scrypt,
+    Pbkdf2,
+}
+
+impl From<KdfFunction> for String {
+    fn from(from: KdfFunction) -> String {
+        match from {
+            KdfFunction::Scrypt => "scrypt".into(),
+            KdfFunction::Pbkdf2 => "pbkdf2".into(),
+        }
+    }
+}
+
+impl TryFrom<String> for KdfFunction {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        match s.as_ref() {
+            "scrypt" => Ok(KdfFunction::Scrypt),
+            "pbkdf2" => Ok(KdfFunction::Pbkdf2),
+            other => Err(format!("Unsupported kdf function: {}", other)),
+        }
+    }
+}
+
+/// Parameters for `scrypt` key derivation.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Scrypt {
+    pub dklen: u32,
+    pub n: u32,
+    pub r: u32,
+    pub p: u32,
+    pub salt: HexBytes,
+}
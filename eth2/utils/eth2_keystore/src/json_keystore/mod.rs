@@ -0,0 +1,56 @@
+//! This module intends to separate the JSON representation of the keystore from the actual crypto
+//! logic.
+//!
+//! This module **does not do any cryptography**. It only ensures that the JSON is in the exact
+//! format defined by EIP-2335.
+
+mod checksum_module;
+mod cipher_module;
+mod hex_bytes;
+mod kdf_module;
+
+pub use checksum_module::{ChecksumModule, EmptyMap, Sha256Checksum};
+pub use cipher_module::{Aes128Ctr, Cipher, CipherFunction, CipherModule};
+pub use hex_bytes::HexBytes;
+pub use kdf_module::{EmptyString, Kdf, KdfFunction, KdfModule, Pbkdf2, Prf, Scrypt};
+pub use uuid::Uuid;
+
+use serde_derive::{Deserialize, Serialize};
+use serde_repr::*;
+
+/// JSON representation of [EIP-2335](https://eips.ethereum.org/EIPS/eip-2335) keystore.
+///
+/// This struct is the top-level object of the keystore JSON.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct JsonKeystore {
+    pub crypto: Crypto,
+    pub uuid: Uuid,
+    pub path: String,
+    pub pubkey: String,
+    pub version: Version,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+/// Version for `JsonKeystore`.
+#[derive(Debug, Clone, PartialEq, Serialize_repr, Deserialize_repr)]
+#[repr(u8)]
+pub enum Version {
+    V4 = 4,
+}
+
+impl Version {
+    pub fn four() -> Self {
+        Version::V4
+    }
+}
+
+/// Crypto module for keystore.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Crypto {
+    pub kdf: KdfModule,
+    pub checksum: ChecksumModule,
+    pub cipher: CipherModule,
+}
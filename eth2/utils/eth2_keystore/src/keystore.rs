@@ -0,0 +1,454 @@
+//! Provides a JSON keystore for a BLS keypair, as specified by
+//! [EIP-2335](https://eips.ethereum.org/EIPS/eip-2335).
+
+use crate::json_keystore::{
+    Aes128Ctr, ChecksumModule, Cipher, CipherModule, Crypto, EmptyMap, EmptyString, JsonKeystore,
+    Kdf, KdfModule, Prf, Scrypt, Sha256Checksum, Version,
+};
+use crate::PlainText;
+use aes_ctr::cipher::generic_array::GenericArray;
+use aes_ctr::cipher::stream::{NewStreamCipher, SyncStreamCipher};
+use bls::{Keypair, PublicKey, SecretKey};
+use hmac::Hmac;
+use pbkdf2::pbkdf2;
+use rand::prelude::*;
+use scrypt::{
+    errors::{InvalidOutputLen, InvalidParams},
+    scrypt, ScryptParams,
+};
+use serde_derive::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use ssz::Encode;
+use std::io::{Read, Write};
+use unicode_normalization::UnicodeNormalization;
+use uuid::Uuid;
+
+/// The byte-length of a BLS secret key.
+const SECRET_KEY_LEN: usize = 32;
+/// The default byte length of the salt used to seed the KDF.
+///
+/// NOTE: there is no clear guidance in EIP-2335 regarding the size of this salt. Neither
+/// [pbkdf2](https://www.ietf.org/rfc/rfc2898.txt) or [scrypt](https://tools.ietf.org/html/rfc7914)
+/// make a clear statement about what size it should be, however 32-bytes certainly seems
+/// reasonable and larger than their examples.
+pub const SALT_SIZE: usize = 32;
+/// The length of the derived key.
+pub const DKLEN: u32 = 32;
+/// Size of the IV (initialization vector) used for aes-128-ctr encryption.
+///
+/// Must be 16 bytes, as per EIP-2335.
+pub const IV_SIZE: usize = 16;
+/// The byte size of a SHA256 hash.
+pub const HASH_SIZE: usize = 32;
+
+/// The default scrypt parameters, as recommended by EIP-2335.
+const DEFAULT_SCRYPT_N: u32 = 262_144;
+const DEFAULT_SCRYPT_R: u32 = 8;
+const DEFAULT_SCRYPT_P: u32 = 1;
+
+#[derive(Debug, PartialEq)]
+pub enum Error {
+    InvalidSecretKeyLen { len: usize, expected: usize },
+    InvalidPassword,
+    InvalidPasswordUtf8,
+    InvalidSecretKeyBytes(String),
+    PublicKeyMismatch,
+    EmptyPassword,
+    UnableToSerialize(String),
+    InvalidJson(String),
+    WriteError(String),
+    ReadError(String),
+    InvalidPbkdf2Param,
+    InvalidScryptParam,
+    InvalidSaltLength,
+    IncorrectIvSize { expected: usize, len: usize },
+    InvalidDkLen { expected: u32, len: u32 },
+    ScryptInvalidParams(InvalidParams),
+    ScryptInvalidOutputLen(InvalidOutputLen),
+}
+
+/// Constructs a `Keystore`.
+pub struct KeystoreBuilder<'a> {
+    keypair: &'a Keypair,
+    password: &'a [u8],
+    kdf: Kdf,
+    cipher: Cipher,
+    uuid: Uuid,
+    path: String,
+    description: Option<String>,
+}
+
+impl<'a> KeystoreBuilder<'a> {
+    /// Creates a new builder.
+    ///
+    /// Generates the KDF `salt` and AES `IV` using `rand::thread_rng()`.
+    ///
+    /// ## Errors
+    ///
+    /// Returns `Error::EmptyPassword` if `password == ""`.
+    pub fn new(keypair: &'a Keypair, password: &'a [u8], path: String) -> Result<Self, Error> {
+        if password.is_empty() {
+            Err(Error::EmptyPassword)
+        } else {
+            let salt = rand::thread_rng().gen::<[u8; SALT_SIZE]>();
+            let iv = rand::thread_rng().gen::<[u8; IV_SIZE]>().to_vec().into();
+
+            Ok(Self {
+                keypair,
+                password,
+                kdf: default_kdf(salt.to_vec()),
+                cipher: Cipher::Aes128Ctr(Aes128Ctr { iv }),
+                uuid: Uuid::new_v4(),
+                path,
+                description: None,
+            })
+        }
+    }
+
+    /// Build the keystore using the supplied `kdf` instead of `default_kdf`.
+    pub fn kdf(mut self, kdf: Kdf) -> Self {
+        self.kdf = kdf;
+        self
+    }
+
+    /// Attach a human-readable `description` to the keystore.
+    pub fn description(mut self, description: String) -> Self {
+        self.description = Some(description);
+        self
+    }
+
+    /// Consumes `self`, returning a `Keystore`.
+    pub fn build(self) -> Result<Keystore, Error> {
+        Keystore::encrypt(
+            self.keypair,
+            self.password,
+            self.kdf,
+            self.cipher,
+            self.uuid,
+            self.path,
+            self.description,
+        )
+    }
+}
+
+/// Provides a BLS keystore as defined in [EIP-2335](https://eips.ethereum.org/EIPS/eip-2335).
+///
+/// Use `KeystoreBuilder` to create a new keystore.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Keystore {
+    json: JsonKeystore,
+}
+
+impl Keystore {
+    /// Generate `Keystore` object for a BLS12-381 secret key from a
+    /// keypair and password.
+    fn encrypt(
+        keypair: &Keypair,
+        password: &[u8],
+        kdf: Kdf,
+        cipher: Cipher,
+        uuid: Uuid,
+        path: String,
+        description: Option<String>,
+    ) -> Result<Self, Error> {
+        let secret = PlainText::from(keypair.sk.as_ssz_bytes());
+        let (cipher_text, checksum) = encrypt(secret.as_bytes(), password, &kdf, &cipher)?;
+
+        Ok(Keystore {
+            json: JsonKeystore {
+                crypto: Crypto {
+                    kdf: KdfModule {
+                        function: kdf.function(),
+                        params: kdf,
+                        message: EmptyString,
+                    },
+                    checksum: ChecksumModule {
+                        function: Sha256Checksum::function(),
+                        params: EmptyMap,
+                        message: checksum.to_vec().into(),
+                    },
+                    cipher: CipherModule {
+                        function: cipher.function(),
+                        params: cipher,
+                        message: cipher_text.into(),
+                    },
+                },
+                uuid,
+                path,
+                pubkey: hex::encode(keypair.pk.as_ssz_bytes()),
+                version: Version::four(),
+                description,
+            },
+        })
+    }
+
+    /// Regenerate a BLS12-381 `Keypair` from `self` and the correct password.
+    ///
+    /// ## Errors
+    ///
+    /// - The provided password is incorrect.
+    /// - The keystore is badly formed.
+    /// - The public key in the keystore does not match the decrypted secret key.
+    pub fn decrypt_keypair(&self, password: &[u8]) -> Result<Keypair, Error> {
+        let plain_text = decrypt(password, &self.json.crypto)?;
+        let keypair = keypair_from_secret(plain_text.as_bytes())?;
+
+        // Verify that the derived `PublicKey` matches `self`.
+        if hex::encode(keypair.pk.as_ssz_bytes()) != self.json.pubkey {
+            return Err(Error::PublicKeyMismatch);
+        }
+
+        Ok(keypair)
+    }
+
+    /// Returns the UUID for the keystore.
+    pub fn uuid(&self) -> &Uuid {
+        &self.json.uuid
+    }
+
+    /// Returns the path for the keystore.
+    ///
+    /// Note: the path is not validated, it is simply whatever string the keystore provided.
+    pub fn path(&self) -> &str {
+        &self.json.path
+    }
+
+    /// Returns the pubkey for the keystore, as a hex string without a `0x` prefix.
+    pub fn pubkey(&self) -> &str {
+        &self.json.pubkey
+    }
+
+    /// Returns the description for the keystore, if any.
+    pub fn description(&self) -> Option<&str> {
+        self.json.description.as_deref()
+    }
+
+    /// Returns the key derivation function for the keystore.
+    pub fn kdf(&self) -> &Kdf {
+        &self.json.crypto.kdf.params
+    }
+
+    /// Encodes `self` as a JSON object.
+    pub fn to_json_string(&self) -> Result<String, Error> {
+        serde_json::to_string(self).map_err(|e| Error::UnableToSerialize(format!("{}", e)))
+    }
+
+    /// Returns `self` from an encoded JSON object.
+    pub fn from_json_str(json_string: &str) -> Result<Self, Error> {
+        serde_json::from_str(json_string).map_err(|e| Error::InvalidJson(format!("{}", e)))
+    }
+
+    /// Encodes self as a JSON object to the given `writer`.
+    pub fn to_json_writer<W: Write>(&self, writer: W) -> Result<(), Error> {
+        serde_json::to_writer(writer, self).map_err(|e| Error::WriteError(format!("{}", e)))
+    }
+
+    /// Instantiates `self` from a JSON `reader`.
+    pub fn from_json_reader<R: Read>(reader: R) -> Result<Self, Error> {
+        serde_json::from_reader(reader).map_err(|e| Error::ReadError(format!("{}", e)))
+    }
+}
+
+/// Instantiates a BLS keypair from the given `secret`.
+///
+/// ## Errors
+///
+/// - If `secret.len() != 32`.
+/// - If `secret` does not represent a point in the BLS curve.
+pub fn keypair_from_secret(secret: &[u8]) -> Result<Keypair, Error> {
+    if secret.len() != SECRET_KEY_LEN {
+        return Err(Error::InvalidSecretKeyLen {
+            len: secret.len(),
+            expected: SECRET_KEY_LEN,
+        });
+    }
+
+    let sk = SecretKey::from_bytes(secret)
+        .map_err(|e| Error::InvalidSecretKeyBytes(format!("{:?}", e)))?;
+    let pk = PublicKey::from_secret_key(&sk);
+
+    Ok(Keypair { sk, pk })
+}
+
+/// Returns `Kdf` used by default when creating keystores.
+///
+/// Currently this is set to scrypt due to its memory hardness properties.
+pub fn default_kdf(salt: Vec<u8>) -> Kdf {
+    Kdf::Scrypt(Scrypt {
+        dklen: DKLEN,
+        n: DEFAULT_SCRYPT_N,
+        r: DEFAULT_SCRYPT_R,
+        p: DEFAULT_SCRYPT_P,
+        salt: salt.into(),
+    })
+}
+
+/// Returns `(cipher_text, checksum)` for the given `plain_text` encrypted with `Cipher` using a
+/// key derived from `password` via the `Kdf` (key derivation function).
+///
+/// ## Errors
+///
+/// - The `kdf` is badly formed (e.g., has some values set to zero).
+pub fn encrypt(
+    plain_text: &[u8],
+    password: &[u8],
+    kdf: &Kdf,
+    cipher: &Cipher,
+) -> Result<(Vec<u8>, [u8; HASH_SIZE]), Error> {
+    let derived_key = derive_key(password, kdf)?;
+
+    // Encrypt secret.
+    let mut cipher_text = plain_text.to_vec();
+    match cipher {
+        Cipher::Aes128Ctr(params) => {
+            if params.iv.len() != IV_SIZE {
+                return Err(Error::IncorrectIvSize {
+                    expected: IV_SIZE,
+                    len: params.iv.len(),
+                });
+            }
+
+            let key = GenericArray::from_slice(&derived_key.as_bytes()[0..16]);
+            let nonce = GenericArray::from_slice(params.iv.as_bytes());
+
+            let mut cipher = aes_ctr::Aes128Ctr::new(key, nonce);
+            cipher.apply_keystream(&mut cipher_text);
+        }
+    };
+
+    let checksum = generate_checksum(&derived_key, &cipher_text);
+
+    Ok((cipher_text, checksum))
+}
+
+/// Regenerate some `plain_text` from the given `password` and `crypto`.
+///
+/// ## Errors
+///
+/// - The provided password is incorrect.
+/// - The `crypto.kdf` is badly formed (e.g., has some values set to zero).
+pub fn decrypt(password: &[u8], crypto: &Crypto) -> Result<PlainText, Error> {
+    let cipher_message = &crypto.cipher.message;
+
+    // Generate derived key
+    let derived_key = derive_key(password, &crypto.kdf.params)?;
+
+    // Mismatching checksum indicates an invalid password.
+    if &generate_checksum(&derived_key, cipher_message.as_bytes())[..]
+        != crypto.checksum.message.as_bytes()
+    {
+        return Err(Error::InvalidPassword);
+    }
+
+    let mut plain_text = PlainText::from(cipher_message.as_bytes().to_vec());
+    match &crypto.cipher.params {
+        Cipher::Aes128Ctr(params) => {
+            if params.iv.len() != IV_SIZE {
+                return Err(Error::IncorrectIvSize {
+                    expected: IV_SIZE,
+                    len: params.iv.len(),
+                });
+            }
+
+            let key = GenericArray::from_slice(&derived_key.as_bytes()[0..16]);
+            let nonce = GenericArray::from_slice(params.iv.as_bytes());
+
+            let mut cipher = aes_ctr::Aes128Ctr::new(key, nonce);
+            cipher.apply_keystream(plain_text.as_mut_bytes());
+        }
+    };
+
+    Ok(plain_text)
+}
+
+/// Generates a checksum to indicate that the `derived_key` is associated with the
+/// `cipher_message`.
+fn generate_checksum(derived_key: &PlainText, cipher_message: &[u8]) -> [u8; HASH_SIZE] {
+    let mut hasher = Sha256::new();
+    hasher.update(&derived_key.as_bytes()[16..32]);
+    hasher.update(cipher_message);
+
+    let mut digest = [0; HASH_SIZE];
+    digest.copy_from_slice(&hasher.finalize());
+    digest
+}
+
+/// Derive a private key from the given `password` using the given `kdf` (key derivation function).
+fn derive_key(password: &[u8], kdf: &Kdf) -> Result<PlainText, Error> {
+    let password = normalize_password(password)?;
+    let mut dk = PlainText::zero(DKLEN as usize);
+
+    match kdf {
+        Kdf::Pbkdf2(params) => {
+            if params.dklen != DKLEN {
+                return Err(Error::InvalidDkLen {
+                    expected: DKLEN,
+                    len: params.dklen,
+                });
+            }
+
+            if params.c == 0 {
+                return Err(Error::InvalidPbkdf2Param);
+            }
+
+            if params.salt.is_empty() {
+                return Err(Error::InvalidSaltLength);
+            }
+
+            match params.prf {
+                Prf::HmacSha256 => pbkdf2::<Hmac<Sha256>>(
+                    password.as_bytes(),
+                    params.salt.as_bytes(),
+                    params.c,
+                    dk.as_mut_bytes(),
+                ),
+            }
+        }
+        Kdf::Scrypt(params) => {
+            if params.dklen != DKLEN {
+                return Err(Error::InvalidDkLen {
+                    expected: DKLEN,
+                    len: params.dklen,
+                });
+            }
+
+            // `n` must be a power of two greater than one, since the scrypt library takes log2(n).
+            if params.n <= 1 || !params.n.is_power_of_two() || params.r == 0 || params.p == 0 {
+                return Err(Error::InvalidScryptParam);
+            }
+
+            if params.salt.is_empty() {
+                return Err(Error::InvalidSaltLength);
+            }
+
+            let log_n = params.n.trailing_zeros() as u8;
+
+            scrypt(
+                password.as_bytes(),
+                params.salt.as_bytes(),
+                &ScryptParams::new(log_n, params.r, params.p)
+                    .map_err(Error::ScryptInvalidParams)?,
+                dk.as_mut_bytes(),
+            )
+            .map_err(Error::ScryptInvalidOutputLen)?;
+        }
+    }
+
+    Ok(dk)
+}
+
+/// Normalizes the password as required by EIP-2335.
+///
+/// The password is converted to its NFKD representation and the control codes (`0x00 - 0x1F`,
+/// `0x7F` and `0x80 - 0x9F`) are stripped.
+fn normalize_password(password: &[u8]) -> Result<PlainText, Error> {
+    let password = std::str::from_utf8(password).map_err(|_| Error::InvalidPasswordUtf8)?;
+
+    Ok(password
+        .nfkd()
+        .filter(|c| !c.is_control())
+        .collect::<String>()
+        .into_bytes()
+        .into())
+}
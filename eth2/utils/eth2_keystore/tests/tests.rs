@@ -0,0 +1,183 @@
+#![cfg(test)]
+
+use bls::Keypair;
+use eth2_keystore::{
+    json_keystore::{Kdf, Pbkdf2, Prf, Scrypt},
+    Error, Keystore, KeystoreBuilder, DKLEN,
+};
+use std::fs::File;
+use tempfile::tempdir;
+
+const GOOD_PASSWORD: &[u8] = &[42, 42, 42];
+const BAD_PASSWORD: &[u8] = &[43, 43, 43];
+
+/// A cheap KDF so the tests don't spend all their time in scrypt.
+fn insecure_kdf() -> Kdf {
+    Kdf::Scrypt(Scrypt {
+        dklen: DKLEN,
+        n: 4,
+        p: 1,
+        r: 8,
+        salt: vec![1; 32].into(),
+    })
+}
+
+#[test]
+fn empty_password() {
+    assert_eq!(
+        KeystoreBuilder::new(&Keypair::random(), "".as_bytes(), "".into())
+            .err()
+            .unwrap(),
+        Error::EmptyPassword
+    );
+}
+
+#[test]
+fn string_round_trip() {
+    let keypair = Keypair::random();
+
+    let keystore = KeystoreBuilder::new(&keypair, GOOD_PASSWORD, "".into())
+        .unwrap()
+        .kdf(insecure_kdf())
+        .build()
+        .unwrap();
+
+    let json = keystore.to_json_string().unwrap();
+    let decoded = Keystore::from_json_str(&json).unwrap();
+
+    assert_eq!(
+        decoded.decrypt_keypair(BAD_PASSWORD).err().unwrap(),
+        Error::InvalidPassword,
+        "should not decrypt with bad password"
+    );
+
+    assert_eq!(
+        decoded.decrypt_keypair(GOOD_PASSWORD).unwrap(),
+        keypair,
+        "should decrypt with good password"
+    );
+}
+
+#[test]
+fn file() {
+    let keypair = Keypair::random();
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("keystore.json");
+
+    let get_file = || File::create(path.clone()).expect("should create file");
+
+    let keystore = KeystoreBuilder::new(&keypair, GOOD_PASSWORD, "".into())
+        .unwrap()
+        .kdf(insecure_kdf())
+        .build()
+        .unwrap();
+
+    keystore
+        .to_json_writer(&mut get_file())
+        .expect("should write to file");
+
+    let decoded =
+        Keystore::from_json_reader(&mut File::open(&path).unwrap()).expect("should read from file");
+
+    assert_eq!(
+        decoded.decrypt_keypair(BAD_PASSWORD).err().unwrap(),
+        Error::InvalidPassword,
+        "should not decrypt with bad password"
+    );
+
+    assert_eq!(
+        decoded.decrypt_keypair(GOOD_PASSWORD).unwrap(),
+        keypair,
+        "should decrypt with good password"
+    );
+}
+
+#[test]
+fn scrypt_params() {
+    let keypair = Keypair::random();
+
+    let keystore = KeystoreBuilder::new(&keypair, GOOD_PASSWORD, "".into())
+        .unwrap()
+        .kdf(insecure_kdf())
+        .build()
+        .unwrap();
+    let json = keystore.to_json_string().unwrap();
+    let decoded = Keystore::from_json_str(&json).unwrap();
+    assert_eq!(decoded.decrypt_keypair(GOOD_PASSWORD).unwrap(), keypair);
+
+    // n is not a power of two.
+    let my_kdf = Kdf::Scrypt(Scrypt {
+        dklen: DKLEN,
+        n: 5,
+        p: 1,
+        r: 8,
+        salt: vec![1; 32].into(),
+    });
+    let keystore = KeystoreBuilder::new(&keypair, GOOD_PASSWORD, "".into())
+        .unwrap()
+        .kdf(my_kdf);
+    assert_eq!(keystore.build().err().unwrap(), Error::InvalidScryptParam);
+
+    // Empty salt.
+    let my_kdf = Kdf::Scrypt(Scrypt {
+        dklen: DKLEN,
+        n: 4,
+        p: 1,
+        r: 8,
+        salt: vec![].into(),
+    });
+    let keystore = KeystoreBuilder::new(&keypair, GOOD_PASSWORD, "".into())
+        .unwrap()
+        .kdf(my_kdf);
+    assert_eq!(keystore.build().err().unwrap(), Error::InvalidSaltLength);
+}
+
+#[test]
+fn pbkdf2_params() {
+    let keypair = Keypair::random();
+
+    let my_kdf = Kdf::Pbkdf2(Pbkdf2 {
+        dklen: DKLEN,
+        c: 80_000,
+        prf: Prf::HmacSha256,
+        salt: vec![42; 32].into(),
+    });
+    let keystore = KeystoreBuilder::new(&keypair, GOOD_PASSWORD, "".into())
+        .unwrap()
+        .kdf(my_kdf)
+        .build()
+        .unwrap();
+    let json = keystore.to_json_string().unwrap();
+    let decoded = Keystore::from_json_str(&json).unwrap();
+    assert_eq!(decoded.decrypt_keypair(GOOD_PASSWORD).unwrap(), keypair);
+
+    // c == 0
+    let my_kdf = Kdf::Pbkdf2(Pbkdf2 {
+        dklen: DKLEN,
+        c: 0,
+        prf: Prf::HmacSha256,
+        salt: vec![42; 32].into(),
+    });
+    let keystore = KeystoreBuilder::new(&keypair, GOOD_PASSWORD, "".into())
+        .unwrap()
+        .kdf(my_kdf);
+    assert_eq!(keystore.build().err().unwrap(), Error::InvalidPbkdf2Param);
+}
+
+#[test]
+fn unknown_fields_are_rejected() {
+    let keystore = KeystoreBuilder::new(&Keypair::random(), GOOD_PASSWORD, "".into())
+        .unwrap()
+        .kdf(insecure_kdf())
+        .build()
+        .unwrap();
+
+    let mut json: serde_json::Value =
+        serde_json::from_str(&keystore.to_json_string().unwrap()).unwrap();
+    json["crypto"]["cipher"]["function"] = "aes-256-ctr".into();
+
+    assert!(
+        Keystore::from_json_str(&json.to_string()).is_err(),
+        "should reject unsupported cipher"
+    );
+}
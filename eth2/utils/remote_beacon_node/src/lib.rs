@@ -6,6 +6,7 @@
 use eth2_config::Eth2Config;
 use futures::{future, Future, IntoFuture};
 use reqwest::{
+    header::{HeaderMap, HeaderValue, AUTHORIZATION},
     r#async::{Client, ClientBuilder, Response},
     StatusCode,
 };
@@ -16,16 +17,18 @@ use std::time::Duration;
 use types::{
     Attestation, AttestationData, AttesterSlashing, BeaconBlock, BeaconState, CommitteeIndex,
     Epoch, EthSpec, Fork, Hash256, ProposerSlashing, PublicKey, PublicKeyBytes, Signature,
-    SignedAggregateAndProof, SignedBeaconBlock, Slot,
+    SignedAggregateAndProof, SignedBeaconBlock, SignedVoluntaryExit, Slot,
 };
 use url::Url;
 
 pub use operation_pool::PersistedOperationPool;
 pub use proto_array_fork_choice::core::ProtoArray;
 pub use rest_types::{
-    CanonicalHeadResponse, Committee, HeadBeaconBlock, IndividualVotesRequest,
-    IndividualVotesResponse, SyncingResponse, ValidatorDutiesRequest, ValidatorDutyBytes,
-    ValidatorRequest, ValidatorResponse, ValidatorSubscription,
+    BlockId, CanonicalHeadResponse, Committee, EpochRewards, FinalityCheckpointsResponse,
+    HeadBeaconBlock, IndividualVotesRequest, IndividualVotesResponse, LivenessRequest,
+    LivenessResponse, StateId, SyncingResponse, ValidatorBalance, ValidatorDutiesRequest,
+    ValidatorDutyBytes, ValidatorRequest, ValidatorResponse, ValidatorRewardsResponse,
+    ValidatorSubscription,
 };
 
 // Setting a long timeout for debug ensures that crypto-heavy operations can still succeed.
@@ -48,8 +51,17 @@ impl<E: EthSpec> RemoteBeaconNode<E> {
     }
 
     pub fn new_with_timeout(http_endpoint: String, timeout: Duration) -> Result<Self, String> {
+        Self::new_with_token(http_endpoint, timeout, None)
+    }
+
+    /// Sends `token` as a bearer token with each request, if it is `Some`.
+    pub fn new_with_token(
+        http_endpoint: String,
+        timeout: Duration,
+        token: Option<&str>,
+    ) -> Result<Self, String> {
         Ok(Self {
-            http: HttpClient::new(http_endpoint, timeout)
+            http: HttpClient::new_with_token(http_endpoint, timeout, token)
                 .map_err(|e| format!("Unable to create http client: {:?}", e))?,
         })
     }
@@ -80,9 +92,28 @@ pub struct HttpClient<E> {
 impl<E: EthSpec> HttpClient<E> {
     /// Creates a new instance (without connecting to the node).
     pub fn new(server_url: String, timeout: Duration) -> Result<Self, Error> {
+        Self::new_with_token(server_url, timeout, None)
+    }
+
+    /// Creates a new instance which sends `token` in the `Authorization` header of each request,
+    /// if it is `Some`.
+    pub fn new_with_token(
+        server_url: String,
+        timeout: Duration,
+        token: Option<&str>,
+    ) -> Result<Self, Error> {
+        let mut headers = HeaderMap::new();
+        if let Some(token) = token {
+            let mut value = HeaderValue::from_str(&format!("Bearer {}", token))
+                .map_err(|_| Error::InvalidInput)?;
+            value.set_sensitive(true);
+            headers.insert(AUTHORIZATION, value);
+        }
+
         Ok(Self {
             client: ClientBuilder::new()
                 .timeout(timeout)
+                .default_headers(headers)
                 .build()
                 .expect("should build from static configuration"),
             url: Url::parse(&server_url)?,
@@ -91,6 +122,11 @@ impl<E: EthSpec> HttpClient<E> {
         })
     }
 
+    /// Returns the base URL of the node.
+    pub fn url(&self) -> &Url {
+        &self.url
+    }
+
     pub fn beacon(&self) -> Beacon<E> {
         Beacon(self.clone())
     }
@@ -441,6 +477,14 @@ impl<E: EthSpec> Beacon<E> {
         self.get_block("root".to_string(), root_as_string(root))
     }
 
+    /// Returns the block and block root identified by `block_id`.
+    pub fn get_block_by_id(
+        &self,
+        block_id: BlockId,
+    ) -> impl Future<Item = (SignedBeaconBlock<E>, Hash256), Error = Error> {
+        self.get_block("block_id".to_string(), block_id.to_string())
+    }
+
     /// Returns the block and block root at the given slot.
     fn get_block(
         &self,
@@ -472,6 +516,75 @@ impl<E: EthSpec> Beacon<E> {
         self.get_state("root".to_string(), root_as_string(root))
     }
 
+    /// Returns the state and state root identified by `state_id`.
+    pub fn get_state_by_id(
+        &self,
+        state_id: StateId,
+    ) -> impl Future<Item = (BeaconState<E>, Hash256), Error = Error> {
+        self.get_state("state_id".to_string(), state_id.to_string())
+    }
+
+    /// Returns the fork of the state identified by `state_id`.
+    pub fn get_state_fork(&self, state_id: StateId) -> impl Future<Item = Fork, Error = Error> {
+        let client = self.0.clone();
+        self.url("state/fork").into_future().and_then(move |url| {
+            client.json_get(url, vec![("state_id".into(), state_id.to_string())])
+        })
+    }
+
+    /// Returns the justified and finalized checkpoints of the state identified by `state_id`.
+    pub fn get_state_finality_checkpoints(
+        &self,
+        state_id: StateId,
+    ) -> impl Future<Item = FinalityCheckpointsResponse, Error = Error> {
+        let client = self.0.clone();
+        self.url("state/finality_checkpoints")
+            .into_future()
+            .and_then(move |url| {
+                client.json_get(url, vec![("state_id".into(), state_id.to_string())])
+            })
+    }
+
+    /// Returns the balances of the given validators in the state identified by `state_id`, or of
+    /// all validators if `validator_indices` is empty.
+    pub fn get_state_validator_balances(
+        &self,
+        state_id: StateId,
+        validator_indices: Vec<usize>,
+    ) -> impl Future<Item = Vec<ValidatorBalance>, Error = Error> {
+        let client = self.0.clone();
+
+        let mut query_params = vec![("state_id".into(), state_id.to_string())];
+        query_params.extend(
+            validator_indices
+                .into_iter()
+                .map(|index| ("validator_indices".into(), format!("{}", index))),
+        );
+
+        self.url("state/validator_balances")
+            .into_future()
+            .and_then(move |url| client.json_get(url, query_params))
+    }
+
+    /// Returns the RANDAO mix of `epoch` in the state identified by `state_id`. If `epoch` is
+    /// `None`, the current epoch of the state is used.
+    pub fn get_state_randao_mix(
+        &self,
+        state_id: StateId,
+        epoch: Option<Epoch>,
+    ) -> impl Future<Item = Hash256, Error = Error> {
+        let client = self.0.clone();
+
+        let mut query_params = vec![("state_id".into(), state_id.to_string())];
+        if let Some(epoch) = epoch {
+            query_params.push(("epoch".into(), format!("{}", epoch.as_u64())));
+        }
+
+        self.url("state/randao_mix")
+            .into_future()
+            .and_then(move |url| client.json_get(url, query_params))
+    }
+
     /// Returns the root of the state at the given slot.
     pub fn get_state_root(&self, slot: Slot) -> impl Future<Item = Hash256, Error = Error> {
         let client = self.0.clone();
@@ -571,6 +684,31 @@ impl<E: EthSpec> Beacon<E> {
             .and_then(move |url| client.json_get(url, query_params))
     }
 
+    /// Returns the balances and rewards of the given validators for each epoch from
+    /// `start_epoch` to `end_epoch` (inclusive).
+    pub fn get_validator_rewards(
+        &self,
+        validator_indices: Vec<usize>,
+        start_epoch: Epoch,
+        end_epoch: Epoch,
+    ) -> impl Future<Item = Vec<ValidatorRewardsResponse>, Error = Error> {
+        let client = self.0.clone();
+
+        let mut query_params = vec![
+            ("start_epoch".into(), format!("{}", start_epoch.as_u64())),
+            ("end_epoch".into(), format!("{}", end_epoch.as_u64())),
+        ];
+        query_params.extend(
+            validator_indices
+                .into_iter()
+                .map(|index| ("validator_indices".into(), format!("{}", index))),
+        );
+
+        self.url("validators/rewards")
+            .into_future()
+            .and_then(move |url| client.json_get(url, query_params))
+    }
+
     /// Returns committees at the given epoch.
     pub fn get_committees(
         &self,
@@ -614,6 +752,24 @@ impl<E: EthSpec> Beacon<E> {
                     .and_then(|mut success| success.json().map_err(Error::from))
             })
     }
+
+    /// Submits a signed voluntary exit to the beacon node, which will add it to the operation pool
+    /// and publish it to the network.
+    pub fn voluntary_exit(
+        &self,
+        exit: SignedVoluntaryExit,
+    ) -> impl Future<Item = bool, Error = Error> {
+        let client = self.0.clone();
+
+        self.url("voluntary_exit")
+            .into_future()
+            .and_then(move |url| {
+                client
+                    .json_post::<_>(url, exit)
+                    .and_then(|response| error_for_status(response).map_err(Error::from))
+                    .and_then(|mut success| success.json().map_err(Error::from))
+            })
+    }
 }
 
 /// Provides the functions on the `/spec` endpoint of the node.
@@ -722,6 +878,22 @@ impl<E: EthSpec> Consensus<E> {
             .and_then(|mut success| success.json().map_err(Error::from))
     }
 
+    /// Returns whether each of the validators with the given `indices` was live during `epoch`.
+    pub fn get_liveness(
+        &self,
+        epoch: Epoch,
+        indices: Vec<u64>,
+    ) -> impl Future<Item = Vec<LivenessResponse>, Error = Error> {
+        let client = self.0.clone();
+        let req_body = LivenessRequest { epoch, indices };
+
+        self.url("liveness")
+            .into_future()
+            .and_then(move |url| client.json_post::<_>(url, req_body))
+            .and_then(|response| error_for_status(response).map_err(Error::from))
+            .and_then(|mut success| success.json().map_err(Error::from))
+    }
+
     /// Gets a `VoteCount` for the given `epoch`.
     pub fn get_vote_count(
         &self,
@@ -3,8 +3,145 @@
 use bls::PublicKeyBytes;
 use serde::{Deserialize, Serialize};
 use ssz_derive::{Decode, Encode};
+use std::fmt;
+use std::str::FromStr;
 use types::beacon_state::EthSpec;
-use types::{BeaconState, CommitteeIndex, Hash256, SignedBeaconBlock, Slot, Validator};
+use types::{
+    BeaconState, Checkpoint, CommitteeIndex, Epoch, Hash256, SignedBeaconBlock, Slot, Validator,
+};
+
+/// Identifies a `BeaconState` in the `state_id` query parameter.
+///
+/// Formatted as `head`, `genesis`, `finalized`, `justified`, a decimal slot or a `0x`-prefixed
+/// state root.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StateId {
+    /// The state of the canonical head.
+    Head,
+    /// The state at the genesis slot.
+    Genesis,
+    /// The state at the start slot of the finalized epoch of the head.
+    Finalized,
+    /// The state at the start slot of the current justified epoch of the head.
+    Justified,
+    /// The state at the given slot in the canonical chain.
+    Slot(Slot),
+    /// The state with the given root.
+    Root(Hash256),
+}
+
+/// Identifies a `SignedBeaconBlock` in the `block_id` query parameter.
+///
+/// Formatted as `head`, `genesis`, `finalized`, `justified`, a decimal slot or a `0x`-prefixed
+/// block root.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BlockId {
+    /// The canonical head block.
+    Head,
+    /// The genesis block.
+    Genesis,
+    /// The block of the finalized checkpoint of the head.
+    Finalized,
+    /// The block of the current justified checkpoint of the head.
+    Justified,
+    /// The block at the given slot in the canonical chain.
+    Slot(Slot),
+    /// The block with the given root.
+    Root(Hash256),
+}
+
+/// The parts of a `StateId` or `BlockId` that are shared by both.
+enum Selector {
+    Head,
+    Genesis,
+    Finalized,
+    Justified,
+    Slot(Slot),
+    Root(Hash256),
+}
+
+impl FromStr for Selector {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "head" => Ok(Selector::Head),
+            "genesis" => Ok(Selector::Genesis),
+            "finalized" => Ok(Selector::Finalized),
+            "justified" => Ok(Selector::Justified),
+            other if other.starts_with("0x") => Hash256::from_str(&other[2..])
+                .map(Selector::Root)
+                .map_err(|e| format!("Unable to parse root {}: {:?}", other, e)),
+            other => other
+                .parse::<u64>()
+                .map(|slot| Selector::Slot(Slot::new(slot)))
+                .map_err(|_| {
+                    format!(
+                        "{} is not head, genesis, finalized, justified, a slot or a root",
+                        other
+                    )
+                }),
+        }
+    }
+}
+
+impl fmt::Display for Selector {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Selector::Head => write!(f, "head"),
+            Selector::Genesis => write!(f, "genesis"),
+            Selector::Finalized => write!(f, "finalized"),
+            Selector::Justified => write!(f, "justified"),
+            Selector::Slot(slot) => write!(f, "{}", slot),
+            Selector::Root(root) => write!(f, "0x{:x}", root),
+        }
+    }
+}
+
+macro_rules! impl_selector {
+    ($type: ident) => {
+        impl $type {
+            fn from_selector(selector: Selector) -> Self {
+                match selector {
+                    Selector::Head => $type::Head,
+                    Selector::Genesis => $type::Genesis,
+                    Selector::Finalized => $type::Finalized,
+                    Selector::Justified => $type::Justified,
+                    Selector::Slot(slot) => $type::Slot(slot),
+                    Selector::Root(root) => $type::Root(root),
+                }
+            }
+
+            fn to_selector(self) -> Selector {
+                match self {
+                    $type::Head => Selector::Head,
+                    $type::Genesis => Selector::Genesis,
+                    $type::Finalized => Selector::Finalized,
+                    $type::Justified => Selector::Justified,
+                    $type::Slot(slot) => Selector::Slot(slot),
+                    $type::Root(root) => Selector::Root(root),
+                }
+            }
+        }
+
+        impl FromStr for $type {
+            type Err = String;
+
+            fn from_str(s: &str) -> Result<Self, String> {
+                Selector::from_str(s).map(Self::from_selector)
+            }
+        }
+
+        impl fmt::Display for $type {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                self.to_selector().fmt(f)
+            }
+        }
+    };
+}
+
+impl_selector!(StateId);
+impl_selector!(BlockId);
 
 /// Information about a block that is at the head of a chain. May or may not represent the
 /// canonical head.
@@ -63,3 +200,86 @@ pub struct StateResponse<T: EthSpec> {
     pub root: Hash256,
     pub beacon_state: BeaconState<T>,
 }
+
+/// The checkpoints of a `BeaconState`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Encode, Decode)]
+pub struct FinalityCheckpointsResponse {
+    pub previous_justified: Checkpoint,
+    pub current_justified: Checkpoint,
+    pub finalized: Checkpoint,
+}
+
+/// The balance of a validator in a `BeaconState`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Encode, Decode)]
+pub struct ValidatorBalance {
+    pub validator_index: usize,
+    pub pubkey: PublicKeyBytes,
+    pub balance: u64,
+}
+
+/// The balance of a validator at the end of an epoch and the changes to it in the following epoch
+/// transition.
+///
+/// The attestation rewards and penalties are for the duties of the previous epoch, which are
+/// settled in the transition at the end of `epoch`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Encode, Decode)]
+pub struct EpochRewards {
+    pub epoch: Epoch,
+    /// The balance at the last slot of `epoch`, before the epoch transition.
+    pub balance: u64,
+    pub effective_balance: u64,
+    pub source_reward: u64,
+    pub source_penalty: u64,
+    pub target_reward: u64,
+    pub target_penalty: u64,
+    pub head_reward: u64,
+    pub head_penalty: u64,
+    pub inclusion_delay_reward: u64,
+    pub inactivity_penalty: u64,
+    pub proposer_reward: u64,
+    /// The correlated slashing penalty, applied midway through the withdrawal delay of a slashed
+    /// validator.
+    pub slashing_penalty: u64,
+}
+
+/// The balance and reward history of a validator.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Encode, Decode)]
+pub struct ValidatorRewardsResponse {
+    pub validator_index: usize,
+    pub pubkey: PublicKeyBytes,
+    pub epochs: Vec<EpochRewards>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn state_id_round_trip() {
+        let root = Hash256::repeat_byte(0xab);
+        for id in &[
+            StateId::Head,
+            StateId::Genesis,
+            StateId::Finalized,
+            StateId::Justified,
+            StateId::Slot(Slot::new(42)),
+            StateId::Root(root),
+        ] {
+            assert_eq!(id.to_string().parse::<StateId>(), Ok(*id));
+        }
+
+        assert_eq!(
+            format!("0x{:x}", root).parse::<BlockId>(),
+            Ok(BlockId::Root(root))
+        );
+        assert_eq!("0".parse::<BlockId>(), Ok(BlockId::Slot(Slot::new(0))));
+    }
+
+    #[test]
+    fn invalid_ids() {
+        assert!("".parse::<StateId>().is_err());
+        assert!("latest".parse::<StateId>().is_err());
+        assert!("-1".parse::<StateId>().is_err());
+        assert!("0x1234".parse::<BlockId>().is_err());
+    }
+}
@@ -64,3 +64,22 @@ pub struct IndividualVotesResponse {
     /// Voting statistics for the validator, if they voted in the given epoch.
     pub vote: Option<IndividualVote>,
 }
+
+#[derive(PartialEq, Debug, Serialize, Deserialize, Clone, Encode, Decode)]
+pub struct LivenessRequest {
+    /// The epoch in which to check for activity.
+    pub epoch: Epoch,
+    /// The indices of the validators to check.
+    pub indices: Vec<u64>,
+}
+
+#[derive(PartialEq, Debug, Serialize, Deserialize, Clone, Encode, Decode)]
+pub struct LivenessResponse {
+    /// The index of the validator in state.validators.
+    pub index: u64,
+    /// The epoch in which activity was checked.
+    pub epoch: Epoch,
+    /// True if the validator had an attestation for `epoch` included in the chain or proposed a
+    /// block during `epoch`.
+    pub is_live: bool,
+}
@@ -8,14 +8,18 @@ mod node;
 mod validator;
 
 pub use beacon::{
-    BlockResponse, CanonicalHeadResponse, Committee, HeadBeaconBlock, StateResponse,
-    ValidatorRequest, ValidatorResponse,
+    BlockId, BlockResponse, CanonicalHeadResponse, Committee, EpochRewards,
+    FinalityCheckpointsResponse, HeadBeaconBlock, StateId, StateResponse, ValidatorBalance,
+    ValidatorRequest, ValidatorResponse, ValidatorRewardsResponse,
 };
 
 pub use validator::{
     ValidatorDutiesRequest, ValidatorDuty, ValidatorDutyBytes, ValidatorSubscription,
 };
 
-pub use consensus::{IndividualVote, IndividualVotesRequest, IndividualVotesResponse};
+pub use consensus::{
+    IndividualVote, IndividualVotesRequest, IndividualVotesResponse, LivenessRequest,
+    LivenessResponse,
+};
 
 pub use node::{SyncingResponse, SyncingStatus};
@@ -129,8 +129,11 @@ impl<E: EthSpec> LocalNetwork<E> {
                     .http_listen_addr()
                     .expect("Must have http started");
 
-                validator_config.http_server =
-                    format!("http://{}:{}", socket_addr.ip(), socket_addr.port());
+                validator_config.beacon_nodes = vec![format!(
+                    "http://{}:{}",
+                    socket_addr.ip(),
+                    socket_addr.port()
+                )];
 
                 validator_config
             })
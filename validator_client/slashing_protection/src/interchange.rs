@@ -0,0 +1,118 @@
+//! A portable JSON representation of slashing protection history.
+//!
+//! The format follows the cross-client "interchange" format, which allows validators to be moved
+//! between machines (or between clients) without losing their signing history.
+//!
+//! Integers are encoded as decimal strings and roots as `0x`-prefixed hex strings, e.g.:
+//!
+//! ```json
+//! {
+//!   "metadata": {
+//!     "interchange_format_version": "5",
+//!     "genesis_validators_root": "0x04700007fabc8282644aed6d1c7c9e21d38a03a0c4ba193f3afe428824b3a673"
+//!   },
+//!   "data": [
+//!     {
+//!       "pubkey": "0xb845089a1457f811bfc000588fbb4e713669be8ce060ea6be3c6ece09afc3794106c91ca73acda5e5457122d58723bed",
+//!       "signed_blocks": [
+//!         { "slot": "81952", "signing_root": "0x4ff6f743a43f3b4f95350831aeaf0a122a1a392922c45d804280284a69eb850b" }
+//!       ],
+//!       "signed_attestations": [
+//!         { "source_epoch": "2290", "target_epoch": "3007" }
+//!       ]
+//!     }
+//!   ]
+//! }
+//! ```
+
+use serde_derive::{Deserialize, Serialize};
+use std::io;
+use types::{Epoch, Hash256, PublicKey, Slot};
+
+/// The only version of the interchange format that we support.
+pub const INTERCHANGE_FORMAT_VERSION: u64 = 5;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct InterchangeMetadata {
+    #[serde(with = "quoted")]
+    pub interchange_format_version: u64,
+    pub genesis_validators_root: Hash256,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct InterchangeData {
+    pub pubkey: PublicKey,
+    pub signed_blocks: Vec<SignedBlock>,
+    pub signed_attestations: Vec<SignedAttestation>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SignedBlock {
+    #[serde(with = "quoted")]
+    pub slot: Slot,
+    /// The signing root is optional, a block without one can never be signed again.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signing_root: Option<Hash256>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SignedAttestation {
+    #[serde(with = "quoted")]
+    pub source_epoch: Epoch,
+    #[serde(with = "quoted")]
+    pub target_epoch: Epoch,
+    /// The signing root is optional, an attestation without one can never be signed again.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signing_root: Option<Hash256>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Interchange {
+    pub metadata: InterchangeMetadata,
+    pub data: Vec<InterchangeData>,
+}
+
+impl Interchange {
+    pub fn from_json_str(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    pub fn from_json_reader(reader: impl io::Read) -> Result<Self, serde_json::Error> {
+        serde_json::from_reader(reader)
+    }
+
+    pub fn write_to(&self, writer: impl io::Write) -> Result<(), serde_json::Error> {
+        serde_json::to_writer_pretty(writer, self)
+    }
+}
+
+/// Serialize and deserialize `u64`-like values as decimal strings.
+mod quoted {
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S, T>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+        T: Copy + Into<u64>,
+    {
+        let int: u64 = (*value).into();
+        serializer.serialize_str(&int.to_string())
+    }
+
+    pub fn deserialize<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: From<u64>,
+    {
+        let string = String::deserialize(deserializer)?;
+        string
+            .parse::<u64>()
+            .map(T::from)
+            .map_err(|e| D::Error::custom(format!("Invalid quoted integer {}: {}", string, e)))
+    }
+}
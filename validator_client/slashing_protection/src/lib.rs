@@ -0,0 +1,54 @@
+//! Provides a persistent, per-validator record of signed blocks and attestations which is used to
+//! refuse the signing of slashable messages.
+//!
+//! The database is stored as a single SQLite file. All checks are performed inside an exclusive
+//! transaction, so it is safe for multiple processes (e.g., two validator clients pointed at the
+//! same directory) to share a single database file.
+
+pub mod interchange;
+mod signed_attestation;
+mod signed_block;
+mod slashing_database;
+
+pub use crate::signed_attestation::{InvalidAttestation, SignedAttestation};
+pub use crate::signed_block::{InvalidBlock, SignedBlock};
+pub use crate::slashing_database::{
+    InterchangeError, SlashingDatabase, SLASHING_PROTECTION_FILENAME,
+};
+
+use std::io::{Error as IOError, ErrorKind};
+use types::PublicKey;
+
+/// The attestation or block is not safe to sign.
+///
+/// This could be because it's slashable, or because an error occurred.
+#[derive(PartialEq, Debug)]
+pub enum NotSafe {
+    /// The validator has not been registered with the database.
+    UnregisteredValidator(PublicKey),
+    InvalidBlock(InvalidBlock),
+    InvalidAttestation(InvalidAttestation),
+    IOError(ErrorKind),
+    SQLError(String),
+}
+
+/// The attestation or block is safe to sign, and will not cause the signer to be slashed.
+#[derive(PartialEq, Debug)]
+pub enum Safe {
+    /// Casting the exact same data (block or attestation) twice is never slashable.
+    SameData,
+    /// Incoming data is safe from slashing, and is not a duplicate.
+    Valid,
+}
+
+impl From<IOError> for NotSafe {
+    fn from(error: IOError) -> NotSafe {
+        NotSafe::IOError(error.kind())
+    }
+}
+
+impl From<rusqlite::Error> for NotSafe {
+    fn from(error: rusqlite::Error) -> NotSafe {
+        NotSafe::SQLError(error.to_string())
+    }
+}
@@ -0,0 +1,37 @@
+use types::{Epoch, Hash256};
+
+/// An attestation that has been signed by a validator and recorded in the database.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SignedAttestation {
+    pub source_epoch: Epoch,
+    pub target_epoch: Epoch,
+    pub signing_root: Hash256,
+}
+
+/// Reasons why an attestation may be slashable (or slashable in future).
+#[derive(PartialEq, Debug)]
+pub enum InvalidAttestation {
+    /// The attestation has the same target epoch as an attestation from the DB (enclosed).
+    DoubleVote(SignedAttestation),
+    /// The attestation surrounds an existing attestation from the database (`prev`).
+    NewSurroundsPrev { prev: SignedAttestation },
+    /// The attestation is surrounded by an existing attestation from the database (`prev`).
+    PrevSurroundsNew { prev: SignedAttestation },
+    /// The attestation is invalid because its source epoch is greater than its target epoch.
+    SourceExceedsTarget,
+    /// The attestation's source epoch is less than the minimum source epoch in the database.
+    SourceLessThanLowerBound { source: Epoch, bound: Epoch },
+    /// The attestation's target epoch is less than or equal to the minimum target epoch in the
+    /// database.
+    TargetLessThanOrEqLowerBound { target: Epoch, bound: Epoch },
+}
+
+impl SignedAttestation {
+    pub fn new(source_epoch: Epoch, target_epoch: Epoch, signing_root: Hash256) -> Self {
+        Self {
+            source_epoch,
+            target_epoch,
+            signing_root,
+        }
+    }
+}
@@ -0,0 +1,26 @@
+use types::{Hash256, Slot};
+
+/// A block proposal that has been signed by a validator and recorded in the database.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SignedBlock {
+    pub slot: Slot,
+    pub signing_root: Hash256,
+}
+
+/// Reasons why a block may be slashable (or slashable in future).
+#[derive(PartialEq, Debug)]
+pub enum InvalidBlock {
+    /// A different block has already been signed at this slot.
+    DoubleBlockProposal(SignedBlock),
+    /// The block is at a slot at or prior to the earliest block in the database.
+    ///
+    /// We cannot safely sign such a block because we may have pruned (or never received) the
+    /// history that would reveal a conflict.
+    SlotViolatesLowerBound { block_slot: Slot, bound_slot: Slot },
+}
+
+impl SignedBlock {
+    pub fn new(slot: Slot, signing_root: Hash256) -> Self {
+        Self { slot, signing_root }
+    }
+}
@@ -0,0 +1,1095 @@
+use crate::interchange::{
+    self, Interchange, InterchangeData, InterchangeMetadata, INTERCHANGE_FORMAT_VERSION,
+};
+use crate::signed_attestation::InvalidAttestation;
+use crate::signed_block::InvalidBlock;
+use crate::{NotSafe, Safe, SignedAttestation, SignedBlock};
+use parking_lot::Mutex;
+use rusqlite::{params, OptionalExtension, Transaction, TransactionBehavior};
+use std::fs::OpenOptions;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use types::{AttestationData, BeaconBlockHeader, Epoch, Hash256, PublicKey, SignedRoot, Slot};
+
+/// The name of the database file, relative to the validators directory.
+pub const SLASHING_PROTECTION_FILENAME: &str = "slashing_protection.sqlite";
+
+/// How long to wait for a lock on the database held by another process.
+///
+/// This is the maximum amount of time that a signing request will be blocked whilst another
+/// validator client (or an `account_manager` command) writes to the database.
+pub const DEFAULT_BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug)]
+pub enum InterchangeError {
+    UnsupportedVersion(u64),
+    GenesisValidatorsMismatch {
+        interchange_file: Hash256,
+        client: Hash256,
+    },
+    InvalidPubkey(String),
+    /// Unable to determine a watermark from a non-empty set of records (should be unreachable).
+    MaxInconsistent,
+    SQLError(String),
+    NotSafe(NotSafe),
+}
+
+impl From<NotSafe> for InterchangeError {
+    fn from(error: NotSafe) -> Self {
+        InterchangeError::NotSafe(error)
+    }
+}
+
+impl From<rusqlite::Error> for InterchangeError {
+    fn from(error: rusqlite::Error) -> Self {
+        InterchangeError::SQLError(error.to_string())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SlashingDatabase {
+    conn: Arc<Mutex<rusqlite::Connection>>,
+}
+
+impl SlashingDatabase {
+    /// Open an existing database at the given `path`, or create one if none exists.
+    pub fn open_or_create(path: &Path) -> Result<Self, NotSafe> {
+        if path.exists() {
+            Self::open(path)
+        } else {
+            Self::create(path)
+        }
+    }
+
+    /// Create a slashing database at the given path.
+    ///
+    /// Error if a database (or any file) already exists at `path`.
+    pub fn create(path: &Path) -> Result<Self, NotSafe> {
+        let file = OpenOptions::new()
+            .write(true)
+            .read(true)
+            .create_new(true)
+            .open(path)?;
+
+        // Ensure the file has the correct permissions before writing anything to it.
+        let mut perm = file.metadata()?.permissions();
+        perm.set_mode((libc::S_IWUSR | libc::S_IRUSR) as u32);
+        file.set_permissions(perm)?;
+
+        let conn = Self::open_conn(path)?;
+
+        conn.execute(
+            "CREATE TABLE validators (
+                id INTEGER PRIMARY KEY,
+                public_key TEXT NOT NULL UNIQUE
+            )",
+            params![],
+        )?;
+
+        conn.execute(
+            "CREATE TABLE signed_blocks (
+                validator_id INTEGER NOT NULL,
+                slot INTEGER NOT NULL,
+                signing_root BLOB NOT NULL,
+                FOREIGN KEY(validator_id) REFERENCES validators(id),
+                UNIQUE (validator_id, slot)
+            )",
+            params![],
+        )?;
+
+        conn.execute(
+            "CREATE TABLE signed_attestations (
+                validator_id INTEGER,
+                source_epoch INTEGER NOT NULL,
+                target_epoch INTEGER NOT NULL,
+                signing_root BLOB NOT NULL,
+                FOREIGN KEY(validator_id) REFERENCES validators(id),
+                UNIQUE (validator_id, target_epoch)
+            )",
+            params![],
+        )?;
+
+        Ok(Self::from_conn(conn))
+    }
+
+    /// Open an existing `SlashingDatabase` from disk.
+    pub fn open(path: &Path) -> Result<Self, NotSafe> {
+        let conn = Self::open_conn(path)?;
+        Ok(Self::from_conn(conn))
+    }
+
+    fn open_conn(path: &Path) -> Result<rusqlite::Connection, NotSafe> {
+        let conn = rusqlite::Connection::open(path)?;
+        conn.busy_timeout(DEFAULT_BUSY_TIMEOUT)?;
+        conn.execute_batch("PRAGMA foreign_keys = ON;")?;
+        Ok(conn)
+    }
+
+    fn from_conn(conn: rusqlite::Connection) -> Self {
+        Self {
+            conn: Arc::new(Mutex::new(conn)),
+        }
+    }
+
+    /// Register validators with the slashing protection database.
+    ///
+    /// This allows the validator to record their signatures in the database, and check
+    /// for slashings. Registering a validator that is already known is a no-op.
+    pub fn register_validators<'a>(
+        &self,
+        public_keys: impl Iterator<Item = &'a PublicKey>,
+    ) -> Result<(), NotSafe> {
+        let mut conn = self.conn.lock();
+        let txn = conn.transaction_with_behavior(TransactionBehavior::Exclusive)?;
+
+        {
+            let mut stmt =
+                txn.prepare("INSERT OR IGNORE INTO validators (public_key) VALUES (?1)")?;
+
+            for pubkey in public_keys {
+                stmt.execute(params![pubkey.as_hex_string()])?;
+            }
+        }
+
+        txn.commit()?;
+        Ok(())
+    }
+
+    /// Check a block proposal from `validator_pubkey` for slash safety, and if it is safe, record
+    /// it in the database.
+    ///
+    /// The checking and inserting happen atomically and exclusively. We enforce exclusivity to
+    /// prevent concurrent checks and inserts from resulting in slashable data being inserted.
+    ///
+    /// This is the safe, externally-callable interface for checking block proposals.
+    pub fn check_and_insert_block_proposal(
+        &self,
+        validator_pubkey: &PublicKey,
+        block_header: &BeaconBlockHeader,
+        domain: Hash256,
+    ) -> Result<Safe, NotSafe> {
+        self.check_and_insert_block_signing_root(
+            validator_pubkey,
+            block_header.slot,
+            block_header.signing_root(domain),
+        )
+    }
+
+    /// As for `check_and_insert_block_proposal` but without requiring the whole block header.
+    pub fn check_and_insert_block_signing_root(
+        &self,
+        validator_pubkey: &PublicKey,
+        slot: Slot,
+        signing_root: Hash256,
+    ) -> Result<Safe, NotSafe> {
+        let mut conn = self.conn.lock();
+        let txn = conn.transaction_with_behavior(TransactionBehavior::Exclusive)?;
+
+        let safe = check_block_proposal(&txn, validator_pubkey, slot, signing_root)?;
+
+        if safe != Safe::SameData {
+            insert_block_proposal(&txn, validator_pubkey, slot, signing_root)?;
+        }
+
+        txn.commit()?;
+        Ok(safe)
+    }
+
+    /// Check an attestation from `validator_pubkey` for slash safety, and if it is safe, record it
+    /// in the database.
+    ///
+    /// The checking and inserting happen atomically and exclusively. We enforce exclusivity to
+    /// prevent concurrent checks and inserts from resulting in slashable data being inserted.
+    ///
+    /// This is the safe, externally-callable interface for checking attestations.
+    pub fn check_and_insert_attestation(
+        &self,
+        validator_pubkey: &PublicKey,
+        attestation: &AttestationData,
+        domain: Hash256,
+    ) -> Result<Safe, NotSafe> {
+        self.check_and_insert_attestation_signing_root(
+            validator_pubkey,
+            attestation.source.epoch,
+            attestation.target.epoch,
+            attestation.signing_root(domain),
+        )
+    }
+
+    /// As for `check_and_insert_attestation` but without requiring the whole `AttestationData`.
+    pub fn check_and_insert_attestation_signing_root(
+        &self,
+        validator_pubkey: &PublicKey,
+        att_source_epoch: Epoch,
+        att_target_epoch: Epoch,
+        signing_root: Hash256,
+    ) -> Result<Safe, NotSafe> {
+        let mut conn = self.conn.lock();
+        let txn = conn.transaction_with_behavior(TransactionBehavior::Exclusive)?;
+
+        let safe = check_attestation(
+            &txn,
+            validator_pubkey,
+            att_source_epoch,
+            att_target_epoch,
+            signing_root,
+        )?;
+
+        if safe != Safe::SameData {
+            insert_attestation(
+                &txn,
+                validator_pubkey,
+                att_source_epoch,
+                att_target_epoch,
+                signing_root,
+            )?;
+        }
+
+        txn.commit()?;
+        Ok(safe)
+    }
+
+    /// Import slashing protection history from the interchange format.
+    ///
+    /// Records are merged with the existing history by keeping, for each validator, only the most
+    /// conservative watermarks: the highest block slot, and the highest attestation source and
+    /// target epochs from either the database or the `interchange`. Future messages are then
+    /// required to be strictly beyond those watermarks (see the lower-bound checks).
+    ///
+    /// The import is all-or-nothing, a failure leaves the database unchanged.
+    pub fn import_interchange_info(
+        &self,
+        interchange: &Interchange,
+        genesis_validators_root: Hash256,
+    ) -> Result<(), InterchangeError> {
+        let version = interchange.metadata.interchange_format_version;
+        if version != INTERCHANGE_FORMAT_VERSION {
+            return Err(InterchangeError::UnsupportedVersion(version));
+        }
+
+        if genesis_validators_root != interchange.metadata.genesis_validators_root {
+            return Err(InterchangeError::GenesisValidatorsMismatch {
+                client: genesis_validators_root,
+                interchange_file: interchange.metadata.genesis_validators_root,
+            });
+        }
+
+        let mut conn = self.conn.lock();
+        let txn = conn.transaction_with_behavior(TransactionBehavior::Exclusive)?;
+
+        for record in &interchange.data {
+            txn.execute(
+                "INSERT OR IGNORE INTO validators (public_key) VALUES (?1)",
+                params![record.pubkey.as_hex_string()],
+            )?;
+            let validator_id = get_validator_id(&txn, &record.pubkey)?;
+
+            import_signed_blocks(&txn, validator_id, &record.signed_blocks)?;
+            import_signed_attestations(&txn, validator_id, &record.signed_attestations)?;
+        }
+
+        txn.commit()?;
+        Ok(())
+    }
+
+    /// Export the complete slashing protection history of all registered validators.
+    pub fn export_interchange_info(
+        &self,
+        genesis_validators_root: Hash256,
+    ) -> Result<Interchange, InterchangeError> {
+        let mut conn = self.conn.lock();
+        let txn = conn.transaction_with_behavior(TransactionBehavior::Exclusive)?;
+
+        let validators = {
+            let mut stmt = txn.prepare("SELECT id, public_key FROM validators ORDER BY id ASC")?;
+            let rows = stmt.query_map(params![], |row| {
+                Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+            })?;
+            rows.collect::<Result<Vec<_>, _>>()?
+        };
+
+        let data = validators
+            .into_iter()
+            .map(|(validator_id, public_key)| {
+                let signed_blocks = select_signed_blocks(&txn, validator_id)?
+                    .into_iter()
+                    .map(|block| interchange::SignedBlock {
+                        slot: block.slot,
+                        signing_root: known_root(block.signing_root),
+                    })
+                    .collect();
+
+                let signed_attestations = select_signed_attestations(&txn, validator_id)?
+                    .into_iter()
+                    .map(|att| interchange::SignedAttestation {
+                        source_epoch: att.source_epoch,
+                        target_epoch: att.target_epoch,
+                        signing_root: known_root(att.signing_root),
+                    })
+                    .collect();
+
+                Ok(InterchangeData {
+                    pubkey: parse_public_key(&public_key)?,
+                    signed_blocks,
+                    signed_attestations,
+                })
+            })
+            .collect::<Result<Vec<_>, InterchangeError>>()?;
+
+        txn.commit()?;
+
+        Ok(Interchange {
+            metadata: InterchangeMetadata {
+                interchange_format_version: INTERCHANGE_FORMAT_VERSION,
+                genesis_validators_root,
+            },
+            data,
+        })
+    }
+}
+
+/// Get the database-internal ID for a validator.
+///
+/// This is NOT the same as a validator index, and depends on the ordering that validators
+/// are registered with the slashing protection database (and may vary between machines).
+fn get_validator_id(txn: &Transaction, public_key: &PublicKey) -> Result<i64, NotSafe> {
+    txn.query_row(
+        "SELECT id FROM validators WHERE public_key = ?1",
+        params![public_key.as_hex_string()],
+        |row| row.get(0),
+    )
+    .optional()?
+    .ok_or_else(|| NotSafe::UnregisteredValidator(public_key.clone()))
+}
+
+/// Check a block proposal for slash safety, without recording it.
+fn check_block_proposal(
+    txn: &Transaction,
+    validator_pubkey: &PublicKey,
+    slot: Slot,
+    signing_root: Hash256,
+) -> Result<Safe, NotSafe> {
+    let validator_id = get_validator_id(txn, validator_pubkey)?;
+
+    let existing_block = txn
+        .query_row(
+            "SELECT slot, signing_root
+             FROM signed_blocks
+             WHERE validator_id = ?1 AND slot = ?2",
+            params![validator_id, slot.as_u64() as i64],
+            signed_block_from_row,
+        )
+        .optional()?;
+
+    if let Some(existing_block) = existing_block {
+        if existing_block.signing_root == signing_root {
+            // Same slot and same hash -> we're re-broadcasting a previously signed block
+            return Ok(Safe::SameData);
+        } else {
+            // Same epoch but not the same hash -> it's a DoubleBlockProposal
+            return Err(NotSafe::InvalidBlock(InvalidBlock::DoubleBlockProposal(
+                existing_block,
+            )));
+        }
+    }
+
+    // Refuse to sign blocks prior to the earliest block we know about.
+    let min_slot: Option<i64> = txn.query_row(
+        "SELECT MIN(slot) FROM signed_blocks WHERE validator_id = ?1",
+        params![validator_id],
+        |row| row.get(0),
+    )?;
+
+    if let Some(min_slot) = min_slot.map(|slot| Slot::new(slot as u64)) {
+        if slot <= min_slot {
+            return Err(NotSafe::InvalidBlock(
+                InvalidBlock::SlotViolatesLowerBound {
+                    block_slot: slot,
+                    bound_slot: min_slot,
+                },
+            ));
+        }
+    }
+
+    Ok(Safe::Valid)
+}
+
+/// Check an attestation for slash safety, without recording it.
+fn check_attestation(
+    txn: &Transaction,
+    validator_pubkey: &PublicKey,
+    att_source_epoch: Epoch,
+    att_target_epoch: Epoch,
+    signing_root: Hash256,
+) -> Result<Safe, NotSafe> {
+    // Although it's not required to avoid slashing, we disallow attestations
+    // which are obviously invalid by virtue of their source epoch exceeding their target.
+    if att_source_epoch > att_target_epoch {
+        return Err(NotSafe::InvalidAttestation(
+            InvalidAttestation::SourceExceedsTarget,
+        ));
+    }
+
+    let validator_id = get_validator_id(txn, validator_pubkey)?;
+
+    let source = att_source_epoch.as_u64() as i64;
+    let target = att_target_epoch.as_u64() as i64;
+
+    // 1. Check for a double vote. Namely, an existing attestation with the same target epoch,
+    //    and a different signing root.
+    let same_target_att = txn
+        .query_row(
+            "SELECT source_epoch, target_epoch, signing_root
+             FROM signed_attestations
+             WHERE validator_id = ?1 AND target_epoch = ?2",
+            params![validator_id, target],
+            signed_attestation_from_row,
+        )
+        .optional()?;
+
+    if let Some(existing_attestation) = same_target_att {
+        // If the new attestation is identical to the existing attestation, then we already
+        // know that it is safe, and can return immediately.
+        if existing_attestation.signing_root == signing_root {
+            return Ok(Safe::SameData);
+        } else {
+            return Err(NotSafe::InvalidAttestation(InvalidAttestation::DoubleVote(
+                existing_attestation,
+            )));
+        }
+    }
+
+    // 2. Check that no previous vote is surrounding `attestation`.
+    // If there is a surrounding attestation, we only return the most recent one.
+    let surrounding_attestation = txn
+        .query_row(
+            "SELECT source_epoch, target_epoch, signing_root
+             FROM signed_attestations
+             WHERE validator_id = ?1 AND source_epoch < ?2 AND target_epoch > ?3
+             ORDER BY target_epoch DESC
+             LIMIT 1",
+            params![validator_id, source, target],
+            signed_attestation_from_row,
+        )
+        .optional()?;
+
+    if let Some(prev) = surrounding_attestation {
+        return Err(NotSafe::InvalidAttestation(
+            InvalidAttestation::PrevSurroundsNew { prev },
+        ));
+    }
+
+    // 3. Check that no previous vote is surrounded by `attestation`.
+    // If there is a surrounded attestation, we only return the most recent one.
+    let surrounded_attestation = txn
+        .query_row(
+            "SELECT source_epoch, target_epoch, signing_root
+             FROM signed_attestations
+             WHERE validator_id = ?1 AND source_epoch > ?2 AND target_epoch < ?3
+             ORDER BY target_epoch DESC
+             LIMIT 1",
+            params![validator_id, source, target],
+            signed_attestation_from_row,
+        )
+        .optional()?;
+
+    if let Some(prev) = surrounded_attestation {
+        return Err(NotSafe::InvalidAttestation(
+            InvalidAttestation::NewSurroundsPrev { prev },
+        ));
+    }
+
+    // 4. Check lower bounds: ensure that source is greater than or equal to min source,
+    // and target is greater than min target. This allows pruning, and compatibility
+    // with the interchange format's minimal representation.
+    let (min_source, min_target): (Option<i64>, Option<i64>) = txn.query_row(
+        "SELECT MIN(source_epoch), MIN(target_epoch)
+         FROM signed_attestations
+         WHERE validator_id = ?1",
+        params![validator_id],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )?;
+
+    if let Some(bound) = min_source.map(|epoch| Epoch::new(epoch as u64)) {
+        if att_source_epoch < bound {
+            return Err(NotSafe::InvalidAttestation(
+                InvalidAttestation::SourceLessThanLowerBound {
+                    source: att_source_epoch,
+                    bound,
+                },
+            ));
+        }
+    }
+
+    if let Some(bound) = min_target.map(|epoch| Epoch::new(epoch as u64)) {
+        if att_target_epoch <= bound {
+            return Err(NotSafe::InvalidAttestation(
+                InvalidAttestation::TargetLessThanOrEqLowerBound {
+                    target: att_target_epoch,
+                    bound,
+                },
+            ));
+        }
+    }
+
+    // Everything has been checked, return Valid.
+    Ok(Safe::Valid)
+}
+
+/// Insert a block proposal into the slashing database.
+///
+/// This should *only* be called in the same (exclusive) transaction as `check_block_proposal`
+/// so that the check isn't invalidated by a concurrent mutation.
+fn insert_block_proposal(
+    txn: &Transaction,
+    validator_pubkey: &PublicKey,
+    slot: Slot,
+    signing_root: Hash256,
+) -> Result<(), NotSafe> {
+    let validator_id = get_validator_id(txn, validator_pubkey)?;
+
+    txn.execute(
+        "INSERT INTO signed_blocks (validator_id, slot, signing_root)
+         VALUES (?1, ?2, ?3)",
+        params![validator_id, slot.as_u64() as i64, signing_root.as_bytes()],
+    )?;
+    Ok(())
+}
+
+/// Insert an attestation into the slashing database.
+///
+/// This should *only* be called in the same (exclusive) transaction as `check_attestation`
+/// so that the check isn't invalidated by a concurrent mutation.
+fn insert_attestation(
+    txn: &Transaction,
+    validator_pubkey: &PublicKey,
+    att_source_epoch: Epoch,
+    att_target_epoch: Epoch,
+    signing_root: Hash256,
+) -> Result<(), NotSafe> {
+    let validator_id = get_validator_id(txn, validator_pubkey)?;
+
+    txn.execute(
+        "INSERT INTO signed_attestations (validator_id, source_epoch, target_epoch, signing_root)
+         VALUES (?1, ?2, ?3, ?4)",
+        params![
+            validator_id,
+            att_source_epoch.as_u64() as i64,
+            att_target_epoch.as_u64() as i64,
+            signing_root.as_bytes()
+        ],
+    )?;
+    Ok(())
+}
+
+/// Replace the block history of `validator_id` with a single block at the highest slot known to
+/// either the database or the `imported` blocks.
+fn import_signed_blocks(
+    txn: &Transaction,
+    validator_id: i64,
+    imported: &[interchange::SignedBlock],
+) -> Result<(), InterchangeError> {
+    if imported.is_empty() {
+        return Ok(());
+    }
+
+    let blocks = select_signed_blocks(txn, validator_id)?
+        .into_iter()
+        .map(|block| (block.slot, known_root(block.signing_root)))
+        .chain(
+            imported
+                .iter()
+                .map(|block| (block.slot, block.signing_root)),
+        )
+        .collect::<Vec<_>>();
+
+    let max_slot = blocks
+        .iter()
+        .map(|(slot, _)| *slot)
+        .max()
+        .ok_or(InterchangeError::MaxInconsistent)?;
+    let signing_root = watermark_root(
+        blocks
+            .iter()
+            .filter(|(slot, _)| *slot == max_slot)
+            .map(|(_, root)| *root),
+    );
+
+    txn.execute(
+        "DELETE FROM signed_blocks WHERE validator_id = ?1",
+        params![validator_id],
+    )?;
+    txn.execute(
+        "INSERT INTO signed_blocks (validator_id, slot, signing_root)
+         VALUES (?1, ?2, ?3)",
+        params![
+            validator_id,
+            max_slot.as_u64() as i64,
+            signing_root.as_bytes()
+        ],
+    )?;
+    Ok(())
+}
+
+/// Replace the attestation history of `validator_id` with a single attestation at the highest
+/// source and target epochs known to either the database or the `imported` attestations.
+fn import_signed_attestations(
+    txn: &Transaction,
+    validator_id: i64,
+    imported: &[interchange::SignedAttestation],
+) -> Result<(), InterchangeError> {
+    if imported.is_empty() {
+        return Ok(());
+    }
+
+    // An attestation with a source after its target could never have been signed safely, and
+    // would produce a watermark that rejects every valid attestation that follows it.
+    if imported
+        .iter()
+        .any(|att| att.source_epoch > att.target_epoch)
+    {
+        return Err(NotSafe::InvalidAttestation(InvalidAttestation::SourceExceedsTarget).into());
+    }
+
+    let attestations = select_signed_attestations(txn, validator_id)?
+        .into_iter()
+        .map(|att| {
+            (
+                att.source_epoch,
+                att.target_epoch,
+                known_root(att.signing_root),
+            )
+        })
+        .chain(
+            imported
+                .iter()
+                .map(|att| (att.source_epoch, att.target_epoch, att.signing_root)),
+        )
+        .collect::<Vec<_>>();
+
+    let max_source = attestations
+        .iter()
+        .map(|(source, _, _)| *source)
+        .max()
+        .ok_or(InterchangeError::MaxInconsistent)?;
+    let max_target = attestations
+        .iter()
+        .map(|(_, target, _)| *target)
+        .max()
+        .ok_or(InterchangeError::MaxInconsistent)?;
+
+    // The signing root is only retained if every attestation at the watermark target was signed
+    // with the watermark source. Otherwise the single record we insert would not match any
+    // message that was actually signed.
+    let signing_root = watermark_root(
+        attestations
+            .iter()
+            .filter(|(_, target, _)| *target == max_target)
+            .map(|(source, _, root)| if *source == max_source { *root } else { None }),
+    );
+
+    txn.execute(
+        "DELETE FROM signed_attestations WHERE validator_id = ?1",
+        params![validator_id],
+    )?;
+    txn.execute(
+        "INSERT INTO signed_attestations (validator_id, source_epoch, target_epoch, signing_root)
+         VALUES (?1, ?2, ?3, ?4)",
+        params![
+            validator_id,
+            max_source.as_u64() as i64,
+            max_target.as_u64() as i64,
+            signing_root.as_bytes()
+        ],
+    )?;
+    Ok(())
+}
+
+/// Returns the signing root to store for a watermark, given the roots of all records that share
+/// the watermark.
+///
+/// If the records do not agree (or any root is unknown) the zero hash is returned, which never
+/// matches a real signing root and so prevents any message at the watermark from being signed.
+fn watermark_root(mut roots: impl Iterator<Item = Option<Hash256>>) -> Hash256 {
+    match roots.next() {
+        Some(Some(first)) if roots.all(|root| root == Some(first)) => first,
+        _ => Hash256::zero(),
+    }
+}
+
+/// Maps the zero hash (used to represent an unknown signing root) to `None`.
+fn known_root(root: Hash256) -> Option<Hash256> {
+    if root == Hash256::zero() {
+        None
+    } else {
+        Some(root)
+    }
+}
+
+fn parse_public_key(public_key: &str) -> Result<PublicKey, InterchangeError> {
+    if !public_key.starts_with("0x") {
+        return Err(InterchangeError::InvalidPubkey(public_key.to_string()));
+    }
+
+    hex::decode(&public_key[2..])
+        .ok()
+        .and_then(|bytes| PublicKey::from_bytes(&bytes).ok())
+        .ok_or_else(|| InterchangeError::InvalidPubkey(public_key.to_string()))
+}
+
+fn select_signed_blocks(txn: &Transaction, validator_id: i64) -> Result<Vec<SignedBlock>, NotSafe> {
+    let mut stmt = txn.prepare(
+        "SELECT slot, signing_root
+         FROM signed_blocks
+         WHERE validator_id = ?1
+         ORDER BY slot ASC",
+    )?;
+    let rows = stmt.query_map(params![validator_id], signed_block_from_row)?;
+    Ok(rows.collect::<Result<_, _>>()?)
+}
+
+fn select_signed_attestations(
+    txn: &Transaction,
+    validator_id: i64,
+) -> Result<Vec<SignedAttestation>, NotSafe> {
+    let mut stmt = txn.prepare(
+        "SELECT source_epoch, target_epoch, signing_root
+         FROM signed_attestations
+         WHERE validator_id = ?1
+         ORDER BY target_epoch ASC",
+    )?;
+    let rows = stmt.query_map(params![validator_id], signed_attestation_from_row)?;
+    Ok(rows.collect::<Result<_, _>>()?)
+}
+
+fn signed_block_from_row(row: &rusqlite::Row) -> rusqlite::Result<SignedBlock> {
+    let slot: i64 = row.get(0)?;
+    let signing_root: Vec<u8> = row.get(1)?;
+    Ok(SignedBlock::new(
+        Slot::new(slot as u64),
+        Hash256::from_slice(&signing_root),
+    ))
+}
+
+fn signed_attestation_from_row(row: &rusqlite::Row) -> rusqlite::Result<SignedAttestation> {
+    let source: i64 = row.get(0)?;
+    let target: i64 = row.get(1)?;
+    let signing_root: Vec<u8> = row.get(2)?;
+    Ok(SignedAttestation::new(
+        Epoch::new(source as u64),
+        Epoch::new(target as u64),
+        Hash256::from_slice(&signing_root),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempdir::TempDir;
+    use types::test_utils::generate_deterministic_keypair;
+
+    fn setup(num_validators: usize) -> (TempDir, SlashingDatabase, Vec<PublicKey>) {
+        let dir = TempDir::new("slashing_protection").expect("should create temp dir");
+        let db = SlashingDatabase::create(&dir.path().join(SLASHING_PROTECTION_FILENAME))
+            .expect("should create db");
+        let pubkeys = (0..num_validators)
+            .map(|i| generate_deterministic_keypair(i).pk)
+            .collect::<Vec<_>>();
+        db.register_validators(pubkeys.iter())
+            .expect("should register validators");
+        (dir, db, pubkeys)
+    }
+
+    fn root(i: u64) -> Hash256 {
+        Hash256::from_low_u64_be(i)
+    }
+
+    fn block(db: &SlashingDatabase, pk: &PublicKey, slot: u64, r: u64) -> Result<Safe, NotSafe> {
+        db.check_and_insert_block_signing_root(pk, Slot::new(slot), root(r))
+    }
+
+    fn attest(
+        db: &SlashingDatabase,
+        pk: &PublicKey,
+        source: u64,
+        target: u64,
+        r: u64,
+    ) -> Result<Safe, NotSafe> {
+        db.check_and_insert_attestation_signing_root(
+            pk,
+            Epoch::new(source),
+            Epoch::new(target),
+            root(r),
+        )
+    }
+
+    #[test]
+    fn unregistered_validator() {
+        let (_dir, db, _) = setup(1);
+        let unknown = generate_deterministic_keypair(42).pk;
+
+        assert_eq!(
+            block(&db, &unknown, 1, 1),
+            Err(NotSafe::UnregisteredValidator(unknown.clone()))
+        );
+        assert_eq!(
+            attest(&db, &unknown, 0, 1, 1),
+            Err(NotSafe::UnregisteredValidator(unknown))
+        );
+    }
+
+    #[test]
+    fn reopen_preserves_history() {
+        let (dir, db, pubkeys) = setup(1);
+        let pk = &pubkeys[0];
+
+        assert_eq!(block(&db, pk, 10, 1), Ok(Safe::Valid));
+        drop(db);
+
+        let db = SlashingDatabase::open_or_create(&dir.path().join(SLASHING_PROTECTION_FILENAME))
+            .expect("should open db");
+        db.register_validators(pubkeys.iter())
+            .expect("re-registering should be a no-op");
+
+        assert_eq!(
+            block(&db, pk, 10, 2),
+            Err(NotSafe::InvalidBlock(InvalidBlock::DoubleBlockProposal(
+                SignedBlock::new(Slot::new(10), root(1))
+            )))
+        );
+    }
+
+    #[test]
+    fn block_proposals() {
+        let (_dir, db, pubkeys) = setup(2);
+        let pk = &pubkeys[0];
+
+        assert_eq!(block(&db, pk, 10, 1), Ok(Safe::Valid));
+        assert_eq!(block(&db, pk, 10, 1), Ok(Safe::SameData));
+        assert_eq!(block(&db, pk, 11, 2), Ok(Safe::Valid));
+        assert_eq!(
+            block(&db, pk, 11, 3),
+            Err(NotSafe::InvalidBlock(InvalidBlock::DoubleBlockProposal(
+                SignedBlock::new(Slot::new(11), root(2))
+            )))
+        );
+        assert_eq!(
+            block(&db, pk, 9, 4),
+            Err(NotSafe::InvalidBlock(
+                InvalidBlock::SlotViolatesLowerBound {
+                    block_slot: Slot::new(9),
+                    bound_slot: Slot::new(10),
+                }
+            ))
+        );
+
+        // Another validator is unaffected.
+        assert_eq!(block(&db, &pubkeys[1], 11, 3), Ok(Safe::Valid));
+    }
+
+    #[test]
+    fn attestation_double_vote() {
+        let (_dir, db, pubkeys) = setup(1);
+        let pk = &pubkeys[0];
+
+        assert_eq!(attest(&db, pk, 0, 1, 1), Ok(Safe::Valid));
+        assert_eq!(attest(&db, pk, 0, 1, 1), Ok(Safe::SameData));
+        assert_eq!(
+            attest(&db, pk, 0, 1, 2),
+            Err(NotSafe::InvalidAttestation(InvalidAttestation::DoubleVote(
+                SignedAttestation::new(Epoch::new(0), Epoch::new(1), root(1))
+            )))
+        );
+    }
+
+    #[test]
+    fn attestation_surrounds() {
+        let (_dir, db, pubkeys) = setup(1);
+        let pk = &pubkeys[0];
+
+        assert_eq!(attest(&db, pk, 1, 2, 1), Ok(Safe::Valid));
+        assert_eq!(attest(&db, pk, 4, 10, 2), Ok(Safe::Valid));
+
+        assert_eq!(
+            attest(&db, pk, 5, 6, 3),
+            Err(NotSafe::InvalidAttestation(
+                InvalidAttestation::PrevSurroundsNew {
+                    prev: SignedAttestation::new(Epoch::new(4), Epoch::new(10), root(2))
+                }
+            ))
+        );
+        assert_eq!(
+            attest(&db, pk, 3, 11, 4),
+            Err(NotSafe::InvalidAttestation(
+                InvalidAttestation::NewSurroundsPrev {
+                    prev: SignedAttestation::new(Epoch::new(4), Epoch::new(10), root(2))
+                }
+            ))
+        );
+
+        assert_eq!(attest(&db, pk, 10, 11, 5), Ok(Safe::Valid));
+    }
+
+    #[test]
+    fn attestation_bounds() {
+        let (_dir, db, pubkeys) = setup(1);
+        let pk = &pubkeys[0];
+
+        assert_eq!(
+            attest(&db, pk, 3, 2, 1),
+            Err(NotSafe::InvalidAttestation(
+                InvalidAttestation::SourceExceedsTarget
+            ))
+        );
+
+        assert_eq!(attest(&db, pk, 5, 6, 1), Ok(Safe::Valid));
+        assert_eq!(
+            attest(&db, pk, 4, 5, 2),
+            Err(NotSafe::InvalidAttestation(
+                InvalidAttestation::SourceLessThanLowerBound {
+                    source: Epoch::new(4),
+                    bound: Epoch::new(5),
+                }
+            ))
+        );
+        assert_eq!(
+            attest(&db, pk, 5, 5, 3),
+            Err(NotSafe::InvalidAttestation(
+                InvalidAttestation::TargetLessThanOrEqLowerBound {
+                    target: Epoch::new(5),
+                    bound: Epoch::new(6),
+                }
+            ))
+        );
+    }
+
+    fn interchange(data: Vec<InterchangeData>) -> Interchange {
+        Interchange {
+            metadata: InterchangeMetadata {
+                interchange_format_version: INTERCHANGE_FORMAT_VERSION,
+                genesis_validators_root: root(42),
+            },
+            data,
+        }
+    }
+
+    #[test]
+    fn interchange_round_trip() {
+        let (_dir, db, pubkeys) = setup(2);
+
+        assert_eq!(block(&db, &pubkeys[0], 10, 1), Ok(Safe::Valid));
+        assert_eq!(block(&db, &pubkeys[0], 12, 2), Ok(Safe::Valid));
+        assert_eq!(attest(&db, &pubkeys[0], 0, 1, 3), Ok(Safe::Valid));
+        assert_eq!(attest(&db, &pubkeys[1], 1, 2, 4), Ok(Safe::Valid));
+
+        let exported = db.export_interchange_info(root(42)).expect("should export");
+        assert_eq!(exported.data.len(), 2);
+        assert_eq!(exported.data[0].signed_blocks.len(), 2);
+
+        let mut json = vec![];
+        exported.write_to(&mut json).expect("should write json");
+        let parsed = Interchange::from_json_reader(&json[..]).expect("should parse json");
+        assert_eq!(exported, parsed);
+
+        let (_dir_2, db_2, _) = setup(0);
+        db_2.import_interchange_info(&parsed, root(42))
+            .expect("should import");
+
+        // The re-exported history is reduced to the watermarks.
+        let reexported = db_2
+            .export_interchange_info(root(42))
+            .expect("should export");
+        assert_eq!(
+            reexported.data[0].signed_blocks,
+            vec![interchange::SignedBlock {
+                slot: Slot::new(12),
+                signing_root: Some(root(2)),
+            }]
+        );
+
+        assert_eq!(block(&db_2, &pubkeys[0], 12, 2), Ok(Safe::SameData));
+        assert!(block(&db_2, &pubkeys[0], 11, 5).is_err());
+        assert!(attest(&db_2, &pubkeys[1], 1, 2, 5).is_err());
+        assert_eq!(attest(&db_2, &pubkeys[1], 2, 3, 5), Ok(Safe::Valid));
+    }
+
+    #[test]
+    fn interchange_merge_is_conservative() {
+        let (_dir, db, pubkeys) = setup(1);
+        let pk = &pubkeys[0];
+
+        assert_eq!(block(&db, pk, 20, 1), Ok(Safe::Valid));
+        assert_eq!(attest(&db, pk, 5, 6, 2), Ok(Safe::Valid));
+
+        let import = interchange(vec![InterchangeData {
+            pubkey: pk.clone(),
+            signed_blocks: vec![interchange::SignedBlock {
+                slot: Slot::new(10),
+                signing_root: None,
+            }],
+            signed_attestations: vec![interchange::SignedAttestation {
+                source_epoch: Epoch::new(7),
+                target_epoch: Epoch::new(8),
+                signing_root: Some(root(9)),
+            }],
+        }]);
+        db.import_interchange_info(&import, root(42))
+            .expect("should import");
+
+        // The local block watermark is higher than the imported one.
+        assert_eq!(block(&db, pk, 20, 1), Ok(Safe::SameData));
+        assert!(block(&db, pk, 15, 3).is_err());
+        assert_eq!(block(&db, pk, 21, 3), Ok(Safe::Valid));
+
+        // The imported attestation watermark is higher than the local one.
+        assert_eq!(attest(&db, pk, 7, 8, 9), Ok(Safe::SameData));
+        assert!(attest(&db, pk, 6, 9, 4).is_err());
+        assert_eq!(attest(&db, pk, 7, 9, 4), Ok(Safe::Valid));
+    }
+
+    #[test]
+    fn interchange_mismatches() {
+        let (_dir, db, _) = setup(1);
+
+        match db.import_interchange_info(&interchange(vec![]), root(1)) {
+            Err(InterchangeError::GenesisValidatorsMismatch { .. }) => {}
+            other => panic!("expected genesis validators root mismatch, got {:?}", other),
+        }
+
+        let mut import = interchange(vec![]);
+        import.metadata.interchange_format_version = 4;
+        match db.import_interchange_info(&import, root(42)) {
+            Err(InterchangeError::UnsupportedVersion(4)) => {}
+            other => panic!("expected unsupported version, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn interchange_rejects_source_exceeding_target() {
+        let (_dir, db, pubkeys) = setup(1);
+        let pk = &pubkeys[0];
+
+        assert_eq!(attest(&db, pk, 1, 2, 1), Ok(Safe::Valid));
+
+        let import = interchange(vec![InterchangeData {
+            pubkey: pk.clone(),
+            signed_blocks: vec![],
+            signed_attestations: vec![interchange::SignedAttestation {
+                source_epoch: Epoch::new(5),
+                target_epoch: Epoch::new(4),
+                signing_root: None,
+            }],
+        }]);
+        match db.import_interchange_info(&import, root(42)) {
+            Err(InterchangeError::NotSafe(NotSafe::InvalidAttestation(
+                InvalidAttestation::SourceExceedsTarget,
+            ))) => {}
+            other => panic!("expected source exceeding target, got {:?}", other),
+        }
+
+        // Nothing from the rejected interchange was written.
+        assert_eq!(attest(&db, pk, 2, 3, 2), Ok(Safe::Valid));
+    }
+}
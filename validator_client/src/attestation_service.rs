@@ -1,11 +1,12 @@
 use crate::{
+    beacon_node_fallback::BeaconNodeFallback,
     duties_service::{DutiesService, DutyAndState},
     validator_store::ValidatorStore,
 };
 use environment::RuntimeContext;
 use exit_future::Signal;
 use futures::{future, Future, Stream};
-use remote_beacon_node::{PublishStatus, RemoteBeaconNode};
+use remote_beacon_node::PublishStatus;
 use rest_types::ValidatorSubscription;
 use slog::{crit, debug, info, trace};
 use slot_clock::SlotClock;
@@ -21,7 +22,7 @@ pub struct AttestationServiceBuilder<T, E: EthSpec> {
     duties_service: Option<DutiesService<T, E>>,
     validator_store: Option<ValidatorStore<T, E>>,
     slot_clock: Option<T>,
-    beacon_node: Option<RemoteBeaconNode<E>>,
+    beacon_nodes: Option<BeaconNodeFallback<T, E>>,
     context: Option<RuntimeContext<E>>,
 }
 
@@ -31,7 +32,7 @@ impl<T: SlotClock + 'static, E: EthSpec> AttestationServiceBuilder<T, E> {
             duties_service: None,
             validator_store: None,
             slot_clock: None,
-            beacon_node: None,
+            beacon_nodes: None,
             context: None,
         }
     }
@@ -51,8 +52,8 @@ impl<T: SlotClock + 'static, E: EthSpec> AttestationServiceBuilder<T, E> {
         self
     }
 
-    pub fn beacon_node(mut self, beacon_node: RemoteBeaconNode<E>) -> Self {
-        self.beacon_node = Some(beacon_node);
+    pub fn beacon_nodes(mut self, beacon_nodes: BeaconNodeFallback<T, E>) -> Self {
+        self.beacon_nodes = Some(beacon_nodes);
         self
     }
 
@@ -73,9 +74,9 @@ impl<T: SlotClock + 'static, E: EthSpec> AttestationServiceBuilder<T, E> {
                 slot_clock: self
                     .slot_clock
                     .ok_or_else(|| "Cannot build AttestationService without slot_clock")?,
-                beacon_node: self
-                    .beacon_node
-                    .ok_or_else(|| "Cannot build AttestationService without beacon_node")?,
+                beacon_nodes: self
+                    .beacon_nodes
+                    .ok_or_else(|| "Cannot build AttestationService without beacon_nodes")?,
                 context: self
                     .context
                     .ok_or_else(|| "Cannot build AttestationService without runtime_context")?,
@@ -89,7 +90,7 @@ pub struct Inner<T, E: EthSpec> {
     duties_service: DutiesService<T, E>,
     validator_store: ValidatorStore<T, E>,
     slot_clock: T,
-    beacon_node: RemoteBeaconNode<E>,
+    beacon_nodes: BeaconNodeFallback<T, E>,
     context: RuntimeContext<E>,
 }
 
@@ -287,11 +288,14 @@ impl<T: SlotClock + 'static, E: EthSpec> AttestationService<T, E> {
 
         let num_failed_duties = num_duties - successful_duties.len();
 
-        self.beacon_node
-            .http
-            .validator()
-            .subscribe(validator_subscriptions)
-            .map_err(|e| format!("Failed to subscribe validators: {:?}", e))
+        self.beacon_nodes
+            .first_success(move |beacon_node| {
+                beacon_node
+                    .http
+                    .validator()
+                    .subscribe(validator_subscriptions.clone())
+            })
+            .map_err(|e| format!("Failed to subscribe validators: {}", e))
             .map(move |publish_status| match publish_status {
                 PublishStatus::Valid => info!(
                     log_1,
@@ -430,11 +434,14 @@ impl<T: SlotClock + 'static, E: EthSpec> AttestationService<T, E> {
         let service = self.clone();
 
         Box::new(
-            self.beacon_node
-                .http
-                .validator()
-                .produce_attestation(slot, committee_index)
-                .map_err(|e| format!("Failed to produce attestation: {:?}", e))
+            self.beacon_nodes
+                .first_success(move |beacon_node| {
+                    beacon_node
+                        .http
+                        .validator()
+                        .produce_attestation(slot, committee_index)
+                })
+                .map_err(|e| format!("Failed to produce attestation: {}", e))
                 .and_then::<_, Box<dyn Future<Item = _, Error = _> + Send>>(move |attestation| {
                     let log = service.context.log.clone();
 
@@ -509,11 +516,14 @@ impl<T: SlotClock + 'static, E: EthSpec> AttestationService<T, E> {
 
                         Box::new(
                             service
-                                .beacon_node
-                                .http
-                                .validator()
-                                .publish_attestations(signed_attestations)
-                                .map_err(|e| format!("Failed to publish attestation: {:?}", e))
+                                .beacon_nodes
+                                .first_success(move |beacon_node| {
+                                    beacon_node
+                                        .http
+                                        .validator()
+                                        .publish_attestations(signed_attestations.clone())
+                                })
+                                .map_err(|e| format!("Failed to publish attestation: {}", e))
                                 .map(move |publish_status| match publish_status {
                                     PublishStatus::Valid => info!(
                                         log,
@@ -570,11 +580,15 @@ impl<T: SlotClock + 'static, E: EthSpec> AttestationService<T, E> {
         let service_1 = self.clone();
         let log_1 = self.context.log.clone();
 
-        self.beacon_node
-            .http
-            .validator()
-            .produce_aggregate_attestation(&attestation.data)
-            .map_err(|e| format!("Failed to produce an aggregate attestation: {:?}", e))
+        let attestation_data = attestation.data.clone();
+        self.beacon_nodes
+            .first_success(move |beacon_node| {
+                beacon_node
+                    .http
+                    .validator()
+                    .produce_aggregate_attestation(&attestation_data)
+            })
+            .map_err(|e| format!("Failed to produce an aggregate attestation: {}", e))
             .and_then::<_, Box<dyn Future<Item = _, Error = _> + Send>>(
                 move |aggregated_attestation| {
                     // For each validator, clone the `aggregated_attestation` and convert it into
@@ -628,12 +642,15 @@ impl<T: SlotClock + 'static, E: EthSpec> AttestationService<T, E> {
                         let attestation = first.message.aggregate;
 
                         Box::new(service_1
-                        .beacon_node
-                        .http
-                        .validator()
-                        .publish_aggregate_and_proof(signed_aggregate_and_proofs)
+                        .beacon_nodes
+                        .first_success(move |beacon_node| {
+                            beacon_node
+                                .http
+                                .validator()
+                                .publish_aggregate_and_proof(signed_aggregate_and_proofs.clone())
+                        })
                         .map(|publish_status| (attestation, publish_status))
-                        .map_err(|e| format!("Failed to publish aggregate and proofs: {:?}", e))
+                        .map_err(|e| format!("Failed to publish aggregate and proofs: {}", e))
                         .map(move |(attestation, publish_status)| match publish_status {
                             PublishStatus::Valid => info!(
                                 log_1,
@@ -0,0 +1,534 @@
+//! Allows the validator client to connect to multiple beacon nodes, sending each request to the
+//! first node which is online, compatible and synced.
+
+use environment::RuntimeContext;
+use exit_future::Signal;
+use futures::{
+    future::{self, loop_fn, Loop},
+    Future, IntoFuture, Stream,
+};
+use parking_lot::RwLock;
+use remote_beacon_node::RemoteBeaconNode;
+use slog::{crit, debug, error, info, warn};
+use slot_clock::SlotClock;
+use std::fmt::Debug;
+use std::ops::Deref;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::timer::Interval;
+use types::{ChainSpec, EthSpec};
+
+/// Delay this period of time after the slot starts. This allows the node to process the new slot.
+const TIME_DELAY_FROM_SLOT: Duration = Duration::from_millis(80);
+
+/// Indicates why a beacon node is not ready to receive requests.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CandidateError {
+    /// The status of the node has not yet been checked.
+    Uninitialized,
+    /// The node could not be contacted.
+    Offline,
+    /// The node is using different spec constants to the validator client.
+    Incompatible,
+    /// The head of the node is too far behind the wall-clock slot.
+    NotSynced,
+}
+
+/// A beacon node which may be used to serve requests.
+pub struct CandidateBeaconNode<E: EthSpec> {
+    endpoint: String,
+    beacon_node: RemoteBeaconNode<E>,
+    status: RwLock<Result<(), CandidateError>>,
+}
+
+impl<E: EthSpec> CandidateBeaconNode<E> {
+    pub fn new(endpoint: String, beacon_node: RemoteBeaconNode<E>) -> Self {
+        Self {
+            endpoint,
+            beacon_node,
+            status: RwLock::new(Err(CandidateError::Uninitialized)),
+        }
+    }
+
+    /// Returns the HTTP endpoint of the node.
+    pub fn endpoint(&self) -> &str {
+        &self.endpoint
+    }
+
+    /// Returns the last known status of the node.
+    pub fn status(&self) -> Result<(), CandidateError> {
+        *self.status.read()
+    }
+
+    fn is_ready(&self) -> bool {
+        self.status().is_ok()
+    }
+}
+
+/// Helper to minimise `Arc` usage.
+pub struct Inner<T, E: EthSpec> {
+    candidates: Vec<Arc<CandidateBeaconNode<E>>>,
+    slot_clock: RwLock<Option<T>>,
+    allow_unsynced_beacon_node: bool,
+    context: RuntimeContext<E>,
+}
+
+/// A list of beacon nodes, ordered by preference.
+///
+/// Requests are sent to the first node which is ready (i.e., online, using the same spec constants
+/// and synced). If a request fails the node is marked as offline and the request is retried on the
+/// next ready node. If all ready nodes fail,
+/// the nodes which were not ready are re-checked and the request is tried on any which have become
+/// ready.
+pub struct BeaconNodeFallback<T, E: EthSpec> {
+    inner: Arc<Inner<T, E>>,
+}
+
+impl<T, E: EthSpec> Clone for BeaconNodeFallback<T, E> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T, E: EthSpec> Deref for BeaconNodeFallback<T, E> {
+    type Target = Inner<T, E>;
+
+    fn deref(&self) -> &Self::Target {
+        self.inner.deref()
+    }
+}
+
+impl<T: SlotClock + 'static, E: EthSpec> BeaconNodeFallback<T, E> {
+    /// Instantiates a fallback over `candidates`, in order of preference.
+    ///
+    /// If `allow_unsynced_beacon_node` is `true`, nodes will be used even if they are not synced.
+    pub fn new(
+        candidates: Vec<CandidateBeaconNode<E>>,
+        allow_unsynced_beacon_node: bool,
+        context: RuntimeContext<E>,
+    ) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                candidates: candidates.into_iter().map(Arc::new).collect(),
+                slot_clock: RwLock::new(None),
+                allow_unsynced_beacon_node,
+                context,
+            }),
+        }
+    }
+
+    /// Sets the slot clock used to determine if a node is synced.
+    ///
+    /// Until this is called, the sync status of the nodes is not checked.
+    pub fn set_slot_clock(&self, slot_clock: T) {
+        *self.slot_clock.write() = Some(slot_clock);
+    }
+
+    /// Returns the total number of nodes.
+    pub fn num_total(&self) -> usize {
+        self.candidates.len()
+    }
+
+    /// Returns the number of nodes which are ready to receive requests.
+    pub fn num_ready(&self) -> usize {
+        self.candidates.iter().filter(|c| c.is_ready()).count()
+    }
+
+    /// Re-checks the status of every node.
+    pub fn update_all_candidates(&self) -> impl Future<Item = (), Error = ()> {
+        let futures = self
+            .candidates
+            .iter()
+            .map(|candidate| self.refresh_status(candidate.clone()))
+            .collect::<Vec<_>>();
+
+        future::join_all(futures).map(|_| ())
+    }
+
+    /// Re-checks the status of the nodes which are not ready.
+    fn update_unready_candidates(&self) -> impl Future<Item = (), Error = ()> {
+        let futures = self
+            .candidates
+            .iter()
+            .filter(|candidate| !candidate.is_ready())
+            .map(|candidate| self.refresh_status(candidate.clone()))
+            .collect::<Vec<_>>();
+
+        future::join_all(futures).map(|_| ())
+    }
+
+    /// Checks that `candidate` is online, compatible and synced, storing the result as its status.
+    fn refresh_status(
+        &self,
+        candidate: Arc<CandidateBeaconNode<E>>,
+    ) -> impl Future<Item = (), Error = ()> {
+        let log = self.context.log.clone();
+        let spec_constants = self.context.eth2_config.spec_constants.clone();
+        let current_epoch = if self.allow_unsynced_beacon_node {
+            None
+        } else {
+            self.slot_clock
+                .read()
+                .as_ref()
+                .and_then(|slot_clock| slot_clock.now())
+                .map(|slot| slot.epoch(E::slots_per_epoch()))
+        };
+        let beacon_node = candidate.beacon_node.clone();
+
+        candidate
+            .beacon_node
+            .http
+            .spec()
+            .get_eth2_config()
+            .map_err(|_| CandidateError::Offline)
+            .and_then(move |eth2_config| {
+                if eth2_config.spec_constants == spec_constants {
+                    Ok(())
+                } else {
+                    Err(CandidateError::Incompatible)
+                }
+            })
+            .and_then::<_, Box<dyn Future<Item = _, Error = _> + Send>>(move |()| {
+                if let Some(current_epoch) = current_epoch {
+                    Box::new(
+                        beacon_node
+                            .http
+                            .beacon()
+                            .get_head()
+                            .map_err(|_| CandidateError::Offline)
+                            .and_then(move |head| {
+                                let head_epoch = head.slot.epoch(E::slots_per_epoch());
+                                if head_epoch + 1 < current_epoch {
+                                    Err(CandidateError::NotSynced)
+                                } else {
+                                    Ok(())
+                                }
+                            }),
+                    )
+                } else {
+                    Box::new(future::ok(()))
+                }
+            })
+            .then(move |result| {
+                let previous = candidate.status();
+                *candidate.status.write() = result;
+
+                if previous != result {
+                    match result {
+                        Ok(()) => info!(
+                            log,
+                            "Beacon node is ready";
+                            "endpoint" => candidate.endpoint(),
+                        ),
+                        Err(e) => warn!(
+                            log,
+                            "Beacon node is not ready";
+                            "endpoint" => candidate.endpoint(),
+                            "reason" => format!("{:?}", e),
+                        ),
+                    }
+                }
+
+                Ok(())
+            })
+    }
+
+    /// Calls `func` on each ready node in turn, returning the first successful result.
+    ///
+    /// If no ready node succeeds, the unready nodes are re-checked and `func` is tried on any
+    /// which have become ready. An error is returned if `func` fails on all nodes.
+    pub fn first_success<F, R, O>(&self, func: F) -> impl Future<Item = O, Error = String>
+    where
+        F: Fn(RemoteBeaconNode<E>) -> R + Send + Sync + 'static,
+        R: IntoFuture<Item = O> + 'static,
+        R::Future: Send + 'static,
+        R::Error: Debug,
+        O: Send + 'static,
+    {
+        let func = Arc::new(func);
+        let service = self.clone();
+        let ready = self
+            .candidates
+            .iter()
+            .filter(|candidate| candidate.is_ready())
+            .cloned()
+            .collect::<Vec<_>>();
+        let unready = self
+            .candidates
+            .iter()
+            .filter(|candidate| !candidate.is_ready())
+            .cloned()
+            .collect::<Vec<_>>();
+
+        try_candidates(ready, func.clone(), vec![], self.context.log.clone())
+            .or_else::<_, Box<dyn Future<Item = _, Error = _> + Send>>(move |errors| {
+                if unready.is_empty() {
+                    return Box::new(future::err(errors));
+                }
+
+                let log = service.context.log.clone();
+                Box::new(service.update_unready_candidates().then(move |_| {
+                    let now_ready = unready
+                        .into_iter()
+                        .filter(|candidate| candidate.is_ready())
+                        .collect();
+                    try_candidates(now_ready, func, errors, log)
+                }))
+            })
+            .map_err(|errors| {
+                if errors.is_empty() {
+                    "No beacon nodes are ready".to_string()
+                } else {
+                    format!("All beacon nodes failed: {}", errors.join(", "))
+                }
+            })
+    }
+
+    /// Starts the service that periodically checks the status of all nodes.
+    pub fn start_update_service(&self, spec: &ChainSpec) -> Result<Signal, String> {
+        let log = self.context.log.clone();
+
+        let duration_to_next_slot = self
+            .slot_clock
+            .read()
+            .as_ref()
+            .ok_or_else(|| "Cannot start fallback service without slot_clock".to_string())?
+            .duration_to_next_slot()
+            .ok_or_else(|| "Unable to determine duration to next slot".to_string())?;
+
+        let interval = {
+            let slot_duration = Duration::from_millis(spec.milliseconds_per_slot);
+            Interval::new(
+                Instant::now() + duration_to_next_slot + TIME_DELAY_FROM_SLOT,
+                slot_duration,
+            )
+        };
+
+        let (exit_signal, exit_fut) = exit_future::signal();
+        let service = self.clone();
+        let log_1 = log.clone();
+        let log_2 = log.clone();
+
+        self.context.executor.spawn(
+            exit_fut
+                .until(
+                    interval
+                        .map_err(move |e| {
+                            crit! {
+                                log_1,
+                                "Timer thread failed";
+                                "error" => format!("{}", e)
+                            }
+                        })
+                        .for_each(move |_| service.do_update().then(|_| Ok(()))),
+                )
+                .map(move |_| info!(log_2, "Shutdown complete")),
+        );
+
+        Ok(exit_signal)
+    }
+
+    /// Re-checks all nodes and logs if none are ready.
+    fn do_update(&self) -> impl Future<Item = (), Error = ()> {
+        let service = self.clone();
+
+        self.update_all_candidates().map(move |()| {
+            let log = &service.context.log;
+            let num_ready = service.num_ready();
+
+            if num_ready == 0 {
+                error!(
+                    log,
+                    "No synced beacon nodes";
+                    "total" => service.num_total(),
+                );
+            } else {
+                debug!(
+                    log,
+                    "Checked beacon nodes";
+                    "ready" => num_ready,
+                    "total" => service.num_total(),
+                );
+            }
+        })
+    }
+}
+
+/// Calls `func` on each of `candidates` in order, returning the first successful result or the
+/// errors from every candidate appended to `errors`.
+fn try_candidates<E, F, R, O>(
+    candidates: Vec<Arc<CandidateBeaconNode<E>>>,
+    func: Arc<F>,
+    errors: Vec<String>,
+    log: slog::Logger,
+) -> impl Future<Item = O, Error = Vec<String>>
+where
+    E: EthSpec,
+    F: Fn(RemoteBeaconNode<E>) -> R + Send + Sync + 'static,
+    R: IntoFuture<Item = O> + 'static,
+    R::Future: Send + 'static,
+    R::Error: Debug,
+    O: Send + 'static,
+{
+    loop_fn(
+        (candidates.into_iter(), errors),
+        move |(mut candidates, mut errors)| {
+            let log = log.clone();
+            let future: Box<dyn Future<Item = Loop<_, _>, Error = ()> + Send> =
+                match candidates.next() {
+                    Some(candidate) => {
+                        Box::new(func(candidate.beacon_node.clone()).into_future().then(
+                            move |result| match result {
+                                Ok(output) => Ok(Loop::Break(Ok(output))),
+                                Err(e) => {
+                                    // Stop sending requests to the node until the next status
+                                    // check shows that it has recovered.
+                                    *candidate.status.write() = Err(CandidateError::Offline);
+                                    warn!(
+                                        log,
+                                        "Request to beacon node failed";
+                                        "endpoint" => candidate.endpoint(),
+                                        "error" => format!("{:?}", e),
+                                    );
+                                    errors.push(format!("{}: {:?}", candidate.endpoint(), e));
+                                    Ok(Loop::Continue((candidates, errors)))
+                                }
+                            },
+                        ))
+                    }
+                    None => Box::new(future::ok(Loop::Break(Err(errors)))),
+                };
+
+            future
+        },
+    )
+    .then(|result| match result {
+        Ok(result) => result,
+        Err(()) => Err(vec![]),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use environment::{Environment, EnvironmentBuilder};
+    use slot_clock::TestingSlotClock;
+    use types::MinimalEthSpec;
+
+    type E = MinimalEthSpec;
+
+    fn build_env() -> Environment<E> {
+        EnvironmentBuilder::minimal()
+            .null_logger()
+            .expect("should build env logger")
+            .single_thread_tokio_runtime()
+            .expect("should start tokio runtime")
+            .build()
+            .expect("environment should build")
+    }
+
+    fn endpoint(i: usize) -> String {
+        format!("http://node-{}/", i)
+    }
+
+    /// Returns a fallback over `statuses.len()` nodes, where node `i` has status `statuses[i]`.
+    fn build_fallback(
+        env: &mut Environment<E>,
+        statuses: &[Result<(), CandidateError>],
+    ) -> BeaconNodeFallback<TestingSlotClock, E> {
+        let candidates = statuses
+            .iter()
+            .enumerate()
+            .map(|(i, status)| {
+                let beacon_node =
+                    RemoteBeaconNode::new(endpoint(i)).expect("should create remote node");
+                let candidate = CandidateBeaconNode::new(endpoint(i), beacon_node);
+                *candidate.status.write() = *status;
+                candidate
+            })
+            .collect();
+
+        BeaconNodeFallback::new(candidates, false, env.core_context())
+    }
+
+    /// Sends a request which succeeds on the nodes in `online`, returning the endpoint of the node
+    /// which served it.
+    fn request(
+        env: &mut Environment<E>,
+        fallback: &BeaconNodeFallback<TestingSlotClock, E>,
+        online: Vec<usize>,
+    ) -> Result<String, String> {
+        let online = online.into_iter().map(endpoint).collect::<Vec<_>>();
+        env.runtime().block_on(fallback.first_success(move |node| {
+            let url = node.http.url().to_string();
+            if online.contains(&url) {
+                Ok(url)
+            } else {
+                Err(format!("{} is offline", url))
+            }
+        }))
+    }
+
+    fn statuses(
+        fallback: &BeaconNodeFallback<TestingSlotClock, E>,
+    ) -> Vec<Result<(), CandidateError>> {
+        fallback.candidates.iter().map(|c| c.status()).collect()
+    }
+
+    #[test]
+    fn prefers_first_ready_node() {
+        let mut env = build_env();
+        let fallback = build_fallback(&mut env, &[Ok(()), Ok(()), Ok(())]);
+
+        assert_eq!(request(&mut env, &fallback, vec![0, 1, 2]), Ok(endpoint(0)));
+        assert_eq!(request(&mut env, &fallback, vec![1, 2]), Ok(endpoint(1)));
+        assert_eq!(fallback.num_ready(), 2);
+    }
+
+    #[test]
+    fn skips_unready_nodes() {
+        let mut env = build_env();
+        let fallback = build_fallback(
+            &mut env,
+            &[
+                Err(CandidateError::NotSynced),
+                Err(CandidateError::Incompatible),
+                Ok(()),
+            ],
+        );
+
+        assert_eq!(request(&mut env, &fallback, vec![0, 1, 2]), Ok(endpoint(2)));
+    }
+
+    #[test]
+    fn failed_request_demotes_node() {
+        let mut env = build_env();
+        let fallback = build_fallback(&mut env, &[Ok(()), Ok(()), Ok(())]);
+
+        assert_eq!(request(&mut env, &fallback, vec![2]), Ok(endpoint(2)));
+        assert_eq!(
+            statuses(&fallback),
+            vec![
+                Err(CandidateError::Offline),
+                Err(CandidateError::Offline),
+                Ok(())
+            ]
+        );
+
+        // The demoted nodes are no longer preferred, even though they would now succeed.
+        assert_eq!(request(&mut env, &fallback, vec![0, 1, 2]), Ok(endpoint(2)));
+    }
+
+    #[test]
+    fn all_nodes_failing() {
+        let mut env = build_env();
+        let fallback = build_fallback(&mut env, &[Ok(()), Ok(())]);
+
+        let error = request(&mut env, &fallback, vec![]).expect_err("should fail");
+        assert!(error.contains(&endpoint(0)));
+        assert!(error.contains(&endpoint(1)));
+        assert_eq!(fallback.num_ready(), 0);
+    }
+}
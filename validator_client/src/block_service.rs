@@ -1,8 +1,11 @@
-use crate::{duties_service::DutiesService, validator_store::ValidatorStore};
+use crate::{
+    beacon_node_fallback::BeaconNodeFallback, duties_service::DutiesService,
+    validator_store::ValidatorStore,
+};
 use environment::RuntimeContext;
 use exit_future::Signal;
 use futures::{stream, Future, IntoFuture, Stream};
-use remote_beacon_node::{PublishStatus, RemoteBeaconNode};
+use remote_beacon_node::PublishStatus;
 use slog::{crit, error, info, trace};
 use slot_clock::SlotClock;
 use std::ops::Deref;
@@ -19,7 +22,7 @@ pub struct BlockServiceBuilder<T, E: EthSpec> {
     duties_service: Option<DutiesService<T, E>>,
     validator_store: Option<ValidatorStore<T, E>>,
     slot_clock: Option<Arc<T>>,
-    beacon_node: Option<RemoteBeaconNode<E>>,
+    beacon_nodes: Option<BeaconNodeFallback<T, E>>,
     context: Option<RuntimeContext<E>>,
 }
 
@@ -29,7 +32,7 @@ impl<T: SlotClock + 'static, E: EthSpec> BlockServiceBuilder<T, E> {
             duties_service: None,
             validator_store: None,
             slot_clock: None,
-            beacon_node: None,
+            beacon_nodes: None,
             context: None,
         }
     }
@@ -49,8 +52,8 @@ impl<T: SlotClock + 'static, E: EthSpec> BlockServiceBuilder<T, E> {
         self
     }
 
-    pub fn beacon_node(mut self, beacon_node: RemoteBeaconNode<E>) -> Self {
-        self.beacon_node = Some(beacon_node);
+    pub fn beacon_nodes(mut self, beacon_nodes: BeaconNodeFallback<T, E>) -> Self {
+        self.beacon_nodes = Some(beacon_nodes);
         self
     }
 
@@ -71,9 +74,9 @@ impl<T: SlotClock + 'static, E: EthSpec> BlockServiceBuilder<T, E> {
                 slot_clock: self
                     .slot_clock
                     .ok_or_else(|| "Cannot build BlockService without slot_clock")?,
-                beacon_node: self
-                    .beacon_node
-                    .ok_or_else(|| "Cannot build BlockService without beacon_node")?,
+                beacon_nodes: self
+                    .beacon_nodes
+                    .ok_or_else(|| "Cannot build BlockService without beacon_nodes")?,
                 context: self
                     .context
                     .ok_or_else(|| "Cannot build BlockService without runtime_context")?,
@@ -87,7 +90,7 @@ pub struct Inner<T, E: EthSpec> {
     duties_service: DutiesService<T, E>,
     validator_store: ValidatorStore<T, E>,
     slot_clock: Arc<T>,
-    beacon_node: RemoteBeaconNode<E>,
+    beacon_nodes: BeaconNodeFallback<T, E>,
     context: RuntimeContext<E>,
 }
 
@@ -200,13 +203,16 @@ impl<T: SlotClock + 'static, E: EthSpec> BlockService<T, E> {
                             .into_future()
                             .and_then(move |randao_reveal| {
                                 service_1
-                                    .beacon_node
-                                    .http
-                                    .validator()
-                                    .produce_block(slot, randao_reveal)
+                                    .beacon_nodes
+                                    .first_success(move |beacon_node| {
+                                        beacon_node
+                                            .http
+                                            .validator()
+                                            .produce_block(slot, randao_reveal.clone())
+                                    })
                                     .map_err(|e| {
                                         format!(
-                                            "Error from beacon node when producing block: {:?}",
+                                            "Error from beacon node when producing block: {}",
                                             e
                                         )
                                     })
@@ -218,15 +224,19 @@ impl<T: SlotClock + 'static, E: EthSpec> BlockService<T, E> {
                                     .ok_or_else(|| "Unable to sign block".to_string())
                             })
                             .and_then(move |block| {
+                                let signed_block = block.clone();
                                 service_3
-                                    .beacon_node
-                                    .http
-                                    .validator()
-                                    .publish_block(block.clone())
+                                    .beacon_nodes
+                                    .first_success(move |beacon_node| {
+                                        beacon_node
+                                            .http
+                                            .validator()
+                                            .publish_block(signed_block.clone())
+                                    })
                                     .map(|publish_status| (block, publish_status))
                                     .map_err(|e| {
                                         format!(
-                                            "Error from beacon node when publishing block: {:?}",
+                                            "Error from beacon node when publishing block: {}",
                                             e
                                         )
                                     })
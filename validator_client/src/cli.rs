@@ -7,18 +7,51 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
         .about("When connected to a beacon node, performs the duties of a staked \
                 validator (e.g., proposing blocks and attestations).")
         .arg(
-            Arg::with_name("server")
-                .long("server")
-                .value_name("NETWORK_ADDRESS")
-                .help("Address to connect to BeaconNode.")
+            Arg::with_name("beacon-nodes")
+                .long("beacon-nodes")
+                .alias("server")
+                .value_name("NETWORK_ADDRESSES")
+                .help("Comma-separated addresses of one or more beacon node HTTP APIs, in order \
+                       of preference. Each request is sent to the first node which is online and \
+                       synced, falling back to the next node on failure.")
                 .default_value(&DEFAULT_HTTP_SERVER)
                 .takes_value(true),
         )
+        .arg(
+            Arg::with_name("beacon-node-token-file")
+                .long("beacon-node-token-file")
+                .value_name("FILE")
+                .help("Path to a file containing the bearer token sent to the beacon node HTTP \
+                       APIs. Required when the beacon nodes restrict access with \
+                       --http-validator-token-file.")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("secrets-dir")
+                .long("secrets-dir")
+                .value_name("SECRETS_DIRECTORY")
+                .help("The directory which contains the password to unlock the validator \
+                       voting keypairs. Each password should be contained in a file where the \
+                       name is the 0x-prefixed hex representation of the validators voting public \
+                       key. Validators without a password file will be prompted for a password. \
+                       Defaults to ~/.lighthouse/secrets.")
+                .takes_value(true),
+        )
         .arg(
             Arg::with_name("allow-unsynced")
                 .long("allow-unsynced")
-                .help("If present, the validator client will still poll for duties if the beacon
-                      node is not synced.")
+                .help("If present, the validator client will still send requests to beacon
+                      nodes which are not synced.")
+        )
+        .arg(
+            Arg::with_name("doppelganger-epochs")
+                .long("doppelganger-epochs")
+                .value_name("EPOCHS")
+                .help("If present, the validator client will not sign any messages until its \
+                      validators have shown no activity on chain for this many epochs. Any \
+                      validator seen to be active is assumed to be running elsewhere and will \
+                      never sign. Delays the start of validator duties by EPOCHS + 2 epochs.")
+                .takes_value(true)
         )
         /*
          * The "testnet" sub-command.
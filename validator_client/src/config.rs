@@ -4,6 +4,7 @@ use std::path::PathBuf;
 
 pub const DEFAULT_HTTP_SERVER: &str = "http://localhost:5052/";
 pub const DEFAULT_DATA_DIR: &str = ".lighthouse/validators";
+pub const DEFAULT_SECRETS_DIR: &str = ".lighthouse/secrets";
 
 /// Specifies a method for obtaining validator keypairs.
 #[derive(Clone)]
@@ -25,16 +26,23 @@ impl Default for KeySource {
 pub struct Config {
     /// The data directory, which stores all validator databases
     pub data_dir: PathBuf,
+    /// The directory containing the passwords for the validator keystores.
+    pub secrets_dir: PathBuf,
     /// Specifies how the validator client should load keypairs.
     #[serde(skip)]
     pub key_source: KeySource,
-    /// The http endpoint of the beacon node API.
+    /// The http endpoints of the beacon node APIs, in order of preference.
     ///
-    /// Should be similar to `http://localhost:8080`
-    pub http_server: String,
-    /// If true, the validator client will still poll for duties and produce blocks even if the
-    /// beacon node is not synced at startup.
+    /// Each should be similar to `http://localhost:8080`
+    pub beacon_nodes: Vec<String>,
+    /// A file containing the bearer token sent with each request to the beacon nodes.
+    pub beacon_node_token_file: Option<PathBuf>,
+    /// If true, the validator client will still send requests to a beacon node even if it is not
+    /// synced.
     pub allow_unsynced_beacon_node: bool,
+    /// If `Some`, validators will not sign any messages until the beacon nodes have observed no
+    /// activity from them for this many epochs.
+    pub doppelganger_detection_epochs: Option<u64>,
 }
 
 impl Default for Config {
@@ -42,12 +50,16 @@ impl Default for Config {
     fn default() -> Self {
         let mut data_dir = dirs::home_dir().unwrap_or_else(|| PathBuf::from("."));
         data_dir.push(".lighthouse");
+        let secrets_dir = data_dir.join("secrets");
         data_dir.push("validators");
         Self {
             data_dir,
+            secrets_dir,
             key_source: <_>::default(),
-            http_server: DEFAULT_HTTP_SERVER.to_string(),
+            beacon_nodes: vec![DEFAULT_HTTP_SERVER.to_string()],
+            beacon_node_token_file: None,
             allow_unsynced_beacon_node: false,
+            doppelganger_detection_epochs: None,
         }
     }
 }
@@ -71,10 +83,32 @@ impl Config {
                     .unwrap_or_else(|| PathBuf::from("."))
             });
 
-        if let Some(server) = cli_args.value_of("server") {
-            config.http_server = server.to_string();
+        config.secrets_dir = cli_args
+            .value_of("secrets-dir")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| {
+                dirs::home_dir()
+                    .map(|home| home.join(DEFAULT_SECRETS_DIR))
+                    .unwrap_or_else(|| PathBuf::from("."))
+            });
+
+        if let Some(beacon_nodes) = cli_args.value_of("beacon-nodes") {
+            config.beacon_nodes = beacon_nodes
+                .split(',')
+                .map(str::trim)
+                .filter(|endpoint| !endpoint.is_empty())
+                .map(String::from)
+                .collect();
+
+            if config.beacon_nodes.is_empty() {
+                return Err("No beacon nodes supplied to --beacon-nodes".to_string());
+            }
         }
 
+        config.beacon_node_token_file = cli_args
+            .value_of("beacon-node-token-file")
+            .map(PathBuf::from);
+
         let mut config = match cli_args.subcommand() {
             ("testnet", Some(sub_cli_args)) => {
                 if cli_args.is_present("eth2-config") && sub_cli_args.is_present("bootstrap") {
@@ -94,6 +128,14 @@ impl Config {
 
         config.allow_unsynced_beacon_node = cli_args.is_present("allow-unsynced");
 
+        if let Some(epochs) = cli_args.value_of("doppelganger-epochs") {
+            config.doppelganger_detection_epochs = Some(
+                epochs
+                    .parse::<u64>()
+                    .map_err(|e| format!("Unable to parse doppelganger epochs: {:?}", e))?,
+            );
+        }
+
         Ok(config)
     }
 }
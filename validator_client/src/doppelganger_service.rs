@@ -0,0 +1,328 @@
+use crate::{beacon_node_fallback::BeaconNodeFallback, validator_store::ValidatorStore};
+use environment::RuntimeContext;
+use exit_future::Signal;
+use futures::{future, Future, Stream};
+use parking_lot::RwLock;
+use slog::{crit, error, info};
+use slot_clock::SlotClock;
+use std::collections::{HashMap, HashSet};
+use std::ops::Deref;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::timer::Interval;
+use types::{ChainSpec, Epoch, EthSpec, PublicKey, PublicKeyBytes};
+
+/// Delay this period of time after the slot starts. This allows the node to process the new slot.
+const TIME_DELAY_FROM_SLOT: Duration = Duration::from_millis(200);
+
+/// Builds a `DoppelgangerService`.
+pub struct DoppelgangerServiceBuilder<T, E: EthSpec> {
+    validator_store: Option<ValidatorStore<T, E>>,
+    slot_clock: Option<T>,
+    beacon_nodes: Option<BeaconNodeFallback<T, E>>,
+    context: Option<RuntimeContext<E>>,
+    detection_epochs: u64,
+}
+
+impl<T: SlotClock + 'static, E: EthSpec> DoppelgangerServiceBuilder<T, E> {
+    pub fn new() -> Self {
+        Self {
+            validator_store: None,
+            slot_clock: None,
+            beacon_nodes: None,
+            context: None,
+            detection_epochs: 0,
+        }
+    }
+
+    pub fn validator_store(mut self, store: ValidatorStore<T, E>) -> Self {
+        self.validator_store = Some(store);
+        self
+    }
+
+    pub fn slot_clock(mut self, slot_clock: T) -> Self {
+        self.slot_clock = Some(slot_clock);
+        self
+    }
+
+    pub fn beacon_nodes(mut self, beacon_nodes: BeaconNodeFallback<T, E>) -> Self {
+        self.beacon_nodes = Some(beacon_nodes);
+        self
+    }
+
+    pub fn runtime_context(mut self, context: RuntimeContext<E>) -> Self {
+        self.context = Some(context);
+        self
+    }
+
+    /// The number of complete epochs to watch for activity before signing is enabled.
+    pub fn detection_epochs(mut self, detection_epochs: u64) -> Self {
+        self.detection_epochs = detection_epochs;
+        self
+    }
+
+    pub fn build(self) -> Result<DoppelgangerService<T, E>, String> {
+        Ok(DoppelgangerService {
+            inner: Arc::new(Inner {
+                validator_store: self
+                    .validator_store
+                    .ok_or_else(|| "Cannot build DoppelgangerService without validator_store")?,
+                slot_clock: self
+                    .slot_clock
+                    .ok_or_else(|| "Cannot build DoppelgangerService without slot_clock")?,
+                beacon_nodes: self
+                    .beacon_nodes
+                    .ok_or_else(|| "Cannot build DoppelgangerService without beacon_nodes")?,
+                context: self
+                    .context
+                    .ok_or_else(|| "Cannot build DoppelgangerService without runtime_context")?,
+                detection_epochs: self.detection_epochs,
+                state: RwLock::new(DetectionState::default()),
+            }),
+        })
+    }
+}
+
+/// The progress of doppelganger detection.
+#[derive(Default)]
+struct DetectionState {
+    /// The epoch in which the service was started. Activity in this epoch is ignored, since it may
+    /// have come from a previous run of this validator client.
+    start_epoch: Epoch,
+    /// Validators which have not yet been cleared to sign.
+    pending: HashSet<PublicKey>,
+}
+
+/// Helper to minimise `Arc` usage.
+pub struct Inner<T, E: EthSpec> {
+    validator_store: ValidatorStore<T, E>,
+    slot_clock: T,
+    beacon_nodes: BeaconNodeFallback<T, E>,
+    context: RuntimeContext<E>,
+    detection_epochs: u64,
+    state: RwLock<DetectionState>,
+}
+
+/// Prevents validators from signing until the beacon nodes have shown no activity from them for
+/// `detection_epochs` epochs.
+///
+/// Any validator found to be active is assumed to be running in another validator client
+/// (a "doppelganger") and is never permitted to sign.
+pub struct DoppelgangerService<T, E: EthSpec> {
+    inner: Arc<Inner<T, E>>,
+}
+
+impl<T, E: EthSpec> Clone for DoppelgangerService<T, E> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T, E: EthSpec> Deref for DoppelgangerService<T, E> {
+    type Target = Inner<T, E>;
+
+    fn deref(&self) -> &Self::Target {
+        self.inner.deref()
+    }
+}
+
+impl<T: SlotClock + 'static, E: EthSpec> DoppelgangerService<T, E> {
+    /// Disables signing for all validators and starts the service that watches for their activity.
+    pub fn start_update_service(&self, spec: &ChainSpec) -> Result<Signal, String> {
+        let log = self.context.log.clone();
+
+        let start_epoch = self
+            .slot_clock
+            .now()
+            .ok_or_else(|| "Unable to determine current slot".to_string())?
+            .epoch(E::slots_per_epoch());
+
+        let pubkeys = self.validator_store.voting_pubkeys();
+        self.validator_store.disable_signing(&pubkeys);
+        *self.state.write() = DetectionState {
+            start_epoch,
+            pending: pubkeys.into_iter().collect(),
+        };
+
+        info!(
+            log,
+            "Doppelganger detection started";
+            "detection_epochs" => self.detection_epochs,
+            "first_signing_epoch" => self.first_signing_epoch(start_epoch).as_u64(),
+        );
+
+        let duration_to_next_slot = self
+            .slot_clock
+            .duration_to_next_slot()
+            .ok_or_else(|| "Unable to determine duration to next slot".to_string())?;
+
+        let interval = {
+            let slot_duration = Duration::from_millis(spec.milliseconds_per_slot);
+            Interval::new(
+                Instant::now() + duration_to_next_slot + TIME_DELAY_FROM_SLOT,
+                slot_duration,
+            )
+        };
+
+        let (exit_signal, exit_fut) = exit_future::signal();
+        let service = self.clone();
+        let log_1 = log.clone();
+        let log_2 = log.clone();
+
+        self.context.executor.spawn(
+            exit_fut
+                .until(
+                    interval
+                        .map_err(move |e| {
+                            crit! {
+                                log_1,
+                                "Timer thread failed";
+                                "error" => format!("{}", e)
+                            }
+                        })
+                        .for_each(move |_| service.do_update().then(|_| Ok(()))),
+                )
+                .map(move |_| info!(log_2, "Shutdown complete")),
+        );
+
+        Ok(exit_signal)
+    }
+
+    /// Returns the first epoch in which validators may sign, if no doppelganger is detected.
+    ///
+    /// Activity is checked in the `detection_epochs` epochs following `start_epoch`. An
+    /// attestation may be included in the chain up to an epoch after it was made, so the
+    /// validators are not cleared until the epoch after that.
+    fn first_signing_epoch(&self, start_epoch: Epoch) -> Epoch {
+        start_epoch + self.detection_epochs + 2
+    }
+
+    /// Checks the pending validators for activity, disabling them permanently if any is found and
+    /// enabling signing once the detection period has passed.
+    fn do_update(&self) -> impl Future<Item = (), Error = ()> {
+        let service = self.clone();
+        let log = self.context.log.clone();
+
+        let (start_epoch, pending) = {
+            let state = self.state.read();
+            (
+                state.start_epoch,
+                state.pending.iter().cloned().collect::<Vec<_>>(),
+            )
+        };
+
+        let current_epoch = match self.slot_clock.now() {
+            Some(slot) => slot.epoch(E::slots_per_epoch()),
+            None => {
+                error!(log, "Doppelganger service failed to read slot clock");
+                return future::Either::A(future::err(()));
+            }
+        };
+
+        if pending.is_empty() {
+            return future::Either::A(future::ok(()));
+        }
+
+        let last_epoch = std::cmp::min(start_epoch + self.detection_epochs, current_epoch);
+        let epochs = (start_epoch.as_u64() + 1..=last_epoch.as_u64())
+            .map(Epoch::new)
+            .collect::<Vec<_>>();
+
+        let beacon_nodes = self.beacon_nodes.clone();
+        let request_pubkeys = pending.clone();
+
+        future::Either::B(
+            self.beacon_nodes
+                .first_success(move |beacon_node| {
+                    beacon_node
+                        .http
+                        .beacon()
+                        .get_validators(request_pubkeys.clone(), None)
+                })
+                .and_then(move |validators| {
+                    let pubkeys = pending
+                        .into_iter()
+                        .map(|pubkey| (PublicKeyBytes::from(pubkey.clone()), pubkey))
+                        .collect::<HashMap<_, _>>();
+                    let indices = validators
+                        .into_iter()
+                        .filter_map(|validator| {
+                            let index = validator.validator_index? as u64;
+                            let pubkey = pubkeys.get(&validator.pubkey)?.clone();
+                            Some((index, pubkey))
+                        })
+                        .collect::<HashMap<_, _>>();
+
+                    let request_indices = indices.keys().copied().collect::<Vec<_>>();
+                    let queries = if request_indices.is_empty() {
+                        vec![]
+                    } else {
+                        epochs
+                            .into_iter()
+                            .map(|epoch| {
+                                let request_indices = request_indices.clone();
+                                beacon_nodes.first_success(move |beacon_node| {
+                                    beacon_node
+                                        .http
+                                        .consensus()
+                                        .get_liveness(epoch, request_indices.clone())
+                                })
+                            })
+                            .collect()
+                    };
+
+                    future::join_all(queries).map(move |responses| (indices, responses))
+                })
+                .map_err({
+                    let log = log.clone();
+                    move |e| {
+                        error!(
+                            log,
+                            "Unable to check for doppelgangers";
+                            "error" => e,
+                        )
+                    }
+                })
+                .and_then(move |(indices, responses)| {
+                    let mut state = service.state.write();
+
+                    for liveness in responses.into_iter().flatten() {
+                        if !liveness.is_live {
+                            continue;
+                        }
+
+                        if let Some(pubkey) = indices.get(&liveness.index) {
+                            if state.pending.remove(pubkey) {
+                                crit!(
+                                    log,
+                                    "Doppelganger detected, validator will not sign";
+                                    "msg" => "another validator client is using this key, \
+                                              stop it before restarting this validator client",
+                                    "validator_index" => liveness.index,
+                                    "epoch" => liveness.epoch.as_u64(),
+                                    "public_key" => format!("{:?}", pubkey),
+                                );
+                            }
+                        }
+                    }
+
+                    if current_epoch >= service.first_signing_epoch(state.start_epoch) {
+                        let cleared = state.pending.drain().collect::<Vec<_>>();
+                        for pubkey in &cleared {
+                            service.validator_store.enable_signing(pubkey);
+                        }
+
+                        info!(
+                            log,
+                            "Doppelganger detection complete";
+                            "signing_validators" => cleared.len(),
+                        );
+                    }
+
+                    Ok(())
+                }),
+        )
+    }
+}
@@ -1,9 +1,8 @@
-use crate::validator_store::ValidatorStore;
+use crate::{beacon_node_fallback::BeaconNodeFallback, validator_store::ValidatorStore};
 use environment::RuntimeContext;
 use exit_future::Signal;
-use futures::{future, Future, IntoFuture, Stream};
+use futures::{Future, IntoFuture, Stream};
 use parking_lot::RwLock;
-use remote_beacon_node::RemoteBeaconNode;
 use rest_types::{ValidatorDuty, ValidatorDutyBytes};
 use slog::{crit, debug, error, info, trace, warn};
 use slot_clock::SlotClock;
@@ -281,9 +280,8 @@ impl DutiesStore {
 pub struct DutiesServiceBuilder<T, E: EthSpec> {
     validator_store: Option<ValidatorStore<T, E>>,
     slot_clock: Option<T>,
-    beacon_node: Option<RemoteBeaconNode<E>>,
+    beacon_nodes: Option<BeaconNodeFallback<T, E>>,
     context: Option<RuntimeContext<E>>,
-    allow_unsynced_beacon_node: bool,
 }
 
 impl<T: SlotClock + 'static, E: EthSpec> DutiesServiceBuilder<T, E> {
@@ -291,9 +289,8 @@ impl<T: SlotClock + 'static, E: EthSpec> DutiesServiceBuilder<T, E> {
         Self {
             validator_store: None,
             slot_clock: None,
-            beacon_node: None,
+            beacon_nodes: None,
             context: None,
-            allow_unsynced_beacon_node: false,
         }
     }
 
@@ -307,8 +304,8 @@ impl<T: SlotClock + 'static, E: EthSpec> DutiesServiceBuilder<T, E> {
         self
     }
 
-    pub fn beacon_node(mut self, beacon_node: RemoteBeaconNode<E>) -> Self {
-        self.beacon_node = Some(beacon_node);
+    pub fn beacon_nodes(mut self, beacon_nodes: BeaconNodeFallback<T, E>) -> Self {
+        self.beacon_nodes = Some(beacon_nodes);
         self
     }
 
@@ -317,12 +314,6 @@ impl<T: SlotClock + 'static, E: EthSpec> DutiesServiceBuilder<T, E> {
         self
     }
 
-    /// Set to `true` to allow polling for duties when the beacon node is not synced.
-    pub fn allow_unsynced_beacon_node(mut self, allow_unsynced_beacon_node: bool) -> Self {
-        self.allow_unsynced_beacon_node = allow_unsynced_beacon_node;
-        self
-    }
-
     pub fn build(self) -> Result<DutiesService<T, E>, String> {
         Ok(DutiesService {
             inner: Arc::new(Inner {
@@ -333,13 +324,12 @@ impl<T: SlotClock + 'static, E: EthSpec> DutiesServiceBuilder<T, E> {
                 slot_clock: self
                     .slot_clock
                     .ok_or_else(|| "Cannot build DutiesService without slot_clock")?,
-                beacon_node: self
-                    .beacon_node
-                    .ok_or_else(|| "Cannot build DutiesService without beacon_node")?,
+                beacon_nodes: self
+                    .beacon_nodes
+                    .ok_or_else(|| "Cannot build DutiesService without beacon_nodes")?,
                 context: self
                     .context
                     .ok_or_else(|| "Cannot build DutiesService without runtime_context")?,
-                allow_unsynced_beacon_node: self.allow_unsynced_beacon_node,
             }),
         })
     }
@@ -350,11 +340,8 @@ pub struct Inner<T, E: EthSpec> {
     store: Arc<DutiesStore>,
     validator_store: ValidatorStore<T, E>,
     pub(crate) slot_clock: T,
-    beacon_node: RemoteBeaconNode<E>,
+    beacon_nodes: BeaconNodeFallback<T, E>,
     context: RuntimeContext<E>,
-    /// If true, the duties service will poll for duties from the beacon node even if it is not
-    /// synced.
-    allow_unsynced_beacon_node: bool,
 }
 
 /// Maintains a store of the duties for all voting validators in the `validator_store`.
@@ -480,7 +467,6 @@ impl<T: SlotClock + 'static, E: EthSpec> DutiesService<T, E> {
         let service_1 = self.clone();
         let service_2 = self.clone();
         let service_3 = self.clone();
-        let service_4 = self.clone();
         let log_1 = self.context.log.clone();
         let log_2 = self.context.log.clone();
 
@@ -508,60 +494,27 @@ impl<T: SlotClock + 'static, E: EthSpec> DutiesService<T, E> {
 
                 epoch
             })
-            .and_then(move |epoch| {
+            .and_then(move |current_epoch| {
                 let log = service_2.context.log.clone();
 
-                service_2
-                    .beacon_node
-                    .http
-                    .beacon()
-                    .get_head()
-                    .map(move |head| (epoch, head.slot.epoch(E::slots_per_epoch())))
-                    .map_err(move |e| {
+                service_2.update_epoch(current_epoch).then(move |result| {
+                    if let Err(e) = result {
                         error!(
-                                log,
-                                "Failed to contact beacon node";
-                                "error" => format!("{:?}", e)
-                        )
-                    })
-            })
-            .and_then(move |(current_epoch, beacon_head_epoch)| {
-                let log = service_3.context.log.clone();
-
-                let future: Box<dyn Future<Item = (), Error = ()> + Send> = if beacon_head_epoch + 1
-                    < current_epoch
-                    && !service_3.allow_unsynced_beacon_node
-                {
-                    error!(
-                        log,
-                        "Beacon node is not synced";
-                        "node_head_epoch" => format!("{}", beacon_head_epoch),
-                        "current_epoch" => format!("{}", current_epoch),
-                    );
-
-                    Box::new(future::ok(()))
-                } else {
-                    Box::new(service_3.update_epoch(current_epoch).then(move |result| {
-                        if let Err(e) = result {
-                            error!(
-                                log,
-                                "Failed to get current epoch duties";
-                                "http_error" => format!("{:?}", e)
-                            );
-                        }
-
-                        let log = service_4.context.log.clone();
-                        service_4.update_epoch(current_epoch + 1).map_err(move |e| {
-                            error!(
-                                log,
-                                "Failed to get next epoch duties";
-                                "http_error" => format!("{:?}", e)
-                            );
-                        })
-                    }))
-                };
+                            log,
+                            "Failed to get current epoch duties";
+                            "http_error" => format!("{:?}", e)
+                        );
+                    }
 
-                future
+                    let log = service_3.context.log.clone();
+                    service_3.update_epoch(current_epoch + 1).map_err(move |e| {
+                        error!(
+                            log,
+                            "Failed to get next epoch duties";
+                            "http_error" => format!("{:?}", e)
+                        );
+                    })
+                })
             })
             .map(|_| ())
     }
@@ -573,12 +526,15 @@ impl<T: SlotClock + 'static, E: EthSpec> DutiesService<T, E> {
 
         let pubkeys = service_1.validator_store.voting_pubkeys();
         service_1
-            .beacon_node
-            .http
-            .validator()
-            .get_duties(epoch, pubkeys.as_slice())
+            .beacon_nodes
+            .first_success(move |beacon_node| {
+                beacon_node
+                    .http
+                    .validator()
+                    .get_duties(epoch, pubkeys.as_slice())
+            })
             .map(move |all_duties| (epoch, all_duties))
-            .map_err(move |e| format!("Failed to get duties for epoch {}: {:?}", epoch, e))
+            .map_err(move |e| format!("Failed to get duties for epoch {}: {}", epoch, e))
             .and_then(move |(epoch, all_duties)| {
                 let log = service_2.context.log.clone();
 
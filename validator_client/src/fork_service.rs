@@ -1,8 +1,8 @@
+use crate::beacon_node_fallback::BeaconNodeFallback;
 use environment::RuntimeContext;
 use exit_future::Signal;
 use futures::{Future, Stream};
 use parking_lot::RwLock;
-use remote_beacon_node::RemoteBeaconNode;
 use slog::{crit, info, trace};
 use slot_clock::SlotClock;
 use std::ops::Deref;
@@ -18,7 +18,7 @@ const TIME_DELAY_FROM_SLOT: Duration = Duration::from_millis(80);
 pub struct ForkServiceBuilder<T, E: EthSpec> {
     fork: Option<Fork>,
     slot_clock: Option<T>,
-    beacon_node: Option<RemoteBeaconNode<E>>,
+    beacon_nodes: Option<BeaconNodeFallback<T, E>>,
     context: Option<RuntimeContext<E>>,
 }
 
@@ -27,7 +27,7 @@ impl<T: SlotClock + 'static, E: EthSpec> ForkServiceBuilder<T, E> {
         Self {
             fork: None,
             slot_clock: None,
-            beacon_node: None,
+            beacon_nodes: None,
             context: None,
         }
     }
@@ -37,8 +37,8 @@ impl<T: SlotClock + 'static, E: EthSpec> ForkServiceBuilder<T, E> {
         self
     }
 
-    pub fn beacon_node(mut self, beacon_node: RemoteBeaconNode<E>) -> Self {
-        self.beacon_node = Some(beacon_node);
+    pub fn beacon_nodes(mut self, beacon_nodes: BeaconNodeFallback<T, E>) -> Self {
+        self.beacon_nodes = Some(beacon_nodes);
         self
     }
 
@@ -54,9 +54,9 @@ impl<T: SlotClock + 'static, E: EthSpec> ForkServiceBuilder<T, E> {
                 slot_clock: self
                     .slot_clock
                     .ok_or_else(|| "Cannot build ForkService without slot_clock")?,
-                beacon_node: self
-                    .beacon_node
-                    .ok_or_else(|| "Cannot build ForkService without beacon_node")?,
+                beacon_nodes: self
+                    .beacon_nodes
+                    .ok_or_else(|| "Cannot build ForkService without beacon_nodes")?,
                 context: self
                     .context
                     .ok_or_else(|| "Cannot build ForkService without runtime_context")?,
@@ -68,7 +68,7 @@ impl<T: SlotClock + 'static, E: EthSpec> ForkServiceBuilder<T, E> {
 /// Helper to minimise `Arc` usage.
 pub struct Inner<T, E: EthSpec> {
     fork: RwLock<Option<Fork>>,
-    beacon_node: RemoteBeaconNode<E>,
+    beacon_nodes: BeaconNodeFallback<T, E>,
     context: RuntimeContext<E>,
     slot_clock: T,
 }
@@ -151,17 +151,15 @@ impl<T: SlotClock + 'static, E: EthSpec> ForkService<T, E> {
         let log_2 = service_1.context.log.clone();
 
         self.inner
-            .beacon_node
-            .http
-            .beacon()
-            .get_fork()
+            .beacon_nodes
+            .first_success(|beacon_node| beacon_node.http.beacon().get_fork())
             .map(move |fork| *(service_1.fork.write()) = Some(fork))
             .map(move |_| trace!(log_1, "Fork update success"))
             .map_err(move |e| {
                 trace!(
                     log_2,
                     "Fork update failed";
-                    "error" => format!("Error retrieving fork: {:?}", e)
+                    "error" => format!("Error retrieving fork: {}", e)
                 )
             })
             // Returning an error will stop the interval. This is not desired, a single failure
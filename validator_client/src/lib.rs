@@ -1,7 +1,9 @@
 mod attestation_service;
+mod beacon_node_fallback;
 mod block_service;
 mod cli;
 mod config;
+mod doppelganger_service;
 mod duties_service;
 mod fork_service;
 mod notifier;
@@ -13,8 +15,10 @@ pub use cli::cli_app;
 pub use config::{Config, KeySource};
 
 use attestation_service::{AttestationService, AttestationServiceBuilder};
+use beacon_node_fallback::{BeaconNodeFallback, CandidateBeaconNode};
 use block_service::{BlockService, BlockServiceBuilder};
 use clap::ArgMatches;
+use doppelganger_service::{DoppelgangerService, DoppelgangerServiceBuilder};
 use duties_service::{DutiesService, DutiesServiceBuilder};
 use environment::RuntimeContext;
 use exit_future::Signal;
@@ -46,6 +50,8 @@ pub struct ProductionValidatorClient<T: EthSpec> {
     fork_service: ForkService<SystemTimeSlotClock, T>,
     block_service: BlockService<SystemTimeSlotClock, T>,
     attestation_service: AttestationService<SystemTimeSlotClock, T>,
+    beacon_nodes: BeaconNodeFallback<SystemTimeSlotClock, T>,
+    doppelganger_service: Option<DoppelgangerService<SystemTimeSlotClock, T>>,
     exit_signals: Vec<Signal>,
 }
 
@@ -76,31 +82,47 @@ impl<T: EthSpec> ProductionValidatorClient<T> {
         info!(
             log_1,
             "Starting validator client";
-            "beacon_node" => &config.http_server,
+            "beacon_nodes" => format!("{:?}", config.beacon_nodes),
             "datadir" => format!("{:?}", config.data_dir),
         );
 
-        RemoteBeaconNode::new_with_timeout(config.http_server.clone(), HTTP_TIMEOUT)
-            .map_err(|e| format!("Unable to init beacon node http client: {}", e))
+        let fallback_context = context.service_context("fallback".into());
+        let allow_unsynced_beacon_node = config.allow_unsynced_beacon_node;
+
+        read_beacon_node_token(&config)
+            .and_then(|token| {
+                config
+                    .beacon_nodes
+                    .iter()
+                    .map(|endpoint| {
+                        RemoteBeaconNode::new_with_token(
+                            endpoint.clone(),
+                            HTTP_TIMEOUT,
+                            token.as_ref().map(String::as_str),
+                        )
+                        .map(|beacon_node| CandidateBeaconNode::new(endpoint.clone(), beacon_node))
+                        .map_err(|e| format!("Unable to init beacon node http client: {}", e))
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .map(move |candidates| {
+                BeaconNodeFallback::new(candidates, allow_unsynced_beacon_node, fallback_context)
+            })
             .into_future()
-            .and_then(move |beacon_node| wait_for_node(beacon_node, log_2))
-            .and_then(|beacon_node| {
-                beacon_node
-                    .http
-                    .spec()
-                    .get_eth2_config()
-                    .map(|eth2_config| (beacon_node, eth2_config))
-                    .map_err(|e| format!("Unable to read eth2 config from beacon node: {:?}", e))
+            .and_then(move |beacon_nodes| wait_for_node(beacon_nodes, log_2))
+            .and_then(|beacon_nodes| {
+                beacon_nodes
+                    .first_success(|beacon_node| beacon_node.http.spec().get_eth2_config())
+                    .map(|eth2_config| (beacon_nodes, eth2_config))
+                    .map_err(|e| format!("Unable to read eth2 config from beacon node: {}", e))
             })
-            .and_then(|(beacon_node, eth2_config)| {
-                beacon_node
-                    .http
-                    .beacon()
-                    .get_genesis_time()
-                    .map(|genesis_time| (beacon_node, eth2_config, genesis_time))
-                    .map_err(|e| format!("Unable to read genesis time from beacon node: {:?}", e))
+            .and_then(|(beacon_nodes, eth2_config)| {
+                beacon_nodes
+                    .first_success(|beacon_node| beacon_node.http.beacon().get_genesis_time())
+                    .map(|genesis_time| (beacon_nodes, eth2_config, genesis_time))
+                    .map_err(|e| format!("Unable to read genesis time from beacon node: {}", e))
             })
-            .and_then(move |(beacon_node, remote_eth2_config, genesis_time)| {
+            .and_then(move |(beacon_nodes, remote_eth2_config, genesis_time)| {
                 SystemTime::now()
                     .duration_since(UNIX_EPOCH)
                     .into_future()
@@ -126,7 +148,7 @@ impl<T: EthSpec> ProductionValidatorClient<T> {
                                     .map_err(|e| {
                                         format!("Unable to create genesis wait delay: {:?}", e)
                                     })
-                                    .map(move |_| (beacon_node, remote_eth2_config, genesis_time)),
+                                    .map(move |_| (beacon_nodes, remote_eth2_config, genesis_time)),
                             )
                         } else {
                             info!(
@@ -135,18 +157,18 @@ impl<T: EthSpec> ProductionValidatorClient<T> {
                                 "seconds_ago" => (now - genesis).as_secs()
                             );
 
-                            Box::new(future::ok((beacon_node, remote_eth2_config, genesis_time)))
+                            Box::new(future::ok((beacon_nodes, remote_eth2_config, genesis_time)))
                         }
                     })
             })
-            .and_then(|(beacon_node, eth2_config, genesis_time)| {
-                beacon_node
-                    .http
-                    .beacon()
-                    .get_genesis_validators_root()
+            .and_then(|(beacon_nodes, eth2_config, genesis_time)| {
+                beacon_nodes
+                    .first_success(|beacon_node| {
+                        beacon_node.http.beacon().get_genesis_validators_root()
+                    })
                     .map(move |genesis_validators_root| {
                         (
-                            beacon_node,
+                            beacon_nodes,
                             eth2_config,
                             genesis_time,
                             genesis_validators_root,
@@ -154,13 +176,13 @@ impl<T: EthSpec> ProductionValidatorClient<T> {
                     })
                     .map_err(|e| {
                         format!(
-                            "Unable to read genesis validators root from beacon node: {:?}",
+                            "Unable to read genesis validators root from beacon node: {}",
                             e
                         )
                     })
             })
             .and_then(
-                move |(beacon_node, remote_eth2_config, genesis_time, genesis_validators_root)| {
+                move |(beacon_nodes, remote_eth2_config, genesis_time, genesis_validators_root)| {
                     let log = log_4.clone();
 
                     // Do not permit a connection to a beacon node using different spec constants.
@@ -186,9 +208,11 @@ impl<T: EthSpec> ProductionValidatorClient<T> {
                         Duration::from_millis(context.eth2_config.spec.milliseconds_per_slot),
                     );
 
+                    beacon_nodes.set_slot_clock(slot_clock.clone());
+
                     let fork_service = ForkServiceBuilder::new()
                         .slot_clock(slot_clock.clone())
-                        .beacon_node(beacon_node.clone())
+                        .beacon_nodes(beacon_nodes.clone())
                         .runtime_context(context.service_context("fork".into()))
                         .build()?;
 
@@ -199,6 +223,7 @@ impl<T: EthSpec> ProductionValidatorClient<T> {
                             // Use the `account_manager` to generate these files.
                             KeySource::Disk => ValidatorStore::load_from_disk(
                                 config.data_dir.clone(),
+                                config.secrets_dir.clone(),
                                 genesis_validators_root,
                                 context.eth2_config.spec.clone(),
                                 fork_service.clone(),
@@ -227,24 +252,36 @@ impl<T: EthSpec> ProductionValidatorClient<T> {
                     let duties_service = DutiesServiceBuilder::new()
                         .slot_clock(slot_clock.clone())
                         .validator_store(validator_store.clone())
-                        .beacon_node(beacon_node.clone())
+                        .beacon_nodes(beacon_nodes.clone())
                         .runtime_context(context.service_context("duties".into()))
-                        .allow_unsynced_beacon_node(config.allow_unsynced_beacon_node)
                         .build()?;
 
                     let block_service = BlockServiceBuilder::new()
                         .duties_service(duties_service.clone())
                         .slot_clock(slot_clock.clone())
                         .validator_store(validator_store.clone())
-                        .beacon_node(beacon_node.clone())
+                        .beacon_nodes(beacon_nodes.clone())
                         .runtime_context(context.service_context("block".into()))
                         .build()?;
 
+                    let doppelganger_service = config
+                        .doppelganger_detection_epochs
+                        .map(|detection_epochs| {
+                            DoppelgangerServiceBuilder::new()
+                                .slot_clock(slot_clock.clone())
+                                .validator_store(validator_store.clone())
+                                .beacon_nodes(beacon_nodes.clone())
+                                .runtime_context(context.service_context("doppelganger".into()))
+                                .detection_epochs(detection_epochs)
+                                .build()
+                        })
+                        .transpose()?;
+
                     let attestation_service = AttestationServiceBuilder::new()
                         .duties_service(duties_service.clone())
                         .slot_clock(slot_clock)
                         .validator_store(validator_store)
-                        .beacon_node(beacon_node)
+                        .beacon_nodes(beacon_nodes.clone())
                         .runtime_context(context.service_context("attestation".into()))
                         .build()?;
 
@@ -254,6 +291,8 @@ impl<T: EthSpec> ProductionValidatorClient<T> {
                         fork_service,
                         block_service,
                         attestation_service,
+                        beacon_nodes,
+                        doppelganger_service,
                         exit_signals: vec![],
                     })
                 },
@@ -261,6 +300,15 @@ impl<T: EthSpec> ProductionValidatorClient<T> {
     }
 
     pub fn start_service(&mut self) -> Result<(), String> {
+        // Doppelganger detection must disable signing before any other service begins producing
+        // messages.
+        let doppelganger_exit = self
+            .doppelganger_service
+            .as_ref()
+            .map(|service| service.start_update_service(&self.context.eth2_config.spec))
+            .transpose()
+            .map_err(|e| format!("Unable to start doppelganger service: {}", e))?;
+
         let duties_exit = self
             .duties_service
             .start_update_service(&self.context.eth2_config.spec)
@@ -281,6 +329,11 @@ impl<T: EthSpec> ProductionValidatorClient<T> {
             .start_update_service(&self.context.eth2_config.spec)
             .map_err(|e| format!("Unable to start attestation service: {}", e))?;
 
+        let fallback_exit = self
+            .beacon_nodes
+            .start_update_service(&self.context.eth2_config.spec)
+            .map_err(|e| format!("Unable to start beacon node fallback service: {}", e))?;
+
         let notifier_exit =
             spawn_notifier(self).map_err(|e| format!("Failed to start notifier: {}", e))?;
 
@@ -289,57 +342,72 @@ impl<T: EthSpec> ProductionValidatorClient<T> {
             fork_exit,
             block_exit,
             attestation_exit,
+            fallback_exit,
             notifier_exit,
         ];
+        self.exit_signals.extend(doppelganger_exit);
 
         Ok(())
     }
 }
 
-/// Request the version from the node, looping back and trying again on failure. Exit once the node
-/// has been contacted.
+/// Check the status of each beacon node, looping back and trying again until at least one is
+/// ready. Exit once a node is ready.
 fn wait_for_node<E: EthSpec>(
-    beacon_node: RemoteBeaconNode<E>,
+    beacon_nodes: BeaconNodeFallback<SystemTimeSlotClock, E>,
     log: Logger,
-) -> impl Future<Item = RemoteBeaconNode<E>, Error = String> {
-    // Try to get the version string from the node, looping until success is returned.
-    loop_fn(beacon_node.clone(), move |beacon_node| {
+) -> impl Future<Item = BeaconNodeFallback<SystemTimeSlotClock, E>, Error = String> {
+    loop_fn(beacon_nodes, move |beacon_nodes| {
         let log = log.clone();
-        beacon_node
-            .clone()
-            .http
-            .node()
-            .get_version()
-            .map_err(|e| format!("{:?}", e))
-            .then(move |result| {
-                let future: Box<dyn Future<Item = Loop<_, _>, Error = String> + Send> = match result
-                {
-                    Ok(version) => {
+        beacon_nodes
+            .update_all_candidates()
+            .map_err(|()| "Unable to update beacon node status".to_string())
+            .and_then(move |()| {
+                let future: Box<dyn Future<Item = Loop<_, _>, Error = String> + Send> =
+                    if beacon_nodes.num_ready() > 0 {
                         info!(
                             log,
-                            "Connected to beacon node";
-                            "version" => version,
+                            "Connected to beacon nodes";
+                            "ready" => beacon_nodes.num_ready(),
+                            "total" => beacon_nodes.num_total(),
                         );
 
-                        Box::new(future::ok(Loop::Break(beacon_node)))
-                    }
-                    Err(e) => {
+                        Box::new(future::ok(Loop::Break(beacon_nodes)))
+                    } else {
                         error!(
                             log,
-                            "Unable to connect to beacon node";
-                            "error" => format!("{:?}", e),
+                            "Unable to connect to a beacon node";
+                            "total" => beacon_nodes.num_total(),
                         );
 
                         Box::new(
                             Delay::new(Instant::now() + RETRY_DELAY)
                                 .map_err(|e| format!("Failed to trigger delay: {:?}", e))
-                                .and_then(|_| future::ok(Loop::Continue(beacon_node))),
+                                .and_then(|_| future::ok(Loop::Continue(beacon_nodes))),
                         )
-                    }
-                };
+                    };
 
                 future
             })
     })
-    .map(|_| beacon_node)
+}
+
+/// Reads the bearer token for the beacon node HTTP APIs from the first line of
+/// `config.beacon_node_token_file`, if it is set.
+fn read_beacon_node_token(config: &Config) -> Result<Option<String>, String> {
+    config
+        .beacon_node_token_file
+        .as_ref()
+        .map(|path| {
+            let contents = std::fs::read_to_string(path).map_err(|e| {
+                format!("Unable to read beacon node token file {:?}: {:?}", path, e)
+            })?;
+            contents
+                .lines()
+                .next()
+                .map(|token| token.trim().to_string())
+                .filter(|token| !token.is_empty())
+                .ok_or_else(|| format!("Beacon node token file {:?} is empty", path))
+        })
+        .transpose()
 }
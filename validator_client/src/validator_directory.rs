@@ -1,14 +1,21 @@
 use bls::get_withdrawal_credentials;
 use deposit_contract::{encode_eth1_tx_data, DEPOSIT_GAS};
+use eth2_key_derivation::{DerivedKey, KeyType, ValidatorPath};
+use eth2_keystore::{
+    json_keystore::Kdf, keypair_from_secret, Keystore, KeystoreBuilder, PlainText,
+};
 use futures::{Future, IntoFuture};
 use hex;
+use lazy_static::lazy_static;
+use parking_lot::Mutex;
+use rand::{distributions::Alphanumeric, Rng};
 use ssz::{Decode, Encode};
 use ssz_derive::{Decode, Encode};
 use std::fs;
 use std::fs::File;
 use std::io::prelude::*;
 use std::os::unix::fs::PermissionsExt;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use types::{
     test_utils::generate_deterministic_keypair, ChainSpec, DepositData, Hash256, Keypair,
     PublicKey, SecretKey, Signature,
@@ -21,12 +28,31 @@ use web3::{
 const VOTING_KEY_PREFIX: &str = "voting";
 const WITHDRAWAL_KEY_PREFIX: &str = "withdrawal";
 const ETH1_DEPOSIT_DATA_FILE: &str = "eth1_deposit_data.rlp";
+/// The number of characters in a randomly generated keystore password.
+const PASSWORD_LEN: usize = 48;
+
+lazy_static! {
+    /// Ensures that only one password prompt is displayed at a time, even when validator
+    /// directories are being loaded in parallel.
+    static ref PASSWORD_PROMPT_LOCK: Mutex<()> = Mutex::new(());
+}
+
+/// Returns the filename of an EIP-2335 keystore file.
+fn keystore_file(prefix: &str) -> String {
+    format!("{}-keystore.json", prefix)
+}
 
-/// Returns the filename of a keypair file.
-fn keypair_file(prefix: &str) -> String {
+/// Returns the filename of a legacy, unencrypted SSZ keypair file.
+fn legacy_keypair_file(prefix: &str) -> String {
     format!("{}_keypair", prefix)
 }
 
+/// Returns the path to the file containing the password for the keystore with the given
+/// `pubkey` (hex, without a `0x` prefix).
+fn password_file(secrets_dir: &Path, pubkey: &str) -> PathBuf {
+    secrets_dir.join(format!("0x{}", pubkey))
+}
+
 /// Returns the name of the folder to be generated for a validator with the given voting key.
 fn dir_name(voting_pubkey: &PublicKey) -> String {
     format!("0x{}", hex::encode(voting_pubkey.as_ssz_bytes()))
@@ -46,7 +72,12 @@ pub struct ValidatorDirectory {
 impl ValidatorDirectory {
     /// Attempts to load a validator from the given directory, requiring only components necessary
     /// for signing messages.
-    pub fn load_for_signing(directory: PathBuf) -> Result<Self, String> {
+    ///
+    /// The voting keystore is decrypted using the password file for its public key in
+    /// `secrets_dir`. If there is no such file, the user is prompted for the password.
+    ///
+    /// The withdrawal keypair is only loaded if its password file is present in `secrets_dir`.
+    pub fn load_for_signing(directory: PathBuf, secrets_dir: &Path) -> Result<Self, String> {
         if !directory.exists() {
             return Err(format!(
                 "Validator directory does not exist: {:?}",
@@ -56,22 +87,96 @@ impl ValidatorDirectory {
 
         Ok(Self {
             voting_keypair: Some(
-                load_keypair(directory.clone(), VOTING_KEY_PREFIX)
+                load_keypair(&directory, secrets_dir, VOTING_KEY_PREFIX, true)
                     .map_err(|e| format!("Unable to get voting keypair: {}", e))?,
             ),
-            withdrawal_keypair: load_keypair(directory.clone(), WITHDRAWAL_KEY_PREFIX).ok(),
+            withdrawal_keypair: load_keypair(&directory, secrets_dir, WITHDRAWAL_KEY_PREFIX, false)
+                .ok(),
             deposit_data: load_eth1_deposit_data(directory.clone()).ok(),
             directory,
         })
     }
 }
 
-/// Load a `Keypair` from a file.
-fn load_keypair(base_path: PathBuf, file_prefix: &str) -> Result<Keypair, String> {
-    let path = base_path.join(keypair_file(file_prefix));
+/// Load a `Keypair` from the keystore in `base_path`, falling back to a legacy unencrypted
+/// keypair file if no keystore exists.
+///
+/// If `prompt == true` and there is no password file for the keystore in `secrets_dir`, the
+/// password is read from the terminal.
+fn load_keypair(
+    base_path: &Path,
+    secrets_dir: &Path,
+    file_prefix: &str,
+    prompt: bool,
+) -> Result<Keypair, String> {
+    let path = base_path.join(keystore_file(file_prefix));
 
     if !path.exists() {
-        return Err(format!("Keypair file does not exist: {:?}", path));
+        return load_legacy_keypair(base_path, file_prefix);
+    }
+
+    let keystore = File::open(&path)
+        .map_err(|e| format!("Unable to open keystore file: {}", e))
+        .and_then(|file| {
+            Keystore::from_json_reader(file)
+                .map_err(|e| format!("Unable to decode keystore: {:?}", e))
+        })?;
+
+    let password_path = password_file(secrets_dir, keystore.pubkey());
+    let password = if password_path.exists() {
+        read_password_file(&password_path)?
+    } else if prompt {
+        let _lock = PASSWORD_PROMPT_LOCK.lock();
+        rpassword::read_password_from_tty(Some(&format!(
+            "Enter the password for keystore {:?}: ",
+            path
+        )))
+        .map(|password| PlainText::from(password.into_bytes()))
+        .map_err(|e| {
+            format!(
+                "No password file at {:?} and unable to read password from terminal: {}",
+                password_path, e
+            )
+        })?
+    } else {
+        return Err(format!("Password file does not exist: {:?}", password_path));
+    };
+
+    keystore
+        .decrypt_keypair(password.as_bytes())
+        .map_err(|e| format!("Unable to decrypt keystore {:?}: {:?}", path, e))
+}
+
+/// Reads a keystore password from `path`, stripping any trailing newline characters.
+fn read_password_file(path: &Path) -> Result<PlainText, String> {
+    let mut bytes = vec![];
+
+    File::open(path)
+        .map_err(|e| format!("Unable to open password file: {}", e))?
+        .read_to_end(&mut bytes)
+        .map_err(|e| format!("Unable to read password file: {}", e))?;
+
+    let password = PlainText::from(bytes);
+    let len = password
+        .as_bytes()
+        .iter()
+        .rposition(|byte| *byte != b'\n' && *byte != b'\r')
+        .map_or(0, |i| i + 1);
+
+    Ok(PlainText::from(password.as_bytes()[..len].to_vec()))
+}
+
+/// Load an unencrypted, SSZ-encoded `Keypair` from a file.
+///
+/// Keypairs were stored this way before the introduction of EIP-2335 keystores.
+fn load_legacy_keypair(base_path: &Path, file_prefix: &str) -> Result<Keypair, String> {
+    let path = base_path.join(legacy_keypair_file(file_prefix));
+
+    if !path.exists() {
+        return Err(format!(
+            "Neither a keystore nor a keypair file exists for {} in {:?}",
+            file_prefix, base_path
+        ));
     }
 
     let mut bytes = vec![];
@@ -110,7 +215,7 @@ fn load_eth1_deposit_data(base_path: PathBuf) -> Result<Vec<u8>, String> {
     }
 }
 
-/// A helper struct to allow SSZ enc/dec for a `Keypair`.
+/// A helper struct to allow SSZ enc/dec for a `Keypair`, used by legacy keypair files.
 #[derive(Encode, Decode)]
 struct SszEncodableKeypair {
     pk: PublicKey,
@@ -141,6 +246,13 @@ pub struct ValidatorDirectoryBuilder {
     directory: Option<PathBuf>,
     voting_keypair: Option<Keypair>,
     withdrawal_keypair: Option<Keypair>,
+    voting_password: Option<PlainText>,
+    withdrawal_password: Option<PlainText>,
+    /// The EIP-2334 derivation path of the voting keypair, empty if it was not derived.
+    voting_path: String,
+    /// The EIP-2334 derivation path of the withdrawal keypair, empty if it was not derived.
+    withdrawal_path: String,
+    keystore_kdf: Option<Kdf>,
     amount: Option<u64>,
     deposit_data: Option<Vec<u8>>,
     spec: Option<ChainSpec>,
@@ -179,6 +291,68 @@ impl ValidatorDirectoryBuilder {
         self
     }
 
+    /// Derives the voting and withdrawal keypairs for the validator at `index` from `seed` (e.g.,
+    /// the seed of a BIP-39 mnemonic), as per EIP-2333.
+    ///
+    /// The keys are derived along the EIP-2334 paths:
+    ///
+    /// - Withdrawal: `m/12381/3600/{index}/0`
+    /// - Voting: `m/12381/3600/{index}/0/0`
+    pub fn derived_keypairs(mut self, seed: &[u8], index: u32) -> Result<Self, String> {
+        let master = DerivedKey::from_seed(seed)
+            .map_err(|e| format!("Unable to derive master key from seed: {:?}", e))?;
+
+        let derive = |key_type| {
+            let path = ValidatorPath::new(index, key_type);
+            let key = master.descend(&path.nodes());
+            keypair_from_secret(key.secret())
+                .map(|keypair| (keypair, path.to_string()))
+                .map_err(|e| format!("Unable to derive keypair at {}: {:?}", path, e))
+        };
+
+        let (voting_keypair, voting_path) = derive(KeyType::Voting)?;
+        let (withdrawal_keypair, withdrawal_path) = derive(KeyType::Withdrawal)?;
+
+        self.voting_keypair = Some(voting_keypair);
+        self.withdrawal_keypair = Some(withdrawal_keypair);
+        self.voting_path = voting_path;
+        self.withdrawal_path = withdrawal_path;
+        Ok(self)
+    }
+
+    /// Generates a random password for each of the voting and withdrawal keystores.
+    ///
+    /// If the voting and withdrawal keypairs are identical (e.g., `insecure_keypairs`) they share
+    /// a single password, since their password files would have the same name.
+    pub fn random_passwords(mut self) -> Self {
+        let voting_password = random_password();
+        let withdrawal_password = match (&self.voting_keypair, &self.withdrawal_keypair) {
+            (Some(voting), Some(withdrawal)) if voting.pk == withdrawal.pk => {
+                voting_password.clone()
+            }
+            _ => random_password(),
+        };
+
+        self.voting_password = Some(voting_password);
+        self.withdrawal_password = Some(withdrawal_password);
+        self
+    }
+
+    /// Use the given passwords to encrypt the voting and withdrawal keystores.
+    pub fn passwords(mut self, voting: PlainText, withdrawal: PlainText) -> Self {
+        self.voting_password = Some(voting);
+        self.withdrawal_password = Some(withdrawal);
+        self
+    }
+
+    /// Use the given `kdf` for the keystores, instead of the EIP-2335 default.
+    ///
+    /// Cheap KDF parameters are useful for testing, they should not be used in production.
+    pub fn keystore_kdf(mut self, kdf: Kdf) -> Self {
+        self.keystore_kdf = Some(kdf);
+        self
+    }
+
     /// Creates a validator directory in the given `base_path` (e.g., `~/.lighthouse/validators/`).
     pub fn create_directory(mut self, base_path: PathBuf) -> Result<Self, String> {
         let voting_keypair = self
@@ -213,37 +387,102 @@ impl ValidatorDirectoryBuilder {
             .clone()
             .ok_or_else(|| "write_keypair_files requires a withdrawal_keypair")?;
 
-        self.save_keypair(voting_keypair, VOTING_KEY_PREFIX)?;
-        self.save_keypair(withdrawal_keypair, WITHDRAWAL_KEY_PREFIX)?;
+        let voting_password = self
+            .voting_password
+            .as_ref()
+            .ok_or_else(|| "write_keypair_files requires a voting_password")?;
+        let withdrawal_password = self
+            .withdrawal_password
+            .as_ref()
+            .ok_or_else(|| "write_keypair_files requires a withdrawal_password")?;
+
+        self.save_keystore(
+            &voting_keypair,
+            voting_password,
+            &self.voting_path,
+            VOTING_KEY_PREFIX,
+        )?;
+        self.save_keystore(
+            &withdrawal_keypair,
+            withdrawal_password,
+            &self.withdrawal_path,
+            WITHDRAWAL_KEY_PREFIX,
+        )?;
         Ok(self)
     }
 
-    fn save_keypair(&self, keypair: Keypair, file_prefix: &str) -> Result<(), String> {
+    fn save_keystore(
+        &self,
+        keypair: &Keypair,
+        password: &PlainText,
+        derivation_path: &str,
+        file_prefix: &str,
+    ) -> Result<(), String> {
         let path = self
             .directory
             .as_ref()
-            .map(|directory| directory.join(keypair_file(file_prefix)))
-            .ok_or_else(|| "save_keypair requires a directory")?;
+            .map(|directory| directory.join(keystore_file(file_prefix)))
+            .ok_or_else(|| "save_keystore requires a directory")?;
 
         if path.exists() {
-            return Err(format!("Keypair file already exists at: {:?}", path));
+            return Err(format!("Keystore file already exists at: {:?}", path));
         }
 
-        let mut file = File::create(&path).map_err(|e| format!("Unable to create file: {}", e))?;
+        let mut builder =
+            KeystoreBuilder::new(keypair, password.as_bytes(), derivation_path.into())
+                .map_err(|e| format!("Unable to create keystore builder: {:?}", e))?;
+        if let Some(kdf) = &self.keystore_kdf {
+            builder = builder.kdf(kdf.clone());
+        }
+        let keystore = builder
+            .build()
+            .map_err(|e| format!("Unable to build keystore: {:?}", e))?;
 
-        // Ensure file has correct permissions.
-        let mut perm = file
-            .metadata()
-            .map_err(|e| format!("Unable to get file metadata: {}", e))?
-            .permissions();
-        perm.set_mode((libc::S_IWUSR | libc::S_IRUSR) as u32);
-        file.set_permissions(perm)
-            .map_err(|e| format!("Unable to set file permissions: {}", e))?;
+        let file = create_with_600_perms(&path)?;
+        keystore
+            .to_json_writer(file)
+            .map_err(|e| format!("Unable to write keystore to file: {:?}", e))
+    }
 
-        file.write_all(&SszEncodableKeypair::from(keypair).as_ssz_bytes())
-            .map_err(|e| format!("Unable to write keypair to file: {}", e))?;
+    /// Writes the voting and withdrawal keystore passwords to `secrets_dir`, each in a file named
+    /// after the `0x`-prefixed public key of its keystore.
+    ///
+    /// Without these files `ValidatorDirectory::load_for_signing` will prompt for the password of
+    /// the voting keystore. The withdrawal password is not required for signing, it may be moved
+    /// to offline storage.
+    pub fn write_password_files(self, secrets_dir: &Path) -> Result<Self, String> {
+        fs::create_dir_all(secrets_dir)
+            .map_err(|e| format!("Unable to create secrets directory: {}", e))?;
 
-        Ok(())
+        let voting_keypair = self
+            .voting_keypair
+            .as_ref()
+            .ok_or_else(|| "write_password_files requires a voting_keypair")?;
+        let withdrawal_keypair = self
+            .withdrawal_keypair
+            .as_ref()
+            .ok_or_else(|| "write_password_files requires a withdrawal_keypair")?;
+        let voting_password = self
+            .voting_password
+            .as_ref()
+            .ok_or_else(|| "write_password_files requires a voting_password")?;
+        let withdrawal_password = self
+            .withdrawal_password
+            .as_ref()
+            .ok_or_else(|| "write_password_files requires a withdrawal_password")?;
+
+        save_password(secrets_dir, &voting_keypair.pk, voting_password)?;
+
+        // The insecure keypairs use the same key for voting and withdrawals.
+        if withdrawal_keypair.pk != voting_keypair.pk {
+            save_password(secrets_dir, &withdrawal_keypair.pk, withdrawal_password)?;
+        } else if withdrawal_password != voting_password {
+            return Err(
+                "Identical voting and withdrawal keypairs must have the same password".to_string(),
+            );
+        }
+
+        Ok(self)
     }
 
     fn get_deposit_data(&self) -> Result<(Vec<u8>, u64), String> {
@@ -338,6 +577,48 @@ impl ValidatorDirectoryBuilder {
     }
 }
 
+/// Writes `password` to the password file for `pubkey` in `secrets_dir`.
+fn save_password(
+    secrets_dir: &Path,
+    pubkey: &PublicKey,
+    password: &PlainText,
+) -> Result<(), String> {
+    let path = password_file(secrets_dir, &hex::encode(pubkey.as_ssz_bytes()));
+
+    if path.exists() {
+        return Err(format!("Password file already exists at: {:?}", path));
+    }
+
+    create_with_600_perms(&path)?
+        .write_all(password.as_bytes())
+        .map_err(|e| format!("Unable to write password file: {}", e))
+}
+
+/// Creates a file at `path` which is only readable and writable by the current user.
+fn create_with_600_perms(path: &Path) -> Result<File, String> {
+    let file = File::create(path).map_err(|e| format!("Unable to create file: {}", e))?;
+
+    let mut perm = file
+        .metadata()
+        .map_err(|e| format!("Unable to get file metadata: {}", e))?
+        .permissions();
+    perm.set_mode((libc::S_IWUSR | libc::S_IRUSR) as u32);
+    file.set_permissions(perm)
+        .map_err(|e| format!("Unable to set file permissions: {}", e))?;
+
+    Ok(file)
+}
+
+/// Generates a random alphanumeric password of `PASSWORD_LEN` characters.
+fn random_password() -> PlainText {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(PASSWORD_LEN)
+        .collect::<String>()
+        .into_bytes()
+        .into()
+}
+
 /// Converts gwei to wei.
 fn from_gwei(gwei: u64) -> U256 {
     U256::from(gwei) * U256::exp10(9)
@@ -346,32 +627,50 @@ fn from_gwei(gwei: u64) -> U256 {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use eth2_keystore::json_keystore::Scrypt;
     use tempdir::TempDir;
     use types::{EthSpec, MinimalEthSpec};
 
     type E = MinimalEthSpec;
 
+    /// A KDF which is cheap enough to keep the tests fast.
+    fn insecure_kdf() -> Kdf {
+        Kdf::Scrypt(Scrypt {
+            dklen: eth2_keystore::DKLEN,
+            n: 4,
+            p: 1,
+            r: 8,
+            salt: vec![42; 32].into(),
+        })
+    }
+
     #[test]
     fn random_keypairs_round_trip() {
         let spec = E::default_spec();
         let temp_dir = TempDir::new("acc_manager").expect("should create test dir");
+        let secrets_dir = temp_dir.path().join("secrets");
 
         let created_dir = ValidatorDirectoryBuilder::default()
             .spec(spec)
             .full_deposit_amount()
             .expect("should set full deposit amount")
             .thread_random_keypairs()
+            .random_passwords()
+            .keystore_kdf(insecure_kdf())
             .create_directory(temp_dir.path().into())
             .expect("should create directory")
             .write_keypair_files()
             .expect("should write keypair files")
+            .write_password_files(&secrets_dir)
+            .expect("should write password files")
             .write_eth1_data_file()
             .expect("should write eth1 data file")
             .build()
             .expect("should build dir");
 
-        let loaded_dir = ValidatorDirectory::load_for_signing(created_dir.directory.clone())
-            .expect("should load directory");
+        let loaded_dir =
+            ValidatorDirectory::load_for_signing(created_dir.directory.clone(), &secrets_dir)
+                .expect("should load directory");
 
         assert_eq!(
             created_dir, loaded_dir,
@@ -383,6 +682,7 @@ mod tests {
     fn deterministic_keypairs_round_trip() {
         let spec = E::default_spec();
         let temp_dir = TempDir::new("acc_manager").expect("should create test dir");
+        let secrets_dir = temp_dir.path().join("secrets");
         let index = 42;
 
         let created_dir = ValidatorDirectoryBuilder::default()
@@ -390,10 +690,14 @@ mod tests {
             .full_deposit_amount()
             .expect("should set full deposit amount")
             .insecure_keypairs(index)
+            .random_passwords()
+            .keystore_kdf(insecure_kdf())
             .create_directory(temp_dir.path().into())
             .expect("should create directory")
             .write_keypair_files()
             .expect("should write keypair files")
+            .write_password_files(&secrets_dir)
+            .expect("should write password files")
             .write_eth1_data_file()
             .expect("should write eth1 data file")
             .build()
@@ -432,8 +736,135 @@ mod tests {
             "should have some deposit data"
         );
 
-        let loaded_dir = ValidatorDirectory::load_for_signing(created_dir.directory.clone())
-            .expect("should load directory");
+        let loaded_dir =
+            ValidatorDirectory::load_for_signing(created_dir.directory.clone(), &secrets_dir)
+                .expect("should load directory");
+
+        assert_eq!(
+            created_dir, loaded_dir,
+            "the directory created should match the one loaded"
+        );
+    }
+
+    #[test]
+    fn withdrawal_keypair_requires_password_file() {
+        let spec = E::default_spec();
+        let temp_dir = TempDir::new("acc_manager").expect("should create test dir");
+        let secrets_dir = temp_dir.path().join("secrets");
+
+        let created_dir = ValidatorDirectoryBuilder::default()
+            .spec(spec)
+            .full_deposit_amount()
+            .expect("should set full deposit amount")
+            .thread_random_keypairs()
+            .random_passwords()
+            .keystore_kdf(insecure_kdf())
+            .create_directory(temp_dir.path().into())
+            .expect("should create directory")
+            .write_keypair_files()
+            .expect("should write keypair files")
+            .write_password_files(&secrets_dir)
+            .expect("should write password files")
+            .build()
+            .expect("should build dir");
+
+        let withdrawal_pubkey = created_dir
+            .withdrawal_keypair
+            .as_ref()
+            .expect("should have withdrawal keypair")
+            .pk
+            .as_ssz_bytes();
+        fs::remove_file(password_file(&secrets_dir, &hex::encode(withdrawal_pubkey)))
+            .expect("should remove withdrawal password");
+
+        let loaded_dir =
+            ValidatorDirectory::load_for_signing(created_dir.directory.clone(), &secrets_dir)
+                .expect("should load directory");
+
+        assert_eq!(loaded_dir.voting_keypair, created_dir.voting_keypair);
+        assert_eq!(loaded_dir.withdrawal_keypair, None);
+    }
+
+    #[test]
+    fn password_file_newlines_are_stripped() {
+        let temp_dir = TempDir::new("acc_manager").expect("should create test dir");
+        let path = temp_dir.path().join("password");
+
+        File::create(&path)
+            .expect("should create file")
+            .write_all(b"hunter2\r\n")
+            .expect("should write password");
+
+        assert_eq!(
+            read_password_file(&path)
+                .expect("should read password")
+                .as_bytes(),
+            b"hunter2"
+        );
+    }
+
+    #[test]
+    fn legacy_keypair_files_are_loaded() {
+        let temp_dir = TempDir::new("acc_manager").expect("should create test dir");
+        let keypair = Keypair::random();
+
+        File::create(temp_dir.path().join(legacy_keypair_file(VOTING_KEY_PREFIX)))
+            .expect("should create file")
+            .write_all(&SszEncodableKeypair::from(keypair.clone()).as_ssz_bytes())
+            .expect("should write keypair");
+
+        let loaded_dir =
+            ValidatorDirectory::load_for_signing(temp_dir.path().into(), temp_dir.path())
+                .expect("should load directory");
+
+        assert_eq!(loaded_dir.voting_keypair, Some(keypair));
+        assert_eq!(loaded_dir.withdrawal_keypair, None);
+    }
+
+    #[test]
+    fn derived_keypairs_round_trip() {
+        let spec = E::default_spec();
+        let temp_dir = TempDir::new("acc_manager").expect("should create test dir");
+        let secrets_dir = temp_dir.path().join("secrets");
+        let seed = [42; 64];
+
+        let created_dir = ValidatorDirectoryBuilder::default()
+            .spec(spec)
+            .full_deposit_amount()
+            .expect("should set full deposit amount")
+            .derived_keypairs(&seed, 3)
+            .expect("should derive keypairs")
+            .random_passwords()
+            .keystore_kdf(insecure_kdf())
+            .create_directory(temp_dir.path().into())
+            .expect("should create directory")
+            .write_keypair_files()
+            .expect("should write keypair files")
+            .write_password_files(&secrets_dir)
+            .expect("should write password files")
+            .build()
+            .expect("should build dir");
+
+        let master = DerivedKey::from_seed(&seed).expect("should derive master");
+        let expected_voting = keypair_from_secret(
+            master
+                .descend(&ValidatorPath::new(3, KeyType::Voting).nodes())
+                .secret(),
+        )
+        .expect("should build keypair");
+        assert_eq!(created_dir.voting_keypair, Some(expected_voting));
+        assert_ne!(created_dir.voting_keypair, created_dir.withdrawal_keypair);
+
+        let keystore = Keystore::from_json_reader(
+            File::open(created_dir.directory.join(keystore_file(VOTING_KEY_PREFIX)))
+                .expect("should open keystore"),
+        )
+        .expect("should decode keystore");
+        assert_eq!(keystore.path(), "m/12381/3600/3/0/0");
+
+        let loaded_dir =
+            ValidatorDirectory::load_for_signing(created_dir.directory.clone(), &secrets_dir)
+                .expect("should load directory");
 
         assert_eq!(
             created_dir, loaded_dir,
@@ -2,13 +2,14 @@ use crate::fork_service::ForkService;
 use crate::validator_directory::{ValidatorDirectory, ValidatorDirectoryBuilder};
 use parking_lot::RwLock;
 use rayon::prelude::*;
-use slog::{error, Logger};
+use slashing_protection::{NotSafe, Safe, SlashingDatabase, SLASHING_PROTECTION_FILENAME};
+use slog::{crit, debug, error, warn, Logger};
 use slot_clock::SlotClock;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs::read_dir;
 use std::iter::FromIterator;
 use std::marker::PhantomData;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tempdir::TempDir;
 use types::{
@@ -19,6 +20,9 @@ use types::{
 #[derive(Clone)]
 pub struct ValidatorStore<T, E: EthSpec> {
     validators: Arc<RwLock<HashMap<PublicKey, ValidatorDirectory>>>,
+    /// Validators which must not sign any messages, e.g. whilst doppelganger detection is running.
+    signing_disabled: Arc<RwLock<HashSet<PublicKey>>>,
+    slashing_protection: SlashingDatabase,
     genesis_validators_root: Hash256,
     spec: Arc<ChainSpec>,
     log: Logger,
@@ -30,6 +34,7 @@ pub struct ValidatorStore<T, E: EthSpec> {
 impl<T: SlotClock + 'static, E: EthSpec> ValidatorStore<T, E> {
     pub fn load_from_disk(
         base_dir: PathBuf,
+        secrets_dir: PathBuf,
         genesis_validators_root: Hash256,
         spec: ChainSpec,
         fork_service: ForkService<T, E>,
@@ -43,7 +48,7 @@ impl<T: SlotClock + 'static, E: EthSpec> ValidatorStore<T, E> {
                 let path = validator_dir.ok()?.path();
 
                 if path.is_dir() {
-                    match ValidatorDirectory::load_for_signing(path.clone()) {
+                    match ValidatorDirectory::load_for_signing(path.clone(), &secrets_dir) {
                         Ok(validator_directory) => Some(validator_directory),
                         Err(e) => {
                             error!(
@@ -66,8 +71,13 @@ impl<T: SlotClock + 'static, E: EthSpec> ValidatorStore<T, E> {
                     .map(|voting_keypair| (voting_keypair.pk, validator_directory))
             });
 
+        let validators = HashMap::from_par_iter(validator_key_values);
+        let slashing_protection = open_slashing_protection(&base_dir, validators.keys())?;
+
         Ok(Self {
-            validators: Arc::new(RwLock::new(HashMap::from_par_iter(validator_key_values))),
+            validators: Arc::new(RwLock::new(validators)),
+            signing_disabled: Arc::new(RwLock::new(HashSet::new())),
+            slashing_protection,
             genesis_validators_root,
             spec: Arc::new(spec),
             log,
@@ -96,7 +106,6 @@ impl<T: SlotClock + 'static, E: EthSpec> ValidatorStore<T, E> {
                     .full_deposit_amount()?
                     .insecure_keypairs(*index)
                     .create_directory(data_dir.clone())?
-                    .write_keypair_files()?
                     .write_eth1_data_file()?
                     .build()
             })
@@ -109,8 +118,13 @@ impl<T: SlotClock + 'static, E: EthSpec> ValidatorStore<T, E> {
                     .map(|voting_keypair| (voting_keypair.pk, validator_directory))
             });
 
+        let validators = HashMap::from_iter(validators);
+        let slashing_protection = open_slashing_protection(&data_dir, validators.keys())?;
+
         Ok(Self {
-            validators: Arc::new(RwLock::new(HashMap::from_iter(validators))),
+            validators: Arc::new(RwLock::new(validators)),
+            signing_disabled: Arc::new(RwLock::new(HashSet::new())),
+            slashing_protection,
             genesis_validators_root,
             spec: Arc::new(spec),
             log,
@@ -132,6 +146,33 @@ impl<T: SlotClock + 'static, E: EthSpec> ValidatorStore<T, E> {
         self.validators.read().len()
     }
 
+    /// Prevents the given validators from signing any messages until `enable_signing` is called.
+    pub fn disable_signing(&self, validator_pubkeys: &[PublicKey]) {
+        self.signing_disabled
+            .write()
+            .extend(validator_pubkeys.iter().cloned());
+    }
+
+    /// Allows a validator previously passed to `disable_signing` to sign messages again.
+    pub fn enable_signing(&self, validator_pubkey: &PublicKey) {
+        self.signing_disabled.write().remove(validator_pubkey);
+    }
+
+    /// Returns `true` if the validator is permitted to sign messages.
+    pub fn signing_enabled(&self, validator_pubkey: &PublicKey) -> bool {
+        let enabled = !self.signing_disabled.read().contains(validator_pubkey);
+
+        if !enabled {
+            debug!(
+                self.log,
+                "Signing is disabled for validator";
+                "public_key" => format!("{:?}", validator_pubkey)
+            );
+        }
+
+        enabled
+    }
+
     fn fork(&self) -> Option<Fork> {
         if self.fork_service.fork().is_none() {
             error!(
@@ -143,6 +184,10 @@ impl<T: SlotClock + 'static, E: EthSpec> ValidatorStore<T, E> {
     }
 
     pub fn randao_reveal(&self, validator_pubkey: &PublicKey, epoch: Epoch) -> Option<Signature> {
+        if !self.signing_enabled(validator_pubkey) {
+            return None;
+        }
+
         // TODO: check this against the slot clock to make sure it's not an early reveal?
         self.validators
             .read()
@@ -166,19 +211,54 @@ impl<T: SlotClock + 'static, E: EthSpec> ValidatorStore<T, E> {
         validator_pubkey: &PublicKey,
         block: BeaconBlock<E>,
     ) -> Option<SignedBeaconBlock<E>> {
-        // TODO: check for slashing.
-        self.validators
-            .read()
-            .get(validator_pubkey)
-            .and_then(|validator_dir| {
-                let voting_keypair = validator_dir.voting_keypair.as_ref()?;
+        if !self.signing_enabled(validator_pubkey) {
+            return None;
+        }
+
+        let fork = self.fork()?;
+        let domain = self.spec.get_domain(
+            block.epoch(),
+            Domain::BeaconProposer,
+            &fork,
+            self.genesis_validators_root,
+        );
+
+        let slashing_status = self.slashing_protection.check_and_insert_block_proposal(
+            validator_pubkey,
+            &block.block_header(),
+            domain,
+        );
+
+        match slashing_status {
+            // We can safely sign this block.
+            Ok(Safe::Valid) | Ok(Safe::SameData) => {
+                let validators = self.validators.read();
+                let voting_keypair = validators.get(validator_pubkey)?.voting_keypair.as_ref()?;
+
                 Some(block.sign(
                     &voting_keypair.sk,
-                    &self.fork()?,
+                    &fork,
                     self.genesis_validators_root,
                     &self.spec,
                 ))
-            })
+            }
+            Err(NotSafe::UnregisteredValidator(pk)) => {
+                warn!(
+                    self.log,
+                    "Not signing block for unregistered validator";
+                    "public_key" => format!("{:?}", pk)
+                );
+                None
+            }
+            Err(e) => {
+                crit!(
+                    self.log,
+                    "Not signing slashable block";
+                    "error" => format!("{:?}", e)
+                );
+                None
+            }
+        }
     }
 
     pub fn sign_attestation(
@@ -187,18 +267,35 @@ impl<T: SlotClock + 'static, E: EthSpec> ValidatorStore<T, E> {
         validator_committee_position: usize,
         attestation: &mut Attestation<E>,
     ) -> Option<()> {
-        // TODO: check for slashing.
-        self.validators
-            .read()
-            .get(validator_pubkey)
-            .and_then(|validator_dir| {
-                let voting_keypair = validator_dir.voting_keypair.as_ref()?;
+        if !self.signing_enabled(validator_pubkey) {
+            return None;
+        }
+
+        let fork = self.fork()?;
+        let domain = self.spec.get_domain(
+            attestation.data.target.epoch,
+            Domain::BeaconAttester,
+            &fork,
+            self.genesis_validators_root,
+        );
+
+        let slashing_status = self.slashing_protection.check_and_insert_attestation(
+            validator_pubkey,
+            &attestation.data,
+            domain,
+        );
+
+        match slashing_status {
+            // We can safely sign this attestation.
+            Ok(Safe::Valid) | Ok(Safe::SameData) => {
+                let validators = self.validators.read();
+                let voting_keypair = validators.get(validator_pubkey)?.voting_keypair.as_ref()?;
 
                 attestation
                     .sign(
                         &voting_keypair.sk,
                         validator_committee_position,
-                        &self.fork()?,
+                        &fork,
                         self.genesis_validators_root,
                         &self.spec,
                     )
@@ -212,7 +309,25 @@ impl<T: SlotClock + 'static, E: EthSpec> ValidatorStore<T, E> {
                     .ok()?;
 
                 Some(())
-            })
+            }
+            Err(NotSafe::UnregisteredValidator(pk)) => {
+                warn!(
+                    self.log,
+                    "Not signing attestation for unregistered validator";
+                    "public_key" => format!("{:?}", pk)
+                );
+                None
+            }
+            Err(e) => {
+                crit!(
+                    self.log,
+                    "Not signing slashable attestation";
+                    "attestation" => format!("{:?}", attestation.data),
+                    "error" => format!("{:?}", e)
+                );
+                None
+            }
+        }
     }
 
     /// Signs an `AggregateAndProof` for a given validator.
@@ -225,6 +340,10 @@ impl<T: SlotClock + 'static, E: EthSpec> ValidatorStore<T, E> {
         validator_index: u64,
         aggregate: Attestation<E>,
     ) -> Option<SignedAggregateAndProof<E>> {
+        if !self.signing_enabled(validator_pubkey) {
+            return None;
+        }
+
         let validators = self.validators.read();
         let voting_keypair = validators.get(validator_pubkey)?.voting_keypair.as_ref()?;
 
@@ -245,6 +364,10 @@ impl<T: SlotClock + 'static, E: EthSpec> ValidatorStore<T, E> {
         validator_pubkey: &PublicKey,
         slot: Slot,
     ) -> Option<SelectionProof> {
+        if !self.signing_enabled(validator_pubkey) {
+            return None;
+        }
+
         let validators = self.validators.read();
         let voting_keypair = validators.get(validator_pubkey)?.voting_keypair.as_ref()?;
 
@@ -257,3 +380,30 @@ impl<T: SlotClock + 'static, E: EthSpec> ValidatorStore<T, E> {
         ))
     }
 }
+
+/// Opens (or creates) the slashing protection database in `base_dir` and registers all of the
+/// given `public_keys` with it.
+fn open_slashing_protection<'a>(
+    base_dir: &Path,
+    public_keys: impl Iterator<Item = &'a PublicKey>,
+) -> Result<SlashingDatabase, String> {
+    let path = base_dir.join(SLASHING_PROTECTION_FILENAME);
+
+    let slashing_protection = SlashingDatabase::open_or_create(&path).map_err(|e| {
+        format!(
+            "Failed to open slashing protection database at {:?}: {:?}",
+            path, e
+        )
+    })?;
+
+    slashing_protection
+        .register_validators(public_keys)
+        .map_err(|e| {
+            format!(
+                "Failed to register validators for slashing protection: {:?}",
+                e
+            )
+        })?;
+
+    Ok(slashing_protection)
+}